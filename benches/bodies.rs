@@ -0,0 +1,86 @@
+//! Validation time of JSON request bodies as they grow, reported as throughput in bytes.
+//!
+//! Run with `cargo bench --features benchmarks --bench bodies`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use std::hint::black_box;
+
+use common::BenchRequest;
+use validator::Validator;
+
+const SPEC: &str = r##"
+openapi: 3.0.0
+info: {title: bench, version: 1.0.0}
+paths:
+  /orders:
+    post:
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: array
+              items: {$ref: '#/components/schemas/Order'}
+      responses:
+        200:
+          description: ok
+components:
+  schemas:
+    Order:
+      type: object
+      required: [id, customer, lines]
+      properties:
+        id: {type: string, format: uuid}
+        customer:
+          type: object
+          required: [email]
+          properties:
+            email: {type: string, format: email}
+            name: {type: string, maxLength: 128}
+        lines:
+          type: array
+          items:
+            type: object
+            required: [sku, quantity, price]
+            properties:
+              sku: {type: string, pattern: '^[A-Z]{3}-[0-9]{4}$'}
+              quantity: {type: integer, minimum: 1}
+              price: {type: number, minimum: 0}
+        note: {type: string, nullable: true}
+"##;
+
+/// A body of `orders` orders, each with three lines.
+fn make_body(orders: usize) -> Vec<u8> {
+    let order = r#"{
+        "id": "8e5c2a3e-7c1b-4d6a-9f0e-2b4c6d8e0f1a",
+        "customer": {"email": "ada@example.com", "name": "Ada"},
+        "lines": [
+            {"sku": "ABC-0001", "quantity": 1, "price": 9.99},
+            {"sku": "DEF-0002", "quantity": 3, "price": 0.5},
+            {"sku": "GHI-0003", "quantity": 2, "price": 120}
+        ],
+        "note": null
+    }"#;
+    format!("[{}]", vec![order; orders].join(",")).into_bytes()
+}
+
+fn bodies(c: &mut Criterion) {
+    let validator = Validator::from_yaml_str(SPEC).unwrap().shared();
+    let mut group = c.benchmark_group("bodies");
+    for orders in [1, 100, 10_000] {
+        let request = BenchRequest::post_json("http://test.com/orders", make_body(orders));
+        assert_eq!(Ok(()), validator.validate_request(&request).map(|_| ()));
+        group.throughput(Throughput::Bytes(request.body.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(orders),
+            &request,
+            |b, request| b.iter(|| validator.validate_request(black_box(request)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bodies);
+criterion_main!(benches);
@@ -0,0 +1,90 @@
+//! Requests and responses shared by the benchmarks.
+
+#![allow(dead_code)]
+
+pub struct BenchRequest {
+    pub url: String,
+    pub operation: &'static str,
+    pub body: Vec<u8>,
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl BenchRequest {
+    pub fn get(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            operation: "get",
+            body: vec![],
+            headers: vec![],
+        }
+    }
+
+    pub fn post_json(url: impl Into<String>, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            url: url.into(),
+            operation: "post",
+            body: body.into(),
+            headers: vec![("Content-Type", "application/json".to_string())],
+        }
+    }
+
+    pub fn with_header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+impl validator::Request for BenchRequest {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn operation(&self) -> &str {
+        self.operation
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    fn get_header(&self, name: &str) -> Option<String> {
+        header(&self.headers, name)
+    }
+}
+
+pub struct BenchResponse {
+    pub status_code: u16,
+    pub body: Vec<u8>,
+    pub headers: Vec<(&'static str, String)>,
+}
+
+impl BenchResponse {
+    pub fn json(status_code: u16, body: impl Into<Vec<u8>>) -> Self {
+        Self {
+            status_code,
+            body: body.into(),
+            headers: vec![("Content-Type", "application/json".to_string())],
+        }
+    }
+}
+
+impl validator::Response for BenchResponse {
+    fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    fn get_header(&self, name: &str) -> Option<String> {
+        header(&self.headers, name)
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+fn header(headers: &[(&str, String)], name: &str) -> Option<String> {
+    headers
+        .iter()
+        .find(|(header, _)| header.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.clone())
+}
@@ -0,0 +1,89 @@
+//! Validation time of a request to an operation declaring many path, query and header
+//! parameters, as the number of query parameters sent grows.
+//!
+//! Run with `cargo bench --features benchmarks --bench parameters`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+use std::sync::Arc;
+
+use common::BenchRequest;
+use validator::Validator;
+
+const QUERY_PARAMETERS: usize = 32;
+
+/// An operation with two path parameters, `QUERY_PARAMETERS` query parameters of assorted
+/// schemas and two header parameters.
+fn make_validator() -> Arc<Validator> {
+    let mut parameters = String::from(indoc::indoc!(
+        "
+        - {in: path, name: owner, required: true, schema: {type: integer, minimum: 1}}
+        - {in: path, name: id, required: true, schema: {type: integer, minimum: 1}}
+        - {in: header, name: X-Request-Id, required: true, schema: {type: string, format: uuid}}
+        - {in: header, name: X-Retries, schema: {type: integer, maximum: 5}}
+        "
+    ));
+    for index in 0..QUERY_PARAMETERS {
+        let schema = match index % 4 {
+            0 => "{type: integer, minimum: 0, maximum: 1000}",
+            1 => "{type: boolean}",
+            2 => "{type: string, enum: [asc, desc]}",
+            _ => "{type: array, items: {type: integer}, maxItems: 10}",
+        };
+        parameters.push_str(&format!(
+            "- {{in: query, name: q{index}, schema: {schema}}}\n"
+        ));
+    }
+
+    let mut spec = String::from(indoc::indoc!(
+        "
+        openapi: 3.0.0
+        info: {title: bench, version: 1.0.0}
+        paths:
+          /owners/{owner}/things/{id}:
+            get:
+              responses:
+                200:
+                  description: ok
+              parameters:
+        "
+    ));
+    for line in parameters.lines() {
+        spec.push_str(&format!("        {line}\n"));
+    }
+    Validator::from_yaml_str(&spec).unwrap().shared()
+}
+
+/// A request sending the first `sent` query parameters.
+fn make_request(sent: usize) -> BenchRequest {
+    let query = (0..sent)
+        .map(|index| match index % 4 {
+            0 => format!("q{index}=42"),
+            1 => format!("q{index}=true"),
+            2 => format!("q{index}=%22asc%22"),
+            _ => format!("q{index}=1&q{index}=2&q{index}=3"),
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    BenchRequest::get(format!("http://test.com/owners/12/things/7?{query}"))
+        .with_header("X-Request-Id", "\"8e5c2a3e-7c1b-4d6a-9f0e-2b4c6d8e0f1a\"")
+        .with_header("X-Retries", "2")
+}
+
+fn parameters(c: &mut Criterion) {
+    let validator = make_validator();
+    let mut group = c.benchmark_group("parameters");
+    for sent in [0, 8, QUERY_PARAMETERS] {
+        let request = make_request(sent);
+        assert_eq!(Ok(()), validator.validate_request(&request).map(|_| ()));
+        group.bench_with_input(BenchmarkId::from_parameter(sent), &request, |b, request| {
+            b.iter(|| validator.validate_request(black_box(request)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, parameters);
+criterion_main!(benches);
@@ -0,0 +1,57 @@
+//! Routing time per request as the number of paths in the specification grows. With routes
+//! precomputed into a tree it should stay flat rather than grow with the spec.
+//!
+//! Run with `cargo bench --features benchmarks --bench routing`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+use common::BenchRequest;
+use validator::Validator;
+
+/// A spec with `paths` resources, each with a collection and an item path.
+fn make_validator(paths: usize) -> Validator {
+    let mut spec = String::from("openapi: 3.0.0\ninfo: {title: bench, version: 1.0.0}\npaths:\n");
+    for index in 0..paths / 2 {
+        let resource = indoc::formatdoc!(
+            "
+              /resource{index}/things:
+                get:
+                  responses:
+                    200:
+                      description: ok
+              /resource{index}/things/{{id}}:
+                get:
+                  parameters:
+                    - {{in: path, name: id, required: true, schema: {{type: string}}}}
+                  responses:
+                    200:
+                      description: ok
+            "
+        );
+        for line in resource.lines() {
+            spec.push_str(&format!("  {line}\n"));
+        }
+    }
+    Validator::from_yaml_str(&spec).unwrap()
+}
+
+fn routing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("routing");
+    for paths in [10, 100, 1_000, 10_000] {
+        let validator = make_validator(paths);
+        let last = paths / 2 - 1;
+        let request = BenchRequest::get(format!("http://test.com/resource{last}/things/7"));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(paths),
+            &request,
+            |b, request| b.iter(|| validator.match_operation(black_box(request)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, routing);
+criterion_main!(benches);
@@ -0,0 +1,131 @@
+//! Request and response validation time on a pet store style spec, first for a validator that
+//! still has to compile its schemas and then once they are compiled, both ahead and on first
+//! use into a bounded cache. Schemas are compiled once per validator, so only the first request
+//! should pay for it.
+//!
+//! Run with `cargo bench --features benchmarks --bench schemas`.
+
+mod common;
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use std::hint::black_box;
+
+use common::{BenchRequest, BenchResponse};
+use validator::{CacheStrategy, Validator};
+
+const SPEC: &str = r##"
+openapi: 3.0.0
+info: {title: pets, version: 1.0.0}
+paths:
+  /pets:
+    get:
+      parameters:
+        - {in: query, name: limit, schema: {type: integer, minimum: 1, maximum: 100}}
+        - {in: query, name: tag, schema: {type: string, pattern: '^[a-z]+$'}}
+      responses:
+        200:
+          description: ok
+          headers:
+            X-Next: {schema: {type: string, format: uri}}
+          content:
+            application/json:
+              schema: {type: array, items: {$ref: '#/components/schemas/Pet'}}
+    post:
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: {$ref: '#/components/schemas/NewPet'}
+      responses:
+        201:
+          description: created
+          content:
+            application/json:
+              schema: {$ref: '#/components/schemas/Pet'}
+        default:
+          description: error
+          content:
+            application/json:
+              schema: {$ref: '#/components/schemas/Error'}
+  /pets/{id}:
+    parameters:
+      - {in: path, name: id, required: true, schema: {type: string, format: uuid}}
+    get:
+      responses:
+        200:
+          description: ok
+          content:
+            application/json:
+              schema: {$ref: '#/components/schemas/Pet'}
+components:
+  schemas:
+    NewPet:
+      type: object
+      required: [name, kind]
+      properties:
+        name: {type: string, minLength: 1, maxLength: 64}
+        kind: {type: string, enum: [cat, dog, fish, bird]}
+        birthday: {type: string, format: date}
+        tags: {type: array, items: {type: string}, uniqueItems: true, maxItems: 10}
+        owner: {$ref: '#/components/schemas/Owner'}
+    Pet:
+      allOf:
+        - $ref: '#/components/schemas/NewPet'
+        - type: object
+          required: [id]
+          properties:
+            id: {type: string, format: uuid, readOnly: true}
+    Owner:
+      type: object
+      required: [email]
+      properties:
+        email: {type: string, format: email}
+        phone: {type: string, pattern: '^\+[0-9]{6,15}$'}
+    Error:
+      type: object
+      required: [code, message]
+      properties:
+        code: {type: integer}
+        message: {type: string}
+"##;
+
+const BODY: &str = r#"{
+    "name": "Rex",
+    "kind": "dog",
+    "birthday": "2020-04-01",
+    "tags": ["good", "boy"],
+    "owner": {"email": "owner@example.com", "phone": "+441234567890"}
+}"#;
+
+fn validate(validator: &Validator, request: &BenchRequest, response: &BenchResponse) {
+    let validated = validator.validate_request(black_box(request)).unwrap();
+    validated.validate_response(black_box(response)).unwrap();
+}
+
+fn schemas(c: &mut Criterion) {
+    let request = BenchRequest::post_json("http://test.com/pets", BODY);
+    let response = BenchResponse::json(
+        201,
+        BODY.replacen('{', r#"{"id": "8e5c2a3e-7c1b-4d6a-9f0e-2b4c6d8e0f1a", "#, 1),
+    );
+    let uncompiled = Validator::from_yaml_str(SPEC).unwrap();
+    let mut group = c.benchmark_group("schemas");
+
+    group.bench_function("uncompiled", |b| {
+        b.iter_batched(
+            || uncompiled.clone(),
+            |validator| validate(&validator, &request, &response),
+            BatchSize::SmallInput,
+        )
+    });
+    let precompiled = uncompiled.clone().shared();
+    group.bench_function("precompiled", |b| {
+        b.iter(|| validate(&precompiled, &request, &response))
+    });
+    let lru = (uncompiled.clone()).with_schema_cache(CacheStrategy::Lru { capacity: 16 });
+    group.bench_function("lru", |b| b.iter(|| validate(&lru, &request, &response)));
+    group.finish();
+}
+
+criterion_group!(benches, schemas);
+criterion_main!(benches);
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use axum::body::{Body, Bytes};
+use axum::extract::{FromRef, FromRequest, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use http_body_util::BodyExt;
+use serde::de::DeserializeOwned;
+
+use crate::error::ValidationError;
+use crate::http_request::problem_json;
+use crate::request::Validator;
+
+/// A request that does not conform to the specification, answered with [`problem_json`] and
+/// the error's suggested status, e.g. 400, 415 or 406.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidationRejection(pub ValidationError);
+
+impl IntoResponse for ValidationRejection {
+    fn into_response(self) -> Response {
+        problem_json(&self.0).into_response()
+    }
+}
+
+/// A JSON body deserialized once the whole request has been validated against the
+/// specification, taken from the `Arc<Validator>` in the router state.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+    Arc<Validator>: FromRef<S>,
+{
+    type Rejection = ValidationRejection;
+
+    async fn from_request(
+        request: axum::extract::Request,
+        state: &S,
+    ) -> Result<Self, Self::Rejection> {
+        let validator = Arc::<Validator>::from_ref(state);
+        let request = buffer(request, validator.options().max_body_size).await?;
+        validator
+            .validate_request(&request)
+            .map_err(ValidationRejection)?;
+        serde_json::from_slice(request.body())
+            .map(ValidatedJson)
+            .map_err(|_| ValidationRejection(ValidationError::InvalidBody))
+    }
+}
+
+/// Middleware validating every request against the specification before it reaches the
+/// router, for use with `axum::middleware::from_fn_with_state`. Requests are routed by their
+/// URL, not by the axum route they match, so the specification's path templates need not be
+/// the router's.
+pub async fn validation_middleware(
+    State(validator): State<Arc<Validator>>,
+    request: axum::extract::Request,
+    next: Next,
+) -> Response {
+    let request = match buffer(request, validator.options().max_body_size).await {
+        Ok(request) => request,
+        Err(rejection) => return rejection.into_response(),
+    };
+    if let Err(error) = validator.validate_request(&request) {
+        return ValidationRejection(error).into_response();
+    }
+    next.run(request.map(Body::from)).await
+}
+
+/// Reads the whole body, giving up as soon as it is larger than
+/// [`crate::ValidatorOptions::max_body_size`].
+async fn buffer(
+    request: axum::extract::Request,
+    limit: Option<usize>,
+) -> Result<http::Request<Bytes>, ValidationRejection> {
+    let (parts, mut body) = request.into_parts();
+    let mut bytes = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|_| ValidationRejection(ValidationError::UnreadableBody))?;
+        if let Ok(data) = frame.into_data() {
+            bytes.extend_from_slice(&data);
+        }
+        match limit {
+            Some(limit) if bytes.len() > limit => {
+                return Err(ValidationRejection(ValidationError::BodyTooLarge {
+                    size: bytes.len(),
+                    limit,
+                }))
+            }
+            _ => (),
+        }
+    }
+    Ok(http::Request::from_parts(parts, bytes.into()))
+}
+
+#[cfg(test)]
+mod test_axum_integration {
+    use super::*;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use axum::routing::post;
+    use axum::Router;
+    use indoc::indoc;
+    use serde::Deserialize;
+    use tower::ServiceExt;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+                      properties:
+                        name:
+                          type: string
+              responses:
+                201:
+                  description: created
+        "#
+    );
+
+    #[derive(Deserialize)]
+    struct Pet {
+        name: String,
+    }
+
+    fn validator() -> Arc<Validator> {
+        Arc::new(make_validator_from_spec(PATH_SPEC))
+    }
+
+    fn post_pet(content_type: &str, body: &'static str) -> axum::extract::Request {
+        http::Request::post("/pets")
+            .header("Content-Type", content_type)
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    fn extracting_router() -> Router {
+        Router::new()
+            .route(
+                "/pets",
+                post(|ValidatedJson(pet): ValidatedJson<Pet>| async move { pet.name }),
+            )
+            .with_state(validator())
+    }
+
+    async fn text(response: Response) -> String {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX);
+        String::from_utf8(body.await.unwrap().to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn extract_a_validated_body() {
+        let router = extracting_router();
+        let response = router
+            .oneshot(post_pet("application/json", r#"{"name": "rex"}"#))
+            .await
+            .unwrap();
+        assert_eq!(200, response.status());
+        assert_eq!("rex", text(response).await);
+    }
+
+    #[tokio::test]
+    async fn reject_an_invalid_body_before_the_handler() {
+        let router = extracting_router();
+        let response = router
+            .oneshot(post_pet("application/json", r#"{"name": 1}"#))
+            .await
+            .unwrap();
+        assert_eq!(400, response.status());
+    }
+
+    #[tokio::test]
+    async fn reject_an_undeclared_content_type_in_middleware() {
+        let router = Router::new()
+            .route("/pets", post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn_with_state(
+                validator(),
+                validation_middleware,
+            ));
+        let response = (router.clone())
+            .oneshot(post_pet("text/plain", "rex"))
+            .await
+            .unwrap();
+        assert_eq!(415, response.status());
+
+        let response = router
+            .oneshot(post_pet("application/json", r#"{"name": "rex"}"#))
+            .await
+            .unwrap();
+        assert_eq!(200, response.status());
+        assert_eq!(r#"{"name": "rex"}"#, text(response).await);
+    }
+
+    #[tokio::test]
+    async fn stop_reading_a_body_over_the_limit() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            max_body_size: Some(8),
+            ..Default::default()
+        });
+        let router = Router::new()
+            .route("/pets", post(|body: String| async move { body }))
+            .layer(axum::middleware::from_fn_with_state(
+                Arc::new(validator),
+                validation_middleware,
+            ));
+        let response = router
+            .oneshot(post_pet("application/json", r#"{"name": "rex"}"#))
+            .await
+            .unwrap();
+        assert_eq!(413, response.status());
+    }
+}
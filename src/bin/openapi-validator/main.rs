@@ -0,0 +1,52 @@
+use std::path::Path;
+use std::process::ExitCode;
+
+use clap::{Parser, Subcommand};
+use validator::{LoadError, Validator};
+
+#[cfg(feature = "mock-server")]
+mod mock_server;
+mod validate;
+
+/// Validates HTTP traffic against an OpenAPI document.
+#[derive(Debug, Parser)]
+#[command(name = "openapi-validator", version)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    Validate(validate::Args),
+    #[cfg(feature = "mock-server")]
+    MockServer(mock_server::Args),
+}
+
+/// Exit code of a run that could not validate anything, e.g. because the specification could
+/// not be loaded. Invalid requests exit with 1.
+const UNUSABLE: u8 = 2;
+
+fn main() -> ExitCode {
+    let outcome = match Cli::parse().command {
+        Command::Validate(args) => validate::run(args),
+        #[cfg(feature = "mock-server")]
+        Command::MockServer(args) => mock_server::run(args),
+    };
+    outcome.unwrap_or_else(|error| {
+        eprintln!("error: {error}");
+        ExitCode::from(UNUSABLE)
+    })
+}
+
+/// Loads a JSON document when the file is named `*.json`, and a YAML one otherwise.
+fn load(spec: &Path) -> Result<Validator, LoadError> {
+    if spec
+        .extension()
+        .is_some_and(|extension| extension == "json")
+    {
+        Validator::from_json_file(spec)
+    } else {
+        Validator::from_yaml_file(spec)
+    }
+}
@@ -0,0 +1,33 @@
+use std::error::Error;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use validator::MockServer;
+
+/// Serve responses generated from a specification, validating every request.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The specification, as YAML or, when named `*.json`, JSON.
+    spec: PathBuf,
+    #[arg(long, default_value = "127.0.0.1")]
+    host: IpAddr,
+    #[arg(long, default_value_t = 4010)]
+    port: u16,
+}
+
+pub fn run(args: Args) -> Result<ExitCode, Box<dyn Error>> {
+    let server = MockServer::new(Arc::new(super::load(&args.spec)?));
+    let runtime = tokio::runtime::Runtime::new()?;
+    runtime.block_on(async {
+        let listener = tokio::net::TcpListener::bind((args.host, args.port)).await?;
+        eprintln!(
+            "serving {} on http://{}",
+            args.spec.display(),
+            listener.local_addr()?
+        );
+        server.serve(listener).await
+    })?;
+    Ok(ExitCode::SUCCESS)
+}
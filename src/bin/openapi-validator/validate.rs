@@ -0,0 +1,157 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use serde::Deserialize;
+use serde_json::json;
+use validator::Request;
+
+/// Validate one request against a specification, exiting with 1 when it does not conform.
+#[derive(Debug, clap::Args)]
+pub struct Args {
+    /// The specification, as YAML or, when named `*.json`, JSON.
+    spec: PathBuf,
+    /// A JSON file describing the request: `method`, `url`, `headers` as an object and `body`,
+    /// sent as is when a string and as JSON otherwise.
+    #[arg(long, conflicts_with_all = ["method", "url", "header", "body", "json"])]
+    request: Option<PathBuf>,
+    #[arg(long, required_unless_present = "request")]
+    method: Option<String>,
+    /// The absolute URL, or its path and query such as `/pets?limit=10`.
+    #[arg(long, required_unless_present = "request")]
+    url: Option<String>,
+    /// A header as `name: value`, repeated for each header.
+    #[arg(short = 'H', long, value_parser = parse_header)]
+    header: Vec<(String, String)>,
+    /// A file holding the body.
+    #[arg(long, conflicts_with = "json")]
+    body: Option<PathBuf>,
+    /// The body as JSON text.
+    #[arg(long)]
+    json: Option<String>,
+    #[arg(long, value_enum, default_value_t = Format::Human)]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum Format {
+    Human,
+    Json,
+}
+
+fn parse_header(header: &str) -> Result<(String, String), String> {
+    let (name, value) = (header.split_once(':')).ok_or("expected 'name: value'")?;
+    Ok((name.trim().to_string(), value.trim().to_string()))
+}
+
+struct RecordedRequest {
+    method: String,
+    url: String,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request for RecordedRequest {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn operation(&self) -> &str {
+        &self.method
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        (self.headers.iter())
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+#[derive(Deserialize)]
+struct RequestFile {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    body: Option<serde_json::Value>,
+}
+
+impl RecordedRequest {
+    fn from_args(args: &Args) -> Result<Self, Box<dyn Error>> {
+        if let Some(path) = &args.request {
+            let file: RequestFile = serde_json::from_slice(&std::fs::read(path)?)?;
+            let body = match file.body {
+                None => Vec::new(),
+                Some(serde_json::Value::String(text)) => text.into_bytes(),
+                Some(value) => value.to_string().into_bytes(),
+            };
+            return Ok(Self {
+                method: file.method,
+                url: file.url,
+                headers: file.headers.into_iter().collect(),
+                body,
+            });
+        }
+        let body = match (&args.body, &args.json) {
+            (Some(path), _) => std::fs::read(path)?,
+            (None, Some(json)) => json.clone().into_bytes(),
+            (None, None) => Vec::new(),
+        };
+        Ok(Self {
+            method: args.method.clone().unwrap_or_default(),
+            url: args.url.clone().unwrap_or_default(),
+            headers: args.header.clone(),
+            body,
+        })
+    }
+}
+
+pub fn run(args: Args) -> Result<ExitCode, Box<dyn Error>> {
+    let validator = super::load(&args.spec)?;
+    let request = RecordedRequest::from_args(&args)?;
+    let target = format!("{} {}", request.method, request.url);
+
+    let outcome = validator.validate_request(&request);
+    let report = match &outcome {
+        Ok(validated) => json!({
+            "request": target,
+            "valid": true,
+            "operation": validated.operation_id(),
+            "path": validated.path_template(),
+            "warnings": (validated.report.warnings.iter())
+                .map(ToString::to_string)
+                .collect::<Vec<_>>(),
+        }),
+        Err(error) => json!({
+            "request": target,
+            "valid": false,
+            "status": error.suggested_status(),
+            "error": error.to_string(),
+        }),
+    };
+    match args.format {
+        Format::Json => println!("{report}"),
+        Format::Human => match &outcome {
+            Ok(validated) => {
+                let operation = (validated.operation_id()).unwrap_or(validated.path_template());
+                println!("{target}: valid, routed to {operation}");
+                for warning in &validated.report.warnings {
+                    println!("  warning: {warning}");
+                }
+            }
+            Err(error) => println!(
+                "{target}: invalid, {error} (status {})",
+                error.suggested_status()
+            ),
+        },
+    }
+    Ok(match outcome {
+        Ok(_) => ExitCode::SUCCESS,
+        Err(_) => ExitCode::FAILURE,
+    })
+}
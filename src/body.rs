@@ -1,75 +1,506 @@
+use crate::error::ValidationError;
+use crate::form::{FormDecoder, FormEncoding};
 use crate::item_or_fetch::ItemOrFetch;
-use crate::to_jsonschema::ToJSONSchema;
-use crate::jsonschema::JSONSchemaValidator;
+use crate::jsonschema::SchemaLocation;
+use crate::report::{ValidationReport, ValidationWarning};
+use crate::utf8::Utf8Decoder;
+use std::borrow::Cow;
+use std::io::{BufRead, BufReader, Read};
 
 pub enum BodyValidator<'api> {
-    NoSpecification,
+    NoSpecification {
+        reject_body: bool,
+    },
     EmptyContentType {
         body_spec: &'api openapiv3::RequestBody,
     },
     JSONBody {
-        body_spec: &'api openapiv3::RequestBody,
+        media_type: &'api openapiv3::MediaType,
+        strip_bom: bool,
+        schema: SchemaLocation<'api>,
+        components: &'api Option<openapiv3::Components>,
+    },
+    TextBody {
+        media_type: &'api openapiv3::MediaType,
+        charset: TextCharset,
+        schema: SchemaLocation<'api>,
         components: &'api Option<openapiv3::Components>,
     },
-    PlainUTF8Body,
+    FormBody {
+        media_type: &'api openapiv3::MediaType,
+        encoding: FormEncoding,
+        schema: SchemaLocation<'api>,
+        components: &'api Option<openapiv3::Components>,
+    },
+    Unsupported {
+        content_type: mime::Mime,
+        media_type: &'api openapiv3::MediaType,
+        components: &'api Option<openapiv3::Components>,
+    },
+}
+
+/// A body read incrementally. Bytes are counted as they are read so that a body over its size
+/// limit is rejected without reading the rest of it.
+pub struct BodyStream<'body> {
+    reader: BufReader<CountingReader<'body>>,
+    /// The whole body when it is already in memory, so it can be parsed in place.
+    in_memory: Option<&'body [u8]>,
+}
+
+struct CountingReader<'body> {
+    inner: Box<dyn Read + 'body>,
+    size: usize,
+    limit: Option<usize>,
+}
+
+impl Read for CountingReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.size += read;
+        match self.limit {
+            Some(limit) if self.size > limit => Err(std::io::ErrorKind::FileTooLarge.into()),
+            _ => Ok(read),
+        }
+    }
+}
+
+impl<'body> BodyStream<'body> {
+    pub fn new(inner: Box<dyn Read + 'body>) -> Self {
+        Self {
+            reader: BufReader::new(CountingReader {
+                inner,
+                size: 0,
+                limit: None,
+            }),
+            in_memory: None,
+        }
+    }
+
+    /// A body already in memory, which JSON is parsed from without copying.
+    pub fn from_slice(body: &'body [u8]) -> Self {
+        Self {
+            in_memory: Some(body),
+            ..Self::new(Box::new(body))
+        }
+    }
+
+    pub fn is_empty(&mut self) -> Result<bool, ValidationError> {
+        self.fill_buf().map(<[u8]>::is_empty)
+    }
+
+    /// Rejects the body once more than `limit` bytes of it have been read, including any
+    /// already buffered.
+    fn limit(&mut self, limit: usize) -> Result<(), ValidationError> {
+        let reader = self.reader.get_mut();
+        reader.limit = Some(limit);
+        match reader.size > limit {
+            true => Err(self.error()),
+            false => Ok(()),
+        }
+    }
+
+    /// Reads the rest of the body without keeping it, so that its size limit is enforced.
+    fn drain(&mut self) -> Result<(), ValidationError> {
+        std::io::copy(&mut self.reader, &mut std::io::sink())
+            .map(|_| ())
+            .map_err(|_| self.error())
+    }
+
+    /// The rest of the body, borrowed when it is held in memory.
+    fn read_to_end(&mut self) -> Result<Cow<'body, [u8]>, ValidationError> {
+        if let Some(body) = self.take_in_memory()? {
+            return Ok(Cow::Borrowed(body));
+        }
+        let mut body = vec![];
+        match self.reader.read_to_end(&mut body) {
+            Ok(..) => Ok(Cow::Owned(body)),
+            Err(..) => Err(self.error()),
+        }
+    }
+
+    /// The unread rest of a body held in memory, all of which counts as read from then on.
+    /// `None` for a streamed body.
+    fn take_in_memory(&mut self) -> Result<Option<&'body [u8]>, ValidationError> {
+        let Some(body) = self.in_memory.take() else {
+            return Ok(None);
+        };
+        let buffered = self.reader.buffer().len();
+        self.reader.consume(buffered);
+        let reader = self.reader.get_mut();
+        let unread = &body[reader.size - buffered..];
+        reader.inner = Box::new(std::io::empty());
+        reader.size = body.len();
+        match reader.limit {
+            Some(limit) if reader.size > limit => Err(self.error()),
+            _ => Ok(Some(unread)),
+        }
+    }
+
+    /// Removes a leading UTF-8 byte order mark, reading until there are three bytes to compare
+    /// or the body ends. A body that does not start with one is left as it was.
+    fn strip_bom(&mut self) -> Result<(), ValidationError> {
+        const BOM: &[u8] = b"\xef\xbb\xbf";
+        let buffered = self.fill_buf()?;
+        if buffered.starts_with(BOM) {
+            self.reader.consume(BOM.len());
+            return Ok(());
+        }
+        if buffered.is_empty() || buffered.len() >= BOM.len() || !BOM.starts_with(buffered) {
+            return Ok(());
+        }
+        let mut start = buffered.to_vec();
+        self.reader.consume(start.len());
+        let reader = self.reader.get_mut();
+        while start.len() < BOM.len() && BOM.starts_with(&start) {
+            let mut byte = [0];
+            match reader.read(&mut byte) {
+                Ok(0) => break,
+                Ok(..) => start.push(byte[0]),
+                Err(..) => return Err(self.error()),
+            }
+        }
+        if start != BOM {
+            // Puts the bytes back so the body is read again from its first byte.
+            reader.size -= start.len();
+            let rest = std::mem::replace(&mut reader.inner, Box::new(std::io::empty()));
+            reader.inner = Box::new(std::io::Cursor::new(start).chain(rest));
+        }
+        Ok(())
+    }
+
+    fn fill_buf(&mut self) -> Result<&[u8], ValidationError> {
+        if self.reader.fill_buf().is_err() {
+            return Err(self.error());
+        }
+        Ok(self.reader.buffer())
+    }
+
+    /// The error for a failed read: the body was either too large or could not be read.
+    fn error(&self) -> ValidationError {
+        let reader = self.reader.get_ref();
+        match reader.limit {
+            Some(limit) if reader.size > limit => ValidationError::BodyTooLarge {
+                size: reader.size,
+                limit,
+            },
+            _ => ValidationError::UnreadableBody,
+        }
+    }
+}
+
+/// Character sets a `text/*` body can be decoded from.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum TextCharset {
+    Utf8,
+    Ascii,
+    Latin1,
+}
+
+impl TextCharset {
+    /// The charset of a `text/*` media type, UTF-8 when none is given. `None` for other media
+    /// types and for charsets that cannot be decoded.
+    pub fn from_mime(mime: &mime::Mime) -> Option<Self> {
+        if mime.type_() != mime::TEXT {
+            return None;
+        }
+        match mime.get_param(mime::CHARSET) {
+            None => Some(Self::Utf8),
+            Some(charset) if charset == mime::UTF_8 => Some(Self::Utf8),
+            Some(charset) if charset == "us-ascii" => Some(Self::Ascii),
+            Some(charset) if charset == "iso-8859-1" || charset == "latin1" => Some(Self::Latin1),
+            Some(..) => None,
+        }
+    }
+
+    /// Decodes the body a buffer at a time, failing with the offset of the first byte invalid
+    /// in this charset.
+    fn decode(self, body: &mut BodyStream) -> Result<String, ValidationError> {
+        let mut decoder = Utf8Decoder::default();
+        let mut text = String::new();
+        let mut offset = 0;
+        loop {
+            let chunk = body.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            match self {
+                Self::Utf8 => decoder.push(chunk, &mut text),
+                Self::Ascii => match chunk.iter().position(|byte| !byte.is_ascii()) {
+                    Some(position) => Err(offset + position),
+                    None => {
+                        text.extend(chunk.iter().map(|byte| *byte as char));
+                        Ok(())
+                    }
+                },
+                Self::Latin1 => {
+                    text.extend(chunk.iter().map(|byte| *byte as char));
+                    Ok(())
+                }
+            }
+            .map_err(|offset| ValidationError::InvalidEncoding { offset })?;
+
+            let read = chunk.len();
+            offset += read;
+            body.reader.consume(read);
+        }
+        decoder
+            .finish()
+            .map(|_| text)
+            .map_err(|offset| ValidationError::InvalidEncoding { offset })
+    }
 }
 
 impl<'api> BodyValidator<'api> {
-    pub fn validate_body(self, body: &[u8]) -> Result<(), ()> {
+    /// Checks the body size against `max_body_size` and any limit implied by the media type's
+    /// schema while decoding or parsing it.
+    pub fn validate_body(
+        &self,
+        body: &mut BodyStream,
+        max_body_size: Option<usize>,
+        report: &mut ValidationReport,
+    ) -> Result<(), ValidationError> {
+        let limit = [max_body_size, self.media_type_size_limit()]
+            .into_iter()
+            .flatten()
+            .min();
+        if let Some(limit) = limit {
+            body.limit(limit)?;
+        }
+
         match self {
             Self::JSONBody {
-                body_spec,
+                media_type,
+                strip_bom,
+                schema,
+                components,
+            } => Self::validate_json(
+                media_type,
+                body,
+                *strip_bom,
+                schema,
+                components,
+                Some(report),
+            )
+            .map_err(|error| error.unwrap_or(ValidationError::InvalidBody)),
+            Self::TextBody {
+                media_type,
+                charset,
+                schema,
                 components,
-            } => {
-                return Self::validate_json(body_spec, body, components);
+            } => Self::validate_text(media_type, *charset, body, schema, components, report),
+            Self::FormBody {
+                media_type,
+                encoding,
+                schema,
+                components,
+            } => Self::validate_form(media_type, encoding, body, schema, components, report),
+            other => {
+                let is_empty = body.is_empty()?;
+                if limit.is_some() {
+                    body.drain()?;
+                }
+                other.validate_unparsed(is_empty, report)
             }
-            Self::PlainUTF8Body => return std::str::from_utf8(body).map_err(|_| ()).map(|_| ()),
+        }
+    }
+
+    /// Bodies that are not decoded only need to be present or absent as the specification
+    /// requires.
+    fn validate_unparsed(
+        &self,
+        is_empty: bool,
+        report: &mut ValidationReport,
+    ) -> Result<(), ValidationError> {
+        match self {
             Self::EmptyContentType { body_spec } => {
-                if !body_spec.required && body.is_empty() {
-                    Ok(())
+                if !is_empty {
+                    Err(ValidationError::MissingContentType)
+                } else if body_spec.required {
+                    Err(ValidationError::MissingBody)
                 } else {
-                    Err(())
+                    Ok(())
                 }
             }
-            Self::NoSpecification => Ok(()),
+            Self::Unsupported { content_type, .. } => {
+                report.warn(ValidationWarning::UnvalidatedBody {
+                    content_type: content_type.to_string(),
+                });
+                Ok(())
+            }
+            Self::NoSpecification { .. } if is_empty => Ok(()),
+            Self::NoSpecification { reject_body: true } => Err(ValidationError::UnexpectedBody),
+            Self::NoSpecification { reject_body: false } => {
+                report.warn(ValidationWarning::UnexpectedBody);
+                Ok(())
+            }
+            Self::JSONBody { .. } | Self::TextBody { .. } | Self::FormBody { .. } => Ok(()),
         }
     }
 
-    fn validate_json(
-        body_spec: &openapiv3::RequestBody,
-        body: &[u8],
-        components: &Option<openapiv3::Components>,
-    ) -> Result<(), ()> {
-        if let Some(body_schema) = body_spec
-            .content
-            .get("application/json")
-            .and_then(|content| {
-                content
-                    .schema
-                    .as_ref()
-                    .map(|schema| schema.item_or_fetch(components))
-            })
+    /// Byte limit from the `maxLength` of a string schema on a text or unvalidated body. Binary
+    /// strings count bytes, other strings count characters of up to four bytes each.
+    fn media_type_size_limit(&self) -> Option<usize> {
+        let (media_type, components) = match self {
+            Self::TextBody {
+                media_type,
+                components,
+                ..
+            }
+            | Self::Unsupported {
+                media_type,
+                components,
+                ..
+            } => (media_type, components),
+            _ => return None,
+        };
+
+        match &media_type
+            .schema
+            .as_ref()?
+            .item_or_fetch(components)
+            .ok()?
+            .schema_kind
         {
-            let body = match std::str::from_utf8(body) {
-                Ok(body) => body,
-                Err(..) => return Err(()),
-            };
+            openapiv3::SchemaKind::Type(openapiv3::Type::String(string)) => {
+                let max_length = string.max_length?;
+                match string.format {
+                    openapiv3::VariantOrUnknownOrEmpty::Item(openapiv3::StringFormat::Binary) => {
+                        Some(max_length)
+                    }
+                    _ => Some(max_length.saturating_mul(4)),
+                }
+            }
+            _ => None,
+        }
+    }
+
+    fn validate_text(
+        media_type: &openapiv3::MediaType,
+        charset: TextCharset,
+        body: &mut BodyStream,
+        location: &SchemaLocation,
+        components: &Option<openapiv3::Components>,
+        report: &mut ValidationReport,
+    ) -> Result<(), ValidationError> {
+        let text = charset.decode(body)?;
 
-            return body_schema.to_json_schema().validates(body);
+        match &media_type.schema {
+            Some(schema) => location
+                .validate(
+                    &*location.compile(schema, components)?,
+                    &serde_json::Value::String(text),
+                    Some(report),
+                )
+                .map_err(|error| error.unwrap_or(ValidationError::InvalidBody)),
+            None => Ok(()),
         }
+    }
 
-        if serde_json::from_slice::<serde_json::Value>(body).is_ok() {
-            return Ok(());
+    /// Decodes a form into an object, field by field as the media type's `encoding` describes,
+    /// and checks it against the media type's schema if there is one.
+    fn validate_form(
+        media_type: &openapiv3::MediaType,
+        encoding: &FormEncoding,
+        body: &mut BodyStream,
+        location: &SchemaLocation,
+        components: &Option<openapiv3::Components>,
+        report: &mut ValidationReport,
+    ) -> Result<(), ValidationError> {
+        let body = body.read_to_end()?;
+        let decoder = FormDecoder {
+            media_type,
+            components,
+        };
+        let value = match encoding {
+            FormEncoding::UrlEncoded => decoder.decode_urlencoded(&body)?,
+            FormEncoding::Multipart { boundary } => {
+                decoder.decode_multipart(&body, boundary.as_deref(), location, report)?
+            }
+        };
+
+        match &media_type.schema {
+            Some(schema) => location
+                .validate(
+                    &*location.compile(schema, components)?,
+                    &value,
+                    Some(report),
+                )
+                .map_err(|error| error.unwrap_or(ValidationError::InvalidBody)),
+            None => Ok(()),
         }
+    }
 
-        Err(())
+    /// Parses the body as it is read, after removing a leading UTF-8 byte order mark when
+    /// `strip_bom` is set, and checks it against the media type's schema if there is one.
+    /// Fails with `None` when the body does not match the schema.
+    pub(crate) fn validate_json(
+        media_type: &openapiv3::MediaType,
+        body: &mut BodyStream,
+        strip_bom: bool,
+        location: &SchemaLocation,
+        components: &Option<openapiv3::Components>,
+        report: Option<&mut ValidationReport>,
+    ) -> Result<(), Option<ValidationError>> {
+        if strip_bom {
+            body.strip_bom().map_err(Some)?;
+        }
+
+        let syntax_error = |error: serde_json::Error, has_content: bool| match error.classify() {
+            serde_json::error::Category::Eof if !has_content => ValidationError::EmptyJsonBody,
+            _ => ValidationError::InvalidJsonSyntax {
+                line: error.line(),
+                column: error.column(),
+            },
+        };
+        let value = match body.take_in_memory().map_err(Some)? {
+            Some(bytes) => serde_json::from_slice::<serde_json::Value>(bytes).map_err(|error| {
+                let has_content = bytes.iter().any(|byte| !byte.is_ascii_whitespace());
+                Some(syntax_error(error, has_content))
+            })?,
+            None => {
+                let mut reader = JsonReader {
+                    body,
+                    has_content: false,
+                };
+                serde_json::from_reader::<_, serde_json::Value>(&mut reader).map_err(|error| {
+                    Some(match error.classify() {
+                        serde_json::error::Category::Io => reader.body.error(),
+                        _ => syntax_error(error, reader.has_content),
+                    })
+                })?
+            }
+        };
+
+        match &media_type.schema {
+            Some(schema) => {
+                location.validate(&*location.compile(schema, components)?, &value, report)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+/// Feeds a body to the JSON parser, noting whether it held anything but whitespace so an empty
+/// body can be told apart from a truncated one.
+struct JsonReader<'stream, 'body> {
+    body: &'stream mut BodyStream<'body>,
+    has_content: bool,
+}
+
+impl Read for JsonReader<'_, '_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.body.reader.read(buf)?;
+        self.has_content |= buf[..read].iter().any(|byte| !byte.is_ascii_whitespace());
+        Ok(read)
     }
 }
 
 #[cfg(test)]
 mod test_body {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
+    use parameterized::parameterized;
     use std::collections::HashMap;
 
     #[test]
@@ -94,7 +525,7 @@ mod test_body {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingBody),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -179,7 +610,7 @@ mod test_body {
             headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidJsonSyntax { line: 1, column: 1 }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -244,7 +675,7 @@ mod test_body {
             )]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidEncoding { offset: 0 }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -280,7 +711,7 @@ mod test_body {
             headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidBody),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -401,9 +832,57 @@ mod test_body {
             .is_ok());
     }
 
+    #[parameterized(body = {
+        r#"{"children": [{"children": []}, {}]}"#,
+        r#"{"children": [{"children": [{"name": 1}]}]}"#,
+    }, valid = {
+        true, false
+    })]
+    fn validate_a_json_body_against_a_recursive_schema(body: &str, valid: bool) {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /json/against/schema:
+                post:
+                  requestBody:
+                    required: true
+                    content:
+                      application/json:
+                        schema:
+                          $ref: '#/components/schemas/Node'
+                  responses:
+                    200:
+                      description: API call successful
+
+            components:
+              schemas:
+                Node:
+                  type: object
+                  properties:
+                    name:
+                      type: string
+                    children:
+                      type: array
+                      items:
+                        $ref: '#/components/schemas/Node'
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/json/against/schema".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert_eq!(
+            valid,
+            make_validator_from_spec(path_spec)
+                .validate_request(&request)
+                .is_ok()
+        );
+    }
+
     #[test]
-    #[should_panic]
-    fn reject_given_component_schema_reference_with_incorrect_reference_panics() {
+    fn reject_given_component_schema_reference_with_incorrect_reference() {
         let path_spec = indoc!(
             r#"
             paths:
@@ -432,7 +911,14 @@ mod test_body {
             body: r#"true"#.as_bytes().to_vec(),
             headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
         };
-        let _ = make_validator_from_spec(path_spec).validate_request(&request);
+        assert_eq!(
+            Err(ValidationError::UnresolvedReference {
+                reference: "#/components/schemas/NotThere".to_string()
+            }),
+            make_validator_from_spec(path_spec)
+                .validate_request(&request)
+                .map(|_| ())
+        );
     }
 
     #[test]
@@ -469,4 +955,657 @@ mod test_body {
             .validate_request(&request)
             .is_ok());
     }
+
+    #[parameterized(body = {
+        "null", "true", "1.5", r#""text""#, "[1, \"a\"]", r#"{"any": "thing"}"#
+    })]
+    fn accept_any_json_body_given_an_untyped_schema(body: &str) {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /anything:
+                post:
+                  requestBody:
+                    content:
+                      application/json:
+                        schema: {}
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/anything".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert!(make_validator_from_spec(path_spec)
+            .validate_request(&request)
+            .is_ok());
+    }
+
+    #[test]
+    fn apply_the_constraints_of_an_untyped_schema() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /anything:
+                post:
+                  requestBody:
+                    content:
+                      application/json:
+                        schema:
+                          minLength: 2
+                          minimum: 1
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = |body: &str| FakeRequest {
+            url: "http://test.com/anything".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        let validator = make_validator_from_spec(path_spec);
+
+        assert!(validator.validate_request(&request(r#""ab""#)).is_ok());
+        assert!(validator.validate_request(&request("[]")).is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&request(r#""a""#)).map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&request("0")).map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_text_bodies {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /text/body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  text/csv:
+                    schema:
+                      type: string
+                      pattern: "^[a-z,]+$"
+                      maxLength: 8
+                  text/html: {}
+                  text/x-colour:
+                    schema:
+                      type: string
+                      enum: [red, green]
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str, body: &[u8]) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/text/body".to_string(),
+            operation: "post".to_string(),
+            body: body.to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[parameterized(content_type = {
+        "text/csv", "text/html", "text/html; charset=iso-8859-1", "text/x-colour"
+    }, body = {
+        b"a,b,c".as_slice(), b"<p>hi</p>".as_slice(), b"caf\xe9".as_slice(), b"red".as_slice()
+    })]
+    fn accept_a_valid_text_body(content_type: &str, body: &[u8]) {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(content_type, body))
+            .is_ok());
+    }
+
+    #[parameterized(content_type = {
+        "text/csv", "text/csv", "text/x-colour"
+    }, body = {
+        b"A,B".as_slice(), b"a,b,c,d,e".as_slice(), b"blue".as_slice()
+    })]
+    fn reject_an_invalid_text_body(content_type: &str, body: &[u8]) {
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(content_type, body))
+        );
+    }
+
+    #[parameterized(content_type = {
+        "text/html", "text/html; charset=us-ascii", "text/html"
+    }, body = {
+        b"caf\xe9".as_slice(), "café".as_bytes(), b"<p>\xf0\x9f".as_slice()
+    })]
+    fn report_the_offset_of_an_invalid_encoding(content_type: &str, body: &[u8]) {
+        assert_eq!(
+            Err(ValidationError::InvalidEncoding { offset: 3 }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(content_type, body))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_body_size_limits {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                  application/octet-stream:
+                    schema:
+                      type: string
+                      format: binary
+                      maxLength: 4
+                  text/plain:
+                    schema:
+                      type: string
+                      maxLength: 2
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/body".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[test]
+    fn reject_a_body_over_the_global_limit_before_parsing() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            max_body_size: Some(8),
+            ..Default::default()
+        });
+
+        assert!(validator
+            .validate_request(&make_request("application/json", "{}"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::BodyTooLarge { size: 10, limit: 8 }),
+            validator.validate_request(&make_request("application/json", "not json!!"))
+        );
+    }
+
+    #[test]
+    fn reject_a_binary_body_over_its_schema_max_length() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            pass_through_unsupported_media_types: true,
+            ..Default::default()
+        });
+
+        assert!(validator
+            .validate_request(&make_request("application/octet-stream", "abcd"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::BodyTooLarge { size: 5, limit: 4 }),
+            validator.validate_request(&make_request("application/octet-stream", "abcde"))
+        );
+    }
+
+    #[test]
+    fn allow_four_bytes_per_character_for_text_bodies() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+
+        assert!(validator
+            .validate_request(&make_request("text/plain", "éé"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::BodyTooLarge { size: 9, limit: 8 }),
+            validator.validate_request(&make_request("text/plain", "123456789"))
+        );
+    }
+
+    #[test]
+    fn apply_the_smaller_of_the_global_and_schema_limits() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            max_body_size: Some(3),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            Err(ValidationError::BodyTooLarge { size: 4, limit: 3 }),
+            validator.validate_request(&make_request("text/plain", "éé"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_json_syntax {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /json/body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(body: &[u8]) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/json/body".to_string(),
+            operation: "post".to_string(),
+            body: body.to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    #[test]
+    fn accept_a_json_body_starting_with_a_byte_order_mark() {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(b"\xef\xbb\xbf{}"))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_a_byte_order_mark_when_configured() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            reject_json_bom: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(ValidationError::InvalidJsonSyntax { line: 1, column: 1 }),
+            validator.validate_request(&make_request(b"\xef\xbb\xbf{}"))
+        );
+    }
+
+    #[parameterized(body = { b" ".as_slice(), b"\n\t \r\n".as_slice(), b"\xef\xbb\xbf  ".as_slice() })]
+    fn reject_a_whitespace_only_json_body(body: &[u8]) {
+        assert_eq!(
+            Err(ValidationError::EmptyJsonBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(body))
+        );
+    }
+
+    #[test]
+    fn report_the_position_of_a_syntax_error() {
+        assert_eq!(
+            Err(ValidationError::InvalidJsonSyntax {
+                line: 2,
+                column: 11
+            }),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&make_request(b"{\n  \"name\": }"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_streamed_bodies {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use crate::request::Request;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::io::Read;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required:
+                        - name
+                  text/plain:
+                    schema:
+                      type: string
+                      minLength: 2
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    /// Hands out its body a byte at a time, or fails part way through when `fail_after` is
+    /// set, and never exposes it through `body()`.
+    struct StreamedRequest {
+        content_type: &'static str,
+        body: Vec<u8>,
+        fail_after: Option<usize>,
+        read: Cell<usize>,
+    }
+
+    impl StreamedRequest {
+        fn new(content_type: &'static str, body: &[u8]) -> Self {
+            Self {
+                content_type,
+                body: body.to_vec(),
+                fail_after: None,
+                read: Cell::new(0),
+            }
+        }
+    }
+
+    struct Trickle<'request>(&'request StreamedRequest);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.0.read.get();
+            if self.0.fail_after == Some(read) {
+                return Err(std::io::ErrorKind::ConnectionReset.into());
+            }
+            match (self.0.body.get(read), buf.first_mut()) {
+                (Some(byte), Some(first)) => {
+                    *first = *byte;
+                    self.0.read.set(read + 1);
+                    Ok(1)
+                }
+                _ => Ok(0),
+            }
+        }
+    }
+
+    impl Request for StreamedRequest {
+        fn url(&self) -> &str {
+            "http://test.com/body"
+        }
+
+        fn operation(&self) -> &str {
+            "post"
+        }
+
+        fn body(&self) -> &[u8] {
+            &[]
+        }
+
+        fn body_stream(&self) -> Box<dyn Read + '_> {
+            Box::new(Trickle(self))
+        }
+
+        fn get_header(&self, key: &str) -> Option<String> {
+            (key == "Content-Type").then(|| self.content_type.to_string())
+        }
+    }
+
+    #[test]
+    fn validate_json_read_a_byte_at_a_time() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+
+        assert!(validator
+            .validate_request(&StreamedRequest::new(
+                "application/json",
+                br#"{"name": "Rex"}"#
+            ))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&StreamedRequest::new("application/json", b"{}"))
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidJsonSyntax { line: 2, column: 9 }),
+            validator
+                .validate_request(&StreamedRequest::new("application/json", b"{\n\"name\": }"))
+        );
+        assert_eq!(
+            Err(ValidationError::EmptyJsonBody),
+            validator.validate_request(&StreamedRequest::new("application/json", b" \n "))
+        );
+        assert!(validator
+            .validate_request(&StreamedRequest::new(
+                "application/json",
+                b"\xef\xbb\xbf{\"name\": \"Rex\"}"
+            ))
+            .is_ok());
+    }
+
+    #[parameterized(body = {
+        br#"{"name": "Rex"}"#.as_slice(),
+        b"{}".as_slice(),
+        b"{\n\"name\": }".as_slice(),
+        b" \n ".as_slice(),
+        b"\xef\xbb\xbf{\"name\": \"Rex\"}".as_slice(),
+        b"\xef\xbb{}".as_slice(),
+        b"\xef".as_slice(),
+    })]
+    fn validate_json_held_in_memory_as_when_streamed(body: &[u8]) {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let in_memory = FakeRequest {
+            url: "http://test.com/body".to_string(),
+            operation: "post".to_string(),
+            body: body.to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+
+        assert_eq!(
+            validator
+                .validate_request(&StreamedRequest::new("application/json", body))
+                .map(|_| ()),
+            validator.validate_request(&in_memory).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn decode_characters_split_across_reads() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+
+        assert!(validator
+            .validate_request(&StreamedRequest::new("text/plain", "né€😀".as_bytes()))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidEncoding { offset: 3 }),
+            validator.validate_request(&StreamedRequest::new("text/plain", b"n\xc3\xa9\xff"))
+        );
+    }
+
+    #[test]
+    fn stop_reading_once_the_size_limit_is_exceeded() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            max_body_size: Some(8),
+            ..Default::default()
+        });
+        let request = StreamedRequest::new("application/json", &[b' '; 1024]);
+
+        assert_eq!(
+            Err(ValidationError::BodyTooLarge { size: 9, limit: 8 }),
+            validator.validate_request(&request)
+        );
+        assert_eq!(9, request.read.get());
+    }
+
+    #[test]
+    fn reject_a_json_body_that_streams_nothing() {
+        assert_eq!(
+            Err(ValidationError::EmptyJsonBody),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&StreamedRequest::new("application/json", b""))
+        );
+    }
+
+    #[test]
+    fn report_a_stream_that_fails_part_way() {
+        let request = StreamedRequest {
+            fail_after: Some(4),
+            ..StreamedRequest::new("application/json", br#"{"name": "Rex"}"#)
+        };
+        assert_eq!(
+            Err(ValidationError::UnreadableBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&request)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_read_and_write_only_properties {
+    use crate::error::ValidationError;
+    use crate::options::{ReadOnlyProperties, ValidatorOptions};
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /users:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      $ref: '#/components/schemas/User'
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        $ref: '#/components/schemas/User'
+        components:
+          schemas:
+            Id:
+              type: integer
+              readOnly: true
+            User:
+              type: object
+              required: [id, name, password]
+              properties:
+                id:
+                  $ref: '#/components/schemas/Id'
+                name:
+                  type: string
+                password:
+                  type: string
+                  writeOnly: true
+        "#
+    );
+
+    fn post(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/users".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    fn respond(validator: &Validator, body: &str) -> Result<(), ValidationError> {
+        validator
+            .validate_request(&post(r#"{"name": "ada", "password": "secret"}"#))
+            .unwrap()
+            .validate_response(&FakeResponse {
+                status_code: 200,
+                headers: HashMap::from([(
+                    "Content-Type".to_string(),
+                    "application/json".to_string(),
+                )]),
+                body: body.as_bytes().to_vec(),
+            })
+    }
+
+    #[test]
+    fn read_only_properties_are_not_required_in_requests() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert!(validator
+            .validate_request(&post(r#"{"name": "ada", "password": "secret"}"#))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_read_only_properties_in_requests() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator
+                .validate_request(&post(r#"{"id": 1, "name": "ada", "password": "secret"}"#))
+                .map(|_| ())
+        );
+    }
+
+    #[parameterized(body = {
+        r#"{"id": 1, "name": "ada", "password": "secret"}"#,
+        r#"{"id": "not checked", "name": "ada", "password": "secret"}"#,
+    })]
+    fn strip_read_only_properties_when_configured(body: &str) {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            read_only_properties: ReadOnlyProperties::Strip,
+            ..Default::default()
+        });
+        assert!(validator.validate_request(&post(body)).is_ok());
+    }
+
+    #[test]
+    fn write_only_properties_are_not_required_in_responses() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(Ok(()), respond(&validator, r#"{"id": 1, "name": "ada"}"#));
+    }
+
+    #[test]
+    fn reject_write_only_properties_in_responses() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            respond(
+                &validator,
+                r#"{"id": 1, "name": "ada", "password": "secret"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn read_only_properties_stay_required_in_responses() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            respond(&validator, r#"{"name": "ada"}"#)
+        );
+    }
 }
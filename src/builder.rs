@@ -0,0 +1,249 @@
+use crate::options::ValidatorOptions;
+use crate::request::Validator;
+use crate::schema_cache::CacheStrategy;
+
+/// Configures a [`Validator`] one setting at a time, starting from the default options. Each
+/// setting maps onto a field of [`ValidatorOptions`], which [`ValidatorBuilder::options`]
+/// replaces wholesale, e.g. with [`ValidatorOptions::strict`].
+#[derive(Debug, Clone)]
+pub struct ValidatorBuilder {
+    api: openapiv3::OpenAPI,
+    options: ValidatorOptions,
+    schema_cache: CacheStrategy,
+    #[cfg(feature = "parallel")]
+    parallel: bool,
+}
+
+impl ValidatorBuilder {
+    pub fn new(api: openapiv3::OpenAPI) -> Self {
+        Self {
+            api,
+            options: ValidatorOptions::default(),
+            schema_cache: CacheStrategy::default(),
+            #[cfg(feature = "parallel")]
+            parallel: false,
+        }
+    }
+
+    /// Replaces every option set so far.
+    pub fn options(mut self, options: ValidatorOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Reads parameter values that are not valid JSON as strings instead of failing them.
+    pub fn coerce_parameter_strings(mut self, coerce: bool) -> Self {
+        self.options.coerce_parameter_strings = coerce;
+        self
+    }
+
+    /// Fails requests with query parameters the operation does not declare.
+    pub fn reject_unknown_query_parameters(mut self, reject: bool) -> Self {
+        self.options.reject_unknown_query_parameters = reject;
+        self
+    }
+
+    /// Checks string formats such as `date-time` and `uuid`, the default, or accepts any.
+    pub fn validate_formats(mut self, validate: bool) -> Self {
+        self.options.skip_format_validation = !validate;
+        self
+    }
+
+    /// Checks strings with `format: <name>` using `check`.
+    pub fn format(mut self, name: &'static str, check: fn(&str) -> bool) -> Self {
+        self.options.formats.insert(name, check);
+        self
+    }
+
+    /// Largest request body, in bytes, accepted for any operation.
+    pub fn max_body_size(mut self, limit: usize) -> Self {
+        self.options.max_body_size = Some(limit);
+        self
+    }
+
+    /// Fails requests that do not meet the operation's security requirements, instead of only
+    /// recording the outcome.
+    pub fn enforce_security(mut self, enforce: bool) -> Self {
+        self.options.enforce_security = enforce;
+        self
+    }
+
+    /// Routes requests by their whole URL path, as if the specification declared no `servers`.
+    pub fn ignore_servers(mut self, ignore: bool) -> Self {
+        self.options.ignore_servers = ignore;
+        self
+    }
+
+    /// Also requires the scheme, host and port of the request URL to match a server.
+    pub fn enforce_server_origin(mut self, enforce: bool) -> Self {
+        self.options.enforce_server_origin = enforce;
+        self
+    }
+
+    /// See [`Validator::with_schema_cache`].
+    pub fn schema_cache(mut self, strategy: CacheStrategy) -> Self {
+        self.schema_cache = strategy;
+        self
+    }
+
+    /// See [`Validator::new_parallel`].
+    #[cfg(feature = "parallel")]
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.parallel = parallel;
+        self
+    }
+
+    pub fn build(self) -> Validator {
+        #[cfg(feature = "parallel")]
+        let validator = match self.parallel {
+            true => Validator::new_parallel(self.api),
+            false => Validator::new(self.api),
+        };
+        #[cfg(not(feature = "parallel"))]
+        let validator = Validator::new(self.api);
+        validator
+            .with_options(self.options)
+            .with_schema_cache(self.schema_cache)
+    }
+}
+
+#[cfg(test)]
+mod test_builder {
+    use super::*;
+    use crate::error::ValidationError;
+    use crate::options::UnexpectedBody;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const SPEC: &str = indoc!(
+        "
+        openapi: 3.0.0
+        info:
+          title: test
+          version: 1.0.0
+        servers:
+          - url: http://test.com/api
+        paths:
+          /pets:
+            get:
+              parameters:
+                - in: query
+                  name: name
+                  schema:
+                    type: string
+                    format: uuid
+                - in: query
+                  name: tag
+                  schema:
+                    type: array
+                    items:
+                      type: string
+              responses:
+                200:
+                  description: ok
+        "
+    );
+
+    fn builder() -> ValidatorBuilder {
+        Validator::builder(serde_yaml::from_str(SPEC).unwrap())
+    }
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn build_a_validator_with_the_default_options() {
+        assert_eq!(&ValidatorOptions::default(), builder().build().options());
+    }
+
+    #[parameterized(coerce = {false, true}, expected = {
+        Err(ValidationError::InvalidParameter { name: "name".to_string() }), Ok(()),
+    })]
+    fn coerce_unquoted_parameter_strings(coerce: bool, expected: Result<(), ValidationError>) {
+        let validator = builder()
+            .coerce_parameter_strings(coerce)
+            .validate_formats(false)
+            .build();
+        assert_eq!(
+            expected,
+            validator
+                .validate_request(&get("http://test.com/api/pets?name=rex"))
+                .map(|_| ())
+        );
+    }
+
+    #[parameterized(query = {"tag=a", "tag=a,b", "tag=a&tag=b", r#"tag="a"&tag=b"#}, expected = {
+        vec!["a"], vec!["a", "b"], vec!["a", "b"], vec!["a", "b"],
+    })]
+    fn coerce_the_items_of_an_array_parameter(query: &str, expected: Vec<&str>) {
+        let validator = builder().coerce_parameter_strings(true).build();
+        let url = format!("http://test.com/api/pets?{query}");
+        let validated = validator.validate_request(&get(&url)).unwrap();
+        assert_eq!(
+            Some(expected.into_iter().map(String::from).collect()),
+            validated.query_parameter::<Vec<String>>("tag")
+        );
+    }
+
+    #[test]
+    fn reject_unknown_query_parameters() {
+        let validator = builder().reject_unknown_query_parameters(true).build();
+        assert_eq!(
+            Err(ValidationError::UnknownParameter {
+                name: "page".to_string()
+            }),
+            validator
+                .validate_request(&get("http://test.com/api/pets?page=2"))
+                .map(|_| ())
+        );
+    }
+
+    #[parameterized(validate = {true, false}, valid = {false, true})]
+    fn toggle_format_validation(validate: bool, valid: bool) {
+        let validator = builder().validate_formats(validate).build();
+        let request = get(r#"http://test.com/api/pets?name="rex""#);
+        assert_eq!(valid, validator.validate_request(&request).is_ok());
+    }
+
+    #[test]
+    fn route_by_the_whole_path_when_ignoring_servers() {
+        let validator = builder().ignore_servers(true).build();
+        assert!(validator
+            .validate_request(&get("http://test.com/pets"))
+            .is_ok());
+    }
+
+    #[test]
+    fn map_settings_onto_options() {
+        let options = ValidatorOptions {
+            max_body_size: Some(1024),
+            enforce_security: true,
+            enforce_server_origin: true,
+            ..Default::default()
+        };
+        let validator = builder()
+            .max_body_size(1024)
+            .enforce_security(true)
+            .enforce_server_origin(true)
+            .build();
+        assert_eq!(&options, validator.options());
+    }
+
+    #[test]
+    fn keep_options_set_after_replacing_them() {
+        let validator = builder()
+            .options(ValidatorOptions::strict())
+            .reject_unknown_query_parameters(true)
+            .build();
+        assert_eq!(UnexpectedBody::Reject, validator.options().unexpected_body);
+        assert!(validator.options().reject_unknown_query_parameters);
+    }
+}
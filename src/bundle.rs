@@ -0,0 +1,301 @@
+use serde_yaml::{Mapping, Value};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use crate::load::{read, LoadError};
+
+/// Sections of a document holding named, reusable objects. An object another file keeps in one
+/// of them moves to the same section of the bundled document rather than being inlined, so it
+/// can still be shared and refer to itself.
+const SECTIONS: [&str; 12] = [
+    "/components/schemas",
+    "/components/responses",
+    "/components/parameters",
+    "/components/examples",
+    "/components/requestBodies",
+    "/components/headers",
+    "/components/securitySchemes",
+    "/components/links",
+    "/components/callbacks",
+    "/definitions",
+    "/parameters",
+    "/responses",
+];
+
+/// Reads the specification at `path` and every file its references point into, returning a
+/// single self-contained document:
+/// - objects referenced from another file's components are added to the document's own
+///   components, renamed with a numeric suffix when the name is taken
+/// - anything else referenced in another file is inlined in place of the reference
+///
+/// References within the document itself are kept as they are.
+pub fn bundle(path: impl AsRef<Path>) -> Result<openapiv3::OpenAPI, LoadError> {
+    let path = path.as_ref();
+    let document = serde_yaml::from_str(&read(path)?).map_err(LoadError::Yaml)?;
+    serde_yaml::from_value(bundle_document(document, path)?).map_err(LoadError::Document)
+}
+
+/// Bundles a document already read from `path`, which its relative references are resolved
+/// against.
+pub(crate) fn bundle_document(mut document: Value, path: &Path) -> Result<Value, LoadError> {
+    let root = canonical(path)?;
+    let mut names: HashMap<&str, HashSet<String>> = HashMap::new();
+    for section in SECTIONS {
+        if let Some(Value::Mapping(objects)) = pointer(&document, section) {
+            let taken = objects.keys().filter_map(Value::as_str).map(String::from);
+            names.entry(section).or_default().extend(taken);
+        }
+    }
+
+    let mut bundler = Bundler {
+        files: HashMap::from([(root.clone(), document.clone())]),
+        root,
+        names,
+        rehomed: HashMap::new(),
+        inlining: vec![],
+        additions: vec![],
+    };
+    let root = bundler.root.clone();
+    bundler.walk(&mut document, &root)?;
+
+    for (section, name, object) in bundler.additions {
+        if let Some(objects) = section_mut(&mut document, section) {
+            objects.insert(name.into(), object);
+        }
+    }
+    Ok(document)
+}
+
+/// The mapping at a section of the document, created if it is missing.
+fn section_mut<'a>(document: &'a mut Value, section: &str) -> Option<&'a mut Mapping> {
+    section
+        .split('/')
+        .skip(1)
+        .try_fold(document, |value, token| {
+            let objects = value.as_mapping_mut()?;
+            Some(
+                objects
+                    .entry(token.into())
+                    .or_insert_with(|| Mapping::new().into()),
+            )
+        })?
+        .as_mapping_mut()
+}
+
+struct Bundler {
+    root: PathBuf,
+    /// Every file read so far, by canonical path.
+    files: HashMap<PathBuf, Value>,
+    /// The names in use in each section of the bundled document.
+    names: HashMap<&'static str, HashSet<String>>,
+    /// The reference that replaces each object already moved into the bundled document.
+    rehomed: HashMap<(PathBuf, String), String>,
+    /// The objects being inlined, innermost last, to detect ones that contain themselves.
+    inlining: Vec<(PathBuf, String)>,
+    /// Objects to add to the bundled document, with their section and name.
+    additions: Vec<(&'static str, String, Value)>,
+}
+
+impl Bundler {
+    /// Rewrites the references in `value`, which is part of `file`.
+    fn walk(&mut self, value: &mut Value, file: &Path) -> Result<(), LoadError> {
+        if let Some(reference) = value.get("$ref").and_then(Value::as_str) {
+            if let Some(replacement) = self.resolve(reference, file)? {
+                *value = replacement;
+                return Ok(());
+            }
+        }
+        match value {
+            Value::Mapping(mapping) => {
+                for (_, value) in mapping.iter_mut() {
+                    self.walk(value, file)?;
+                }
+            }
+            Value::Sequence(sequence) => {
+                for value in sequence.iter_mut() {
+                    self.walk(value, file)?;
+                }
+            }
+            _ => (),
+        }
+        Ok(())
+    }
+
+    /// What replaces a reference made from `file`, or `None` to keep it.
+    fn resolve(&mut self, reference: &str, file: &Path) -> Result<Option<Value>, LoadError> {
+        let (location, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+        let target = match location {
+            "" => file.to_path_buf(),
+            location => canonical(&file.parent().unwrap_or(Path::new("")).join(location))?,
+        };
+        if target == self.root {
+            return Ok(match file == self.root {
+                true => None,
+                false => Some(reference_to(&format!("#{fragment}"))),
+            });
+        }
+
+        let key = (target.clone(), fragment.to_string());
+        if let Some(rehomed) = self.rehomed.get(&key) {
+            return Ok(Some(reference_to(rehomed)));
+        }
+        let mut object = self.target(&target, fragment, reference)?;
+
+        match section(fragment) {
+            Some((section, name)) => {
+                let taken = self.names.entry(section).or_default();
+                let name = (1..)
+                    .map(|n| match n {
+                        1 => name.clone(),
+                        n => format!("{name}{n}"),
+                    })
+                    .find(|name| !taken.contains(name))
+                    .unwrap_or(name);
+                taken.insert(name.clone());
+                let rehomed = format!("#{section}/{}", escape(&name));
+                self.rehomed.insert(key, rehomed.clone());
+                self.walk(&mut object, &target)?;
+                self.additions.push((section, name, object));
+                Ok(Some(reference_to(&rehomed)))
+            }
+            None => {
+                if self.inlining.contains(&key) {
+                    return Err(LoadError::CyclicReference {
+                        path: file.to_path_buf(),
+                        reference: reference.to_string(),
+                    });
+                }
+                self.inlining.push(key);
+                self.walk(&mut object, &target)?;
+                self.inlining.pop();
+                Ok(Some(object))
+            }
+        }
+    }
+
+    /// The object at `fragment` in `file`, reading the file if it has not been yet.
+    fn target(&mut self, file: &Path, fragment: &str, reference: &str) -> Result<Value, LoadError> {
+        if !self.files.contains_key(file) {
+            let document = serde_yaml::from_str(&read(file)?).map_err(LoadError::Yaml)?;
+            self.files.insert(file.to_path_buf(), document);
+        }
+        pointer(&self.files[file], fragment)
+            .cloned()
+            .ok_or_else(|| LoadError::UnresolvedReference {
+                path: file.to_path_buf(),
+                reference: reference.to_string(),
+            })
+    }
+}
+
+fn canonical(path: &Path) -> Result<PathBuf, LoadError> {
+    path.canonicalize().map_err(|source| LoadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+fn reference_to(reference: &str) -> Value {
+    Value::Mapping(Mapping::from_iter([("$ref".into(), reference.into())]))
+}
+
+/// The section a fragment points into and the name of the object it points at, if it points
+/// at one directly.
+fn section(fragment: &str) -> Option<(&'static str, String)> {
+    let (parent, name) = fragment.rsplit_once('/')?;
+    let section = SECTIONS.into_iter().find(|section| *section == parent)?;
+    Some((section, unescape(name)))
+}
+
+/// Resolves a JSON pointer against a YAML document.
+fn pointer<'a>(document: &'a Value, fragment: &str) -> Option<&'a Value> {
+    if fragment.is_empty() {
+        return Some(document);
+    }
+    fragment
+        .strip_prefix('/')?
+        .split('/')
+        .try_fold(document, |value, token| {
+            let token = unescape(token);
+            match value {
+                Value::Sequence(sequence) => sequence.get(token.parse::<usize>().ok()?),
+                Value::Mapping(mapping) => mapping.get(token.as_str()).or_else(|| {
+                    // status codes read from YAML are numbers
+                    mapping.get(Value::from(token.parse::<u64>().ok()?))
+                }),
+                _ => None,
+            }
+        })
+}
+
+fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+#[cfg(test)]
+mod test_bundle {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use std::collections::HashMap;
+
+    #[test]
+    fn bundle_references_into_other_files() {
+        let api = bundle("./specs/bundle/openapi.yaml").unwrap();
+        let value = serde_json::to_value(&api).unwrap();
+        let schemas = &value["components"]["schemas"];
+
+        assert_eq!(
+            serde_json::json!({"$ref": "#/components/schemas/Pet2"}),
+            value["paths"]["/pets"]["post"]["requestBody"]["content"]["application/json"]["schema"]
+        );
+        assert_eq!(serde_json::json!("string"), schemas["Pet"]["type"]);
+        assert_eq!(
+            serde_json::json!({"$ref": "#/components/schemas/Pet2"}),
+            schemas["Pet2"]["properties"]["parent"]
+        );
+        assert_eq!(
+            serde_json::json!({"$ref": "#/components/schemas/Name"}),
+            schemas["Pet2"]["properties"]["name"]
+        );
+        assert_eq!(
+            serde_json::json!({"type": "integer", "minimum": 0}),
+            schemas["Pet2"]["properties"]["age"]
+        );
+    }
+
+    #[test]
+    fn validate_requests_against_a_bundled_specification() {
+        let validator = Validator::from_yaml_file("./specs/bundle/openapi.yaml").unwrap();
+        let request = |body: &str| FakeRequest {
+            url: "http://test.com/pets".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+
+        assert!(validator
+            .validate_request(&request(r#"{"name": "Rex", "parent": {"name": "Max"}}"#))
+            .is_ok());
+        assert!(validator
+            .validate_request(&request(r#"{"name": "Rex", "age": -1}"#))
+            .is_err());
+    }
+
+    #[test]
+    fn report_references_that_cannot_be_bundled() {
+        assert!(matches!(
+            bundle("./specs/bundle/cyclic.yaml"),
+            Err(LoadError::CyclicReference { .. })
+        ));
+        assert!(matches!(
+            bundle("./specs/bundle/dangling.yaml"),
+            Err(LoadError::UnresolvedReference { reference, .. })
+                if reference == "schemas.yaml#/components/schemas/Missing"
+        ));
+    }
+}
@@ -0,0 +1,340 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ValidationError;
+use crate::request::Request;
+use crate::response::Response;
+
+/// An individual response check. Names match the schemathesis checks of the same purpose, so
+/// configurations can be carried over from Python tooling.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum Check {
+    /// The response status code, or a range covering it, is documented.
+    StatusCodeConformance,
+    /// Headers documented for the response are present when required and match their schema.
+    ResponseHeadersConformance,
+    /// The response Content-Type is one of the documented media types.
+    ContentTypeConformance,
+    /// The response body matches the schema of its documented media type.
+    ResponseSchemaConformance,
+    /// The response arrived within the operation's `x-sla` latency or `x-timeout`.
+    ResponseTimeConformance,
+    /// Every `$response` runtime expression in the documented response's `links` resolves
+    /// against the response.
+    ResponseLinksConformance,
+}
+
+impl Check {
+    pub const ALL: [Check; 6] = [
+        Check::StatusCodeConformance,
+        Check::ResponseHeadersConformance,
+        Check::ContentTypeConformance,
+        Check::ResponseSchemaConformance,
+        Check::ResponseTimeConformance,
+        Check::ResponseLinksConformance,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Check::StatusCodeConformance => "status_code_conformance",
+            Check::ResponseHeadersConformance => "response_headers_conformance",
+            Check::ContentTypeConformance => "content_type_conformance",
+            Check::ResponseSchemaConformance => "response_schema_conformance",
+            Check::ResponseTimeConformance => "max_response_time",
+            Check::ResponseLinksConformance => "response_links_conformance",
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownCheck(pub String);
+
+impl fmt::Display for UnknownCheck {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown check '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownCheck {}
+
+impl FromStr for Check {
+    type Err = UnknownCheck;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Check::ALL
+            .into_iter()
+            .find(|check| check.name() == s)
+            .ok_or_else(|| UnknownCheck(s.to_string()))
+    }
+}
+
+/// Callbacks invoked while [`crate::request::Validator::run_checks`] works through an
+/// exchange. Every method defaults to doing nothing.
+pub trait CheckHooks {
+    fn pre_request(&mut self, _request: &dyn Request) {}
+    fn post_response(&mut self, _response: &dyn Response) {}
+    fn on_check(&mut self, _check: Check, _outcome: &Result<(), ValidationError>) {}
+}
+
+impl CheckHooks for () {}
+
+#[cfg(test)]
+mod test_checks {
+    use super::*;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                  headers:
+                    X-Total:
+                      required: true
+                      schema:
+                        type: integer
+                  content:
+                    application/json:
+                      schema:
+                        type: array
+        "#
+    );
+
+    fn get_things() -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/things".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn make_response(status_code: u16, headers: &[(&str, &str)], body: &str) -> FakeResponse {
+        FakeResponse {
+            status_code,
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordingHooks {
+        events: Vec<String>,
+    }
+
+    impl CheckHooks for RecordingHooks {
+        fn pre_request(&mut self, _request: &dyn Request) {
+            self.events.push("pre_request".to_string());
+        }
+
+        fn post_response(&mut self, _response: &dyn Response) {
+            self.events.push("post_response".to_string());
+        }
+
+        fn on_check(&mut self, check: Check, outcome: &Result<(), ValidationError>) {
+            self.events.push(format!(
+                "{check}: {}",
+                if outcome.is_ok() { "ok" } else { "failed" }
+            ));
+        }
+    }
+
+    #[parameterized(check = {
+        Check::StatusCodeConformance,
+        Check::ResponseHeadersConformance,
+        Check::ContentTypeConformance,
+        Check::ResponseSchemaConformance,
+        Check::ResponseTimeConformance,
+        Check::ResponseLinksConformance,
+    })]
+    fn check_names_round_trip(check: Check) {
+        assert_eq!(Ok(check), check.name().parse());
+    }
+
+    #[test]
+    fn reject_an_unknown_check_name() {
+        assert_eq!(
+            Err(UnknownCheck("not_a_check".to_string())),
+            "not_a_check".parse::<Check>()
+        );
+    }
+
+    #[test]
+    fn call_hooks_for_every_check() {
+        let mut hooks = RecordingHooks::default();
+        let response = make_response(
+            200,
+            &[("X-Total", "1"), ("Content-Type", "application/json")],
+            "[1]",
+        );
+
+        assert_eq!(
+            Ok(vec![]),
+            make_validator_from_spec(PATH_SPEC).run_checks(&get_things(), &response, &mut hooks)
+        );
+        assert_eq!(
+            vec![
+                "pre_request",
+                "post_response",
+                "status_code_conformance: ok",
+                "response_headers_conformance: ok",
+                "content_type_conformance: ok",
+                "response_schema_conformance: ok",
+                "max_response_time: ok",
+                "response_links_conformance: ok",
+            ],
+            hooks.events
+        );
+    }
+
+    #[test]
+    fn collect_every_failing_check() {
+        let response = make_response(200, &[("Content-Type", "application/json")], "{}");
+
+        assert_eq!(
+            Ok(vec![
+                (
+                    Check::ResponseHeadersConformance,
+                    ValidationError::MissingResponseHeader {
+                        name: "X-Total".to_string()
+                    }
+                ),
+                (
+                    Check::ResponseSchemaConformance,
+                    ValidationError::InvalidResponseBody
+                ),
+            ]),
+            make_validator_from_spec(PATH_SPEC).run_checks(&get_things(), &response, &mut ())
+        );
+    }
+
+    #[test]
+    fn report_an_undeclared_response_content_type() {
+        let response = make_response(200, &[("X-Total", "1"), ("Content-Type", "text/html")], "");
+
+        assert_eq!(
+            Ok(vec![(
+                Check::ContentTypeConformance,
+                ValidationError::UndeclaredResponseContentType {
+                    content_type: "text/html".to_string()
+                }
+            )]),
+            make_validator_from_spec(PATH_SPEC).run_checks(&get_things(), &response, &mut ())
+        );
+    }
+
+    #[test]
+    fn skip_remaining_checks_for_an_undocumented_status_code() {
+        let response = make_response(404, &[], "");
+
+        assert_eq!(
+            Ok(vec![(
+                Check::StatusCodeConformance,
+                ValidationError::UndocumentedStatusCode {
+                    status_code: 404,
+                    nearest: vec!["200".to_string()]
+                }
+            )]),
+            make_validator_from_spec(PATH_SPEC).run_checks(&get_things(), &response, &mut ())
+        );
+    }
+
+    #[test]
+    fn skip_disabled_checks() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            disabled_checks: vec![
+                Check::ResponseHeadersConformance,
+                Check::ResponseSchemaConformance,
+            ],
+            ..Default::default()
+        });
+        let response = make_response(200, &[("Content-Type", "application/json")], "{}");
+
+        assert!(validator
+            .validate_request(&get_things())
+            .unwrap()
+            .validate_response(&response)
+            .is_ok());
+    }
+
+    struct TimedResponse {
+        response: FakeResponse,
+        elapsed: std::time::Duration,
+    }
+
+    impl Response for TimedResponse {
+        fn status_code(&self) -> u16 {
+            self.response.status_code()
+        }
+
+        fn get_header(&self, key: &str) -> Option<String> {
+            self.response.get_header(key)
+        }
+
+        fn body(&self) -> &[u8] {
+            self.response.body()
+        }
+
+        fn elapsed(&self) -> Option<std::time::Duration> {
+            Some(self.elapsed)
+        }
+    }
+
+    #[parameterized(extension = {
+        "x-sla: { latency: 250ms }",
+        "x-timeout: 250ms",
+    })]
+    fn flag_a_response_over_the_latency_budget(extension: &str) {
+        let path_spec = format!(
+            indoc!(
+                r#"
+                paths:
+                  /things:
+                    get:
+                      {}
+                      responses:
+                        200:
+                          description: API call successful
+                "#
+            ),
+            extension
+        );
+        let validator = make_validator_from_spec(&path_spec);
+        let timed = |milliseconds| TimedResponse {
+            response: make_response(200, &[], ""),
+            elapsed: std::time::Duration::from_millis(milliseconds),
+        };
+
+        assert_eq!(
+            Ok(vec![]),
+            validator.run_checks(&get_things(), &timed(250), &mut ())
+        );
+        assert_eq!(
+            Ok(vec![(
+                Check::ResponseTimeConformance,
+                ValidationError::SlowResponse {
+                    elapsed: std::time::Duration::from_millis(300),
+                    budget: std::time::Duration::from_millis(250),
+                }
+            )]),
+            validator.run_checks(&get_things(), &timed(300), &mut ())
+        );
+    }
+}
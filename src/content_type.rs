@@ -1,40 +1,228 @@
-use super::body::BodyValidator;
+use super::body::{BodyValidator, TextCharset};
+use crate::error::ValidationError;
+use crate::form::FormEncoding;
 use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::{spec_pointer, SchemaLocation};
+use crate::method::Method;
+use crate::options::{ContentTypeFallback, ReadOnlyProperties, UnexpectedBody, ValidatorOptions};
+use crate::schema_cache::CompiledSchemas;
+use crate::to_jsonschema::Direction;
 
 pub struct ContentTypeValidator<'api> {
+    pub method: Method,
     pub operation_spec: &'api openapiv3::Operation,
     pub components: &'api Option<openapiv3::Components>,
+    pub options: &'api ValidatorOptions,
+    pub(crate) schemas: &'api CompiledSchemas,
+    /// JSON pointer to the operation, for locating its body schemas.
+    pub pointer: String,
 }
 
 impl<'api> ContentTypeValidator<'api> {
     pub fn validate_content_type(
-        self,
+        &self,
         content_type: Option<String>,
-    ) -> Result<BodyValidator<'api>, ()> {
+        body_is_empty: bool,
+    ) -> Result<BodyValidator<'api>, ValidationError> {
         let body_spec = match &self.operation_spec.request_body {
-            Some(body_spec) => body_spec.item_or_fetch(self.components),
-            None => return Ok(BodyValidator::NoSpecification),
+            Some(body_spec) => body_spec.item_or_fetch(self.components)?,
+            None => {
+                return Ok(BodyValidator::NoSpecification {
+                    reject_body: match self.options.unexpected_body {
+                        UnexpectedBody::Warn => false,
+                        UnexpectedBody::RejectForBodylessMethods => self.method.is_bodyless(),
+                        UnexpectedBody::Reject => true,
+                    },
+                })
+            }
         };
 
-        match content_type {
-            Some(content_type) if body_spec.content.contains_key(&content_type) => {
-                match content_type.as_str() {
-                    "application/json" => Ok(BodyValidator::JSONBody {
-                        body_spec,
-                        components: self.components,
-                    }),
-                    "text/plain; charset=utf-8" => Ok(BodyValidator::PlainUTF8Body),
-                    _ => Err(()),
+        let content_type = match content_type {
+            Some(content_type) => content_type,
+            None if body_is_empty => return Ok(BodyValidator::EmptyContentType { body_spec }),
+            None => match &self.options.content_type_fallback {
+                ContentTypeFallback::SingleDeclared if body_spec.content.len() == 1 => {
+                    body_spec.content.keys().next().cloned().unwrap_or_default()
                 }
+                ContentTypeFallback::Assume(content_type) => content_type.clone(),
+                _ => return Ok(BodyValidator::EmptyContentType { body_spec }),
+            },
+        };
+        let Ok(mime) = content_type.parse::<mime::Mime>() else {
+            return Err(ValidationError::UndeclaredContentType { content_type });
+        };
+        let Some((range, media_type)) = find_media_type(&body_spec.content, &content_type, &mime)
+        else {
+            return Err(ValidationError::UndeclaredContentType { content_type });
+        };
+        let schema = SchemaLocation {
+            options: self.options,
+            schemas: self.schemas,
+            pointer: spec_pointer(
+                &match &self.operation_spec.request_body {
+                    Some(openapiv3::ReferenceOr::Reference { reference }) => reference.clone(),
+                    _ => spec_pointer(&self.pointer, ["requestBody"]),
+                },
+                ["content", range, "schema"],
+            ),
+            direction: Direction::Request {
+                strip_read_only: self.options.read_only_properties == ReadOnlyProperties::Strip,
+            },
+        };
+
+        if is_json(&mime) {
+            return Ok(BodyValidator::JSONBody {
+                media_type,
+                strip_bom: !self.options.reject_json_bom,
+                schema,
+                components: self.components,
+            });
+        }
+        if let Some(encoding) = form_encoding(&mime) {
+            return Ok(BodyValidator::FormBody {
+                media_type,
+                encoding,
+                schema,
+                components: self.components,
+            });
+        }
+        match TextCharset::from_mime(&mime) {
+            Some(charset) => Ok(BodyValidator::TextBody {
+                media_type,
+                charset,
+                schema,
+                components: self.components,
+            }),
+            None if self.options.pass_through_unsupported_media_types => {
+                Ok(BodyValidator::Unsupported {
+                    content_type: mime,
+                    media_type,
+                    components: self.components,
+                })
             }
-            Some(_) => Err(()),
-            None => Ok(BodyValidator::EmptyContentType { body_spec }),
+            None => Err(ValidationError::UnsupportedMediaTypeHandler { content_type }),
+        }
+    }
+}
+
+fn form_encoding(mime: &mime::Mime) -> Option<FormEncoding> {
+    match (mime.type_(), mime.subtype()) {
+        (mime::APPLICATION, mime::WWW_FORM_URLENCODED) => Some(FormEncoding::UrlEncoded),
+        (mime::MULTIPART, mime::FORM_DATA) => Some(FormEncoding::Multipart {
+            boundary: mime
+                .get_param(mime::BOUNDARY)
+                .map(|boundary| boundary.to_string()),
+        }),
+        _ => None,
+    }
+}
+
+/// `application/json` or any media type with the `+json` structured syntax suffix, such as
+/// `application/problem+json`.
+pub(crate) fn is_json(mime: &mime::Mime) -> bool {
+    (mime.type_(), mime.subtype()) == (mime::APPLICATION, mime::JSON)
+        || mime.suffix() == Some(mime::JSON)
+}
+
+/// Selects the content entry for a request content type, preferring the most specific
+/// media range: an exact key, then a subtype wildcard such as `application/*`, then `*/*`.
+///
+/// Parameters on the request content type are ignored, so `application/json; charset=utf-8`
+/// matches an `application/json` entry.
+pub(crate) fn find_media_type<'api>(
+    content: &'api indexmap::IndexMap<String, openapiv3::MediaType>,
+    content_type: &str,
+    mime: &mime::Mime,
+) -> Option<(&'api str, &'api openapiv3::MediaType)> {
+    if let Some((range, media_type)) = content.get_key_value(content_type) {
+        return Some((range, media_type));
+    }
+
+    let mut best: Option<(u8, &str, &openapiv3::MediaType)> = None;
+    for (key, media_type) in content {
+        let Ok(range) = key.parse::<mime::Mime>() else {
+            continue;
+        };
+        let specificity = if range.essence_str() == mime.essence_str() {
+            3
+        } else if range.type_() == mime.type_() && range.subtype() == mime::STAR {
+            2
+        } else if range.type_() == mime::STAR && range.subtype() == mime::STAR {
+            1
+        } else {
+            continue;
+        };
+        if best.is_none_or(|(current, ..)| specificity > current) {
+            best = Some((specificity, key, media_type));
         }
     }
+    best.map(|(_, key, media_type)| (key, media_type))
+}
+
+/// Fails with a not acceptable error when none of the media types declared across an
+/// operation's responses satisfies the request's `Accept` header. Operations declaring no
+/// response content accept any header.
+pub(crate) fn validate_accept(
+    accept: &str,
+    responses: &openapiv3::Responses,
+    components: &Option<openapiv3::Components>,
+) -> Result<(), ValidationError> {
+    let responses = responses
+        .default
+        .iter()
+        .chain(responses.responses.values())
+        .map(|response| response.item_or_fetch(components))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut declared = responses
+        .iter()
+        .flat_map(|response| response.content.keys())
+        .peekable();
+    if declared.peek().is_none() || declared.any(|media_type| is_acceptable(accept, media_type)) {
+        return Ok(());
+    }
+    Err(ValidationError::NotAcceptable {
+        accept: accept.to_string(),
+    })
+}
+
+/// Whether an `Accept` header admits a media type, or a media range such as `image/*`.
+/// Parameters other than the `q` weight are ignored, and a range weighted `q=0` refuses the
+/// media type it names exactly. A header with no parseable range admits everything.
+pub(crate) fn is_acceptable(accept: &str, media_type: &str) -> bool {
+    let Ok(media_type) = media_type.parse::<mime::Mime>() else {
+        return false;
+    };
+    let ranges: Vec<(mime::Mime, bool)> = accept
+        .split(',')
+        .filter_map(|range| range.trim().parse::<mime::Mime>().ok())
+        .map(|range| {
+            let refused = range
+                .get_param("q")
+                .is_some_and(|q| q.as_str().parse::<f32>() == Ok(0.0));
+            (range, refused)
+        })
+        .collect();
+    if ranges.is_empty() {
+        return true;
+    }
+
+    let matches = |left: &mime::Name, right: &mime::Name| {
+        left == right || *left == mime::STAR || *right == mime::STAR
+    };
+    let refused = ranges
+        .iter()
+        .any(|(range, refused)| *refused && range.essence_str() == media_type.essence_str());
+    !refused
+        && ranges.iter().any(|(range, refused)| {
+            !refused
+                && matches(&range.type_(), &media_type.type_())
+                && matches(&range.subtype(), &media_type.subtype())
+        })
 }
 
 #[cfg(test)]
 mod test_content_type {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
     use std::collections::HashMap;
@@ -64,7 +252,9 @@ mod test_content_type {
             )]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::UndeclaredContentType {
+                content_type: "text/plain; charset=utf-8".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -94,7 +284,9 @@ mod test_content_type {
             )]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::UndeclaredContentType {
+                content_type: "text/plain; charset=utf-8".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -126,7 +318,7 @@ mod test_content_type {
             headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidJsonSyntax { line: 1, column: 1 }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -165,3 +357,620 @@ mod test_content_type {
             .is_ok());
     }
 }
+
+#[cfg(test)]
+mod test_unsupported_media_types {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::report::ValidationWarning;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /xml/body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/xml:
+                    schema:
+                      type: object
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/xml/body".to_string(),
+            operation: "post".to_string(),
+            body: "<thing/>".as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[test]
+    fn reject_a_content_type_missing_from_the_spec() {
+        assert_eq!(
+            Err(ValidationError::UndeclaredContentType {
+                content_type: "application/yaml".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request("application/yaml"))
+        );
+    }
+
+    #[test]
+    fn reject_a_declared_content_type_without_handler() {
+        assert_eq!(
+            Err(ValidationError::UnsupportedMediaTypeHandler {
+                content_type: "application/xml".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request("application/xml"))
+        );
+    }
+
+    #[test]
+    fn pass_through_a_declared_content_type_without_handler_when_configured() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            pass_through_unsupported_media_types: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            vec![ValidationWarning::UnvalidatedBody {
+                content_type: "application/xml".to_string()
+            }],
+            validator
+                .validate_request(&make_request("application/xml"))
+                .unwrap()
+                .report
+                .warnings
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_media_ranges {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    fn make_request(content_type: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/ranged/body".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[test]
+    fn validate_against_a_subtype_wildcard_entry() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /ranged/body:
+                post:
+                  requestBody:
+                    required: true
+                    content:
+                      application/*:
+                        schema:
+                          type: object
+                          required:
+                            - name
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let validator = make_validator_from_spec(path_spec);
+        assert!(validator
+            .validate_request(&make_request("application/json", r#"{"name": "thing"}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request("application/json", r#"{}"#))
+        );
+    }
+
+    #[test]
+    fn accept_any_content_type_with_a_full_wildcard_entry() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /ranged/body:
+                post:
+                  requestBody:
+                    required: true
+                    content:
+                      "*/*": {}
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        assert!(make_validator_from_spec(path_spec)
+            .validate_request(&make_request("text/plain; charset=utf-8", "hello"))
+            .is_ok());
+    }
+
+    #[test]
+    fn prefer_the_most_specific_media_range() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /ranged/body:
+                post:
+                  requestBody:
+                    required: true
+                    content:
+                      "*/*":
+                        schema:
+                          type: string
+                      application/*:
+                        schema:
+                          type: array
+                      application/json:
+                        schema:
+                          type: object
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let validator = make_validator_from_spec(path_spec);
+        assert!(validator
+            .validate_request(&make_request("application/json", r#"{}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request("application/json", r#"[]"#))
+        );
+    }
+
+    #[test]
+    fn reject_a_content_type_outside_a_subtype_wildcard() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /ranged/body:
+                post:
+                  requestBody:
+                    required: true
+                    content:
+                      application/*: {}
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        assert_eq!(
+            Err(ValidationError::UndeclaredContentType {
+                content_type: "text/plain; charset=utf-8".to_string()
+            }),
+            make_validator_from_spec(path_spec)
+                .validate_request(&make_request("text/plain; charset=utf-8", "hello"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_content_type_parameters {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::report::ValidationWarning;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /parameterised/body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                  text/plain:
+                    schema:
+                      type: string
+                  multipart/mixed: {}
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/parameterised/body".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[parameterized(content_type = {
+        "application/json; charset=utf-8",
+        "application/json;charset=UTF-8",
+        "Application/JSON",
+    })]
+    fn accept_a_json_content_type_with_parameters(content_type: &str) {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(content_type, "{}"))
+            .is_ok());
+    }
+
+    #[parameterized(content_type = {
+        "text/plain",
+        "text/plain; charset=utf-8",
+        "text/plain; charset=us-ascii",
+    })]
+    fn accept_a_plain_text_content_type_with_utf8_compatible_charset(content_type: &str) {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(content_type, "hello"))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_a_plain_text_content_type_with_an_unknown_charset() {
+        assert_eq!(
+            Err(ValidationError::UnsupportedMediaTypeHandler {
+                content_type: "text/plain; charset=shift_jis".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&make_request("text/plain; charset=shift_jis", "hello"))
+        );
+    }
+
+    #[test]
+    fn reject_an_unparseable_content_type() {
+        assert_eq!(
+            Err(ValidationError::UndeclaredContentType {
+                content_type: "json".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request("json", "{}"))
+        );
+    }
+
+    #[test]
+    fn keep_the_boundary_parameter_for_unsupported_bodies() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            pass_through_unsupported_media_types: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            vec![ValidationWarning::UnvalidatedBody {
+                content_type: "multipart/mixed; boundary=xyz".to_string()
+            }],
+            validator
+                .validate_request(&make_request("multipart/mixed; boundary=xyz", "--xyz--"))
+                .unwrap()
+                .report
+                .warnings
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_content_type_fallback {
+    use crate::error::ValidationError;
+    use crate::options::{ContentTypeFallback, ValidatorOptions};
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const SINGLE_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required:
+                        - name
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    const MULTIPLE_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /body:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                  text/plain:
+                    schema:
+                      type: string
+                      maxLength: 3
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/body".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::new(),
+        }
+    }
+
+    fn with_fallback(spec: &str, fallback: ContentTypeFallback) -> crate::request::Validator {
+        make_validator_from_spec(spec).with_options(ValidatorOptions {
+            content_type_fallback: fallback,
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn reject_a_body_without_content_type_by_default() {
+        assert_eq!(
+            Err(ValidationError::MissingContentType),
+            make_validator_from_spec(SINGLE_SPEC).validate_request(&make_request("{}"))
+        );
+    }
+
+    #[test]
+    fn validate_against_the_single_declared_content_entry() {
+        let validator = with_fallback(SINGLE_SPEC, ContentTypeFallback::SingleDeclared);
+
+        assert!(validator
+            .validate_request(&make_request(r#"{"name": "thing"}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request("{}"))
+        );
+    }
+
+    #[test]
+    fn keep_rejecting_when_several_content_entries_are_declared() {
+        assert_eq!(
+            Err(ValidationError::MissingContentType),
+            with_fallback(MULTIPLE_SPEC, ContentTypeFallback::SingleDeclared)
+                .validate_request(&make_request("{}"))
+        );
+    }
+
+    #[test]
+    fn validate_against_an_assumed_content_type() {
+        let validator = with_fallback(
+            MULTIPLE_SPEC,
+            ContentTypeFallback::Assume("text/plain".to_string()),
+        );
+
+        assert!(validator.validate_request(&make_request("abc")).is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request("abcd"))
+        );
+    }
+
+    #[test]
+    fn still_require_a_body_with_a_fallback() {
+        assert_eq!(
+            Err(ValidationError::MissingBody),
+            with_fallback(SINGLE_SPEC, ContentTypeFallback::SingleDeclared)
+                .validate_request(&make_request(""))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_json_suffix {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /suffixed/body:
+            patch:
+              requestBody:
+                required: true
+                content:
+                  application/vnd.api+json:
+                    schema:
+                      type: object
+                      required: [data]
+                  application/merge-patch+json:
+                    schema:
+                      type: object
+                      required: [data]
+                  application/problem+json:
+                    schema:
+                      type: object
+                      required: [data]
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/suffixed/body".to_string(),
+            operation: "patch".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[parameterized(content_type = {
+        "application/vnd.api+json",
+        "application/merge-patch+json",
+        "application/problem+json; charset=utf-8",
+    })]
+    fn validate_a_json_suffixed_body_against_its_schema(content_type: &str) {
+        let validator = make_validator_from_spec(PATH_SPEC);
+
+        assert!(validator
+            .validate_request(&make_request(content_type, r#"{"data": 1}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request(content_type, "{}"))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_accept {
+    use super::is_acceptable;
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /reports:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                    text/csv:
+                      schema:
+                        type: string
+                default:
+                  $ref: '#/components/responses/Problem'
+          /health:
+            get:
+              responses:
+                204:
+                  description: Healthy
+        components:
+          responses:
+            Problem:
+              description: Unexpected error
+              content:
+                application/problem+json:
+                  schema:
+                    type: object
+        "#
+    );
+
+    fn make_request(url: &str, accept: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::from([("Accept".to_string(), accept.to_string())]),
+        }
+    }
+
+    #[parameterized(accept = {
+        "application/json",
+        "text/*",
+        "*/*",
+        "application/problem+json",
+        "text/html, application/json;q=0.5",
+        "not a media type",
+    })]
+    fn accept_a_request_a_documented_response_can_satisfy(accept: &str) {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request("http://test.com/reports", accept))
+            .is_ok());
+    }
+
+    #[parameterized(accept = {"text/html", "image/*", "text/csv;q=0, application/xml"})]
+    fn reject_a_request_no_documented_response_can_satisfy(accept: &str) {
+        assert_eq!(
+            Err(ValidationError::NotAcceptable {
+                accept: accept.to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&make_request("http://test.com/reports", accept))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn accept_any_header_for_an_operation_without_response_content() {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request("http://test.com/health", "text/html"))
+            .is_ok());
+    }
+
+    #[parameterized(accept = {
+        "text/csv", "text/csv;q=0", "text/*;q=0.1", "*/*", "application/json, text/csv;q=0",
+    }, acceptable = {
+        true, false, true, true, false,
+    })]
+    fn match_media_types_against_accepted_ranges(accept: &str, acceptable: bool) {
+        assert_eq!(acceptable, is_acceptable(accept, "text/csv"));
+    }
+
+    #[test]
+    fn match_declared_media_ranges() {
+        assert!(is_acceptable("image/png", "image/*"));
+        assert!(!is_acceptable("text/plain", "image/*"));
+    }
+
+    fn respond_with(validator: &crate::request::Validator, content_type: &str) -> bool {
+        let response = FakeResponse {
+            status_code: 200,
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+            body: "{}".as_bytes().to_vec(),
+        };
+        validator
+            .validate_request(&make_request("http://test.com/reports", "application/json"))
+            .unwrap()
+            .validate_response(&response)
+            == Err(ValidationError::NotAcceptable {
+                accept: "application/json".to_string(),
+            })
+    }
+
+    #[test]
+    fn reject_a_response_the_request_does_not_accept_when_configured() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            reject_unacceptable_responses: true,
+            ..Default::default()
+        });
+        assert!(respond_with(&validator, "text/csv"));
+        assert!(!respond_with(&validator, "application/json"));
+    }
+
+    #[test]
+    fn accept_a_response_the_request_does_not_accept_by_default() {
+        assert!(!respond_with(
+            &make_validator_from_spec(PATH_SPEC),
+            "text/csv"
+        ));
+    }
+}
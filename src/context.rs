@@ -0,0 +1,155 @@
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Source of the current time for checks that depend on it.
+pub trait Clock: Send + Sync {
+    fn now(&self) -> SystemTime;
+
+    /// Time since a fixed point, which unlike [`Clock::now`] never jumps when the wall clock is
+    /// set, for measuring intervals. Defaults to the time since it was first asked for.
+    fn elapsed(&self) -> Duration {
+        static START: OnceLock<Instant> = OnceLock::new();
+        START.get_or_init(Instant::now).elapsed()
+    }
+}
+
+/// The operating system's wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests.
+#[derive(Debug)]
+pub struct FixedClock {
+    /// The wall clock time, and how far the clock has been advanced in all.
+    time: Mutex<(SystemTime, Duration)>,
+}
+
+impl FixedClock {
+    pub fn new(now: SystemTime) -> Self {
+        Self {
+            time: Mutex::new((now, Duration::ZERO)),
+        }
+    }
+
+    /// Sets the wall clock time, leaving [`Clock::elapsed`] where it was.
+    pub fn set(&self, now: SystemTime) {
+        self.time.lock().unwrap_or_else(PoisonError::into_inner).0 = now;
+    }
+
+    pub fn advance(&self, by: Duration) {
+        let mut time = self.time.lock().unwrap_or_else(PoisonError::into_inner);
+        time.0 += by;
+        time.1 += by;
+    }
+}
+
+impl Clock for FixedClock {
+    fn now(&self) -> SystemTime {
+        self.time.lock().unwrap_or_else(PoisonError::into_inner).0
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.time.lock().unwrap_or_else(PoisonError::into_inner).1
+    }
+}
+
+/// The clock a validator runs with. Defaults to the system clock; inject a fixed one to make
+/// behaviour reproducible. Mock responses are derived from the specification alone, so they
+/// are reproducible without a random number generator.
+#[derive(Clone)]
+pub struct ValidationContext {
+    clock: Arc<dyn Clock>,
+}
+
+impl ValidationContext {
+    pub fn new(clock: impl Clock + 'static) -> Self {
+        Self {
+            clock: Arc::new(clock),
+        }
+    }
+
+    /// Shares a clock that the caller keeps a handle to, e.g. to advance a [`FixedClock`].
+    pub fn with_shared_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub fn with_clock(self, clock: impl Clock + 'static) -> Self {
+        self.with_shared_clock(Arc::new(clock))
+    }
+
+    pub fn now(&self) -> SystemTime {
+        self.clock.now()
+    }
+
+    /// See [`Clock::elapsed`].
+    pub fn elapsed(&self) -> Duration {
+        self.clock.elapsed()
+    }
+}
+
+impl Default for ValidationContext {
+    /// The system clock, shared by every default context.
+    fn default() -> Self {
+        static SYSTEM: OnceLock<Arc<dyn Clock>> = OnceLock::new();
+        Self {
+            clock: SYSTEM.get_or_init(|| Arc::new(SystemClock)).clone(),
+        }
+    }
+}
+
+impl fmt::Debug for ValidationContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationContext").finish_non_exhaustive()
+    }
+}
+
+/// Contexts are equal when they share the same clock, as default contexts do.
+impl PartialEq for ValidationContext {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.clock, &other.clock)
+    }
+}
+
+#[cfg(test)]
+mod test_context {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn fixed_clock_only_moves_when_advanced() {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let clock = Arc::new(FixedClock::new(start));
+        let context = ValidationContext::default().with_shared_clock(clock.clone());
+
+        assert_eq!(start, context.now());
+        clock.advance(Duration::from_secs(5));
+        assert_eq!(start + Duration::from_secs(5), context.now());
+    }
+
+    #[test]
+    fn setting_the_wall_clock_leaves_elapsed_time_alone() {
+        let clock = FixedClock::new(UNIX_EPOCH + Duration::from_secs(60));
+        clock.advance(Duration::from_secs(5));
+        clock.set(UNIX_EPOCH);
+
+        assert_eq!(UNIX_EPOCH, clock.now());
+        assert_eq!(Duration::from_secs(5), clock.elapsed());
+    }
+
+    #[test]
+    fn default_contexts_are_equal() {
+        assert_eq!(ValidationContext::default(), ValidationContext::default());
+        assert_ne!(
+            ValidationContext::default(),
+            ValidationContext::new(SystemClock)
+        );
+    }
+}
@@ -0,0 +1,913 @@
+use indexmap::IndexMap;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::spec_pointer;
+use crate::method::Method;
+use crate::to_jsonschema::{schema_document, DEFINITIONS};
+
+/// A difference between two versions of a specification.
+#[derive(Debug, PartialEq, Clone)]
+pub enum ChangeKind {
+    PathAdded,
+    PathRemoved,
+    OperationAdded,
+    OperationRemoved,
+    ParameterAdded {
+        name: String,
+        location: String,
+        required: bool,
+    },
+    ParameterRemoved {
+        name: String,
+        location: String,
+    },
+    /// An optional parameter became required.
+    ParameterRequired {
+        name: String,
+        location: String,
+    },
+    /// A required parameter became optional.
+    ParameterOptional {
+        name: String,
+        location: String,
+    },
+    RequestBodyAdded {
+        required: bool,
+    },
+    RequestBodyRemoved,
+    RequestBodyRequired,
+    RequestBodyOptional,
+    MediaTypeAdded {
+        media_type: String,
+    },
+    MediaTypeRemoved {
+        media_type: String,
+    },
+    ResponseAdded {
+        status: String,
+    },
+    ResponseRemoved {
+        status: String,
+    },
+    TypeChanged {
+        from: Option<Value>,
+        to: Option<Value>,
+    },
+    /// A keyword now accepts fewer values, e.g. a lower `maxLength` or a new `pattern`.
+    Narrowed {
+        keyword: String,
+    },
+    /// A keyword now accepts more values, e.g. a lower `minimum` or a removed `pattern`.
+    Widened {
+        keyword: String,
+    },
+    PropertyRequired {
+        name: String,
+    },
+    PropertyOptional {
+        name: String,
+    },
+    EnumValueAdded {
+        value: Value,
+    },
+    EnumValueRemoved {
+        value: Value,
+    },
+}
+
+/// A change found by [`diff`], at a pointer into the new specification, or into the old one
+/// for what was removed. A change is breaking when clients written against the old
+/// specification may no longer work: requests they send may be rejected or responses they
+/// receive may not be understood.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Change {
+    pub pointer: String,
+    pub kind: ChangeKind,
+    pub breaking: bool,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let severity = match self.breaking {
+            true => "breaking",
+            false => "non-breaking",
+        };
+        write!(f, "{}: {severity}: ", self.pointer)?;
+        match &self.kind {
+            ChangeKind::PathAdded => write!(f, "path added"),
+            ChangeKind::PathRemoved => write!(f, "path removed"),
+            ChangeKind::OperationAdded => write!(f, "operation added"),
+            ChangeKind::OperationRemoved => write!(f, "operation removed"),
+            ChangeKind::ParameterAdded {
+                name,
+                location,
+                required,
+            } => match required {
+                true => write!(f, "required {location} parameter '{name}' added"),
+                false => write!(f, "optional {location} parameter '{name}' added"),
+            },
+            ChangeKind::ParameterRemoved { name, location } => {
+                write!(f, "{location} parameter '{name}' removed")
+            }
+            ChangeKind::ParameterRequired { name, location } => {
+                write!(f, "{location} parameter '{name}' became required")
+            }
+            ChangeKind::ParameterOptional { name, location } => {
+                write!(f, "{location} parameter '{name}' became optional")
+            }
+            ChangeKind::RequestBodyAdded { required: true } => {
+                write!(f, "required request body added")
+            }
+            ChangeKind::RequestBodyAdded { required: false } => {
+                write!(f, "optional request body added")
+            }
+            ChangeKind::RequestBodyRemoved => write!(f, "request body removed"),
+            ChangeKind::RequestBodyRequired => write!(f, "request body became required"),
+            ChangeKind::RequestBodyOptional => write!(f, "request body became optional"),
+            ChangeKind::MediaTypeAdded { media_type } => {
+                write!(f, "media type '{media_type}' added")
+            }
+            ChangeKind::MediaTypeRemoved { media_type } => {
+                write!(f, "media type '{media_type}' removed")
+            }
+            ChangeKind::ResponseAdded { status } => write!(f, "response {status} added"),
+            ChangeKind::ResponseRemoved { status } => write!(f, "response {status} removed"),
+            ChangeKind::TypeChanged { from, to } => {
+                let describe = |value: &Option<Value>| match value {
+                    Some(value) => value.to_string(),
+                    None => "any".to_string(),
+                };
+                write!(
+                    f,
+                    "type changed from {} to {}",
+                    describe(from),
+                    describe(to)
+                )
+            }
+            ChangeKind::Narrowed { keyword } => write!(f, "'{keyword}' narrowed"),
+            ChangeKind::Widened { keyword } => write!(f, "'{keyword}' widened"),
+            ChangeKind::PropertyRequired { name } => {
+                write!(f, "property '{name}' became required")
+            }
+            ChangeKind::PropertyOptional { name } => {
+                write!(f, "property '{name}' became optional")
+            }
+            ChangeKind::EnumValueAdded { value } => write!(f, "enum value {value} added"),
+            ChangeKind::EnumValueRemoved { value } => write!(f, "enum value {value} removed"),
+        }
+    }
+}
+
+/// Every change between two versions of a specification.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SpecDiff {
+    pub changes: Vec<Change>,
+}
+
+impl SpecDiff {
+    pub fn breaking(&self) -> impl Iterator<Item = &Change> {
+        self.changes.iter().filter(|change| change.breaking)
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.breaking().next().is_some()
+    }
+}
+
+/// Which way the data a schema describes flows. Accepting fewer values breaks clients sending
+/// requests, while sending more breaks clients reading responses.
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum Flow {
+    Request,
+    Response,
+}
+
+/// Compares two versions of a specification path by path and operation by operation:
+/// - paths, operations, parameters, request bodies, media types and responses added or removed
+/// - parameters and request bodies becoming required or optional
+/// - the schemas of parameters and of request and response content, property by property:
+///   their `type`, bounds, `pattern`, `enum` values, `required` properties and
+///   `additionalProperties`
+///
+/// References are followed; those that do not resolve are not compared.
+pub fn diff(old: &openapiv3::OpenAPI, new: &openapiv3::OpenAPI) -> SpecDiff {
+    let mut diff = Diff {
+        old: &old.components,
+        new: &new.components,
+        changes: vec![],
+    };
+
+    for (path, old_item) in old.paths.paths.iter() {
+        let pointer = spec_pointer("#/paths", [path.as_str()]);
+        match new.paths.paths.get(path) {
+            None => diff.push(&pointer, ChangeKind::PathRemoved, true),
+            Some(new_item) => {
+                if let (Some(old_item), Some(new_item)) = (old_item.as_item(), new_item.as_item()) {
+                    diff.path_item(old_item, new_item, &pointer);
+                }
+            }
+        }
+    }
+    for path in new.paths.paths.keys() {
+        if !old.paths.paths.contains_key(path) {
+            let pointer = spec_pointer("#/paths", [path.as_str()]);
+            diff.push(&pointer, ChangeKind::PathAdded, false);
+        }
+    }
+
+    SpecDiff {
+        changes: diff.changes,
+    }
+}
+
+struct Diff<'api> {
+    old: &'api Option<openapiv3::Components>,
+    new: &'api Option<openapiv3::Components>,
+    changes: Vec<Change>,
+}
+
+impl<'api> Diff<'api> {
+    fn push(&mut self, pointer: &str, kind: ChangeKind, breaking: bool) {
+        self.changes.push(Change {
+            pointer: pointer.to_string(),
+            kind,
+            breaking,
+        });
+    }
+
+    fn path_item(
+        &mut self,
+        old_item: &'api openapiv3::PathItem,
+        new_item: &'api openapiv3::PathItem,
+        pointer: &str,
+    ) {
+        for method in Method::ALL {
+            let pointer = spec_pointer(pointer, [method.as_str()]);
+            match (method.operation(old_item), method.operation(new_item)) {
+                (Some(_), None) => self.push(&pointer, ChangeKind::OperationRemoved, true),
+                (None, Some(_)) => self.push(&pointer, ChangeKind::OperationAdded, false),
+                (Some(old_operation), Some(new_operation)) => {
+                    let old_parameters = self.parameters(old_item, old_operation, self.old);
+                    let new_parameters = self.parameters(new_item, new_operation, self.new);
+                    self.parameter_changes(&old_parameters, &new_parameters, &pointer);
+                    self.request_body(old_operation, new_operation, &pointer);
+                    self.responses(&old_operation.responses, &new_operation.responses, &pointer);
+                }
+                (None, None) => (),
+            }
+        }
+    }
+
+    /// The parameters of an operation by location and name, those of the operation replacing
+    /// those of its path.
+    fn parameters(
+        &self,
+        item: &'api openapiv3::PathItem,
+        operation: &'api openapiv3::Operation,
+        components: &'api Option<openapiv3::Components>,
+    ) -> IndexMap<(&'static str, &'api str), &'api openapiv3::ParameterData> {
+        item.parameters
+            .iter()
+            .chain(operation.parameters.iter())
+            .filter_map(|parameter| parameter.item_or_fetch(components).ok())
+            .map(|parameter| {
+                let location = match parameter {
+                    openapiv3::Parameter::Query { .. } => "query",
+                    openapiv3::Parameter::Header { .. } => "header",
+                    openapiv3::Parameter::Path { .. } => "path",
+                    openapiv3::Parameter::Cookie { .. } => "cookie",
+                };
+                let parameter_data = parameter.parameter_data_ref();
+                ((location, parameter_data.name.as_str()), parameter_data)
+            })
+            .collect()
+    }
+
+    fn parameter_changes(
+        &mut self,
+        old_parameters: &IndexMap<(&'static str, &'api str), &'api openapiv3::ParameterData>,
+        new_parameters: &IndexMap<(&'static str, &'api str), &'api openapiv3::ParameterData>,
+        pointer: &str,
+    ) {
+        let pointer = spec_pointer(pointer, ["parameters"]);
+        for ((location, name), old_parameter) in old_parameters {
+            let parameter_pointer = spec_pointer(&pointer, [*location, *name]);
+            let new_parameter = new_parameters.get(&(*location, *name));
+            let (name, location) = (name.to_string(), location.to_string());
+            let Some(new_parameter) = new_parameter else {
+                let removed = ChangeKind::ParameterRemoved { name, location };
+                self.push(&parameter_pointer, removed, false);
+                continue;
+            };
+            match (old_parameter.required, new_parameter.required) {
+                (false, true) => {
+                    let required = ChangeKind::ParameterRequired { name, location };
+                    self.push(&parameter_pointer, required, true)
+                }
+                (true, false) => {
+                    let optional = ChangeKind::ParameterOptional { name, location };
+                    self.push(&parameter_pointer, optional, false)
+                }
+                _ => (),
+            }
+            if let (
+                openapiv3::ParameterSchemaOrContent::Schema(old_schema),
+                openapiv3::ParameterSchemaOrContent::Schema(new_schema),
+            ) = (&old_parameter.format, &new_parameter.format)
+            {
+                let pointer = spec_pointer(&parameter_pointer, ["schema"]);
+                self.schema(old_schema, new_schema, Flow::Request, &pointer);
+            }
+        }
+        for ((location, name), new_parameter) in new_parameters {
+            if !old_parameters.contains_key(&(*location, *name)) {
+                let added = ChangeKind::ParameterAdded {
+                    name: name.to_string(),
+                    location: location.to_string(),
+                    required: new_parameter.required,
+                };
+                let pointer = spec_pointer(&pointer, [*location, *name]);
+                self.push(&pointer, added, new_parameter.required);
+            }
+        }
+    }
+
+    fn request_body(
+        &mut self,
+        old_operation: &'api openapiv3::Operation,
+        new_operation: &'api openapiv3::Operation,
+        pointer: &str,
+    ) {
+        let pointer = spec_pointer(pointer, ["requestBody"]);
+        let old_body = (old_operation.request_body.as_ref())
+            .and_then(|body| body.item_or_fetch(self.old).ok());
+        let new_body = (new_operation.request_body.as_ref())
+            .and_then(|body| body.item_or_fetch(self.new).ok());
+        match (old_body, new_body) {
+            (None, Some(new_body)) => {
+                let added = ChangeKind::RequestBodyAdded {
+                    required: new_body.required,
+                };
+                self.push(&pointer, added, new_body.required);
+            }
+            (Some(_), None) => self.push(&pointer, ChangeKind::RequestBodyRemoved, false),
+            (Some(old_body), Some(new_body)) => {
+                match (old_body.required, new_body.required) {
+                    (false, true) => self.push(&pointer, ChangeKind::RequestBodyRequired, true),
+                    (true, false) => self.push(&pointer, ChangeKind::RequestBodyOptional, false),
+                    _ => (),
+                }
+                self.content(
+                    &old_body.content,
+                    &new_body.content,
+                    Flow::Request,
+                    &pointer,
+                );
+            }
+            (None, None) => (),
+        }
+    }
+
+    fn responses(
+        &mut self,
+        old_responses: &'api openapiv3::Responses,
+        new_responses: &'api openapiv3::Responses,
+        pointer: &str,
+    ) {
+        let pointer = spec_pointer(pointer, ["responses"]);
+        let by_status = |responses: &'api openapiv3::Responses| {
+            let default = responses
+                .default
+                .iter()
+                .map(|response| ("default".to_string(), response));
+            let statuses = (responses.responses.iter())
+                .map(|(status, response)| (status.to_string(), response));
+            default.chain(statuses).collect::<IndexMap<_, _>>()
+        };
+        let (old_responses, new_responses) = (by_status(old_responses), by_status(new_responses));
+
+        for (status, old_response) in &old_responses {
+            let pointer = spec_pointer(&pointer, [status.as_str()]);
+            let Some(new_response) = new_responses.get(status) else {
+                let removed = ChangeKind::ResponseRemoved {
+                    status: status.clone(),
+                };
+                self.push(&pointer, removed, true);
+                continue;
+            };
+            if let (Ok(old_response), Ok(new_response)) = (
+                old_response.item_or_fetch(self.old),
+                new_response.item_or_fetch(self.new),
+            ) {
+                let (old_content, new_content) = (&old_response.content, &new_response.content);
+                self.content(old_content, new_content, Flow::Response, &pointer);
+            }
+        }
+        for status in new_responses.keys() {
+            if !old_responses.contains_key(status) {
+                let added = ChangeKind::ResponseAdded {
+                    status: status.clone(),
+                };
+                self.push(&spec_pointer(&pointer, [status.as_str()]), added, false);
+            }
+        }
+    }
+
+    /// Media types no longer accepted break clients sending them, while those no longer sent
+    /// may be relied on by clients reading them.
+    fn content(
+        &mut self,
+        old_content: &'api openapiv3::Content,
+        new_content: &'api openapiv3::Content,
+        flow: Flow,
+        pointer: &str,
+    ) {
+        let pointer = spec_pointer(pointer, ["content"]);
+        for (media_type, old_media_type) in old_content {
+            let pointer = spec_pointer(&pointer, [media_type.as_str()]);
+            let Some(new_media_type) = new_content.get(media_type) else {
+                let removed = ChangeKind::MediaTypeRemoved {
+                    media_type: media_type.clone(),
+                };
+                self.push(&pointer, removed, true);
+                continue;
+            };
+            if let (Some(old_schema), Some(new_schema)) =
+                (&old_media_type.schema, &new_media_type.schema)
+            {
+                self.schema(
+                    old_schema,
+                    new_schema,
+                    flow,
+                    &spec_pointer(&pointer, ["schema"]),
+                );
+            }
+        }
+        for media_type in new_content.keys() {
+            if !old_content.contains_key(media_type) {
+                let added = ChangeKind::MediaTypeAdded {
+                    media_type: media_type.clone(),
+                };
+                let pointer = spec_pointer(&pointer, [media_type.as_str()]);
+                self.push(&pointer, added, flow == Flow::Response);
+            }
+        }
+    }
+
+    fn schema(
+        &mut self,
+        old_schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+        new_schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+        flow: Flow,
+        pointer: &str,
+    ) {
+        let (Ok(old_document), Ok(new_document)) = (
+            schema_document(old_schema, self.old, None),
+            schema_document(new_schema, self.new, None),
+        ) else {
+            return;
+        };
+        // a root given by reference is that definition, compared once like any other
+        let name = |schema: &openapiv3::ReferenceOr<openapiv3::Schema>| match schema {
+            openapiv3::ReferenceOr::Reference { reference } => reference
+                .strip_prefix("#/components/schemas/")
+                .map(String::from),
+            openapiv3::ReferenceOr::Item(..) => None,
+        };
+        let mut schemas = SchemaDiff {
+            old_definitions: old_document.get("$defs"),
+            new_definitions: new_document.get("$defs"),
+            flow,
+            compared: name(old_schema).zip(name(new_schema)).into_iter().collect(),
+            diff: self,
+        };
+        schemas.compare(&old_document, &new_document, pointer);
+    }
+}
+
+/// Bounds on a value, and whether raising them accepts more values.
+const BOUNDS: [(&str, bool); 8] = [
+    ("maxLength", true),
+    ("maximum", true),
+    ("maxItems", true),
+    ("maxProperties", true),
+    ("minLength", false),
+    ("minimum", false),
+    ("minItems", false),
+    ("minProperties", false),
+];
+
+/// Compares two converted schemas keyword by keyword.
+struct SchemaDiff<'diff, 'api> {
+    old_definitions: Option<&'diff Value>,
+    new_definitions: Option<&'diff Value>,
+    flow: Flow,
+    /// The pairs of definitions already compared, so recursive schemas are compared once.
+    compared: HashSet<(String, String)>,
+    diff: &'diff mut Diff<'api>,
+}
+
+impl<'diff> SchemaDiff<'diff, '_> {
+    fn compare(&mut self, old: &'diff Value, new: &'diff Value, pointer: &str) {
+        let (old, old_name) = resolve(old, self.old_definitions);
+        let (new, new_name) = resolve(new, self.new_definitions);
+        if let (Some(old_name), Some(new_name)) = (old_name, new_name) {
+            if !self.compared.insert((old_name, new_name)) {
+                return;
+            }
+        }
+
+        if old.get("type") != new.get("type") {
+            let changed = ChangeKind::TypeChanged {
+                from: old.get("type").cloned(),
+                to: new.get("type").cloned(),
+            };
+            self.diff.push(pointer, changed, true);
+            return;
+        }
+
+        for (keyword, upper) in BOUNDS {
+            let bound = |schema: &Value| schema.get(keyword).and_then(Value::as_f64);
+            match (bound(old), bound(new)) {
+                (None, Some(_)) => self.narrowed(keyword, pointer),
+                (Some(_), None) => self.widened(keyword, pointer),
+                (Some(old), Some(new)) if old != new => match (new > old) == upper {
+                    true => self.widened(keyword, pointer),
+                    false => self.narrowed(keyword, pointer),
+                },
+                _ => (),
+            }
+        }
+
+        match (old.get("pattern"), new.get("pattern")) {
+            (None, Some(_)) => self.narrowed("pattern", pointer),
+            (Some(_), None) => self.widened("pattern", pointer),
+            (Some(old), Some(new)) if old != new => self.narrowed("pattern", pointer),
+            _ => (),
+        }
+
+        match (enum_values(old), enum_values(new)) {
+            (None, Some(_)) => self.narrowed("enum", pointer),
+            (Some(_), None) => self.widened("enum", pointer),
+            (Some(old_values), Some(new_values)) => {
+                for value in old_values
+                    .iter()
+                    .filter(|value| !new_values.contains(value))
+                {
+                    let removed = ChangeKind::EnumValueRemoved {
+                        value: (*value).clone(),
+                    };
+                    self.diff.push(pointer, removed, self.flow == Flow::Request);
+                }
+                for value in new_values
+                    .iter()
+                    .filter(|value| !old_values.contains(value))
+                {
+                    let added = ChangeKind::EnumValueAdded {
+                        value: (*value).clone(),
+                    };
+                    self.diff.push(pointer, added, self.flow == Flow::Response);
+                }
+            }
+            (None, None) => (),
+        }
+
+        let (old_required, new_required) = (required(old), required(new));
+        for name in new_required.difference(&old_required) {
+            let required = ChangeKind::PropertyRequired {
+                name: name.to_string(),
+            };
+            self.diff
+                .push(pointer, required, self.flow == Flow::Request);
+        }
+        for name in old_required.difference(&new_required) {
+            let optional = ChangeKind::PropertyOptional {
+                name: name.to_string(),
+            };
+            self.diff
+                .push(pointer, optional, self.flow == Flow::Response);
+        }
+
+        let closed = |schema: &Value| schema.get("additionalProperties") == Some(&false.into());
+        match (closed(old), closed(new)) {
+            (false, true) => self.narrowed("additionalProperties", pointer),
+            (true, false) => self.widened("additionalProperties", pointer),
+            _ => (),
+        }
+
+        if let (Some(Value::Object(old_properties)), Some(Value::Object(new_properties))) =
+            (old.get("properties"), new.get("properties"))
+        {
+            for (name, old_property) in old_properties {
+                if let Some(new_property) = new_properties.get(name) {
+                    let pointer = spec_pointer(pointer, ["properties", name.as_str()]);
+                    self.compare(old_property, new_property, &pointer);
+                }
+            }
+        }
+        if let (Some(old_items), Some(new_items)) = (old.get("items"), new.get("items")) {
+            self.compare(old_items, new_items, &spec_pointer(pointer, ["items"]));
+        }
+    }
+
+    fn narrowed(&mut self, keyword: &str, pointer: &str) {
+        let narrowed = ChangeKind::Narrowed {
+            keyword: keyword.to_string(),
+        };
+        self.diff
+            .push(pointer, narrowed, self.flow == Flow::Request);
+    }
+
+    fn widened(&mut self, keyword: &str, pointer: &str) {
+        let widened = ChangeKind::Widened {
+            keyword: keyword.to_string(),
+        };
+        self.diff
+            .push(pointer, widened, self.flow == Flow::Response);
+    }
+}
+
+/// The schema a converted schema refers to through `$defs`, with the name of the last
+/// definition followed, or the schema itself.
+fn resolve<'a>(schema: &'a Value, definitions: Option<&'a Value>) -> (&'a Value, Option<String>) {
+    let mut schema = schema;
+    let mut name = None;
+    // a chain of references can be no longer than the number of definitions
+    for _ in 0..=definitions
+        .and_then(Value::as_object)
+        .map_or(0, serde_json::Map::len)
+    {
+        let Some(reference) = schema
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix(DEFINITIONS))
+        else {
+            break;
+        };
+        let reference = reference.replace("~1", "/").replace("~0", "~");
+        match definitions.and_then(|definitions| definitions.get(&reference)) {
+            Some(definition) => schema = definition,
+            None => break,
+        }
+        name = Some(reference);
+    }
+    (schema, name)
+}
+
+fn enum_values(schema: &Value) -> Option<Vec<&Value>> {
+    schema
+        .get("enum")
+        .and_then(Value::as_array)
+        .map(|values| values.iter().collect())
+}
+
+fn required(schema: &Value) -> HashSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod test_diff {
+    use super::*;
+    use indoc::indoc;
+
+    const OLD: &str = indoc!(
+        r#"
+        openapi: 3.0.0
+        info:
+          title: Pets
+          version: 1.0.0
+        paths:
+          /pets:
+            get:
+              parameters:
+                - in: query
+                  name: limit
+                  schema:
+                    type: integer
+                    maximum: 100
+                - in: query
+                  name: owner
+                  required: true
+                  schema:
+                    type: string
+              responses:
+                200:
+                  description: Pets
+                  content:
+                    application/json:
+                      schema:
+                        type: array
+                        items:
+                          $ref: '#/components/schemas/Pet'
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      $ref: '#/components/schemas/Pet'
+              responses:
+                201:
+                  description: Created
+                400:
+                  description: Invalid pet
+          /toys:
+            get:
+              responses:
+                200:
+                  description: Toys
+        components:
+          schemas:
+            Pet:
+              type: object
+              required: [name]
+              properties:
+                name:
+                  type: string
+                kind:
+                  type: string
+                  enum: [cat, dog, bird]
+                parent:
+                  $ref: '#/components/schemas/Pet'
+        "#
+    );
+
+    fn parse(spec: &str) -> openapiv3::OpenAPI {
+        serde_yaml::from_str(spec).unwrap()
+    }
+
+    fn parse_paths(paths: &str) -> openapiv3::OpenAPI {
+        parse(&format!(
+            "openapi: 3.0.0\ninfo:\n  title: Pets\n  version: 1.0.0\n{paths}"
+        ))
+    }
+
+    #[test]
+    fn no_changes_between_identical_specifications() {
+        assert_eq!(SpecDiff::default(), diff(&parse(OLD), &parse(OLD)));
+    }
+
+    #[test]
+    fn classify_changes_as_breaking_or_not() {
+        let new = indoc!(
+            r#"
+            openapi: 3.0.0
+            info:
+              title: Pets
+              version: 1.0.0
+            paths:
+              /pets:
+                get:
+                  parameters:
+                    - in: query
+                      name: limit
+                      schema:
+                        type: integer
+                        maximum: 50
+                    - in: query
+                      name: owner
+                      schema:
+                        type: string
+                  responses:
+                    200:
+                      description: Pets
+                      content:
+                        application/json:
+                          schema:
+                            type: array
+                            items:
+                              $ref: '#/components/schemas/Pet'
+                post:
+                  requestBody:
+                    content:
+                      application/json:
+                        schema:
+                          $ref: '#/components/schemas/Pet'
+                  responses:
+                    201:
+                      description: Created
+              /food:
+                get:
+                  responses:
+                    200:
+                      description: Food
+            components:
+              schemas:
+                Pet:
+                  type: object
+                  required: [name, kind]
+                  properties:
+                    name:
+                      type: string
+                    kind:
+                      type: string
+                      enum: [cat, dog, fish]
+                    parent:
+                      $ref: '#/components/schemas/Pet'
+            "#
+        );
+        let changes = diff(&parse(OLD), &parse(new))
+            .changes
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            vec![
+                "#/paths/~1pets/get/parameters/query/limit/schema: breaking: 'maximum' narrowed",
+                "#/paths/~1pets/get/parameters/query/owner: non-breaking: \
+                query parameter 'owner' became optional",
+                "#/paths/~1pets/get/responses/200/content/application~1json/schema/items: \
+                non-breaking: property 'kind' became required",
+                "#/paths/~1pets/get/responses/200/content/application~1json/schema/items\
+                /properties/kind: non-breaking: enum value \"bird\" removed",
+                "#/paths/~1pets/get/responses/200/content/application~1json/schema/items\
+                /properties/kind: breaking: enum value \"fish\" added",
+                "#/paths/~1pets/post/requestBody/content/application~1json/schema: \
+                breaking: property 'kind' became required",
+                "#/paths/~1pets/post/requestBody/content/application~1json/schema\
+                /properties/kind: breaking: enum value \"bird\" removed",
+                "#/paths/~1pets/post/requestBody/content/application~1json/schema\
+                /properties/kind: non-breaking: enum value \"fish\" added",
+                "#/paths/~1pets/post/responses/400: breaking: response 400 removed",
+                "#/paths/~1toys: breaking: path removed",
+                "#/paths/~1food: non-breaking: path added",
+            ],
+            changes
+        );
+    }
+
+    #[test]
+    fn detect_type_changes_and_new_requirements() {
+        let old = indoc!(
+            r#"
+            paths:
+              /pets:
+                post:
+                  parameters:
+                    - in: query
+                      name: limit
+                      schema:
+                        type: integer
+                  requestBody:
+                    content:
+                      text/plain: {}
+                  responses:
+                    201:
+                      description: Created
+            "#
+        );
+        let new = indoc!(
+            r#"
+            paths:
+              /pets:
+                post:
+                  parameters:
+                    - in: query
+                      name: limit
+                      schema:
+                        type: string
+                    - in: header
+                      name: X-Key
+                      required: true
+                      schema:
+                        type: string
+                  requestBody:
+                    required: true
+                    content:
+                      text/plain: {}
+                  responses:
+                    201:
+                      description: Created
+            "#
+        );
+        let diff = diff(&parse_paths(old), &parse_paths(new));
+
+        assert!(diff.is_breaking());
+        assert_eq!(
+            vec![
+                ChangeKind::TypeChanged {
+                    from: Some("integer".into()),
+                    to: Some("string".into())
+                },
+                ChangeKind::ParameterAdded {
+                    name: "X-Key".to_string(),
+                    location: "header".to_string(),
+                    required: true
+                },
+                ChangeKind::RequestBodyRequired,
+            ],
+            diff.breaking()
+                .map(|change| change.kind.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,405 @@
+use std::fmt;
+use std::time::Duration;
+
+use crate::method::Method;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationError {
+    InvalidUrl,
+    PathNotFound,
+    MethodNotAllowed {
+        allowed: Vec<Method>,
+    },
+    MissingCredentials {
+        scheme: String,
+    },
+    InvalidCredentials {
+        scheme: String,
+    },
+    InsufficientScope {
+        scheme: String,
+        scopes: Vec<String>,
+    },
+    /// None of the operation's security requirements is met. Each alternative holds why the
+    /// schemes of that requirement were not satisfied.
+    Unauthorized {
+        alternatives: Vec<Vec<ValidationError>>,
+    },
+    MissingParameter {
+        name: String,
+    },
+    InvalidParameter {
+        name: String,
+    },
+    /// A query parameter the operation does not declare.
+    UnknownParameter {
+        name: String,
+    },
+    DuplicateParameter {
+        name: String,
+    },
+    MissingOneOfParameters {
+        names: Vec<String>,
+    },
+    ConflictingParameters {
+        names: Vec<String>,
+    },
+    UndeclaredContentType {
+        content_type: String,
+    },
+    UnsupportedMediaTypeHandler {
+        content_type: String,
+    },
+    MissingContentType,
+    NotAcceptable {
+        accept: String,
+    },
+    MissingBody,
+    UnexpectedBody,
+    InvalidBody,
+    InvalidBodyPart {
+        name: String,
+    },
+    InvalidEncoding {
+        offset: usize,
+    },
+    EmptyJsonBody,
+    InvalidJsonSyntax {
+        line: usize,
+        column: usize,
+    },
+    BodyTooLarge {
+        size: usize,
+        limit: usize,
+    },
+    UnreadableBody,
+    UndocumentedStatusCode {
+        status_code: u16,
+        nearest: Vec<String>,
+    },
+    MissingResponseHeader {
+        name: String,
+    },
+    InvalidResponseHeader {
+        name: String,
+    },
+    MissingResponseContentType,
+    UndeclaredResponseContentType {
+        content_type: String,
+    },
+    InvalidResponseBody,
+    UnexpectedResponseBody,
+    InvalidStreamItem {
+        index: usize,
+    },
+    BrokenLink {
+        link: String,
+        expression: String,
+    },
+    SlowResponse {
+        elapsed: Duration,
+        budget: Duration,
+    },
+    UncompilableSchema {
+        location: String,
+    },
+    /// A `$ref` in the specification points at nothing.
+    UnresolvedReference {
+        reference: String,
+    },
+}
+
+impl ValidationError {
+    /// The HTTP status a server should answer with when rejecting a request for this reason.
+    ///
+    /// Parameter and body violations map to 400, missing or malformed credentials to 401, a
+    /// token without the required scopes to 403, an unknown path to 404, an unsupported
+    /// operation to 405, an unsatisfiable `Accept` header to 406, an oversized body to 413 and
+    /// content type problems to 415. Response violations are the server's fault and map to 500.
+    pub fn suggested_status(&self) -> u16 {
+        match self {
+            Self::InvalidUrl
+            | Self::MissingParameter { .. }
+            | Self::InvalidParameter { .. }
+            | Self::UnknownParameter { .. }
+            | Self::DuplicateParameter { .. }
+            | Self::MissingOneOfParameters { .. }
+            | Self::ConflictingParameters { .. }
+            | Self::MissingBody
+            | Self::UnexpectedBody
+            | Self::InvalidBody
+            | Self::InvalidBodyPart { .. }
+            | Self::InvalidEncoding { .. }
+            | Self::EmptyJsonBody
+            | Self::InvalidJsonSyntax { .. }
+            | Self::UnreadableBody => 400,
+            Self::MissingCredentials { .. } | Self::InvalidCredentials { .. } => 401,
+            Self::InsufficientScope { .. } => 403,
+            // authenticated, but without the scopes any alternative asks for
+            Self::Unauthorized { alternatives }
+                if alternatives
+                    .iter()
+                    .flatten()
+                    .all(|error| matches!(error, Self::InsufficientScope { .. })) =>
+            {
+                403
+            }
+            Self::Unauthorized { .. } => 401,
+            Self::PathNotFound => 404,
+            Self::BodyTooLarge { .. } => 413,
+            Self::MethodNotAllowed { .. } => 405,
+            Self::NotAcceptable { .. } => 406,
+            Self::UndeclaredContentType { .. }
+            | Self::UnsupportedMediaTypeHandler { .. }
+            | Self::MissingContentType => 415,
+            Self::UndocumentedStatusCode { .. }
+            | Self::MissingResponseHeader { .. }
+            | Self::InvalidResponseHeader { .. }
+            | Self::MissingResponseContentType
+            | Self::UndeclaredResponseContentType { .. }
+            | Self::InvalidResponseBody
+            | Self::UnexpectedResponseBody
+            | Self::InvalidStreamItem { .. }
+            | Self::BrokenLink { .. }
+            | Self::SlowResponse { .. }
+            | Self::UncompilableSchema { .. }
+            | Self::UnresolvedReference { .. } => 500,
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidUrl => write!(f, "request URL could not be parsed"),
+            Self::PathNotFound => write!(f, "no path in the specification matches the request"),
+            Self::MethodNotAllowed { allowed } => {
+                let allowed: Vec<String> = allowed
+                    .iter()
+                    .map(|method| method.as_str().to_uppercase())
+                    .collect();
+                write!(
+                    f,
+                    "operation is not allowed on this path, allowed: {}",
+                    allowed.join(", ")
+                )
+            }
+            Self::MissingCredentials { scheme } => {
+                write!(f, "credentials for security scheme '{scheme}' are missing")
+            }
+            Self::InvalidCredentials { scheme } => {
+                write!(
+                    f,
+                    "credentials for security scheme '{scheme}' are malformed"
+                )
+            }
+            Self::InsufficientScope { scheme, scopes } => {
+                write!(
+                    f,
+                    "token for security scheme '{scheme}' lacks the scopes '{}'",
+                    scopes.join("', '")
+                )
+            }
+            Self::Unauthorized { alternatives } => {
+                let alternatives: Vec<String> = alternatives
+                    .iter()
+                    .map(|failures| {
+                        let failures: Vec<String> =
+                            failures.iter().map(ToString::to_string).collect();
+                        format!("({})", failures.join(" and "))
+                    })
+                    .collect();
+                write!(
+                    f,
+                    "no security requirement is met: {}",
+                    alternatives.join(" or ")
+                )
+            }
+            Self::MissingParameter { name } => write!(f, "required parameter '{name}' is missing"),
+            Self::InvalidParameter { name } => write!(f, "parameter '{name}' is invalid"),
+            Self::UnknownParameter { name } => write!(f, "parameter '{name}' is not declared"),
+            Self::DuplicateParameter { name } => {
+                write!(f, "parameter '{name}' was given more than once")
+            }
+            Self::MissingOneOfParameters { names } => {
+                write!(f, "exactly one of '{}' is required", names.join("', '"))
+            }
+            Self::ConflictingParameters { names } => {
+                write!(
+                    f,
+                    "parameters '{}' cannot be sent together",
+                    names.join("', '")
+                )
+            }
+            Self::UndeclaredContentType { content_type } => {
+                write!(
+                    f,
+                    "content type '{content_type}' is not declared for this operation"
+                )
+            }
+            Self::UnsupportedMediaTypeHandler { content_type } => {
+                write!(f, "content type '{content_type}' cannot be validated")
+            }
+            Self::MissingContentType => write!(f, "body was sent without a content type"),
+            Self::NotAcceptable { accept } => {
+                write!(
+                    f,
+                    "no documented response media type is acceptable for '{accept}'"
+                )
+            }
+            Self::MissingBody => write!(f, "required body is missing"),
+            Self::UnexpectedBody => write!(f, "operation does not accept a body"),
+            Self::InvalidBody => write!(f, "body does not match the specification"),
+            Self::InvalidBodyPart { name } => write!(f, "body field '{name}' is invalid"),
+            Self::InvalidEncoding { offset } => {
+                write!(f, "body has an invalid character at byte {offset}")
+            }
+            Self::EmptyJsonBody => write!(f, "JSON body is empty"),
+            Self::InvalidJsonSyntax { line, column } => {
+                write!(f, "JSON body is malformed at line {line} column {column}")
+            }
+            Self::BodyTooLarge { size, limit } => {
+                write!(f, "body of {size} bytes exceeds the limit of {limit} bytes")
+            }
+            Self::UnreadableBody => write!(f, "body could not be read"),
+            Self::UndocumentedStatusCode {
+                status_code,
+                nearest,
+            } if nearest.is_empty() => write!(f, "status code {status_code} is not documented"),
+            Self::UndocumentedStatusCode {
+                status_code,
+                nearest,
+            } => write!(
+                f,
+                "status code {status_code} is not documented, nearest documented: {}",
+                nearest.join(", ")
+            ),
+            Self::MissingResponseHeader { name } => {
+                write!(f, "required response header '{name}' is missing")
+            }
+            Self::InvalidResponseHeader { name } => {
+                write!(f, "response header '{name}' is invalid")
+            }
+            Self::MissingResponseContentType => {
+                write!(f, "response body was sent without a content type")
+            }
+            Self::UndeclaredResponseContentType { content_type } => {
+                write!(
+                    f,
+                    "response content type '{content_type}' is not documented"
+                )
+            }
+            Self::InvalidResponseBody => {
+                write!(f, "response body does not match the specification")
+            }
+            Self::UnexpectedResponseBody => {
+                write!(
+                    f,
+                    "response has a body, which its status code does not allow"
+                )
+            }
+            Self::InvalidStreamItem { index } => {
+                write!(
+                    f,
+                    "item {index} of the response stream does not match the specification"
+                )
+            }
+            Self::BrokenLink { link, expression } => {
+                write!(
+                    f,
+                    "link '{link}' uses '{expression}', which the response does not contain"
+                )
+            }
+            Self::SlowResponse { elapsed, budget } => {
+                write!(
+                    f,
+                    "response took {elapsed:?}, over the budget of {budget:?}"
+                )
+            }
+            Self::UncompilableSchema { location } => {
+                write!(f, "schema at '{location}' could not be compiled")
+            }
+            Self::UnresolvedReference { reference } => {
+                write!(
+                    f,
+                    "reference '{reference}' in the specification does not resolve"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+#[cfg(test)]
+mod test_suggested_status {
+    use super::ValidationError;
+    use crate::method::Method;
+    use parameterized::parameterized;
+    use std::time::Duration;
+
+    #[parameterized(error = {
+        ValidationError::InvalidUrl,
+        ValidationError::PathNotFound,
+        ValidationError::MethodNotAllowed { allowed: vec![Method::Get] },
+        ValidationError::MissingCredentials { scheme: "bearerAuth".to_string() },
+        ValidationError::InvalidCredentials { scheme: "bearerAuth".to_string() },
+        ValidationError::InsufficientScope {
+            scheme: "oauth".to_string(),
+            scopes: vec!["pets:write".to_string()],
+        },
+        ValidationError::Unauthorized {
+            alternatives: vec![vec![ValidationError::MissingCredentials {
+                scheme: "bearerAuth".to_string(),
+            }]],
+        },
+        ValidationError::Unauthorized {
+            alternatives: vec![vec![ValidationError::InsufficientScope {
+                scheme: "oauth".to_string(),
+                scopes: vec!["pets:write".to_string()],
+            }]],
+        },
+        ValidationError::MissingParameter { name: "id".to_string() },
+        ValidationError::InvalidParameter { name: "id".to_string() },
+        ValidationError::UnknownParameter { name: "id".to_string() },
+        ValidationError::DuplicateParameter { name: "id".to_string() },
+        ValidationError::MissingOneOfParameters { names: vec!["page".to_string()] },
+        ValidationError::ConflictingParameters { names: vec!["page".to_string()] },
+        ValidationError::UndeclaredContentType { content_type: "text/xml".to_string() },
+        ValidationError::UnsupportedMediaTypeHandler { content_type: "text/xml".to_string() },
+        ValidationError::MissingContentType,
+        ValidationError::NotAcceptable { accept: "text/csv".to_string() },
+        ValidationError::MissingBody,
+        ValidationError::UnexpectedBody,
+        ValidationError::InvalidBody,
+        ValidationError::InvalidBodyPart { name: "avatar".to_string() },
+        ValidationError::InvalidEncoding { offset: 3 },
+        ValidationError::EmptyJsonBody,
+        ValidationError::InvalidJsonSyntax { line: 1, column: 2 },
+        ValidationError::BodyTooLarge { size: 2048, limit: 1024 },
+        ValidationError::UnreadableBody,
+        ValidationError::UndocumentedStatusCode { status_code: 418, nearest: vec![] },
+        ValidationError::MissingResponseHeader { name: "X-Rate-Limit".to_string() },
+        ValidationError::InvalidResponseHeader { name: "X-Rate-Limit".to_string() },
+        ValidationError::MissingResponseContentType,
+        ValidationError::UndeclaredResponseContentType { content_type: "text/html".to_string() },
+        ValidationError::InvalidResponseBody,
+        ValidationError::UnexpectedResponseBody,
+        ValidationError::InvalidStreamItem { index: 3 },
+        ValidationError::BrokenLink {
+            link: "GetOwner".to_string(),
+            expression: "$response.body#/owner".to_string(),
+        },
+        ValidationError::SlowResponse {
+            elapsed: Duration::from_secs(2),
+            budget: Duration::from_secs(1),
+        },
+        ValidationError::UncompilableSchema { location: "#/paths".to_string() },
+        ValidationError::UnresolvedReference { reference: "#/components/schemas/Pet".to_string() },
+    }, status = {
+        400, 404, 405, 401, 401, 403, 401, 403, 400, 400, 400, 400, 400, 400, 415, 415, 415, 406, 400, 400, 400, 400, 400, 400, 400, 413, 400, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500, 500
+    })]
+    fn maps_error_to_status(error: ValidationError, status: u16) {
+        assert_eq!(status, error.suggested_status());
+    }
+}
@@ -0,0 +1,489 @@
+use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::{spec_pointer, JSONSchemaValidator, SchemaFailure};
+use crate::method::Method;
+use crate::options::{ReadOnlyProperties, ValidatorOptions};
+use crate::spec_check::SpecError;
+use crate::to_jsonschema::{schema_document, Direction};
+
+/// Checks every `example` and `examples` entry of parameters, headers, request bodies and
+/// responses, including those of component callbacks, against the schema it illustrates.
+/// Entries given by reference are checked where they are defined; examples without a schema,
+/// with an `externalValue` or whose schema does not compile are skipped.
+pub(crate) fn lint_examples(
+    api: &openapiv3::OpenAPI,
+    options: &ValidatorOptions,
+) -> Vec<SpecError> {
+    let mut lint = ExampleLint {
+        components: &api.components,
+        options,
+        errors: vec![],
+    };
+
+    for (path, path_item) in api.paths.paths.iter() {
+        let Some(path_item) = path_item.as_item() else {
+            continue;
+        };
+        lint.path_item(path_item, &spec_pointer("#/paths", [path.as_str()]));
+    }
+
+    if let Some(components) = &api.components {
+        for (name, parameter) in components.parameters.iter() {
+            if let openapiv3::ReferenceOr::Item(parameter) = parameter {
+                lint.parameter(
+                    parameter,
+                    &spec_pointer("#/components/parameters", [name.as_str()]),
+                );
+            }
+        }
+        for (name, request_body) in components.request_bodies.iter() {
+            if let openapiv3::ReferenceOr::Item(request_body) = request_body {
+                lint.request_body(
+                    request_body,
+                    &spec_pointer("#/components/requestBodies", [name.as_str()]),
+                );
+            }
+        }
+        for (name, response) in components.responses.iter() {
+            if let openapiv3::ReferenceOr::Item(response) = response {
+                lint.response(
+                    response,
+                    &spec_pointer("#/components/responses", [name.as_str()]),
+                );
+            }
+        }
+        for (name, header) in components.headers.iter() {
+            if let openapiv3::ReferenceOr::Item(header) = header {
+                lint.header(
+                    header,
+                    &spec_pointer("#/components/headers", [name.as_str()]),
+                );
+            }
+        }
+        for (name, callback) in components.callbacks.iter() {
+            if let openapiv3::ReferenceOr::Item(callback) = callback {
+                for (expression, path_item) in callback.iter() {
+                    lint.path_item(
+                        path_item,
+                        &spec_pointer(
+                            "#/components/callbacks",
+                            [name.as_str(), expression.as_str()],
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    lint.errors
+}
+
+struct ExampleLint<'api> {
+    components: &'api Option<openapiv3::Components>,
+    options: &'api ValidatorOptions,
+    errors: Vec<SpecError>,
+}
+
+impl ExampleLint<'_> {
+    fn path_item(&mut self, path_item: &openapiv3::PathItem, pointer: &str) {
+        self.parameters(&path_item.parameters, pointer);
+
+        for method in Method::ALL {
+            let Some(operation) = method.operation(path_item) else {
+                continue;
+            };
+            let pointer = spec_pointer(pointer, [method.as_str()]);
+            self.parameters(&operation.parameters, &pointer);
+            if let Some(openapiv3::ReferenceOr::Item(request_body)) = &operation.request_body {
+                self.request_body(request_body, &spec_pointer(&pointer, ["requestBody"]));
+            }
+            let responses = operation
+                .responses
+                .default
+                .iter()
+                .map(|response| ("default".to_string(), response))
+                .chain(
+                    operation
+                        .responses
+                        .responses
+                        .iter()
+                        .map(|(status, response)| (status.to_string(), response)),
+                );
+            for (status, response) in responses {
+                if let openapiv3::ReferenceOr::Item(response) = response {
+                    self.response(response, &spec_pointer(&pointer, ["responses", &status]));
+                }
+            }
+        }
+    }
+
+    fn parameters(
+        &mut self,
+        parameters: &[openapiv3::ReferenceOr<openapiv3::Parameter>],
+        pointer: &str,
+    ) {
+        for (index, parameter) in parameters.iter().enumerate() {
+            if let openapiv3::ReferenceOr::Item(parameter) = parameter {
+                self.parameter(
+                    parameter,
+                    &spec_pointer(pointer, ["parameters", &index.to_string()]),
+                );
+            }
+        }
+    }
+
+    fn parameter(&mut self, parameter: &openapiv3::Parameter, pointer: &str) {
+        let parameter_data = parameter.parameter_data_ref();
+        self.format(
+            &parameter_data.format,
+            &parameter_data.example,
+            &parameter_data.examples,
+            pointer,
+        );
+    }
+
+    fn header(&mut self, header: &openapiv3::Header, pointer: &str) {
+        self.format(&header.format, &header.example, &header.examples, pointer);
+    }
+
+    fn format(
+        &mut self,
+        format: &openapiv3::ParameterSchemaOrContent,
+        example: &Option<serde_json::Value>,
+        examples: &indexmap::IndexMap<String, openapiv3::ReferenceOr<openapiv3::Example>>,
+        pointer: &str,
+    ) {
+        match format {
+            openapiv3::ParameterSchemaOrContent::Schema(schema) => {
+                self.examples(schema, None, example, examples, pointer)
+            }
+            openapiv3::ParameterSchemaOrContent::Content(content) => {
+                self.content(content, None, pointer)
+            }
+        }
+    }
+
+    fn request_body(&mut self, request_body: &openapiv3::RequestBody, pointer: &str) {
+        let direction = Direction::Request {
+            strip_read_only: self.options.read_only_properties == ReadOnlyProperties::Strip,
+        };
+        self.content(&request_body.content, Some(direction), pointer);
+    }
+
+    fn response(&mut self, response: &openapiv3::Response, pointer: &str) {
+        for (name, header) in response.headers.iter() {
+            if let openapiv3::ReferenceOr::Item(header) = header {
+                self.header(header, &spec_pointer(pointer, ["headers", name]));
+            }
+        }
+        self.content(&response.content, Some(Direction::Response), pointer);
+    }
+
+    fn content(
+        &mut self,
+        content: &openapiv3::Content,
+        direction: Option<Direction>,
+        pointer: &str,
+    ) {
+        for (media_type, media_type_spec) in content.iter() {
+            if let Some(schema) = &media_type_spec.schema {
+                self.examples(
+                    schema,
+                    direction,
+                    &media_type_spec.example,
+                    &media_type_spec.examples,
+                    &spec_pointer(pointer, ["content", media_type]),
+                );
+            }
+        }
+    }
+
+    fn examples(
+        &mut self,
+        schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+        direction: Option<Direction>,
+        example: &Option<serde_json::Value>,
+        examples: &indexmap::IndexMap<String, openapiv3::ReferenceOr<openapiv3::Example>>,
+        pointer: &str,
+    ) {
+        let components = self.components;
+        let values = example
+            .iter()
+            .map(|example| (spec_pointer(pointer, ["example"]), example))
+            .chain(examples.iter().filter_map(|(name, example)| {
+                example.item_or_fetch(components).ok().and_then(|example| {
+                    example
+                        .value
+                        .as_ref()
+                        .map(|value| (spec_pointer(pointer, ["examples", name, "value"]), value))
+                })
+            }))
+            .collect::<Vec<_>>();
+        if values.is_empty() {
+            return;
+        }
+
+        let Ok(document) = schema_document(schema, self.components, direction) else {
+            return;
+        };
+        for (pointer, value) in values {
+            if document.validates_value(value, self.options) == Err(SchemaFailure::Invalid) {
+                self.errors.push(SpecError::InvalidExample { pointer });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_example_lint {
+    use crate::request::test_helpers::*;
+    use crate::spec_check::SpecError;
+    use indoc::indoc;
+
+    fn invalid_examples(spec: &str) -> Vec<String> {
+        make_validator_from_spec(spec)
+            .lint_examples()
+            .into_iter()
+            .map(|error| match error {
+                SpecError::InvalidExample { pointer } => pointer,
+                error => panic!("unexpected error {error}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn accept_examples_matching_their_schema() {
+        assert!(invalid_examples(indoc!(
+            r#"
+            paths:
+              /pets/{id}:
+                parameters:
+                  - in: path
+                    name: id
+                    required: true
+                    schema:
+                      type: integer
+                    example: 3
+                post:
+                  requestBody:
+                    content:
+                      application/json:
+                        schema:
+                          type: object
+                          required: [name]
+                          properties:
+                            name:
+                              type: string
+                        example:
+                          name: Rex
+                  responses:
+                    200:
+                      description: API call successful
+                      content:
+                        application/json:
+                          schema:
+                            type: string
+                          examples:
+                            rex:
+                              value: Rex
+            "#
+        ))
+        .is_empty());
+    }
+
+    #[test]
+    fn report_examples_not_matching_their_schema() {
+        assert_eq!(
+            vec![
+                "#/paths/~1pets~1{id}/parameters/0/example",
+                "#/paths/~1pets~1{id}/post/parameters/0/examples/many/value",
+                "#/paths/~1pets~1{id}/post/requestBody/content/application~1json/example",
+                concat!(
+                    "#/paths/~1pets~1{id}/post/responses/200",
+                    "/content/application~1json/examples/rex/value"
+                ),
+            ],
+            invalid_examples(indoc!(
+                r#"
+                paths:
+                  /pets/{id}:
+                    parameters:
+                      - in: path
+                        name: id
+                        required: true
+                        schema:
+                          type: integer
+                        example: three
+                    post:
+                      parameters:
+                        - in: query
+                          name: limit
+                          schema:
+                            type: integer
+                            maximum: 10
+                          examples:
+                            few:
+                              value: 2
+                            many:
+                              value: 20
+                      requestBody:
+                        content:
+                          application/json:
+                            schema:
+                              type: object
+                              required: [name]
+                            example: {}
+                      responses:
+                        200:
+                          description: API call successful
+                          content:
+                            application/json:
+                              schema:
+                                type: string
+                              examples:
+                                rex:
+                                  $ref: '#/components/examples/Number'
+                components:
+                  examples:
+                    Number:
+                      value: 3
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn report_examples_in_components() {
+        assert_eq!(
+            vec![
+                "#/components/parameters/Limit/example",
+                "#/components/requestBodies/Pet/content/application~1json/example",
+                "#/components/responses/Pet/content/application~1json/example",
+            ],
+            invalid_examples(indoc!(
+                r#"
+                paths: {}
+                components:
+                  parameters:
+                    Limit:
+                      in: query
+                      name: limit
+                      schema:
+                        type: integer
+                      example: lots
+                  requestBodies:
+                    Pet:
+                      content:
+                        application/json:
+                          schema:
+                            $ref: '#/components/schemas/Pet'
+                          example:
+                            name: 7
+                  responses:
+                    Pet:
+                      description: A pet
+                      content:
+                        application/json:
+                          schema:
+                            $ref: '#/components/schemas/Pet'
+                          example: []
+                  schemas:
+                    Pet:
+                      type: object
+                      properties:
+                        name:
+                          type: string
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn read_only_properties_do_not_belong_in_request_examples() {
+        assert_eq!(
+            vec!["#/paths/~1pets/post/requestBody/content/application~1json/example"],
+            invalid_examples(indoc!(
+                r#"
+                paths:
+                  /pets:
+                    post:
+                      requestBody:
+                        content:
+                          application/json:
+                            schema:
+                              type: object
+                              properties:
+                                id:
+                                  type: integer
+                                  readOnly: true
+                            example:
+                              id: 1
+                      responses:
+                        200:
+                          description: API call successful
+                          content:
+                            application/json:
+                              schema:
+                                type: object
+                                properties:
+                                  id:
+                                    type: integer
+                                    readOnly: true
+                              example:
+                                id: 1
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn check_header_and_callback_examples() {
+        assert_eq!(
+            vec![
+                "#/paths/~1pets/get/responses/200/headers/X-Rate-Limit/example",
+                "#/components/headers/X-Request-Id/examples/chained/value",
+                "#/components/callbacks/onAdopted/{$request.body#~1url}/post/parameters/0/example",
+            ],
+            invalid_examples(indoc!(
+                r#"
+                paths:
+                  /pets:
+                    get:
+                      responses:
+                        200:
+                          description: API call successful
+                          headers:
+                            X-Rate-Limit:
+                              schema:
+                                type: integer
+                              example: lots
+                components:
+                  headers:
+                    X-Request-Id:
+                      schema:
+                        type: integer
+                      examples:
+                        chained:
+                          $ref: '#/components/examples/Chained'
+                  examples:
+                    Chained:
+                      $ref: '#/components/examples/Text'
+                    Text:
+                      value: abc
+                  callbacks:
+                    onAdopted:
+                      '{$request.body#/url}':
+                        post:
+                          parameters:
+                            - in: query
+                              name: id
+                              schema:
+                                type: integer
+                              example: none
+                          responses:
+                            200:
+                              description: Received
+                "#
+            ))
+        );
+    }
+}
@@ -0,0 +1,273 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Parsed form of the `x-rate-limit` extension, written as `<requests>/<unit>` where the unit
+/// is one of `s`, `m`, `h` or `d`, e.g. `100/m`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct RateLimit {
+    pub requests: u32,
+    pub per: Duration,
+}
+
+impl RateLimit {
+    pub(crate) fn from_extension(value: &serde_json::Value) -> Option<Self> {
+        let (requests, unit) = value.as_str()?.split_once('/')?;
+        Some(Self {
+            requests: requests.trim().parse().ok()?,
+            per: parse_unit(unit.trim())?,
+        })
+    }
+}
+
+/// Parsed form of the `x-sla` extension, an object with an optional `latency` duration and an
+/// optional `availability` percentage.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Sla {
+    pub latency: Option<Duration>,
+    pub availability: Option<f64>,
+}
+
+impl Sla {
+    pub(crate) fn from_extension(value: &serde_json::Value) -> Option<Self> {
+        let sla = value.as_object()?;
+        Some(Self {
+            latency: sla.get("latency").and_then(duration_from_extension),
+            availability: sla.get("availability").and_then(serde_json::Value::as_f64),
+        })
+    }
+}
+
+/// Parsed form of the `x-parameter-dependencies` extension, relationships between parameters
+/// that OpenAPI cannot express. Parameters are referred to by name, whatever their location:
+///
+/// ```yaml
+/// x-parameter-dependencies:
+///   exactlyOne:
+///     - [page, cursor]
+///   requires:
+///     sort_order: [sort]
+///   conflicts:
+///     cursor: [offset]
+/// ```
+///
+/// Only parameters sent with the request count as present; schema defaults do not.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ParameterDependencies {
+    /// Groups of parameters of which exactly one must be present.
+    pub exactly_one: Vec<Vec<String>>,
+    /// Parameters that may only be sent together with all of the listed parameters.
+    pub requires: BTreeMap<String, Vec<String>>,
+    /// Parameters that must not be sent together with any of the listed parameters.
+    pub conflicts: BTreeMap<String, Vec<String>>,
+}
+
+impl ParameterDependencies {
+    pub(crate) fn from_extension(value: &serde_json::Value) -> Option<Self> {
+        let dependencies = value.as_object()?;
+        Some(Self {
+            exactly_one: match dependencies.get("exactlyOne") {
+                Some(groups) => groups
+                    .as_array()?
+                    .iter()
+                    .map(names)
+                    .collect::<Option<_>>()?,
+                None => vec![],
+            },
+            requires: named_lists(dependencies.get("requires"))?,
+            conflicts: named_lists(dependencies.get("conflicts"))?,
+        })
+    }
+}
+
+fn names(value: &serde_json::Value) -> Option<Vec<String>> {
+    value
+        .as_array()?
+        .iter()
+        .map(|name| name.as_str().map(str::to_string))
+        .collect()
+}
+
+fn named_lists(value: Option<&serde_json::Value>) -> Option<BTreeMap<String, Vec<String>>> {
+    match value {
+        Some(value) => value
+            .as_object()?
+            .iter()
+            .map(|(name, others)| Some((name.clone(), names(others)?)))
+            .collect(),
+        None => Some(BTreeMap::new()),
+    }
+}
+
+/// Durations are written with a unit suffix (`250ms`, `5s`, `2m`, `1h`); bare numbers are
+/// taken as milliseconds.
+pub(crate) fn duration_from_extension(value: &serde_json::Value) -> Option<Duration> {
+    if let Some(milliseconds) = value.as_u64() {
+        return Some(Duration::from_millis(milliseconds));
+    }
+
+    let value = value.as_str()?.trim();
+    let split = value.find(|character: char| !character.is_ascii_digit())?;
+    let (amount, unit) = value.split_at(split);
+    let amount: u32 = amount.parse().ok()?;
+
+    match unit.trim() {
+        "ms" => Some(Duration::from_millis(amount.into())),
+        unit => parse_unit(unit).map(|unit| unit * amount),
+    }
+}
+
+fn parse_unit(unit: &str) -> Option<Duration> {
+    match unit {
+        "s" => Some(Duration::from_secs(1)),
+        "m" => Some(Duration::from_secs(60)),
+        "h" => Some(Duration::from_secs(60 * 60)),
+        "d" => Some(Duration::from_secs(24 * 60 * 60)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test_rate_limit {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn per_minute() {
+        assert_eq!(
+            Some(RateLimit {
+                requests: 100,
+                per: Duration::from_secs(60)
+            }),
+            RateLimit::from_extension(&json!("100/m"))
+        );
+    }
+
+    #[test]
+    fn per_day_with_whitespace() {
+        assert_eq!(
+            Some(RateLimit {
+                requests: 5,
+                per: Duration::from_secs(86400)
+            }),
+            RateLimit::from_extension(&json!("5 / d"))
+        );
+    }
+
+    #[test]
+    fn unknown_unit() {
+        assert_eq!(None, RateLimit::from_extension(&json!("100/w")));
+    }
+
+    #[test]
+    fn not_a_string() {
+        assert_eq!(None, RateLimit::from_extension(&json!(100)));
+    }
+}
+
+#[cfg(test)]
+mod test_duration {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn milliseconds() {
+        assert_eq!(
+            Some(Duration::from_millis(250)),
+            duration_from_extension(&json!("250ms"))
+        );
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            duration_from_extension(&json!("5s"))
+        );
+    }
+
+    #[test]
+    fn minutes() {
+        assert_eq!(
+            Some(Duration::from_secs(120)),
+            duration_from_extension(&json!("2m"))
+        );
+    }
+
+    #[test]
+    fn bare_number_is_milliseconds() {
+        assert_eq!(
+            Some(Duration::from_millis(1500)),
+            duration_from_extension(&json!(1500))
+        );
+    }
+
+    #[test]
+    fn missing_unit() {
+        assert_eq!(None, duration_from_extension(&json!("15")));
+    }
+
+    #[test]
+    fn unknown_unit() {
+        assert_eq!(None, duration_from_extension(&json!("15 fortnights")));
+    }
+}
+
+#[cfg(test)]
+mod test_sla {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn latency_and_availability() {
+        assert_eq!(
+            Some(Sla {
+                latency: Some(Duration::from_millis(300)),
+                availability: Some(99.9)
+            }),
+            Sla::from_extension(&json!({"latency": "300ms", "availability": 99.9}))
+        );
+    }
+
+    #[test]
+    fn not_an_object() {
+        assert_eq!(None, Sla::from_extension(&json!("300ms")));
+    }
+}
+
+#[cfg(test)]
+mod test_parameter_dependencies {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn all_relationships() {
+        assert_eq!(
+            Some(ParameterDependencies {
+                exactly_one: vec![vec!["page".to_string(), "cursor".to_string()]],
+                requires: BTreeMap::from([("sort_order".to_string(), vec!["sort".to_string()])]),
+                conflicts: BTreeMap::from([("cursor".to_string(), vec!["offset".to_string()])]),
+            }),
+            ParameterDependencies::from_extension(&json!({
+                "exactlyOne": [["page", "cursor"]],
+                "requires": {"sort_order": ["sort"]},
+                "conflicts": {"cursor": ["offset"]}
+            }))
+        );
+    }
+
+    #[test]
+    fn relationships_are_optional() {
+        assert_eq!(
+            Some(ParameterDependencies::default()),
+            ParameterDependencies::from_extension(&json!({}))
+        );
+    }
+
+    #[test]
+    fn names_must_be_strings() {
+        assert_eq!(
+            None,
+            ParameterDependencies::from_extension(&json!({"requires": {"sort_order": [1]}}))
+        );
+    }
+}
@@ -0,0 +1,322 @@
+//! C functions for gateways written in other languages, e.g. nginx modules or Envoy filters.
+//! Build a shared library with `cargo rustc --release --features ffi --crate-type cdylib`.
+//!
+//! A validator is created from the text of a YAML or JSON document with
+//! `openapi_validator_new` and released with `openapi_validator_free`. Validating a request
+//! returns `0` when it conforms, `1` when it does not and `-1` when the arguments are unusable.
+//! After a failure, `openapi_validator_last_error` returns the reason for the calling thread as
+//! JSON, e.g. `{"status": 400, "detail": "body does not match the specification"}`. A panic
+//! never unwinds into the host: the function returns as if its arguments were unusable, with
+//! the panic message as the last error.
+
+use std::cell::RefCell;
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::request::{Request, Validator};
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+fn set_last_error(status: Option<u16>, detail: &str) {
+    let error = serde_json::json!({ "status": status, "detail": detail });
+    let error = CString::new(error.to_string()).expect("JSON escapes NUL characters");
+    LAST_ERROR.with(|last| *last.borrow_mut() = Some(error));
+}
+
+fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+/// Runs the body of an entry point, returning `fallback` if it panics, as unwinding out of an
+/// `extern "C"` function aborts the process hosting the library.
+fn guard<T>(fallback: T, body: impl FnOnce() -> T) -> T {
+    catch_unwind(AssertUnwindSafe(body)).unwrap_or_else(|panic| {
+        let message = (panic.downcast_ref::<&str>().copied())
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown cause");
+        set_last_error(None, &format!("validator panicked: {message}"));
+        fallback
+    })
+}
+
+unsafe fn to_str<'a>(text: *const c_char) -> Option<&'a str> {
+    if text.is_null() {
+        return None;
+    }
+    CStr::from_ptr(text).to_str().ok()
+}
+
+struct FfiRequest<'a> {
+    method: &'a str,
+    url: &'a str,
+    headers: Vec<(&'a str, &'a str)>,
+    body: &'a [u8],
+}
+
+impl Request for FfiRequest<'_> {
+    fn url(&self) -> &str {
+        self.url
+    }
+
+    fn operation(&self) -> &str {
+        self.method
+    }
+
+    fn body(&self) -> &[u8] {
+        self.body
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        (self.headers.iter())
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.to_string())
+    }
+}
+
+/// Reads `name: value` header lines separated by `\n` or `\r\n`, ignoring blank lines.
+fn parse_headers(headers: &str) -> Option<Vec<(&str, &str)>> {
+    (headers.lines())
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            let (name, value) = line.split_once(':')?;
+            Some((name.trim(), value.trim()))
+        })
+        .collect()
+}
+
+/// Loads a validator from a NUL-terminated YAML or JSON document, returning null when it
+/// cannot be loaded.
+///
+/// # Safety
+///
+/// `spec` must be null or point to a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn openapi_validator_new(spec: *const c_char) -> *mut Validator {
+    guard(std::ptr::null_mut(), || {
+        let Some(spec) = to_str(spec) else {
+            set_last_error(None, "specification is not a UTF-8 string");
+            return std::ptr::null_mut();
+        };
+        match Validator::from_yaml_str(spec) {
+            Ok(validator) => {
+                clear_last_error();
+                Box::into_raw(Box::new(validator))
+            }
+            Err(error) => {
+                set_last_error(None, &error.to_string());
+                std::ptr::null_mut()
+            }
+        }
+    })
+}
+
+/// Releases a validator.
+///
+/// # Safety
+///
+/// `validator` must be null or have been returned by `openapi_validator_new`, and must not be
+/// used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn openapi_validator_free(validator: *mut Validator) {
+    guard((), || {
+        if !validator.is_null() {
+            drop(Box::from_raw(validator));
+        }
+    })
+}
+
+/// Validates a request. `url` is absolute or origin-form, such as `/pets?limit=10`, `headers`
+/// holds `name: value` lines and may be null, and `body` may be null when `body_len` is 0.
+///
+/// # Safety
+///
+/// `validator` must have been returned by `openapi_validator_new`, the strings must be null or
+/// NUL-terminated, and `body` must point to `body_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn openapi_validator_validate_request(
+    validator: *const Validator,
+    method: *const c_char,
+    url: *const c_char,
+    headers: *const c_char,
+    body: *const u8,
+    body_len: usize,
+) -> c_int {
+    guard(-1, || {
+        let Some(validator) = validator.as_ref() else {
+            set_last_error(None, "validator is null");
+            return -1;
+        };
+        let (Some(method), Some(url)) = (to_str(method), to_str(url)) else {
+            set_last_error(None, "method and URL must be UTF-8 strings");
+            return -1;
+        };
+        let headers = match headers.is_null() {
+            true => Some(Vec::new()),
+            false => to_str(headers).and_then(parse_headers),
+        };
+        let Some(headers) = headers else {
+            set_last_error(None, "headers must be UTF-8 'name: value' lines");
+            return -1;
+        };
+        let body = match body.is_null() {
+            true if body_len > 0 => {
+                set_last_error(None, "body is null");
+                return -1;
+            }
+            true => &[],
+            false => std::slice::from_raw_parts(body, body_len),
+        };
+
+        let request = FfiRequest {
+            method,
+            url,
+            headers,
+            body,
+        };
+        match validator.validate_request(&request) {
+            Ok(_) => {
+                clear_last_error();
+                0
+            }
+            Err(error) => {
+                set_last_error(Some(error.suggested_status()), &error.to_string());
+                1
+            }
+        }
+    })
+}
+
+/// Returns the last failure on the calling thread as a JSON object with a `status`, null when
+/// the failure was not a validation error, and a `detail`, or null when the last call
+/// succeeded. The string must be released with `openapi_validator_string_free`.
+#[no_mangle]
+pub extern "C" fn openapi_validator_last_error() -> *mut c_char {
+    guard(std::ptr::null_mut(), || {
+        LAST_ERROR.with(|last| match &*last.borrow() {
+            Some(error) => error.clone().into_raw(),
+            None => std::ptr::null_mut(),
+        })
+    })
+}
+
+/// Releases a string returned by this library.
+///
+/// # Safety
+///
+/// `string` must be null or have been returned by `openapi_validator_last_error`, and must not
+/// be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn openapi_validator_string_free(string: *mut c_char) {
+    guard((), || {
+        if !string.is_null() {
+            drop(CString::from_raw(string));
+        }
+    })
+}
+
+#[cfg(test)]
+mod test_ffi {
+    use super::*;
+    use indoc::indoc;
+    use std::ptr::null;
+
+    const SPEC: &str = indoc!(
+        r#"
+        openapi: 3.0.0
+        info:
+          title: pets
+          version: "1.0.0"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+              responses:
+                201:
+                  description: created
+        "#
+    );
+
+    fn last_error() -> Option<serde_json::Value> {
+        let error = openapi_validator_last_error();
+        if error.is_null() {
+            return None;
+        }
+        unsafe {
+            let value = serde_json::from_slice(CStr::from_ptr(error).to_bytes()).unwrap();
+            openapi_validator_string_free(error);
+            Some(value)
+        }
+    }
+
+    fn validate(headers: &CStr, body: &str) -> c_int {
+        let spec = CString::new(SPEC).unwrap();
+        unsafe {
+            let validator = openapi_validator_new(spec.as_ptr());
+            assert!(!validator.is_null());
+            let outcome = openapi_validator_validate_request(
+                validator,
+                c"POST".as_ptr(),
+                c"/pets".as_ptr(),
+                headers.as_ptr(),
+                body.as_ptr(),
+                body.len(),
+            );
+            openapi_validator_free(validator);
+            outcome
+        }
+    }
+
+    #[test]
+    fn accept_a_valid_request() {
+        let headers = c"Content-Type: application/json\r\nX-Trace: 1\r\n";
+        assert_eq!(0, validate(headers, r#"{"name": "rex"}"#));
+        assert_eq!(None, last_error());
+    }
+
+    #[test]
+    fn report_why_a_request_is_invalid() {
+        assert_eq!(1, validate(c"content-type: application/json", "{}"));
+        let detail = "body does not match the specification";
+        assert_eq!(
+            Some(serde_json::json!({"status": 400, "detail": detail})),
+            last_error()
+        );
+    }
+
+    #[test]
+    fn reject_unusable_arguments() {
+        assert_eq!(-1, validate(c"Content-Type", "{}"));
+        let outcome = unsafe {
+            openapi_validator_validate_request(null(), null(), null(), null(), null(), 0)
+        };
+        assert_eq!(-1, outcome);
+        assert!(last_error().is_some());
+    }
+
+    #[test]
+    fn report_why_a_specification_cannot_be_loaded() {
+        let validator = unsafe { openapi_validator_new(c"openapi: [".as_ptr()) };
+        assert!(validator.is_null());
+        assert!(last_error().unwrap()["detail"]
+            .as_str()
+            .unwrap()
+            .starts_with("specification is not valid YAML"));
+    }
+
+    #[test]
+    fn report_a_panic_instead_of_unwinding_into_the_host() {
+        assert_eq!(-1, guard(-1, || panic!("no operation")));
+        assert_eq!(
+            Some(serde_json::json!({"status": null, "detail": "validator panicked: no operation"})),
+            last_error()
+        );
+    }
+}
@@ -0,0 +1,723 @@
+use std::borrow::Cow;
+
+use indexmap::IndexMap;
+
+use crate::content_type::is_json;
+use crate::error::ValidationError;
+use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::{spec_pointer, SchemaLocation};
+use crate::parameters::validate_value;
+use crate::report::ValidationReport;
+
+/// How the fields of a form body are encoded.
+#[derive(Debug, PartialEq, Clone)]
+pub enum FormEncoding {
+    /// `application/x-www-form-urlencoded`
+    UrlEncoded,
+    /// `multipart/form-data`, split on the boundary given with the Content-Type.
+    Multipart { boundary: Option<String> },
+}
+
+/// Turns `application/x-www-form-urlencoded` and `multipart/form-data` bodies into a JSON
+/// object that can be checked against the media type's schema. Each property is decoded as
+/// its entry in the media type's `encoding` map describes.
+pub struct FormDecoder<'api> {
+    pub media_type: &'api openapiv3::MediaType,
+    pub components: &'api Option<openapiv3::Components>,
+}
+
+impl<'api> FormDecoder<'api> {
+    /// Collects the fields of a URL-encoded form. Arrays may repeat their key or, when their
+    /// encoding sets `explode: false`, join their items with the style's delimiter.
+    /// `deepObject` properties are read from `name[key]` fields.
+    ///
+    /// openapiv3 reads a missing `explode` as `false`, so an encoding entry that should
+    /// explode has to say so.
+    pub fn decode_urlencoded(&self, body: &[u8]) -> Result<serde_json::Value, ValidationError> {
+        let mut fields: IndexMap<String, Vec<String>> = IndexMap::new();
+        let mut deep_objects: IndexMap<String, serde_json::Map<String, serde_json::Value>> =
+            IndexMap::new();
+
+        for (key, value) in url::form_urlencoded::parse(body) {
+            match deep_object_key(&key).filter(|(name, _)| self.is_deep_object(name)) {
+                Some((name, property)) => {
+                    let schema = self.property_schema(name).and_then(|schema| {
+                        additional_property_schema(schema, property, self.components)
+                    });
+                    deep_objects
+                        .entry(name.to_string())
+                        .or_default()
+                        .insert(property.to_string(), coerce(&value, schema));
+                }
+                None => fields
+                    .entry(key.into_owned())
+                    .or_default()
+                    .push(value.into_owned()),
+            }
+        }
+
+        let mut object: serde_json::Map<String, serde_json::Value> = deep_objects
+            .into_iter()
+            .map(|(name, value)| (name, value.into()))
+            .collect();
+        for (name, values) in fields {
+            let encoding = self.media_type.encoding.get(&name);
+            let values = match encoding {
+                Some(encoding) if !encoding.explode && values.len() == 1 => {
+                    let delimiter = match encoding.style {
+                        Some(openapiv3::QueryStyle::SpaceDelimited) => ' ',
+                        Some(openapiv3::QueryStyle::PipeDelimited) => '|',
+                        _ => ',',
+                    };
+                    match self.is_array(&name) {
+                        true => values[0].split(delimiter).map(str::to_string).collect(),
+                        false => values,
+                    }
+                }
+                _ => values,
+            };
+            let value = self.field_value(&name, values, |value, schema| {
+                match encoding.and_then(|encoding| encoding.content_type.as_deref()) {
+                    Some(content_type) if accepts_json(content_type) => {
+                        serde_json::from_str(value).map_err(|_| ())
+                    }
+                    _ => Ok(coerce(value, schema)),
+                }
+            })?;
+            object.insert(name, value);
+        }
+
+        Ok(object.into())
+    }
+
+    /// Collects the parts of a multipart form. A part's Content-Type must be one of those its
+    /// encoding allows and the headers its encoding declares are checked against their
+    /// schemas. Parts that share a name make up an array.
+    pub fn decode_multipart(
+        &self,
+        body: &[u8],
+        boundary: Option<&str>,
+        location: &SchemaLocation,
+        report: &mut ValidationReport,
+    ) -> Result<serde_json::Value, ValidationError> {
+        let parts = parse_multipart(body, boundary.ok_or(ValidationError::InvalidBody)?)
+            .ok_or(ValidationError::InvalidBody)?;
+        let media_pointer = location
+            .pointer
+            .strip_suffix("/schema")
+            .unwrap_or(&location.pointer);
+
+        let mut grouped: IndexMap<&str, Vec<&Part>> = IndexMap::new();
+        for part in &parts {
+            grouped.entry(&part.name).or_default().push(part);
+        }
+
+        let mut object = serde_json::Map::new();
+        for (name, parts) in grouped {
+            let encoding = self.media_type.encoding.get(name);
+            let invalid = || ValidationError::InvalidBodyPart {
+                name: name.to_string(),
+            };
+
+            for part in &parts {
+                let content_type = part
+                    .header("Content-Type")
+                    .map(Cow::Borrowed)
+                    .unwrap_or_else(|| Cow::Owned(self.default_content_type(name)));
+                if let Some(allowed) =
+                    encoding.and_then(|encoding| encoding.content_type.as_deref())
+                {
+                    if !content_type_allowed(allowed, &content_type) {
+                        return Err(invalid());
+                    }
+                }
+
+                for (header_name, header) in encoding.iter().flat_map(|encoding| &encoding.headers)
+                {
+                    if header_name.eq_ignore_ascii_case("content-type") {
+                        continue;
+                    }
+                    let header_spec = header.item_or_fetch(self.components)?;
+                    match part.header(header_name) {
+                        Some(value) => {
+                            let header_location = || match header {
+                                openapiv3::ReferenceOr::Reference { reference } => {
+                                    spec_pointer(reference, ["schema"])
+                                }
+                                openapiv3::ReferenceOr::Item(..) => spec_pointer(
+                                    media_pointer,
+                                    ["encoding", name, "headers", header_name, "schema"],
+                                ),
+                            };
+                            validate_value(
+                                value,
+                                &header_spec.format,
+                                self.components,
+                                location.options,
+                                location.schemas,
+                                header_location,
+                                Some(report),
+                            )
+                            .map_err(|error| error.unwrap_or_else(invalid))?;
+                        }
+                        None if header_spec.required => return Err(invalid()),
+                        None => {}
+                    }
+                }
+            }
+
+            let texts = parts
+                .iter()
+                .map(|part| part.text(self.is_binary(name), &self.default_content_type(name)))
+                .collect::<Option<Vec<_>>>()
+                .ok_or_else(invalid)?;
+            let value = self.field_value(name, texts, |text, schema| match text {
+                PartText::Json(json) => serde_json::from_str(json).map_err(|_| ()),
+                PartText::Plain(plain) => Ok(coerce(plain, schema)),
+            })?;
+            object.insert(name.to_string(), value);
+        }
+
+        Ok(object.into())
+    }
+
+    /// A property's value from the strings given for it: an array of their items when the
+    /// property is an array, otherwise its only value.
+    fn field_value<T>(
+        &self,
+        name: &str,
+        values: Vec<T>,
+        decode: impl Fn(&T, Option<&openapiv3::Schema>) -> Result<serde_json::Value, ()>,
+    ) -> Result<serde_json::Value, ValidationError> {
+        let invalid = || ValidationError::InvalidBodyPart {
+            name: name.to_string(),
+        };
+        let schema = self.property_schema(name);
+
+        match schema.map(|schema| &schema.schema_kind) {
+            Some(openapiv3::SchemaKind::Type(openapiv3::Type::Array(array))) => {
+                let items = array
+                    .items
+                    .as_ref()
+                    .map(|items| items.item_or_fetch(self.components))
+                    .transpose()?;
+                values
+                    .iter()
+                    .map(|value| decode(value, items))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(serde_json::Value::from)
+                    .map_err(|_| invalid())
+            }
+            _ => match values.as_slice() {
+                [value] => decode(value, schema).map_err(|_| invalid()),
+                _ => Err(invalid()),
+            },
+        }
+    }
+
+    fn property_schema(&self, name: &str) -> Option<&'api openapiv3::Schema> {
+        let schema = self
+            .media_type
+            .schema
+            .as_ref()?
+            .item_or_fetch(self.components)
+            .ok()?;
+        match &schema.schema_kind {
+            openapiv3::SchemaKind::Type(openapiv3::Type::Object(object)) => object
+                .properties
+                .get(name)
+                .and_then(|property| property.item_or_fetch(self.components).ok()),
+            _ => None,
+        }
+    }
+
+    fn is_array(&self, name: &str) -> bool {
+        matches!(
+            self.property_schema(name).map(|schema| &schema.schema_kind),
+            Some(openapiv3::SchemaKind::Type(openapiv3::Type::Array(..)))
+        )
+    }
+
+    fn is_binary(&self, name: &str) -> bool {
+        matches!(
+            self.property_schema(name).map(|schema| &schema.schema_kind),
+            Some(openapiv3::SchemaKind::Type(openapiv3::Type::String(
+                openapiv3::StringType {
+                    format: openapiv3::VariantOrUnknownOrEmpty::Item(
+                        openapiv3::StringFormat::Binary
+                    ),
+                    ..
+                }
+            )))
+        )
+    }
+
+    fn is_deep_object(&self, name: &str) -> bool {
+        self.media_type
+            .encoding
+            .get(name)
+            .is_some_and(|encoding| encoding.style == Some(openapiv3::QueryStyle::DeepObject))
+    }
+
+    /// The Content-Type a part has when it sends none: `application/octet-stream` for binary
+    /// strings, `application/json` for objects and `text/plain` otherwise.
+    fn default_content_type(&self, name: &str) -> String {
+        match self.property_schema(name).map(|schema| &schema.schema_kind) {
+            _ if self.is_binary(name) => "application/octet-stream",
+            Some(openapiv3::SchemaKind::Type(openapiv3::Type::Object(..))) => "application/json",
+            _ => "text/plain",
+        }
+        .to_string()
+    }
+}
+
+/// Splits a `name[key]` form key into its name and key.
+fn deep_object_key(key: &str) -> Option<(&str, &str)> {
+    let (name, rest) = key.split_once('[')?;
+    Some((name, rest.strip_suffix(']')?))
+}
+
+/// The schema of one property of an object, declared or additional.
+fn additional_property_schema<'api>(
+    schema: &'api openapiv3::Schema,
+    property: &str,
+    components: &'api Option<openapiv3::Components>,
+) -> Option<&'api openapiv3::Schema> {
+    let openapiv3::SchemaKind::Type(openapiv3::Type::Object(object)) = &schema.schema_kind else {
+        return None;
+    };
+    match (
+        object.properties.get(property),
+        &object.additional_properties,
+    ) {
+        (Some(schema), _) => schema.item_or_fetch(components).ok(),
+        (None, Some(openapiv3::AdditionalProperties::Schema(schema))) => {
+            schema.item_or_fetch(components).ok()
+        }
+        _ => None,
+    }
+}
+
+/// Reads a form value as the type its schema declares, leaving it a string when it is not one
+/// so that schema validation reports it.
+fn coerce(value: &str, schema: Option<&openapiv3::Schema>) -> serde_json::Value {
+    let coerced = match schema.map(|schema| &schema.schema_kind) {
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Integer(..))) => {
+            value.parse::<i64>().ok().map(serde_json::Value::from)
+        }
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Number(..))) => value
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number),
+        Some(openapiv3::SchemaKind::Type(openapiv3::Type::Boolean {})) => {
+            value.parse::<bool>().ok().map(serde_json::Value::from)
+        }
+        _ => None,
+    };
+    coerced.unwrap_or_else(|| serde_json::Value::from(value))
+}
+
+fn accepts_json(content_types: &str) -> bool {
+    content_types
+        .split(',')
+        .filter_map(|content_type| content_type.trim().parse::<mime::Mime>().ok())
+        .any(|mime| is_json(&mime))
+}
+
+/// Whether a part's Content-Type is in an encoding's comma-separated list of media types and
+/// ranges such as `image/*`.
+fn content_type_allowed(allowed: &str, content_type: &str) -> bool {
+    let Ok(mime) = content_type.parse::<mime::Mime>() else {
+        return false;
+    };
+    allowed
+        .split(',')
+        .filter_map(|range| range.trim().parse::<mime::Mime>().ok())
+        .any(|range| {
+            (range.type_() == mime::STAR || range.type_() == mime.type_())
+                && (range.subtype() == mime::STAR || range.subtype() == mime.subtype())
+        })
+}
+
+struct Part<'body> {
+    name: String,
+    headers: Vec<(&'body str, &'body str)>,
+    content: &'body [u8],
+}
+
+enum PartText<'body> {
+    Json(Cow<'body, str>),
+    Plain(Cow<'body, str>),
+}
+
+impl<'body> Part<'body> {
+    fn header(&self, name: &str) -> Option<&'body str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| *value)
+    }
+
+    /// The part's content as text, `None` when it is not valid UTF-8. Binary content is kept
+    /// as it is, with invalid sequences replaced.
+    fn text(&self, binary: bool, default_content_type: &str) -> Option<PartText<'body>> {
+        if binary {
+            return Some(PartText::Plain(String::from_utf8_lossy(self.content)));
+        }
+        let text = Cow::Borrowed(std::str::from_utf8(self.content).ok()?);
+        let content_type = self.header("Content-Type").unwrap_or(default_content_type);
+        match content_type.parse::<mime::Mime>() {
+            Ok(mime) if is_json(&mime) => Some(PartText::Json(text)),
+            _ => Some(PartText::Plain(text)),
+        }
+    }
+}
+
+/// Splits a multipart body into its parts, `None` when it is malformed or a part has no
+/// `Content-Disposition` name.
+fn parse_multipart<'body>(body: &'body [u8], boundary: &str) -> Option<Vec<Part<'body>>> {
+    let delimiter = format!("--{boundary}");
+    let separator = format!("\r\n{delimiter}");
+
+    let start = find(body, delimiter.as_bytes())?;
+    let mut rest = &body[start + delimiter.len()..];
+    let mut parts = vec![];
+    loop {
+        if rest.starts_with(b"--") {
+            return Some(parts);
+        }
+        rest = rest.strip_prefix(b"\r\n")?;
+        let end = find(rest, separator.as_bytes())?;
+        let (head, content) = match rest[..end].strip_prefix(b"\r\n") {
+            Some(content) => (&b""[..], content),
+            None => {
+                let split = find(&rest[..end], b"\r\n\r\n")?;
+                (&rest[..split], &rest[split + 4..end])
+            }
+        };
+        rest = &rest[end + separator.len()..];
+
+        let headers = std::str::from_utf8(head)
+            .ok()?
+            .split("\r\n")
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                line.split_once(':')
+                    .map(|(key, value)| (key.trim(), value.trim()))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let name = headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case("Content-Disposition"))
+            .and_then(|(_, value)| disposition_name(value))?;
+        parts.push(Part {
+            name,
+            headers,
+            content,
+        });
+    }
+}
+
+fn disposition_name(disposition: &str) -> Option<String> {
+    disposition.split(';').skip(1).find_map(|parameter| {
+        let (key, value) = parameter.trim().split_once('=')?;
+        (key.trim() == "name").then(|| value.trim().trim_matches('"').to_string())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod test_urlencoded_forms {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                content:
+                  application/x-www-form-urlencoded:
+                    schema:
+                      type: object
+                      properties:
+                        id:
+                          type: integer
+                        name:
+                          type: string
+                        vip:
+                          type: boolean
+                        tags:
+                          type: array
+                          items:
+                            type: string
+                        ids:
+                          type: array
+                          items:
+                            type: integer
+                        filter:
+                          type: object
+                          properties:
+                            min:
+                              type: integer
+                        address:
+                          type: object
+                          required:
+                            - city
+                      required:
+                        - name
+                    encoding:
+                      ids:
+                        style: pipeDelimited
+                        explode: false
+                      filter:
+                        style: deepObject
+                        explode: true
+                      address:
+                        contentType: application/json
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/pets".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([(
+                "Content-Type".to_string(),
+                "application/x-www-form-urlencoded".to_string(),
+            )]),
+        }
+    }
+
+    #[parameterized(body = {
+        "name=Rex",
+        "id=3&name=Rex+the+Dog&vip=true",
+        "name=Rex&tags=good&tags=boy",
+        "name=Rex&ids=1|2|3",
+        "name=Rex&filter[min]=2",
+        "name=Rex&address=%7B%22city%22%3A%22Paris%22%7D",
+    })]
+    fn accept_a_form_matching_its_schema_and_encoding(body: &str) {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(body))
+            .is_ok());
+    }
+
+    #[parameterized(body = {
+        "id=3",
+        "name=Rex&id=three",
+        "name=Rex&vip=maybe",
+        "name=Rex&ids=1|two",
+        "name=Rex&filter[min]=low",
+        "name=Rex&address=%7B%7D",
+    })]
+    fn reject_a_form_not_matching_its_schema(body: &str) {
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(body))
+        );
+    }
+
+    #[parameterized(body = {
+        "name=Rex&name=Tom",
+        "name=Rex&address=Paris",
+    }, name = {
+        "name",
+        "address",
+    })]
+    fn reject_a_field_that_cannot_be_decoded(body: &str, name: &str) {
+        assert_eq!(
+            Err(ValidationError::InvalidBodyPart {
+                name: name.to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(body))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_multipart_forms {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /uploads:
+            post:
+              requestBody:
+                content:
+                  multipart/form-data:
+                    schema:
+                      type: object
+                      properties:
+                        count:
+                          type: integer
+                        details:
+                          type: object
+                          required:
+                            - title
+                        avatar:
+                          type: string
+                          format: binary
+                      required:
+                        - avatar
+                    encoding:
+                      avatar:
+                        contentType: image/png, image/jpeg
+                        headers:
+                          X-Rate-Limit:
+                            required: true
+                            schema:
+                              type: integer
+                          X-Trace:
+                            content:
+                              text/plain:
+                                schema:
+                                  type: string
+                                  maxLength: 4
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(content_type: &str, parts: &[&str]) -> FakeRequest {
+        let body = parts
+            .iter()
+            .map(|part| format!("--xyz\r\n{part}\r\n"))
+            .collect::<String>()
+            + "--xyz--\r\n";
+        FakeRequest {
+            url: "http://test.com/uploads".to_string(),
+            operation: "post".to_string(),
+            body: body.into_bytes(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    const AVATAR: &str = "Content-Disposition: form-data; name=\"avatar\"; filename=\"a.png\"\r\n\
+        Content-Type: image/png\r\nX-Rate-Limit: 10\r\n\r\n\u{89}PNG";
+
+    #[test]
+    fn accept_parts_matching_their_schema_and_encoding() {
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&make_request(
+                "multipart/form-data; boundary=xyz",
+                &[
+                    "Content-Disposition: form-data; name=\"count\"\r\n\r\n2",
+                    "Content-Disposition: form-data; name=\"details\"\r\n\r\n{\"title\": \"Me\"}",
+                    AVATAR,
+                ]
+            ))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_parts_not_matching_their_schema() {
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(
+                "multipart/form-data; boundary=xyz",
+                &[
+                    "Content-Disposition: form-data; name=\"count\"\r\n\r\ntwo",
+                    AVATAR
+                ]
+            ))
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(
+                "multipart/form-data; boundary=xyz",
+                &[
+                    "Content-Disposition: form-data; name=\"details\"\r\n\r\n{}",
+                    AVATAR
+                ]
+            ))
+        );
+    }
+
+    #[test]
+    fn reject_a_part_with_a_content_type_its_encoding_does_not_allow() {
+        assert_eq!(
+            Err(ValidationError::InvalidBodyPart {
+                name: "avatar".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(
+                "multipart/form-data; boundary=xyz",
+                &[&AVATAR.replace("image/png", "text/plain")]
+            ))
+        );
+    }
+
+    #[test]
+    fn check_the_headers_declared_by_a_part_encoding() {
+        for avatar in [
+            AVATAR.replace("X-Rate-Limit: 10\r\n", ""),
+            AVATAR.replace("X-Rate-Limit: 10", "X-Rate-Limit: lots"),
+        ] {
+            assert_eq!(
+                Err(ValidationError::InvalidBodyPart {
+                    name: "avatar".to_string()
+                }),
+                make_validator_from_spec(PATH_SPEC).validate_request(&make_request(
+                    "multipart/form-data; boundary=xyz",
+                    &[&avatar]
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn check_a_part_header_described_by_content() {
+        let validate = |trace: &str| {
+            let avatar =
+                AVATAR.replace("X-Rate-Limit", &format!("X-Trace: {trace}\r\nX-Rate-Limit"));
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&make_request(
+                    "multipart/form-data; boundary=xyz",
+                    &[&avatar],
+                ))
+                .map(|_| ())
+        };
+        assert_eq!(Ok(()), validate("abc"));
+        assert_eq!(
+            Err(ValidationError::InvalidBodyPart {
+                name: "avatar".to_string()
+            }),
+            validate("too long")
+        );
+    }
+
+    #[test]
+    fn reject_a_malformed_multipart_body() {
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&make_request("multipart/form-data", &[AVATAR]))
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC).validate_request(&make_request(
+                "multipart/form-data; boundary=xyz",
+                &["Content-Type: text/plain\r\n\r\nno name"]
+            ))
+        );
+    }
+}
@@ -0,0 +1,138 @@
+use crate::error::ValidationError;
+use crate::request::Request;
+use crate::response::Response;
+
+/// Validates `http` requests, as hyper, axum and tower services receive them, with the body
+/// already collected. Only the path and query of the URI are read, so the origin of the URL
+/// is not compared with the specification's `servers`.
+impl<B: AsRef<[u8]>> Request for http::Request<B> {
+    fn url(&self) -> &str {
+        (self.uri().path_and_query()).map_or("/", http::uri::PathAndQuery::as_str)
+    }
+
+    fn operation(&self) -> &str {
+        http::Request::method(self).as_str()
+    }
+
+    fn body(&self) -> &[u8] {
+        http::Request::body(self).as_ref()
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        let value = self.headers().get(key)?;
+        value.to_str().ok().map(str::to_string)
+    }
+}
+
+/// Validates `http` responses whose body is held in memory.
+impl<B: AsRef<[u8]>> Response for http::Response<B> {
+    fn status_code(&self) -> u16 {
+        self.status().as_u16()
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        let value = self.headers().get(key)?;
+        value.to_str().ok().map(str::to_string)
+    }
+
+    fn body(&self) -> &[u8] {
+        http::Response::body(self).as_ref()
+    }
+}
+
+/// An RFC 9457 `application/problem+json` response with the error's suggested status and its
+/// message as the detail.
+pub fn problem_json(error: &ValidationError) -> http::Response<String> {
+    let status = http::StatusCode::from_u16(error.suggested_status())
+        .unwrap_or(http::StatusCode::BAD_REQUEST);
+    let problem = serde_json::json!({
+        "type": "about:blank",
+        "title": status.canonical_reason(),
+        "status": status.as_u16(),
+        "detail": error.to_string(),
+    });
+    let mut response = http::Response::new(problem.to_string());
+    *response.status_mut() = status;
+    response.headers_mut().insert(
+        http::header::CONTENT_TYPE,
+        http::HeaderValue::from_static("application/problem+json"),
+    );
+    response
+}
+
+#[cfg(test)]
+mod test_http_request {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            put:
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+                - in: header
+                  name: X-Trace
+                  required: true
+                  schema:
+                    type: integer
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn put(uri: &str, body: &'static str) -> http::Request<&'static str> {
+        http::Request::put(uri)
+            .header("Content-Type", "application/json")
+            .header("X-Trace", "3")
+            .body(body)
+            .unwrap()
+    }
+
+    #[test]
+    fn validate_an_origin_form_request() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator
+            .validate_request(&put("/pets/7", r#"{"name": "rex"}"#))
+            .unwrap();
+        assert_eq!(Some(&json!(7)), validated.parameters.path.get("id"));
+        assert_eq!(Some(&json!(3)), validated.parameters.header.get("X-Trace"));
+    }
+
+    #[test]
+    fn validate_an_absolute_form_request_with_a_byte_body() {
+        let request = http::Request::put("http://test.com/pets/7")
+            .header("Content-Type", "application/json")
+            .header("X-Trace", "3")
+            .body(br#"{"name": "rex"}"#.to_vec())
+            .unwrap();
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_an_invalid_body() {
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&put("/pets/7", "{}"))
+                .map(|_| ())
+        );
+    }
+}
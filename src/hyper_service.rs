@@ -0,0 +1,253 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body, Buf, Bytes};
+
+use crate::error::ValidationError;
+use crate::http_request::problem_json;
+use crate::request::Validator;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Wraps a hyper service so that every request is validated before it reaches `inner`, and
+/// answered with [`problem_json`] when it does not conform. Request bodies are buffered up to
+/// [`crate::ValidatorOptions::max_body_size`], and the inner service receives them in full.
+pub fn validate_service<S>(validator: Arc<Validator>, inner: S) -> ValidatedService<S> {
+    ValidatedService {
+        inner: Arc::new(inner),
+        validator,
+        validate_responses: false,
+    }
+}
+
+pub struct ValidatedService<S> {
+    inner: Arc<S>,
+    validator: Arc<Validator>,
+    validate_responses: bool,
+}
+
+impl<S> ValidatedService<S> {
+    /// Also buffers and validates the responses of the inner service, replacing invalid ones
+    /// with a problem with the status 500.
+    pub fn validate_responses(mut self, validate: bool) -> Self {
+        self.validate_responses = validate;
+        self
+    }
+}
+
+impl<S> Clone for ValidatedService<S> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+            validator: self.validator.clone(),
+            validate_responses: self.validate_responses,
+        }
+    }
+}
+
+impl<S, ReqBody, ResBody> hyper::service::Service<http::Request<ReqBody>> for ValidatedService<S>
+where
+    S: hyper::service::Service<http::Request<Full<Bytes>>, Response = http::Response<ResBody>>
+        + Send
+        + Sync
+        + 'static,
+    S::Error: Into<BoxError>,
+    S::Future: Send,
+    ReqBody: Body + Send + 'static,
+    ReqBody::Data: Send,
+    ResBody: Body + Send + 'static,
+    ResBody::Data: Send,
+    ResBody::Error: Into<BoxError>,
+{
+    type Response = http::Response<Full<Bytes>>;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, BoxError>> + Send>>;
+
+    fn call(&self, request: http::Request<ReqBody>) -> Self::Future {
+        let service = self.clone();
+        Box::pin(async move {
+            let (parts, body) = request.into_parts();
+            let limit = service.validator.options().max_body_size;
+            let body = match collect(body, limit).await {
+                Ok(body) => body,
+                Err(error) => return Ok(reject(&error)),
+            };
+            let request = http::Request::from_parts(parts, body);
+            let operation = match service.validator.validate_request(&request) {
+                Ok(validated) => validated.operation,
+                Err(error) => return Ok(reject(&error)),
+            };
+            // the response is checked against the operation the request was routed to
+            let response_validator = (service.validate_responses).then(|| {
+                let accept = (request.headers().get(http::header::ACCEPT))
+                    .and_then(|accept| accept.to_str().ok())
+                    .map(str::to_string);
+                (service.validator).operation_response_validator(&operation, accept)
+            });
+
+            let response = (service.inner)
+                .call(request.map(Full::new))
+                .await
+                .map_err(Into::into)?;
+            let (parts, body) = response.into_parts();
+            let body = body.collect().await.map_err(Into::into)?.to_bytes();
+            let response = http::Response::from_parts(parts, body);
+            if let Some(response_validator) = response_validator {
+                if let Err(error) = response_validator.validate_response(&response) {
+                    return Ok(reject(&error));
+                }
+            }
+            Ok(response.map(Full::new))
+        })
+    }
+}
+
+/// Reads a body into memory, giving up as soon as it is larger than `limit`.
+pub(crate) async fn collect<B: Body>(
+    body: B,
+    limit: Option<usize>,
+) -> Result<Bytes, ValidationError> {
+    let mut body = std::pin::pin!(body);
+    let mut bytes = Vec::new();
+    while let Some(frame) = body.frame().await {
+        let frame = frame.map_err(|_| ValidationError::UnreadableBody)?;
+        if let Ok(mut data) = frame.into_data() {
+            while data.has_remaining() {
+                let chunk = data.chunk();
+                bytes.extend_from_slice(chunk);
+                let read = chunk.len();
+                data.advance(read);
+            }
+        }
+        match limit {
+            Some(limit) if bytes.len() > limit => {
+                return Err(ValidationError::BodyTooLarge {
+                    size: bytes.len(),
+                    limit,
+                })
+            }
+            _ => (),
+        }
+    }
+    Ok(bytes.into())
+}
+
+fn reject(error: &ValidationError) -> http::Response<Full<Bytes>> {
+    problem_json(error).map(|body| Full::new(body.into()))
+}
+
+#[cfg(test)]
+mod test_hyper_service {
+    use super::*;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use hyper::service::{service_fn, Service};
+    use indoc::indoc;
+    use std::convert::Infallible;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+              responses:
+                201:
+                  description: created
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [id]
+        "#
+    );
+
+    async fn post(
+        service: &impl Service<
+            http::Request<Full<Bytes>>,
+            Response = http::Response<Full<Bytes>>,
+            Error = BoxError,
+        >,
+        body: &'static str,
+    ) -> (u16, Bytes) {
+        let request = http::Request::post("/pets")
+            .header("Content-Type", "application/json")
+            .body(Full::new(Bytes::from(body)))
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        let status = response.status().as_u16();
+        (status, response.collect().await.unwrap().to_bytes())
+    }
+
+    type Inner = http::Request<Full<Bytes>>;
+    type Answer = http::Response<Full<Bytes>>;
+
+    fn service(
+        validator: Validator,
+        answer: &'static str,
+    ) -> ValidatedService<
+        impl Service<Inner, Response = Answer, Error = Infallible, Future: Send> + Send + Sync,
+    > {
+        let inner = service_fn(move |request: Inner| async move {
+            let _ = request.into_body().collect().await;
+            let mut response = http::Response::new(Full::new(Bytes::from(answer)));
+            *response.status_mut() = http::StatusCode::CREATED;
+            response.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("application/json"),
+            );
+            Ok::<_, Infallible>(response)
+        });
+        validate_service(Arc::new(validator), inner)
+    }
+
+    #[tokio::test]
+    async fn pass_a_valid_request_through() {
+        let service = service(make_validator_from_spec(PATH_SPEC), r#"{"id": 1}"#);
+        assert_eq!(
+            (201, Bytes::from(r#"{"id": 1}"#)),
+            post(&service, r#"{"name": "rex"}"#).await
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_an_invalid_request() {
+        let service = service(make_validator_from_spec(PATH_SPEC), r#"{"id": 1}"#);
+        assert_eq!(400, post(&service, "{}").await.0);
+    }
+
+    #[tokio::test]
+    async fn stop_reading_a_body_over_the_limit() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            max_body_size: Some(8),
+            ..Default::default()
+        });
+        let service = service(validator, r#"{"id": 1}"#);
+        assert_eq!(413, post(&service, r#"{"name": "rex"}"#).await.0);
+    }
+
+    #[tokio::test]
+    async fn validate_responses_when_asked() {
+        let service = service(make_validator_from_spec(PATH_SPEC), "{}");
+        assert_eq!(201, post(&service, r#"{"name": "rex"}"#).await.0);
+        let service = service.validate_responses(true);
+        assert_eq!(500, post(&service, r#"{"name": "rex"}"#).await.0);
+    }
+
+    #[tokio::test]
+    async fn validate_the_request_once_when_validating_responses() {
+        let (sender, records) = std::sync::mpsc::channel();
+        let validator = make_validator_from_spec(PATH_SPEC).with_recorder(sender);
+        let service = service(validator, "{}").validate_responses(true);
+        assert_eq!(500, post(&service, r#"{"name": "rex"}"#).await.0);
+        assert_eq!(1, records.try_iter().count());
+    }
+}
@@ -1,7 +1,20 @@
-use std::ops::Index;
+use crate::error::ValidationError;
 
+/// Resolves an item given inline or as a reference to `components`, following chains of
+/// references. Fails with the reference that points at nothing, or with the one closing a
+/// cycle: a chain longer than the components it points into must revisit one of them.
 pub trait ItemOrFetch<T> {
-    fn item_or_fetch<'api>(&'api self, components: &'api Option<openapiv3::Components>) -> &T;
+    fn item_or_fetch<'api>(
+        &'api self,
+        components: &'api Option<openapiv3::Components>,
+    ) -> Result<&'api T, ValidationError>;
+
+    /// Resolves the item following at most `hops` more references.
+    fn item_or_fetch_within<'api>(
+        &'api self,
+        components: &'api Option<openapiv3::Components>,
+        hops: usize,
+    ) -> Result<&'api T, ValidationError>;
 }
 
 macro_rules! item_or_fetch_impl {
@@ -10,15 +23,31 @@ macro_rules! item_or_fetch_impl {
             fn item_or_fetch<'api>(
                 &'api self,
                 components: &'api Option<openapiv3::Components>,
-            ) -> &$item_ty {
+            ) -> Result<&'api $item_ty, ValidationError> {
+                let hops =
+                    (components.as_ref()).map_or(0, |components| components.$component_field.len());
+                self.item_or_fetch_within(components, hops)
+            }
+
+            fn item_or_fetch_within<'api>(
+                &'api self,
+                components: &'api Option<openapiv3::Components>,
+                hops: usize,
+            ) -> Result<&'api $item_ty, ValidationError> {
                 match self {
-                    Self::Item(item) => item,
+                    Self::Item(item) => Ok(item),
                     Self::Reference { reference } => components
                         .as_ref()
-                        .unwrap()
-                        .$component_field
-                        .index(reference.trim_start_matches($component_path))
-                        .item_or_fetch(components),
+                        .filter(|_| hops > 0)
+                        .and_then(|components| {
+                            components
+                                .$component_field
+                                .get(reference.strip_prefix($component_path)?)
+                        })
+                        .ok_or_else(|| ValidationError::UnresolvedReference {
+                            reference: reference.to_string(),
+                        })?
+                        .item_or_fetch_within(components, hops - 1),
                 }
             }
         }
@@ -31,6 +60,12 @@ item_or_fetch_impl!(
     schemas,
     "#/components/schemas/"
 );
+item_or_fetch_impl!(
+    openapiv3::Schema,
+    openapiv3::ReferenceOr<Box<openapiv3::Schema>>,
+    schemas,
+    "#/components/schemas/"
+);
 item_or_fetch_impl!(
     openapiv3::Parameter,
     openapiv3::ReferenceOr<openapiv3::Parameter>,
@@ -43,3 +78,119 @@ item_or_fetch_impl!(
     request_bodies,
     "#/components/requestBodies/"
 );
+item_or_fetch_impl!(
+    openapiv3::Header,
+    openapiv3::ReferenceOr<openapiv3::Header>,
+    headers,
+    "#/components/headers/"
+);
+item_or_fetch_impl!(
+    openapiv3::Response,
+    openapiv3::ReferenceOr<openapiv3::Response>,
+    responses,
+    "#/components/responses/"
+);
+item_or_fetch_impl!(
+    openapiv3::SecurityScheme,
+    openapiv3::ReferenceOr<openapiv3::SecurityScheme>,
+    security_schemes,
+    "#/components/securitySchemes/"
+);
+item_or_fetch_impl!(
+    openapiv3::Link,
+    openapiv3::ReferenceOr<openapiv3::Link>,
+    links,
+    "#/components/links/"
+);
+item_or_fetch_impl!(
+    openapiv3::Example,
+    openapiv3::ReferenceOr<openapiv3::Example>,
+    examples,
+    "#/components/examples/"
+);
+item_or_fetch_impl!(
+    openapiv3::Callback,
+    openapiv3::ReferenceOr<openapiv3::Callback>,
+    callbacks,
+    "#/components/callbacks/"
+);
+
+#[cfg(test)]
+mod test_item_or_fetch {
+    use super::*;
+    use indoc::indoc;
+
+    fn components() -> Option<openapiv3::Components> {
+        serde_yaml::from_str(indoc!(
+            r#"
+            responses:
+              NotFound:
+                $ref: '#/components/responses/Missing'
+              Missing:
+                description: Not found
+            callbacks:
+              onEvent:
+                '{$request.body#/url}':
+                  post:
+                    responses:
+                      200:
+                        description: Received
+            "#
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn follow_chains_of_component_references() {
+        let components = components();
+        let response =
+            openapiv3::ReferenceOr::<openapiv3::Response>::ref_("#/components/responses/NotFound");
+        assert_eq!(
+            "Not found",
+            response.item_or_fetch(&components).unwrap().description
+        );
+        let callback =
+            openapiv3::ReferenceOr::<openapiv3::Callback>::ref_("#/components/callbacks/onEvent");
+        assert!(callback
+            .item_or_fetch(&components)
+            .unwrap()
+            .contains_key("{$request.body#/url}"));
+    }
+
+    #[test]
+    fn report_the_reference_that_resolves_to_nothing() {
+        for reference in [
+            "#/components/headers/Missing",
+            "#/components/responses/Missing",
+        ] {
+            assert_eq!(
+                Err(ValidationError::UnresolvedReference {
+                    reference: reference.to_string()
+                }),
+                openapiv3::ReferenceOr::<openapiv3::Header>::ref_(reference)
+                    .item_or_fetch(&components())
+            );
+        }
+    }
+
+    #[test]
+    fn report_a_cycle_of_references() {
+        let components = serde_yaml::from_str(indoc!(
+            r#"
+            schemas:
+              A:
+                $ref: '#/components/schemas/B'
+              B:
+                $ref: '#/components/schemas/A'
+            "#
+        ))
+        .unwrap();
+        assert_eq!(
+            Err(ValidationError::UnresolvedReference {
+                reference: "#/components/schemas/A".to_string()
+            }),
+            openapiv3::ReferenceOr::<openapiv3::Schema>::ref_("#/components/schemas/A")
+                .item_or_fetch(&Some(components))
+        );
+    }
+}
@@ -1,19 +1,645 @@
 use jsonschema::JSONSchema;
+use std::sync::Arc;
+
+use crate::error::ValidationError;
+use crate::options::{SchemaCompileFailure, ValidatorOptions};
+use crate::report::{ValidationReport, ValidationWarning};
+use crate::schema_cache::CompiledSchemas;
+use crate::to_jsonschema::Direction;
+
+#[derive(Debug, PartialEq)]
+pub enum SchemaFailure {
+    Invalid,
+    Uncompilable,
+}
 
 pub trait JSONSchemaValidator {
-    fn validates(&self, input: &str) -> Result<(), ()>;
+    fn validates_value(
+        &self,
+        instance: &serde_json::Value,
+        options: &ValidatorOptions,
+    ) -> Result<(), SchemaFailure>;
 }
 
 impl JSONSchemaValidator for serde_json::Value {
-    fn validates(&self, input: &str) -> Result<(), ()> {
-        let json_parameter: serde_json::Value = serde_json::from_str(input).map_err(|_| ())?;
+    fn validates_value(
+        &self,
+        instance: &serde_json::Value,
+        options: &ValidatorOptions,
+    ) -> Result<(), SchemaFailure> {
+        check(compile(self, options).as_ref(), self, instance, options)
+    }
+}
+
+/// A converted schema compiled ahead of validation, `compiled` being `None` when it does not
+/// compile. The document is kept for the checks made outside the compiled validator.
+#[derive(Debug)]
+pub struct CompiledSchema {
+    document: serde_json::Value,
+    compiled: Option<JSONSchema>,
+}
 
-        let schema = JSONSchema::compile(&self).map_err(|_| ())?;
+impl CompiledSchema {
+    pub(crate) fn new(document: serde_json::Value, options: &ValidatorOptions) -> Self {
+        Self {
+            compiled: compile(&document, options),
+            document,
+        }
+    }
+}
 
-        if !schema.is_valid(&json_parameter) {
-            return Err(());
+/// Compiled schemas are the same when they were compiled from the same document.
+impl PartialEq for CompiledSchema {
+    fn eq(&self, other: &Self) -> bool {
+        self.document == other.document
+    }
+}
+
+impl JSONSchemaValidator for CompiledSchema {
+    fn validates_value(
+        &self,
+        instance: &serde_json::Value,
+        options: &ValidatorOptions,
+    ) -> Result<(), SchemaFailure> {
+        check(self.compiled.as_ref(), &self.document, instance, options)
+    }
+}
+
+fn compile(document: &serde_json::Value, options: &ValidatorOptions) -> Option<JSONSchema> {
+    let mut compilation = JSONSchema::options();
+    compilation
+        .should_validate_formats(!options.skip_format_validation)
+        .with_format("uuid", is_uuid);
+    for (name, check) in &options.formats {
+        compilation.with_format(name, *check);
+    }
+    compilation.compile(document).ok()
+}
+
+fn check(
+    compiled: Option<&JSONSchema>,
+    document: &serde_json::Value,
+    instance: &serde_json::Value,
+    options: &ValidatorOptions,
+) -> Result<(), SchemaFailure> {
+    let compiled = compiled.ok_or(SchemaFailure::Uncompilable)?;
+
+    if !compiled.is_valid(instance) {
+        return Err(SchemaFailure::Invalid);
+    }
+
+    if options.reject_inexact_floats && !floats_are_exact(document, instance, document) {
+        return Err(SchemaFailure::Invalid);
+    }
+
+    Ok(())
+}
+
+/// Checks `instance` against `schema`, handling a schema that does not compile as the
+/// `schema_compile_failure` option says. `location` names the schema in the specification for
+/// the resulting error or warning; warnings are dropped when there is no `report` to hold them.
+///
+/// Fails with `None` when the instance does not match, leaving the caller to say what was
+/// invalid, or with the error for a schema that could not be compiled.
+pub(crate) fn validate_instance(
+    schema: &CompiledSchema,
+    instance: &serde_json::Value,
+    options: &ValidatorOptions,
+    location: impl FnOnce() -> String,
+    report: Option<&mut ValidationReport>,
+) -> Result<(), Option<ValidationError>> {
+    let policy = options.schema_compile_failure;
+    match schema.validates_value(instance, options) {
+        Ok(()) => Ok(()),
+        Err(SchemaFailure::Invalid) => Err(None),
+        Err(SchemaFailure::Uncompilable) if policy == SchemaCompileFailure::Reject => {
+            Err(Some(ValidationError::UncompilableSchema {
+                location: location(),
+            }))
+        }
+        Err(SchemaFailure::Uncompilable) => {
+            if let Some(report) = report {
+                report.warn(ValidationWarning::UncompilableSchema {
+                    location: location(),
+                });
+            }
+            match policy {
+                SchemaCompileFailure::TypeCheck if !matches_type(&schema.document, instance) => {
+                    Err(None)
+                }
+                _ => Ok(()),
+            }
         }
+    }
+}
+
+/// Where a body schema sits in the specification, which side of the exchange it validates, and
+/// what to do if it does not compile.
+#[derive(Debug, PartialEq, Clone)]
+pub struct SchemaLocation<'api> {
+    pub options: &'api ValidatorOptions,
+    pub(crate) schemas: &'api CompiledSchemas,
+    pub pointer: String,
+    pub direction: Direction,
+}
+
+impl SchemaLocation<'_> {
+    /// The schema at this location, compiled ahead when it could be.
+    pub(crate) fn compile(
+        &self,
+        schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+        components: &Option<openapiv3::Components>,
+    ) -> Result<Arc<CompiledSchema>, ValidationError> {
+        let direction = Some(self.direction);
+        (self.schemas).schema(&self.pointer, schema, components, direction, self.options)
+    }
+
+    pub(crate) fn validate(
+        &self,
+        schema: &CompiledSchema,
+        instance: &serde_json::Value,
+        report: Option<&mut ValidationReport>,
+    ) -> Result<(), Option<ValidationError>> {
+        validate_instance(
+            schema,
+            instance,
+            self.options,
+            || self.pointer.clone(),
+            report,
+        )
+    }
+}
+
+/// Whether every number the schema declares as `format: float` survives a round trip through a
+/// 32-bit float. Only `$ref`, `allOf`, `properties`, `additionalProperties` and `items` are
+/// followed, since which branch of an `anyOf` or `oneOf` the instance matched is not known.
+fn floats_are_exact(
+    schema: &serde_json::Value,
+    instance: &serde_json::Value,
+    root: &serde_json::Value,
+) -> bool {
+    let referenced = schema
+        .get("$ref")
+        .and_then(serde_json::Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| root.pointer(pointer));
+    let all_of = schema
+        .get("allOf")
+        .and_then(serde_json::Value::as_array)
+        .into_iter()
+        .flatten();
+    if !referenced
+        .into_iter()
+        .chain(all_of)
+        .all(|schema| floats_are_exact(schema, instance, root))
+    {
+        return false;
+    }
+
+    match instance {
+        serde_json::Value::Number(number) => {
+            schema.get("format").and_then(serde_json::Value::as_str) != Some("float")
+                || number
+                    .as_f64()
+                    .is_some_and(|number| (number as f32).to_string().parse::<f64>() == Ok(number))
+        }
+        serde_json::Value::Object(object) => object.iter().all(|(name, value)| {
+            schema
+                .get("properties")
+                .and_then(|properties| properties.get(name))
+                .or_else(|| schema.get("additionalProperties"))
+                .is_none_or(|schema| floats_are_exact(schema, value, root))
+        }),
+        serde_json::Value::Array(items) => schema.get("items").is_none_or(|schema| {
+            items
+                .iter()
+                .all(|item| floats_are_exact(schema, item, root))
+        }),
+        _ => true,
+    }
+}
+
+/// The `uuid` format OpenAPI documents use but draft 7 does not define: 32 hexadecimal digits
+/// grouped 8-4-4-4-12.
+fn is_uuid(value: &str) -> bool {
+    let groups: Vec<&str> = value.split('-').collect();
+    groups.iter().map(|group| group.len()).eq([8, 4, 4, 4, 12])
+        && groups
+            .iter()
+            .all(|group| group.chars().all(|digit| digit.is_ascii_hexdigit()))
+}
+
+/// Whether the instance has the schema's `type`. Schemas without one accept any instance.
+fn matches_type(schema: &serde_json::Value, instance: &serde_json::Value) -> bool {
+    match schema.get("type").and_then(serde_json::Value::as_str) {
+        Some("object") => instance.is_object(),
+        Some("array") => instance.is_array(),
+        Some("string") => instance.is_string(),
+        Some("boolean") => instance.is_boolean(),
+        Some("number") => instance.is_number(),
+        Some("integer") => {
+            instance.is_i64()
+                || instance.is_u64()
+                || instance
+                    .as_f64()
+                    .is_some_and(|number| number.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+/// A JSON pointer into the specification built from unescaped segments.
+pub(crate) fn spec_pointer<'a>(base: &str, segments: impl IntoIterator<Item = &'a str>) -> String {
+    segments
+        .into_iter()
+        .fold(base.to_string(), |pointer, segment| {
+            pointer + "/" + &segment.replace('~', "~0").replace('/', "~1")
+        })
+}
+
+#[cfg(test)]
+mod test_uncompilable_schemas {
+    use crate::error::ValidationError;
+    use crate::options::{SchemaCompileFailure, ValidatorOptions};
+    use crate::report::ValidationWarning;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things:
+            post:
+              parameters:
+                - in: query
+                  name: code
+                  schema:
+                    type: string
+                    pattern: '[a-'
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      properties:
+                        name:
+                          type: string
+                          pattern: '[a-'
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: string
+                        pattern: '[a-'
+        "#
+    );
+
+    const BODY_SCHEMA: &str = "#/paths/~1things/post/requestBody/content/application~1json/schema";
+
+    fn make_validator(policy: SchemaCompileFailure) -> Validator {
+        make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            schema_compile_failure: policy,
+            ..Default::default()
+        })
+    }
+
+    fn make_request(query: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: format!("http://test.com/things{query}"),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    #[test]
+    fn reject_with_the_location_of_the_schema_by_default() {
+        let validator = make_validator(SchemaCompileFailure::default());
+
+        assert_eq!(
+            Err(ValidationError::UncompilableSchema {
+                location: BODY_SCHEMA.to_string()
+            }),
+            validator.validate_request(&make_request("", r#"{"name": "a"}"#))
+        );
+        assert_eq!(
+            Err(ValidationError::UncompilableSchema {
+                location: "#/paths/~1things/post/parameters/0/schema".to_string()
+            }),
+            validator.validate_request(&make_request("?code=\"a\"", ""))
+        );
+    }
+
+    #[test]
+    fn apply_the_policy_to_response_schemas() {
+        let response = FakeResponse {
+            status_code: 200,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: br#""a""#.to_vec(),
+        };
+        let request = FakeRequest {
+            headers: HashMap::new(),
+            ..make_request("", "")
+        };
+
+        let validator = make_validator(SchemaCompileFailure::Warn);
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(Ok(()), validated.validate_response(&response));
+
+        let validator = make_validator(SchemaCompileFailure::Reject);
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(
+            Err(ValidationError::UncompilableSchema {
+                location: "#/paths/~1things/post/responses/200/content/application~1json/schema"
+                    .to_string()
+            }),
+            validated.validate_response(&response)
+        );
+    }
+
+    #[test]
+    fn pass_with_a_warning_when_configured() {
+        let validator = make_validator(SchemaCompileFailure::Warn);
+        let validated = validator.validate_request(&make_request("", "[]")).unwrap();
+
+        assert_eq!(
+            vec![ValidationWarning::UncompilableSchema {
+                location: BODY_SCHEMA.to_string()
+            }],
+            validated.report.warnings
+        );
+    }
+
+    #[test]
+    fn fall_back_to_checking_the_type() {
+        let validator = make_validator(SchemaCompileFailure::TypeCheck);
+
+        let validated = validator
+            .validate_request(&make_request("", r#"{"name": "a"}"#))
+            .unwrap();
+        assert_eq!(
+            vec![ValidationWarning::UncompilableSchema {
+                location: BODY_SCHEMA.to_string()
+            }],
+            validated.report.warnings
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&make_request("", "[]"))
+        );
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "code".to_string()
+            }),
+            validator.validate_request(&make_request("?code=1", r#"{}"#))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_formats {
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /users:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      properties:
+                        id:
+                          type: string
+                          format: uuid
+                        email:
+                          type: string
+                          format: email
+                        nickname:
+                          type: string
+                          format: no-such-format
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn post(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/users".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    #[parameterized(body = {
+        r#"{"id": "123e4567-e89b-12d3-a456-426614174000"}"#,
+        r#"{"email": "ada@example.com"}"#,
+        r#"{"nickname": "anything goes"}"#,
+    })]
+    fn accept_values_in_their_format(body: &str) {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert!(validator.validate_request(&post(body)).is_ok());
+    }
+
+    #[parameterized(body = {
+        r#"{"id": "not-a-uuid"}"#,
+        r#"{"id": "123e4567e89b12d3a456426614174000"}"#,
+        r#"{"id": "123e4567-e89b-12d3-a456-42661417400g"}"#,
+        r#"{"email": "ada"}"#,
+    })]
+    fn reject_values_not_in_their_format(body: &str) {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&post(body)).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn accept_any_format_when_format_validation_is_skipped() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            skip_format_validation: true,
+            ..Default::default()
+        });
+        assert!(validator
+            .validate_request(&post(r#"{"id": "not-a-uuid", "email": "ada"}"#))
+            .is_ok());
+    }
+
+    fn is_snowflake_id(value: &str) -> bool {
+        value.len() == 18 && value.chars().all(|digit| digit.is_ascii_digit())
+    }
+
+    const SNOWFLAKE_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /messages:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      properties:
+                        id:
+                          type: string
+                          format: snowflake-id
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: string
+                        format: snowflake-id
+        "#
+    );
+
+    fn post_message(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/messages".to_string(),
+            ..post(body)
+        }
+    }
+
+    #[test]
+    fn check_registered_formats_in_requests() {
+        let validator =
+            make_validator_from_spec(SNOWFLAKE_SPEC).with_format("snowflake-id", is_snowflake_id);
+
+        assert!(validator
+            .validate_request(&post_message(r#"{"id": "175928847299117063"}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator
+                .validate_request(&post_message(r#"{"id": "1759288"}"#))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn check_registered_formats_in_responses() {
+        let validator =
+            make_validator_from_spec(SNOWFLAKE_SPEC).with_format("snowflake-id", is_snowflake_id);
+        let respond = |body: &str| {
+            validator
+                .validate_request(&post_message("{}"))
+                .unwrap()
+                .validate_response(&FakeResponse {
+                    status_code: 200,
+                    headers: HashMap::from([(
+                        "Content-Type".to_string(),
+                        "application/json".to_string(),
+                    )]),
+                    body: body.as_bytes().to_vec(),
+                })
+        };
+
+        assert_eq!(Ok(()), respond(r#""175928847299117063""#));
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            respond(r#""ada""#)
+        );
+    }
+
+    #[test]
+    fn registered_formats_replace_built_in_ones() {
+        let validator = make_validator_from_spec(PATH_SPEC).with_format("email", |_| true);
+        assert!(validator
+            .validate_request(&post(r#"{"email": "ada"}"#))
+            .is_ok());
+    }
+
+    const MEASUREMENTS_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /measurements:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      type: array
+                      items:
+                        $ref: '#/components/schemas/Measurement'
+              responses:
+                200:
+                  description: API call successful
+        components:
+          schemas:
+            Measurement:
+              type: object
+              properties:
+                reading:
+                  type: number
+                  format: float
+                precise:
+                  type: number
+                  format: double
+        "#
+    );
+
+    fn post_measurements(body: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/measurements".to_string(),
+            ..post(body)
+        }
+    }
+
+    #[parameterized(body = {
+        r#"[{"reading": 16777217}]"#,
+        r#"[{"reading": 0.123456789}]"#,
+    })]
+    fn accept_inexact_floats_by_default(body: &str) {
+        let validator = make_validator_from_spec(MEASUREMENTS_SPEC);
+        assert!(validator.validate_request(&post_measurements(body)).is_ok());
+    }
+
+    #[parameterized(body = {
+        r#"[{"reading": 16777217}]"#,
+        r#"[{"reading": 1.5}, {"reading": 0.123456789}]"#,
+    })]
+    fn reject_inexact_floats_when_configured(body: &str) {
+        let validator =
+            make_validator_from_spec(MEASUREMENTS_SPEC).with_options(ValidatorOptions {
+                reject_inexact_floats: true,
+                ..Default::default()
+            });
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator
+                .validate_request(&post_measurements(body))
+                .map(|_| ())
+        );
+    }
 
-        Ok(())
+    #[parameterized(body = {
+        r#"[{"reading": 16777216}]"#,
+        r#"[{"reading": 0.1}]"#,
+        r#"[{"precise": 0.123456789}]"#,
+    })]
+    fn accept_exact_floats_when_configured(body: &str) {
+        let validator =
+            make_validator_from_spec(MEASUREMENTS_SPEC).with_options(ValidatorOptions {
+                reject_inexact_floats: true,
+                ..Default::default()
+            });
+        assert!(validator.validate_request(&post_measurements(body)).is_ok());
     }
 }
@@ -0,0 +1,257 @@
+use serde::de::DeserializeOwned;
+
+use crate::jsonschema::spec_pointer;
+use crate::method::Method;
+use crate::spec_check::SpecError;
+
+/// Removes the operations, path items and components of a document that do not parse, so the
+/// rest of it can still be loaded. Each removal is returned as an error locating what was
+/// skipped. References to a removed component are left in place.
+pub(crate) fn prune_unparseable(document: &mut serde_yaml::Value) -> Vec<SpecError> {
+    let mut errors = vec![];
+
+    if let Some(paths) = document
+        .get_mut("paths")
+        .and_then(serde_yaml::Value::as_mapping_mut)
+    {
+        paths.retain(|path, path_item| {
+            let pointer = spec_pointer("#/paths", [path.as_str().unwrap_or_default()]);
+            if let Some(path_item) = path_item.as_mapping_mut() {
+                for method in Method::ALL {
+                    let keep = match path_item.get(method.as_str()) {
+                        Some(operation) => keep_if_parses::<openapiv3::Operation>(
+                            operation,
+                            || spec_pointer(&pointer, [method.as_str()]),
+                            &mut errors,
+                        ),
+                        None => continue,
+                    };
+                    if !keep {
+                        path_item.remove(method.as_str());
+                    }
+                }
+            }
+            keep_if_parses::<openapiv3::ReferenceOr<openapiv3::PathItem>>(
+                path_item,
+                || pointer.clone(),
+                &mut errors,
+            )
+        });
+    }
+
+    if let Some(components) = document
+        .get_mut("components")
+        .and_then(serde_yaml::Value::as_mapping_mut)
+    {
+        for (section, component) in components.iter_mut() {
+            let parses: fn(&serde_yaml::Value) -> Result<(), serde_yaml::Error> = match section
+                .as_str()
+            {
+                Some("schemas") => parses::<openapiv3::ReferenceOr<openapiv3::Schema>>,
+                Some("responses") => parses::<openapiv3::ReferenceOr<openapiv3::Response>>,
+                Some("parameters") => parses::<openapiv3::ReferenceOr<openapiv3::Parameter>>,
+                Some("examples") => parses::<openapiv3::ReferenceOr<openapiv3::Example>>,
+                Some("requestBodies") => parses::<openapiv3::ReferenceOr<openapiv3::RequestBody>>,
+                Some("headers") => parses::<openapiv3::ReferenceOr<openapiv3::Header>>,
+                Some("securitySchemes") => {
+                    parses::<openapiv3::ReferenceOr<openapiv3::SecurityScheme>>
+                }
+                Some("links") => parses::<openapiv3::ReferenceOr<openapiv3::Link>>,
+                Some("callbacks") => parses::<openapiv3::ReferenceOr<openapiv3::Callback>>,
+                _ => continue,
+            };
+            if let Some(component) = component.as_mapping_mut() {
+                component.retain(|name, value| match parses(value) {
+                    Ok(()) => true,
+                    Err(error) => {
+                        errors.push(SpecError::Unparseable {
+                            pointer: spec_pointer(
+                                "#/components",
+                                [
+                                    section.as_str().unwrap_or_default(),
+                                    name.as_str().unwrap_or_default(),
+                                ],
+                            ),
+                            message: error.to_string(),
+                        });
+                        false
+                    }
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Unquoted keys such as status codes load as numbers, which only parse as strings when read
+/// straight from text.
+pub(crate) fn stringify_keys(value: &mut serde_yaml::Value) {
+    match value {
+        serde_yaml::Value::Mapping(mapping) => {
+            *mapping = std::mem::take(mapping)
+                .into_iter()
+                .map(|(key, mut value)| {
+                    stringify_keys(&mut value);
+                    let key = match key {
+                        serde_yaml::Value::Number(number) => number.to_string().into(),
+                        serde_yaml::Value::Bool(boolean) => boolean.to_string().into(),
+                        key => key,
+                    };
+                    (key, value)
+                })
+                .collect();
+        }
+        serde_yaml::Value::Sequence(items) => items.iter_mut().for_each(stringify_keys),
+        _ => (),
+    }
+}
+
+fn parses<T: DeserializeOwned>(value: &serde_yaml::Value) -> Result<(), serde_yaml::Error> {
+    T::deserialize(value).map(|_| ())
+}
+
+fn keep_if_parses<T: DeserializeOwned>(
+    value: &serde_yaml::Value,
+    pointer: impl FnOnce() -> String,
+    errors: &mut Vec<SpecError>,
+) -> bool {
+    match parses::<T>(value) {
+        Ok(()) => true,
+        Err(error) => {
+            errors.push(SpecError::Unparseable {
+                pointer: pointer(),
+                message: error.to_string(),
+            });
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_lenient_loading {
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use crate::spec_check::SpecError;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    fn load(document: &str) -> Validator {
+        Validator::new_lenient(serde_yaml::from_str(document).unwrap()).unwrap()
+    }
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn pointers(validator: &Validator) -> Vec<&str> {
+        validator
+            .spec_errors()
+            .iter()
+            .filter_map(|error| match error {
+                SpecError::Unparseable { pointer, .. } => Some(pointer.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const DOCUMENT: &str = indoc!(
+        r#"
+        openapi: 3.0.0
+        info:
+          title: Partly broken
+          version: "1.0.0"
+        paths:
+          /pets:
+            get:
+              responses:
+                200:
+                  description: API call successful
+            post:
+              parameters:
+                - in: nowhere
+                  name: id
+              responses:
+                200:
+                  description: API call successful
+          /owners:
+            parameters: not a list
+            get:
+              responses:
+                200:
+                  description: API call successful
+        components:
+          schemas:
+            Pet:
+              type: object
+            Broken:
+              type: object
+              required: not a list
+        "#
+    );
+
+    #[test]
+    fn the_strict_loader_rejects_the_document() {
+        assert!(serde_yaml::from_str::<openapiv3::OpenAPI>(DOCUMENT).is_err());
+    }
+
+    #[test]
+    fn skip_what_does_not_parse() {
+        let validator = load(DOCUMENT);
+
+        assert_eq!(
+            vec![
+                "#/paths/~1pets/post",
+                "#/paths/~1owners",
+                "#/components/schemas/Broken"
+            ],
+            pointers(&validator)
+        );
+        assert!(validator
+            .spec()
+            .components
+            .as_ref()
+            .unwrap()
+            .schemas
+            .contains_key("Pet"));
+    }
+
+    #[test]
+    fn validate_the_operations_that_parsed() {
+        let validator = load(DOCUMENT);
+
+        assert!(validator
+            .validate_request(&get("http://test.com/pets"))
+            .is_ok());
+        assert_eq!(
+            Err(crate::ValidationError::PathNotFound),
+            validator
+                .validate_request(&get("http://test.com/owners"))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn a_valid_document_loads_without_errors() {
+        let validator = load(indoc!(
+            r#"
+            openapi: 3.0.0
+            info:
+              title: Fine
+              version: "1.0.0"
+            paths: {}
+            "#
+        ));
+        assert!(validator.spec_errors().is_empty());
+    }
+
+    #[test]
+    fn fail_when_the_document_itself_does_not_parse() {
+        assert!(Validator::new_lenient(serde_yaml::from_str("paths: {}").unwrap()).is_err());
+    }
+}
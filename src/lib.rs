@@ -1,11 +1,117 @@
+//! Validates HTTP requests and responses against an OpenAPI document.
+//!
+//! Load a document into a [`Validator`], implement [`Request`] and [`Response`] for the
+//! request and response types in use, and validate them with [`Validator::validate_request`]
+//! and [`ValidatedRequest::validate_response`]. Failures are [`ValidationError`]s. Everything
+//! needed is exported from the crate root; the `request` and `schema` modules stay public only
+//! for existing paths, and the `ffi` module holds the C functions.
+
+#[cfg(feature = "axum")]
+mod axum_integration;
 mod body;
+mod builder;
+mod bundle;
+mod checks;
 mod content_type;
+mod context;
+mod diff;
+mod error;
+mod examples;
+mod extensions;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod form;
+#[cfg(feature = "http")]
+mod http_request;
+#[cfg(feature = "hyper")]
+mod hyper_service;
 mod item_or_fetch;
 mod jsonschema;
+mod lenient;
+mod load;
+mod method;
+mod mock;
+#[cfg(feature = "mock-server")]
+mod mock_server;
+mod openapi31;
 mod operation;
+mod operation_rules;
+mod options;
 mod parameters;
+#[cfg(feature = "tower")]
+mod rate_limit;
+mod recording;
+mod report;
 pub mod request;
+#[cfg(feature = "proptest")]
+mod request_strategy;
+#[cfg(feature = "reqwest")]
+mod reqwest_client;
+mod resolver;
 mod response;
+mod router;
+pub mod schema;
+mod schema_cache;
+mod security;
+mod servers;
+mod spec_check;
+mod streaming;
+mod swagger2;
+mod target;
 mod to_jsonschema;
+#[cfg(feature = "ureq")]
+mod ureq_agent;
+mod utf8;
+mod validated_request;
+#[cfg(feature = "tower")]
+mod validation_layer;
+#[cfg(feature = "wiremock")]
+mod wiremock_responder;
 
-pub use request::Request;
+#[cfg(feature = "axum")]
+pub use axum_integration::{validation_middleware, ValidatedJson, ValidationRejection};
+pub use builder::ValidatorBuilder;
+pub use bundle::bundle;
+pub use checks::{Check, CheckHooks, UnknownCheck};
+pub use context::{Clock, FixedClock, SystemClock, ValidationContext};
+pub use diff::{diff, Change, ChangeKind, SpecDiff};
+pub use error::ValidationError;
+pub use extensions::{ParameterDependencies, RateLimit, Sla};
+#[cfg(feature = "http")]
+pub use http_request::problem_json;
+#[cfg(feature = "hyper")]
+pub use hyper_service::{validate_service, ValidatedService};
+pub use load::LoadError;
+pub use method::{Method, UnknownMethod};
+pub use mock::MockResponse;
+#[cfg(feature = "mock-server")]
+pub use mock_server::MockServer;
+pub use operation::OperationMatch;
+pub use operation_rules::OperationRule;
+pub use options::{
+    ContentTypeFallback, ReadOnlyProperties, SchemaCompileFailure, UnexpectedBody, ValidatorOptions,
+};
+pub use parameters::ValidatedParameters;
+#[cfg(feature = "tower")]
+pub use rate_limit::{RateLimitExceeded, RateLimitLayer, RateLimitService};
+pub use recording::{ExchangeRecord, JsonLinesSink, RecordSink};
+pub use report::{ExchangeReport, ValidationReport, ValidationWarning};
+pub use request::{Request, Validator};
+#[cfg(feature = "proptest")]
+pub use request_strategy::{request_strategy, GeneratedRequest, Inputs};
+#[cfg(feature = "reqwest")]
+pub use reqwest_client::{BufferedResponse, ClientError, ValidatedClient};
+pub use resolver::{KeyedValidators, ValidatorResolver, ValidatorSet};
+pub use response::{Response, ResponseValidator};
+pub use schema::to_json_schema;
+pub use schema_cache::CacheStrategy;
+pub use security::{SchemeCheck, ScopeChecker};
+pub use spec_check::SpecError;
+pub use streaming::StreamingResponseValidator;
+#[cfg(feature = "ureq")]
+pub use ureq_agent::{buffer_response, AgentError, ValidatedAgent};
+pub use validated_request::ValidatedRequest;
+#[cfg(feature = "tower")]
+pub use validation_layer::{ValidationLayer, ValidationService};
+#[cfg(feature = "wiremock")]
+pub use wiremock_responder::SpecConformingResponder;
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::bundle::bundle_document;
+use crate::request::Validator;
+
+/// Why a specification could not be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file could not be read.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// The text is not valid YAML.
+    Yaml(serde_yaml::Error),
+    /// The text is not valid JSON.
+    Json(serde_json::Error),
+    /// The text parsed, but is not an OpenAPI document this crate can load.
+    Document(serde_yaml::Error),
+    /// A reference made in the file at `path` points at nothing.
+    UnresolvedReference { path: PathBuf, reference: String },
+    /// A reference made in the file at `path` would have to be inlined into itself.
+    CyclicReference { path: PathBuf, reference: String },
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "could not read {}: {source}", path.display()),
+            Self::Yaml(error) => write!(f, "specification is not valid YAML: {error}"),
+            Self::Json(error) => write!(f, "specification is not valid JSON: {error}"),
+            Self::Document(error) => write!(f, "specification is not a valid document: {error}"),
+            Self::UnresolvedReference { path, reference } => {
+                write!(f, "could not resolve {reference} in {}", path.display())
+            }
+            Self::CyclicReference { path, reference } => {
+                write!(f, "{reference} in {} refers to itself", path.display())
+            }
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Yaml(error) | Self::Document(error) => Some(error),
+            Self::Json(error) => Some(error),
+            Self::UnresolvedReference { .. } | Self::CyclicReference { .. } => None,
+        }
+    }
+}
+
+/// Constructors for documents of any version supported by [`Validator::from_document`]. Those
+/// reading a file also follow its references into other files, see [`crate::bundle()`].
+impl Validator {
+    pub fn from_yaml_str(yaml: &str) -> Result<Self, LoadError> {
+        let document = serde_yaml::from_str(yaml).map_err(LoadError::Yaml)?;
+        Self::from_document(document).map_err(LoadError::Document)
+    }
+
+    /// Keeps the order of the document's keys, which decides between templates such as `/pets`
+    /// and `/pets/` when trailing slashes are ignored.
+    pub fn from_json_str(json: &str) -> Result<Self, LoadError> {
+        let document = serde_json::from_str(json).map_err(LoadError::Json)?;
+        Self::from_document(document).map_err(LoadError::Document)
+    }
+
+    pub fn from_value(value: serde_json::Value) -> Result<Self, LoadError> {
+        let document = serde_yaml::to_value(value).map_err(LoadError::Document)?;
+        Self::from_document(document).map_err(LoadError::Document)
+    }
+
+    pub fn from_yaml_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let document = serde_yaml::from_str(&read(path)?).map_err(LoadError::Yaml)?;
+        Self::from_bundle(document, path)
+    }
+
+    pub fn from_json_file(path: impl AsRef<Path>) -> Result<Self, LoadError> {
+        let path = path.as_ref();
+        let document = serde_json::from_str(&read(path)?).map_err(LoadError::Json)?;
+        Self::from_bundle(document, path)
+    }
+
+    fn from_bundle(document: serde_yaml::Value, path: &Path) -> Result<Self, LoadError> {
+        Self::from_document(bundle_document(document, path)?).map_err(LoadError::Document)
+    }
+}
+
+pub(crate) fn read(path: &Path) -> Result<String, LoadError> {
+    std::fs::read_to_string(path).map_err(|source| LoadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+#[cfg(test)]
+mod test_load {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn load_the_same_validator_from_every_source() {
+        let yaml = std::fs::read_to_string("./specs/openapi.yaml").unwrap();
+        let document: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let json = serde_json::to_string(&document).unwrap();
+        let validator = Validator::from_yaml_file("./specs/openapi.yaml").unwrap();
+
+        for loaded in [
+            Validator::from_yaml_str(&yaml),
+            Validator::from_json_str(&json),
+        ] {
+            assert_eq!(validator, loaded.unwrap());
+        }
+
+        // a JSON value sorts its keys, so it loads as the document with sorted keys
+        let value: serde_json::Value = serde_yaml::from_str(&yaml).unwrap();
+        let sorted = serde_yaml::to_string(&value).unwrap();
+        assert_eq!(
+            Validator::from_yaml_str(&sorted).unwrap(),
+            Validator::from_value(value).unwrap()
+        );
+    }
+
+    #[test]
+    fn report_why_loading_failed() {
+        assert!(matches!(
+            Validator::from_yaml_file("./specs/missing.yaml"),
+            Err(LoadError::Io { .. })
+        ));
+        assert!(matches!(
+            Validator::from_json_file("./specs/openapi.yaml"),
+            Err(LoadError::Json(..))
+        ));
+        assert!(matches!(
+            Validator::from_yaml_str("paths: ["),
+            Err(LoadError::Yaml(..))
+        ));
+        assert!(matches!(
+            Validator::from_value(json!({"openapi": "3.0.0", "paths": {}})),
+            Err(LoadError::Document(..))
+        ));
+    }
+}
@@ -0,0 +1,129 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// The HTTP methods an OpenAPI path item can declare an operation for.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum Method {
+    Get,
+    Put,
+    Post,
+    Delete,
+    Options,
+    Head,
+    Patch,
+    Trace,
+}
+
+impl Method {
+    pub const ALL: [Method; 8] = [
+        Method::Get,
+        Method::Put,
+        Method::Post,
+        Method::Delete,
+        Method::Options,
+        Method::Head,
+        Method::Patch,
+        Method::Trace,
+    ];
+
+    /// The method as written in a path item of the specification, e.g. `get`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "get",
+            Method::Put => "put",
+            Method::Post => "post",
+            Method::Delete => "delete",
+            Method::Options => "options",
+            Method::Head => "head",
+            Method::Patch => "patch",
+            Method::Trace => "trace",
+        }
+    }
+
+    /// Whether requests with this method conventionally carry no body: GET, HEAD and DELETE.
+    pub fn is_bodyless(&self) -> bool {
+        matches!(self, Method::Get | Method::Head | Method::Delete)
+    }
+
+    /// Methods the path item declares an operation for.
+    pub(crate) fn allowed(path_spec: &openapiv3::PathItem) -> Vec<Method> {
+        Method::ALL
+            .into_iter()
+            .filter(|method| method.operation(path_spec).is_some())
+            .collect()
+    }
+
+    pub(crate) fn operation<'api>(
+        &self,
+        path_spec: &'api openapiv3::PathItem,
+    ) -> Option<&'api openapiv3::Operation> {
+        match self {
+            Method::Get => path_spec.get.as_ref(),
+            Method::Put => path_spec.put.as_ref(),
+            Method::Post => path_spec.post.as_ref(),
+            Method::Delete => path_spec.delete.as_ref(),
+            Method::Options => path_spec.options.as_ref(),
+            Method::Head => path_spec.head.as_ref(),
+            Method::Patch => path_spec.patch.as_ref(),
+            Method::Trace => path_spec.trace.as_ref(),
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct UnknownMethod(pub String);
+
+impl fmt::Display for UnknownMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown HTTP method '{}'", self.0)
+    }
+}
+
+impl std::error::Error for UnknownMethod {}
+
+/// Methods are matched case-insensitively, so `GET` as sent by HTTP clients parses too.
+impl FromStr for Method {
+    type Err = UnknownMethod;
+
+    fn from_str(method: &str) -> Result<Self, Self::Err> {
+        Method::ALL
+            .into_iter()
+            .find(|candidate| candidate.as_str().eq_ignore_ascii_case(method))
+            .ok_or_else(|| UnknownMethod(method.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test_method {
+    use super::*;
+    use parameterized::parameterized;
+
+    #[parameterized(method = {
+        "get", "put", "post", "delete", "options", "head", "patch", "trace"
+    })]
+    fn round_trips_through_str(method: &str) {
+        assert_eq!(method, method.parse::<Method>().unwrap().as_str());
+    }
+
+    #[parameterized(method = {"GET", "Post", "pAtCh"})]
+    fn parse_any_case(method: &str) {
+        assert_eq!(
+            method.to_lowercase(),
+            method.parse::<Method>().unwrap().as_str()
+        );
+    }
+
+    #[test]
+    fn reject_unknown_method() {
+        assert_eq!(
+            Err(UnknownMethod("fetch".to_string())),
+            "fetch".parse::<Method>()
+        );
+    }
+}
@@ -0,0 +1,551 @@
+use openapiv3::{ReferenceOr, Schema, SchemaKind, StringFormat, Type, VariantOrUnknownOrEmpty};
+use serde_json::{json, Value};
+
+use crate::content_type::{is_acceptable, is_json};
+use crate::error::ValidationError;
+use crate::item_or_fetch::ItemOrFetch;
+use crate::operation::OperationMatch;
+use crate::request::Validator;
+use crate::response::{forbids_body, Response, ResponseValidator};
+
+/// Beyond this many nested schemas only required properties are generated, so recursive
+/// schemas end.
+const MAX_DEPTH: usize = 8;
+
+/// A response generated from the specification, e.g. for a mock server.
+#[derive(Debug, PartialEq, Clone)]
+pub struct MockResponse {
+    pub status_code: u16,
+    /// The `Content-Type` when there is a body, and every required header.
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MockResponse {
+    pub fn content_type(&self) -> Option<&str> {
+        (self.headers.iter())
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl Response for MockResponse {
+    fn status_code(&self) -> u16 {
+        self.status_code
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        (self.headers.iter())
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+}
+
+impl Validator {
+    /// Generates the response the operation documents for `status_code`, see
+    /// [`ResponseValidator::mock_response`].
+    pub fn mock_response(
+        &self,
+        operation: &OperationMatch,
+        status_code: u16,
+    ) -> Result<MockResponse, ValidationError> {
+        self.operation_response_validator(operation, None)
+            .mock_response(status_code)
+    }
+}
+
+impl ResponseValidator<'_> {
+    /// Generates a response for a documented status code. The body has the media type the
+    /// request's `Accept` header prefers, else JSON, and is the media type's example when it has
+    /// one. Otherwise it is synthesized from the schema, honouring examples, defaults, enums,
+    /// common formats, lengths and bounds; patterns are not. Required headers are generated
+    /// the same way.
+    pub fn mock_response(&self, status_code: u16) -> Result<MockResponse, ValidationError> {
+        let response_spec = self.validate_status_code(status_code)?;
+        let generator = Generator {
+            components: self.components,
+        };
+        let mut headers = vec![];
+        for (name, header) in &response_spec.headers {
+            let header = header.item_or_fetch(self.components)?;
+            if !header.required || name.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+            let value = match (&header.example, &header.format) {
+                (Some(example), _) => example.clone(),
+                (None, openapiv3::ParameterSchemaOrContent::Schema(schema)) => {
+                    generator.schema(schema, 0)
+                }
+                (None, openapiv3::ParameterSchemaOrContent::Content(_)) => Value::Null,
+            };
+            headers.push((name.clone(), to_text(value)));
+        }
+
+        let content = match forbids_body(status_code) {
+            true => None,
+            false => self.pick_media_type(response_spec),
+        };
+        let Some((content_type, media_type)) = content else {
+            return Ok(MockResponse {
+                status_code,
+                headers,
+                body: vec![],
+            });
+        };
+        let value = generator.example(media_type).unwrap_or_else(|| {
+            (media_type.schema.as_ref()).map_or(Value::Null, |schema| generator.schema(schema, 0))
+        });
+        let body = match content_type.parse().is_ok_and(|mime| is_json(&mime)) {
+            true => value.to_string(),
+            false => to_text(value),
+        };
+        headers.insert(0, ("Content-Type".to_string(), content_type.to_string()));
+        Ok(MockResponse {
+            status_code,
+            headers,
+            body: body.into_bytes(),
+        })
+    }
+
+    /// The first concrete media type the `Accept` header admits, else the first JSON one, else
+    /// the first.
+    fn pick_media_type<'a>(
+        &self,
+        response_spec: &'a openapiv3::Response,
+    ) -> Option<(&'a str, &'a openapiv3::MediaType)> {
+        let mut content = (response_spec.content.iter())
+            .map(|(content_type, media_type)| (content_type.as_str(), media_type))
+            .filter(|(content_type, _)| !content_type.contains('*'));
+        let acceptable = |content_type: &str| match &self.accept {
+            Some(accept) => is_acceptable(accept, content_type),
+            None => true,
+        };
+        let json = |content_type: &str| content_type.parse().is_ok_and(|mime| is_json(&mime));
+
+        (content.clone())
+            .find(|(content_type, _)| acceptable(content_type) && json(content_type))
+            .or_else(|| {
+                content
+                    .clone()
+                    .find(|(content_type, _)| acceptable(content_type))
+            })
+            .or_else(|| content.next())
+    }
+}
+
+/// Strings as they are, anything else as JSON text.
+pub(crate) fn to_text(value: Value) -> String {
+    match value {
+        Value::String(text) => text,
+        value => value.to_string(),
+    }
+}
+
+struct Generator<'api> {
+    components: &'api Option<openapiv3::Components>,
+}
+
+impl Generator<'_> {
+    fn example(&self, media_type: &openapiv3::MediaType) -> Option<Value> {
+        if let Some(example) = &media_type.example {
+            return Some(example.clone());
+        }
+        (media_type.examples.values())
+            .filter_map(|example| example.item_or_fetch(self.components).ok())
+            .find_map(|example| example.value.clone())
+    }
+
+    /// Unresolvable references generate `null`.
+    fn schema(&self, schema: &impl ItemOrFetch<Schema>, depth: usize) -> Value {
+        match schema.item_or_fetch(self.components) {
+            Ok(schema) => self.value(schema, depth + 1),
+            Err(_) => Value::Null,
+        }
+    }
+
+    fn value(&self, schema: &Schema, depth: usize) -> Value {
+        let data = &schema.schema_data;
+        if let Some(example) = data.example.as_ref().or(data.default.as_ref()) {
+            return example.clone();
+        }
+        match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => {
+                let value = string.enumeration.iter().flatten().next();
+                Value::String(value.cloned().unwrap_or_else(|| {
+                    let example = format_example(&string.format);
+                    fit_length(example, string.min_length, string.max_length)
+                }))
+            }
+            SchemaKind::Type(Type::Integer(integer)) => {
+                match integer.enumeration.iter().flatten().next() {
+                    Some(value) => json!(value),
+                    None => json!(integer_in(integer)),
+                }
+            }
+            SchemaKind::Type(Type::Number(number)) => {
+                match number.enumeration.iter().flatten().next() {
+                    Some(value) => json!(value),
+                    None => json!(number_in(number)),
+                }
+            }
+            SchemaKind::Type(Type::Boolean {}) => Value::Bool(true),
+            SchemaKind::Type(Type::Object(object)) => {
+                self.object(&object.properties, &object.required, depth)
+            }
+            SchemaKind::Type(Type::Array(array)) => self.array(
+                array.items.as_ref(),
+                array.min_items,
+                array.max_items,
+                depth,
+            ),
+            SchemaKind::OneOf { one_of: schemas } | SchemaKind::AnyOf { any_of: schemas } => {
+                (schemas.first()).map_or(Value::Null, |schema| self.schema(schema, depth))
+            }
+            SchemaKind::AllOf { all_of } => self.all_of(all_of, depth),
+            SchemaKind::Not { .. } => Value::Null,
+            SchemaKind::Any(any) => self.any(any, depth),
+        }
+    }
+
+    fn object(
+        &self,
+        properties: &indexmap::IndexMap<String, ReferenceOr<Box<Schema>>>,
+        required: &[String],
+        depth: usize,
+    ) -> Value {
+        let mut object = serde_json::Map::new();
+        for (name, property) in properties {
+            if depth >= MAX_DEPTH && !required.contains(name) {
+                continue;
+            }
+            let write_only = (property.item_or_fetch(self.components))
+                .is_ok_and(|property| property.schema_data.write_only);
+            if !write_only {
+                object.insert(name.clone(), self.schema(property, depth));
+            }
+        }
+        Value::Object(object)
+    }
+
+    fn array(
+        &self,
+        items: Option<&ReferenceOr<Box<Schema>>>,
+        min_items: Option<usize>,
+        max_items: Option<usize>,
+        depth: usize,
+    ) -> Value {
+        let length = match depth >= MAX_DEPTH {
+            true => min_items.unwrap_or(0),
+            false => min_items.unwrap_or(1).min(max_items.unwrap_or(1)),
+        };
+        let item = items.map_or(Value::Null, |items| self.schema(items, depth));
+        Value::Array(vec![item; length])
+    }
+
+    /// Merges the objects generated for each schema; a schema that is not an object wins.
+    fn all_of(&self, schemas: &[ReferenceOr<Schema>], depth: usize) -> Value {
+        let mut merged = serde_json::Map::new();
+        for schema in schemas {
+            match self.schema(schema, depth) {
+                Value::Object(object) => merged.extend(object),
+                Value::Null => {}
+                value => return value,
+            }
+        }
+        Value::Object(merged)
+    }
+
+    /// Schemas without a `type`, read from whichever keywords they use.
+    fn any(&self, any: &openapiv3::AnySchema, depth: usize) -> Value {
+        if let Some(value) = any.enumeration.first() {
+            return value.clone();
+        }
+        if !any.all_of.is_empty() {
+            return self.all_of(&any.all_of, depth);
+        }
+        if let Some(schema) = any.one_of.first().or(any.any_of.first()) {
+            return self.schema(schema, depth);
+        }
+        match any.typ.as_deref() {
+            Some("object") => self.object(&any.properties, &any.required, depth),
+            None if !any.properties.is_empty() => {
+                self.object(&any.properties, &any.required, depth)
+            }
+            Some("array") => self.array(any.items.as_ref(), any.min_items, any.max_items, depth),
+            None if any.items.is_some() => {
+                self.array(any.items.as_ref(), any.min_items, any.max_items, depth)
+            }
+            Some("string") => Value::String(fit_length("string", any.min_length, any.max_length)),
+            Some("integer") | Some("number") => json!(any.minimum.unwrap_or(0.0).max(0.0) as i64),
+            Some("boolean") => Value::Bool(true),
+            _ => Value::Null,
+        }
+    }
+}
+
+pub(crate) fn format_example(format: &VariantOrUnknownOrEmpty<StringFormat>) -> &'static str {
+    match format {
+        VariantOrUnknownOrEmpty::Item(StringFormat::Date) => "2024-01-01",
+        VariantOrUnknownOrEmpty::Item(StringFormat::DateTime) => "2024-01-01T00:00:00Z",
+        VariantOrUnknownOrEmpty::Item(StringFormat::Password) => "password",
+        VariantOrUnknownOrEmpty::Item(StringFormat::Byte) => "c3RyaW5n",
+        VariantOrUnknownOrEmpty::Unknown(format) => match format.as_str() {
+            "email" => "user@example.com",
+            "uuid" => "00000000-0000-4000-8000-000000000000",
+            "uri" | "url" => "https://example.com",
+            "hostname" => "example.com",
+            "ipv4" => "192.0.2.1",
+            "ipv6" => "2001:db8::1",
+            "time" => "00:00:00Z",
+            _ => "string",
+        },
+        _ => "string",
+    }
+}
+
+/// Pads with `a` up to `min_length` characters, or truncates down to `max_length`.
+pub(crate) fn fit_length(
+    example: &str,
+    min_length: Option<usize>,
+    max_length: Option<usize>,
+) -> String {
+    let mut value: String = example
+        .chars()
+        .take(max_length.unwrap_or(usize::MAX))
+        .collect();
+    let length = value.chars().count();
+    let missing = min_length.unwrap_or(0).saturating_sub(length);
+    value.extend(std::iter::repeat_n('a', missing));
+    value
+}
+
+/// The integer closest to 0 within the bounds that is a multiple of `multipleOf`.
+fn integer_in(integer: &openapiv3::IntegerType) -> i64 {
+    let minimum = (integer.minimum).map(|minimum| minimum + i64::from(integer.exclusive_minimum));
+    let maximum = (integer.maximum).map(|maximum| maximum - i64::from(integer.exclusive_maximum));
+    let mut value = match (minimum, maximum) {
+        (Some(minimum), _) if minimum > 0 => minimum,
+        (_, Some(maximum)) if maximum < 0 => maximum,
+        _ => 0,
+    };
+    if let Some(step) = integer.multiple_of.filter(|step| *step > 0) {
+        let remainder = value.rem_euclid(step);
+        if remainder != 0 {
+            value += step - remainder;
+        }
+        if maximum.is_some_and(|maximum| value > maximum) {
+            value -= step;
+        }
+    }
+    value
+}
+
+/// The number closest to 0 within the bounds that is a multiple of `multipleOf`. Exclusive
+/// bounds are kept clear of by the step, or by half the range when that is smaller.
+fn number_in(number: &openapiv3::NumberType) -> f64 {
+    let step = number.multiple_of.filter(|step| *step > 0.0).unwrap_or(1.0);
+    let gap = match (number.minimum, number.maximum) {
+        (Some(minimum), Some(maximum)) => step.min((maximum - minimum) / 2.0),
+        _ => step,
+    };
+    let mut value = 0.0;
+    if let Some(minimum) = number.minimum.filter(|minimum| value <= *minimum) {
+        value = match number.exclusive_minimum {
+            true => minimum + gap,
+            false => minimum,
+        };
+    }
+    if let Some(maximum) = number.maximum.filter(|maximum| value >= *maximum) {
+        value = match number.exclusive_maximum {
+            true => maximum - gap,
+            false => maximum,
+        };
+    }
+    if let Some(step) = number.multiple_of.filter(|step| *step > 0.0) {
+        let rounded = (value / step).ceil() * step;
+        if number.maximum.is_none_or(|maximum| rounded <= maximum) {
+            value = rounded;
+        }
+    }
+    value
+}
+
+#[cfg(test)]
+mod test_mock {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            get:
+              responses:
+                200:
+                  description: pets
+                  headers:
+                    X-Total:
+                      required: true
+                      schema:
+                        type: integer
+                        minimum: 1
+                  content:
+                    application/json:
+                      schema:
+                        type: array
+                        items:
+                          $ref: '#/components/schemas/Pet'
+                    text/plain:
+                      schema:
+                        type: string
+                201:
+                  description: example
+                  content:
+                    application/json:
+                      example: {"id": 7, "name": "rex"}
+                204:
+                  description: no content
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+        components:
+          schemas:
+            Pet:
+              type: object
+              required: [id, name, tag]
+              properties:
+                id:
+                  type: integer
+                  minimum: 10
+                  exclusiveMinimum: true
+                  multipleOf: 4
+                name:
+                  type: string
+                  minLength: 3
+                  maxLength: 5
+                tag:
+                  type: string
+                  enum: [cat, dog]
+                born:
+                  type: string
+                  format: date
+                weight:
+                  type: number
+                  maximum: -2.5
+                owner:
+                  type: string
+                  format: email
+                password:
+                  type: string
+                  writeOnly: true
+                parent:
+                  $ref: '#/components/schemas/Pet'
+        "#
+    );
+
+    fn mock(status_code: u16, accept: Option<&str>) -> MockResponse {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let mut headers = HashMap::new();
+        if let Some(accept) = accept {
+            headers.insert("Accept".to_string(), accept.to_string());
+        }
+        let validated = validator
+            .validate_request(&FakeRequest {
+                url: "http://test.com/pets".to_string(),
+                operation: "get".to_string(),
+                body: vec![],
+                headers,
+            })
+            .unwrap();
+        let mock = validated
+            .response_validator
+            .mock_response(status_code)
+            .unwrap();
+        assert_eq!(Ok(()), validated.validate_response(&mock));
+        mock
+    }
+
+    fn json_body(mock: &MockResponse) -> Value {
+        serde_json::from_slice(&mock.body).unwrap()
+    }
+
+    #[test]
+    fn synthesize_a_body_honouring_the_schema() {
+        let mock = mock(200, None);
+        assert_eq!(Some("application/json"), mock.content_type());
+        assert_eq!(Some("1".to_string()), mock.get_header("X-Total"));
+        let pet = &json_body(&mock)[0];
+        assert_eq!(json!(12), pet["id"]);
+        assert_eq!(json!("strin"), pet["name"]);
+        assert_eq!(json!("cat"), pet["tag"]);
+        assert_eq!(json!("2024-01-01"), pet["born"]);
+        assert_eq!(json!(-2.5), pet["weight"]);
+        assert_eq!(json!("user@example.com"), pet["owner"]);
+        assert_eq!(None, pet.get("password"));
+        assert!(pet["parent"]["parent"].is_object());
+    }
+
+    #[test]
+    fn use_the_example_of_the_media_type() {
+        assert_eq!(json!({"id": 7, "name": "rex"}), json_body(&mock(201, None)));
+    }
+
+    #[test]
+    fn prefer_the_media_type_the_request_accepts() {
+        let mock = mock(200, Some("text/plain"));
+        assert_eq!(Some("text/plain"), mock.content_type());
+        assert_eq!(b"string", mock.body.as_slice());
+    }
+
+    #[test]
+    fn generate_no_body_where_http_forbids_one() {
+        let mock = mock(204, None);
+        assert_eq!(None, mock.content_type());
+        assert!(mock.body.is_empty());
+    }
+
+    #[test]
+    fn refuse_an_undocumented_status_code() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let responses = validator.response_validator("/pets", "get").unwrap();
+        assert!(matches!(
+            responses.mock_response(500),
+            Err(ValidationError::UndocumentedStatusCode { .. })
+        ));
+    }
+
+    #[parameterized(
+        bounds = {
+            (None, None, false, false, None),
+            (Some(3), None, false, false, None),
+            (Some(3), None, true, false, None),
+            (None, Some(-3), false, true, None),
+            (Some(-10), Some(10), false, false, Some(7)),
+            (Some(1), Some(5), false, false, Some(3)),
+        },
+        expected = { 0, 3, 4, -4, 0, 3 }
+    )]
+    fn pick_an_integer_within_bounds(
+        bounds: (Option<i64>, Option<i64>, bool, bool, Option<i64>),
+        expected: i64,
+    ) {
+        let (minimum, maximum, exclusive_minimum, exclusive_maximum, multiple_of) = bounds;
+        let integer = openapiv3::IntegerType {
+            minimum,
+            maximum,
+            exclusive_minimum,
+            exclusive_maximum,
+            multiple_of,
+            ..Default::default()
+        };
+        assert_eq!(expected, integer_in(&integer));
+    }
+}
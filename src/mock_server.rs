@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper_util::rt::TokioIo;
+
+use crate::http_request::problem_json;
+use crate::hyper_service::collect;
+use crate::request::Validator;
+
+/// Answers requests with responses generated from the specification, like a prism mock server.
+/// Requests are routed and validated, and invalid ones answered with [`problem_json`]. Valid
+/// ones get [`crate::ResponseValidator::mock_response`] for the status code asked for with a
+/// `Prefer: code=404` header, or else the operation's first success status.
+#[derive(Debug, Clone)]
+pub struct MockServer {
+    validator: Arc<Validator>,
+}
+
+impl MockServer {
+    pub fn new(validator: Arc<Validator>) -> Self {
+        Self { validator }
+    }
+
+    pub fn respond<B: AsRef<[u8]>>(&self, request: &http::Request<B>) -> http::Response<Bytes> {
+        let validated = match self.validator.validate_request(request) {
+            Ok(validated) => validated,
+            Err(error) => return problem_json(&error).map(Bytes::from),
+        };
+        let status_code = (request.headers().get_all("Prefer").iter())
+            .filter_map(|prefer| prefer.to_str().ok())
+            .find_map(preferred_code)
+            .unwrap_or_else(|| success_status(&validated.operation.operation_spec.responses));
+        let mock = match validated.response_validator.mock_response(status_code) {
+            Ok(mock) => mock,
+            Err(error) => return problem_json(&error).map(Bytes::from),
+        };
+
+        let mut response = http::Response::new(Bytes::from(mock.body));
+        *response.status_mut() =
+            http::StatusCode::from_u16(mock.status_code).unwrap_or(http::StatusCode::OK);
+        for (name, value) in mock.headers {
+            let (Ok(name), Ok(value)) = (
+                http::HeaderName::from_bytes(name.as_bytes()),
+                http::HeaderValue::from_str(&value),
+            ) else {
+                continue;
+            };
+            response.headers_mut().append(name, value);
+        }
+        response
+    }
+
+    /// Serves HTTP/1.1 connections accepted on `listener` until accepting fails.
+    pub async fn serve(self, listener: tokio::net::TcpListener) -> std::io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = self.clone();
+            let service = service_fn(move |request: http::Request<hyper::body::Incoming>| {
+                let server = server.clone();
+                async move {
+                    let (parts, body) = request.into_parts();
+                    let limit = server.validator.options().max_body_size;
+                    let response = match collect(body, limit).await {
+                        Ok(body) => server.respond(&http::Request::from_parts(parts, body)),
+                        Err(error) => problem_json(&error).map(Bytes::from),
+                    };
+                    Ok::<_, std::convert::Infallible>(response.map(Full::new))
+                }
+            });
+            tokio::spawn(async move {
+                let _ = (http1::Builder::new())
+                    .serve_connection(TokioIo::new(stream), service)
+                    .await;
+            });
+        }
+    }
+}
+
+/// The status code of a `Prefer: code=404` preference, as prism reads it.
+fn preferred_code(prefer: &str) -> Option<u16> {
+    (prefer.split([',', ';']))
+        .find_map(|preference| preference.trim().strip_prefix("code="))
+        .and_then(|code| code.trim().parse().ok())
+}
+
+/// The lowest documented 2xx code, else the lowest documented code, else 200 for ranges and
+/// the `default` response.
+fn success_status(responses: &openapiv3::Responses) -> u16 {
+    let codes = (responses.responses.keys()).filter_map(|code| match code {
+        openapiv3::StatusCode::Code(code) => Some(*code),
+        openapiv3::StatusCode::Range(_) => None,
+    });
+    (codes.clone().filter(|code| (200..300).contains(code)).min())
+        .or_else(|| codes.min())
+        .unwrap_or(200)
+}
+
+#[cfg(test)]
+mod test_mock_server {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            get:
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                404:
+                  description: not found
+                  content:
+                    application/problem+json:
+                      example: {"title": "Not Found"}
+                200:
+                  description: found
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [name]
+                        properties:
+                          name:
+                            type: string
+        "#
+    );
+
+    fn server() -> MockServer {
+        MockServer::new(Arc::new(make_validator_from_spec(PATH_SPEC)))
+    }
+
+    fn get(uri: &str, prefer: Option<&str>) -> http::Request<Bytes> {
+        let mut request = http::Request::get(uri).body(Bytes::new()).unwrap();
+        if let Some(prefer) = prefer {
+            let prefer = http::HeaderValue::from_str(prefer).unwrap();
+            request.headers_mut().insert("Prefer", prefer);
+        }
+        request
+    }
+
+    fn json_body(response: &http::Response<Bytes>) -> serde_json::Value {
+        serde_json::from_slice(response.body()).unwrap()
+    }
+
+    #[test]
+    fn answer_with_the_success_response() {
+        let response = server().respond(&get("/pets/1", None));
+        assert_eq!(200, response.status());
+        assert_eq!(
+            "application/json",
+            response.headers()[http::header::CONTENT_TYPE]
+        );
+        assert_eq!(json!({"name": "string"}), json_body(&response));
+    }
+
+    #[test]
+    fn answer_with_the_preferred_status_code() {
+        let response = server().respond(&get("/pets/1", Some("respond-async, code=404")));
+        assert_eq!(404, response.status());
+        assert_eq!(
+            "application/problem+json",
+            response.headers()[http::header::CONTENT_TYPE]
+        );
+        assert_eq!(json!({"title": "Not Found"}), json_body(&response));
+    }
+
+    #[test]
+    fn reject_an_invalid_request() {
+        let response = server().respond(&get("/pets/rex", None));
+        assert_eq!(400, response.status());
+        let response = server().respond(&get("/pets/1", Some("code=418")));
+        assert_eq!(
+            Some("application/problem+json"),
+            response.headers()[http::header::CONTENT_TYPE].to_str().ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn serve_over_http() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(server().serve(listener));
+
+        let response = reqwest::get(format!("http://{address}/pets/1"))
+            .await
+            .unwrap();
+        assert_eq!(200, response.status().as_u16());
+        assert_eq!(
+            json!({"name": "string"}),
+            response.json::<serde_json::Value>().await.unwrap()
+        );
+    }
+}
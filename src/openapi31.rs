@@ -0,0 +1,508 @@
+use serde_yaml::{Mapping, Value};
+
+use crate::to_jsonschema::{EXAMPLES, PREFIX_ITEMS};
+
+/// Keywords describing a schema rather than constraining it, kept on the outer schema when a
+/// list of types is split into alternatives.
+const ANNOTATIONS: [&str; 11] = [
+    "title",
+    "description",
+    "default",
+    "example",
+    EXAMPLES,
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "nullable",
+    "externalDocs",
+    "xml",
+];
+
+/// Whether the document declares OpenAPI 3.1, whose schemas follow JSON Schema 2020-12 rather
+/// than the 3.0 dialect.
+pub(crate) fn is_openapi_31(document: &Value) -> bool {
+    document
+        .get("openapi")
+        .and_then(Value::as_str)
+        .is_some_and(|version| version.starts_with("3.1"))
+}
+
+/// Rewrites a 3.1 document so it loads into the 3.0 model:
+/// - a list of types becomes `nullable` and, for more than one other type, an `anyOf`
+/// - numeric `exclusiveMinimum` and `exclusiveMaximum` become flagged bounds
+/// - `const` becomes a one-value `enum`
+/// - boolean schemas become `{}` and `{not: {}}`
+/// - `prefixItems` and `examples` move to extensions the conversion to JSON Schema reads
+///
+/// The `webhooks` section, which 3.0 has no place for, is removed and returned.
+pub(crate) fn downgrade(document: &mut Value) -> Option<Value> {
+    let mut webhooks = document
+        .as_mapping_mut()
+        .and_then(|document| document.remove("webhooks"));
+    if let Some(document) = document.as_mapping_mut() {
+        // 3.1 documents may describe only webhooks
+        if !document.contains_key("paths") {
+            document.insert("paths".into(), Mapping::new().into());
+        }
+    }
+    downgrade_schemas_in(document);
+    if let Some(webhooks) = &mut webhooks {
+        downgrade_schemas_in(webhooks);
+    }
+    webhooks
+}
+
+fn downgrade_schemas_in(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            for (key, value) in mapping.iter_mut() {
+                match key.as_str() {
+                    Some("schema") => downgrade_schema(value),
+                    Some("schemas") => {
+                        if let Some(schemas) = value.as_mapping_mut() {
+                            schemas.values_mut().for_each(downgrade_schema);
+                        }
+                    }
+                    Some("example" | "examples") => (),
+                    _ => downgrade_schemas_in(value),
+                }
+            }
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(downgrade_schemas_in),
+        _ => (),
+    }
+}
+
+fn downgrade_schema(schema: &mut Value) {
+    if let Value::Bool(accept) = schema {
+        *schema = match accept {
+            true => Mapping::new().into(),
+            false => Mapping::from_iter([("not".into(), Mapping::new().into())]).into(),
+        };
+    }
+    let Some(mapping) = schema.as_mapping_mut() else {
+        return;
+    };
+
+    for key in ["items", "not"] {
+        if let Some(subschema) = mapping.get_mut(key) {
+            downgrade_schema(subschema);
+        }
+    }
+    if let Some(subschema) = mapping
+        .get_mut("additionalProperties")
+        .filter(|subschema| !subschema.is_bool())
+    {
+        downgrade_schema(subschema);
+    }
+    for key in ["allOf", "anyOf", "oneOf", "prefixItems"] {
+        if let Some(subschemas) = mapping.get_mut(key).and_then(Value::as_sequence_mut) {
+            subschemas.iter_mut().for_each(downgrade_schema);
+        }
+    }
+    if let Some(properties) = mapping
+        .get_mut("properties")
+        .and_then(Value::as_mapping_mut)
+    {
+        properties.values_mut().for_each(downgrade_schema);
+    }
+
+    if let Some(value) = mapping.remove("const") {
+        if !mapping.contains_key("enum") {
+            mapping.insert("enum".into(), vec![value].into());
+        }
+    }
+    flag_exclusive_bound(
+        mapping,
+        "exclusiveMinimum",
+        "minimum",
+        |inclusive, exclusive| inclusive > exclusive,
+    );
+    flag_exclusive_bound(
+        mapping,
+        "exclusiveMaximum",
+        "maximum",
+        |inclusive, exclusive| inclusive < exclusive,
+    );
+    for (keyword, extension) in [("prefixItems", PREFIX_ITEMS), ("examples", EXAMPLES)] {
+        if let Some(value) = mapping.remove(keyword) {
+            mapping.insert(extension.into(), value);
+        }
+    }
+    split_types(mapping);
+}
+
+/// Keeps whichever of an exclusive and an inclusive bound is stricter, in 3.0 form.
+fn flag_exclusive_bound(
+    mapping: &mut Mapping,
+    exclusive_key: &str,
+    inclusive_key: &str,
+    is_stricter: fn(f64, f64) -> bool,
+) {
+    let Some(exclusive) = mapping.get(exclusive_key).and_then(Value::as_f64) else {
+        return;
+    };
+    let bound = mapping.remove(exclusive_key);
+    let inclusive = mapping.get(inclusive_key).and_then(Value::as_f64);
+    if !inclusive.is_some_and(|inclusive| is_stricter(inclusive, exclusive)) {
+        mapping.insert(inclusive_key.into(), bound.unwrap_or_default());
+        mapping.insert(exclusive_key.into(), true.into());
+    }
+}
+
+fn split_types(mapping: &mut Mapping) {
+    if !mapping.get("type").is_some_and(Value::is_sequence) {
+        return;
+    }
+    let Some(Value::Sequence(types)) = mapping.remove("type") else {
+        return;
+    };
+    let (nulls, types): (Vec<Value>, Vec<Value>) = types
+        .into_iter()
+        .partition(|kind| kind.as_str() == Some("null"));
+    if !nulls.is_empty() {
+        mapping.insert("nullable".into(), true.into());
+    }
+
+    match types.as_slice() {
+        [] if !nulls.is_empty() && !mapping.contains_key("enum") => {
+            mapping.insert("enum".into(), vec![Value::Null].into());
+        }
+        [] => (),
+        [kind] => {
+            mapping.insert("type".into(), kind.clone());
+        }
+        _ => {
+            let (annotations, constraints): (Mapping, Mapping) = std::mem::take(mapping)
+                .into_iter()
+                .partition(|(key, _)| key.as_str().is_some_and(|key| ANNOTATIONS.contains(&key)));
+            let branches: Vec<Value> = types
+                .into_iter()
+                .map(|kind| {
+                    let mut branch = constraints.clone();
+                    branch.insert("type".into(), kind);
+                    branch.into()
+                })
+                .collect();
+            *mapping = annotations;
+            mapping.insert("anyOf".into(), branches.into());
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_openapi_31 {
+    use super::*;
+    use crate::jsonschema::{JSONSchemaValidator, SchemaFailure};
+    use crate::schema::to_json_schema;
+    use indoc::indoc;
+    use serde_json::json;
+
+    fn convert(schema: &str) -> serde_json::Value {
+        let mut document: Value = serde_yaml::from_str(&format!(
+            "openapi: 3.1.0\ncomponents:\n  schemas:\n    Converted:\n{}",
+            schema
+                .lines()
+                .map(|line| format!("      {line}\n"))
+                .collect::<String>()
+        ))
+        .unwrap();
+        downgrade(&mut document);
+        let components: openapiv3::Components =
+            serde_yaml::from_value(document["components"].clone()).unwrap();
+        let schema = components.schemas["Converted"].as_item().unwrap();
+        to_json_schema(schema, &components)
+    }
+
+    #[test]
+    fn recognise_the_version() {
+        assert!(is_openapi_31(
+            &serde_yaml::from_str("openapi: 3.1.0").unwrap()
+        ));
+        assert!(!is_openapi_31(
+            &serde_yaml::from_str("openapi: 3.0.3").unwrap()
+        ));
+    }
+
+    #[test]
+    fn type_with_null() {
+        assert_eq!(
+            json!({"type": ["string", "null"]}),
+            convert("type: [string, 'null']\n")
+        );
+    }
+
+    #[test]
+    fn only_null() {
+        assert_eq!(
+            json!({"anyOf": [{"enum": [null]}, {"type": "null"}]}),
+            convert("type: ['null']\n")
+        );
+    }
+
+    #[test]
+    fn several_types() {
+        let schema = convert(indoc!(
+            r#"
+            type: [string, integer]
+            description: A name or an id
+            minLength: 2
+            minimum: 1
+            "#
+        ));
+        assert_eq!(
+            json!({
+                "description": "A name or an id",
+                "anyOf": [
+                    {"type": "string", "minLength": 2},
+                    {"type": "integer", "minimum": 1}
+                ]
+            }),
+            schema
+        );
+        let options = Default::default();
+        assert_eq!(Ok(()), schema.validates_value(&json!("ab"), &options));
+        assert_eq!(Ok(()), schema.validates_value(&json!(3), &options));
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!("a"), &options)
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(true), &options)
+        );
+    }
+
+    #[test]
+    fn numeric_exclusive_bounds() {
+        assert_eq!(
+            json!({"type": "integer", "exclusiveMinimum": 0, "maximum": 10}),
+            convert("type: integer\nexclusiveMinimum: 0\nmaximum: 10\n")
+        );
+        assert_eq!(
+            json!({"type": "number", "minimum": 5.0, "exclusiveMaximum": 9.5}),
+            convert("type: number\nminimum: 5\nexclusiveMinimum: 1\nexclusiveMaximum: 9.5\n")
+        );
+    }
+
+    #[test]
+    fn constant() {
+        assert_eq!(
+            json!({"type": "string", "enum": ["fixed"]}),
+            convert("type: string\nconst: fixed\n")
+        );
+    }
+
+    #[test]
+    fn prefix_items() {
+        let schema = convert(indoc!(
+            r#"
+            type: array
+            prefixItems:
+              - type: number
+              - type: [string, 'null']
+            items: false
+            "#
+        ));
+        assert_eq!(
+            json!({
+                "type": "array",
+                "items": [{"type": "number"}, {"type": ["string", "null"]}],
+                "additionalItems": {"not": {}}
+            }),
+            schema
+        );
+        let options = Default::default();
+        assert_eq!(
+            Ok(()),
+            schema.validates_value(&json!([1.5, null]), &options)
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!([1.5, "a", 3]), &options)
+        );
+    }
+
+    #[test]
+    fn examples() {
+        assert_eq!(
+            json!({"type": "string", "examples": ["a", "b"]}),
+            convert("type: string\nexamples: [a, b]\n")
+        );
+    }
+
+    #[test]
+    fn nested_schemas() {
+        assert_eq!(
+            json!({
+                "type": "object",
+                "properties": {"id": {"type": ["integer", "null"]}},
+                "additionalProperties": {"type": "string", "enum": ["x"]}
+            }),
+            convert(indoc!(
+                r#"
+                type: object
+                properties:
+                  id:
+                    type: [integer, 'null']
+                additionalProperties:
+                  type: string
+                  const: x
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn remove_webhooks() {
+        let mut document: Value = serde_yaml::from_str(indoc!(
+            r#"
+            openapi: 3.1.0
+            webhooks:
+              newPet:
+                post:
+                  requestBody:
+                    content:
+                      application/json:
+                        schema:
+                          type: [object, 'null']
+            "#
+        ))
+        .unwrap();
+        let webhooks = downgrade(&mut document).unwrap();
+
+        assert_eq!(None, document.get("webhooks"));
+        assert_eq!(Some(&Value::from(Mapping::new())), document.get("paths"));
+        assert_eq!(
+            Some(&Value::from(true)),
+            webhooks["newPet"]["post"]["requestBody"]["content"]["application/json"]["schema"]
+                .get("nullable")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_openapi_31_documents {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const DOCUMENT: &str = indoc!(
+        r#"
+        openapi: 3.1.0
+        info:
+          title: Pets
+          version: "1.0.0"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      $ref: '#/components/schemas/Pet'
+              responses:
+                200:
+                  description: API call successful
+        webhooks:
+          newPet:
+            post:
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      $ref: '#/components/schemas/Pet'
+              responses:
+                200:
+                  description: Webhook received
+        components:
+          schemas:
+            Pet:
+              type: object
+              required: [name]
+              properties:
+                name:
+                  type: string
+                tag:
+                  type: [string, 'null']
+                age:
+                  type: integer
+                  exclusiveMinimum: 0
+        "#
+    );
+
+    fn load() -> Validator {
+        Validator::from_yaml_str(DOCUMENT).unwrap()
+    }
+
+    fn post(url: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        }
+    }
+
+    #[test]
+    fn validate_requests_against_a_31_document() {
+        let validator = load();
+        assert!(validator
+            .validate_request(&post(
+                "http://test.com/pets",
+                r#"{"name": "Rex", "tag": null, "age": 1}"#
+            ))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator
+                .validate_request(&post(
+                    "http://test.com/pets",
+                    r#"{"name": "Rex", "age": 0}"#
+                ))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn validate_webhooks() {
+        let validator = load();
+        let delivery = |body: &str| post("http://receiver.com/hooks/pets", body);
+
+        let validated = validator
+            .validate_webhook("newPet", &delivery(r#"{"name": "Rex"}"#))
+            .unwrap();
+        assert!(validated.operation.webhook);
+        assert_eq!("#/webhooks/newPet/post", validated.operation.pointer());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator
+                .validate_webhook("newPet", &delivery(r#"{"tag": "dog"}"#))
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            validator
+                .validate_webhook("oldPet", &delivery(r#"{"name": "Rex"}"#))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn webhooks_are_not_paths() {
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            load()
+                .validate_request(&post("http://test.com/newPet", r#"{"name": "Rex"}"#))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn only_31_documents_are_rewritten() {
+        let document = DOCUMENT.replace("openapi: 3.1.0", "openapi: 3.0.3");
+        assert!(Validator::from_yaml_str(&document).is_err());
+    }
+}
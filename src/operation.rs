@@ -1,51 +1,208 @@
 use super::parameters::ParametersValidator;
-use crate::content_type::ContentTypeValidator;
+use crate::body::BodyStream;
+use crate::content_type::{validate_accept, ContentTypeValidator};
+use crate::context::ValidationContext;
+use crate::error::ValidationError;
+use crate::extensions::{duration_from_extension, ParameterDependencies, RateLimit, Sla};
+use crate::jsonschema::spec_pointer;
+use crate::method::Method;
+use crate::options::ValidatorOptions;
+use crate::report::ValidationReport;
 use crate::request::Request;
 use crate::response::ResponseValidator;
+use crate::schema_cache::CompiledSchemas;
+use crate::security::{SecurityHandlers, SecurityValidator};
+use crate::validated_request::ValidatedRequest;
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::time::Duration;
+
+/// The operation in the specification that a request was routed to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OperationMatch<'api> {
+    /// The path template, or the name of the webhook.
+    pub path: &'api str,
+    pub method: Method,
+    pub operation_spec: &'api openapiv3::Operation,
+    /// Whether the operation is an OpenAPI 3.1 webhook rather than one of the `paths`.
+    pub webhook: bool,
+}
+
+impl<'api> OperationMatch<'api> {
+    pub fn operation_id(&self) -> Option<&'api str> {
+        self.operation_spec.operation_id.as_deref()
+    }
+
+    pub fn tags(&self) -> &'api [String] {
+        &self.operation_spec.tags
+    }
+
+    /// Raw value of a vendor extension declared on the operation, e.g. `x-rate-limit`.
+    pub fn extension(&self, name: &str) -> Option<&'api serde_json::Value> {
+        self.operation_spec.extensions.get(name)
+    }
+
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        self.extension("x-rate-limit")
+            .and_then(RateLimit::from_extension)
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.extension("x-timeout")
+            .and_then(duration_from_extension)
+    }
+
+    pub fn parameter_dependencies(&self) -> Option<ParameterDependencies> {
+        self.extension("x-parameter-dependencies")
+            .and_then(ParameterDependencies::from_extension)
+    }
+
+    pub fn sla(&self) -> Option<Sla> {
+        self.extension("x-sla").and_then(Sla::from_extension)
+    }
+
+    /// JSON pointer to the operation in the specification, e.g. `#/paths/~1pets/get`.
+    pub fn pointer(&self) -> String {
+        let section = match self.webhook {
+            true => "#/webhooks",
+            false => "#/paths",
+        };
+        spec_pointer(section, [self.path, self.method.as_str()])
+    }
+
+    /// How long a response may take: the `x-sla` latency, falling back to `x-timeout`.
+    pub fn latency_budget(&self) -> Option<Duration> {
+        self.sla()
+            .and_then(|sla| sla.latency)
+            .or_else(|| self.timeout())
+    }
+}
 
 pub struct OperationValidator<'api, 'request> {
+    pub path: &'api str,
     pub path_spec: &'api openapiv3::PathItem,
+    pub webhook: bool,
     pub components: &'api Option<openapiv3::Components>,
+    /// The document's `security`, for operations that do not declare their own.
+    pub security: &'api Option<Vec<openapiv3::SecurityRequirement>>,
+    pub security_handlers: &'api SecurityHandlers,
+    pub context: &'api ValidationContext,
+    pub options: &'api ValidatorOptions,
+    pub(crate) schemas: &'api CompiledSchemas,
     pub path_parameters: HashMap<&'api str, &'request str>,
+    pub query_pairs: &'request [(Cow<'request, str>, Cow<'request, str>)],
 }
 
 impl<'api, 'request> OperationValidator<'api, 'request> {
-    pub fn validate_operation(self, request: &dyn Request) -> Result<ResponseValidator<'api>, ()> {
-        let operation_spec = match request.operation() {
-            "get" => self.path_spec.get.as_ref().ok_or(()),
-            "put" => self.path_spec.put.as_ref().ok_or(()),
-            "delete" => self.path_spec.delete.as_ref().ok_or(()),
-            "post" => self.path_spec.post.as_ref().ok_or(()),
-            _ => Err(()),
-        }?;
-
-        ParametersValidator {
+    pub fn match_operation(
+        &self,
+        request: &dyn Request,
+    ) -> Result<OperationMatch<'api>, ValidationError> {
+        let not_allowed = || ValidationError::MethodNotAllowed {
+            allowed: Method::allowed(self.path_spec),
+        };
+        let method = request.method().ok_or_else(not_allowed)?;
+        let operation_spec = method.operation(self.path_spec).ok_or_else(not_allowed)?;
+
+        Ok(OperationMatch {
+            path: self.path,
+            method,
             operation_spec,
+            webhook: self.webhook,
+        })
+    }
+
+    pub fn validate_operation(
+        &self,
+        request: &dyn Request,
+    ) -> Result<ValidatedRequest<'api>, ValidationError> {
+        let operation = self.match_operation(request)?;
+        let operation_spec = operation.operation_spec;
+
+        let security = SecurityValidator {
+            requirements: operation_spec
+                .security
+                .as_ref()
+                .or(self.security.as_ref())
+                .map_or(&[], Vec::as_slice),
             components: self.components,
-            path_parameters: self.path_parameters,
+            options: self.options,
+            handlers: self.security_handlers,
+            context: self.context,
+            query_pairs: self.query_pairs,
         }
-        .validate_parameters(request)?;
+        .validate_security(request);
+        let security = match security {
+            Err(error) if self.options.enforce_security => return Err(error),
+            security => security.unwrap_or(None),
+        };
 
-        ContentTypeValidator {
+        let mut report = ValidationReport::default();
+        let pointer = operation.pointer();
+
+        let parameters = ParametersValidator {
             operation_spec,
+            path_item_parameters: &self.path_spec.parameters,
             components: self.components,
+            options: self.options,
+            schemas: self.schemas,
+            pointer: &pointer,
+            dependencies: operation.parameter_dependencies(),
+            path_parameters: &self.path_parameters,
+            query_pairs: self.query_pairs,
         }
-        .validate_content_type(request.get_header("Content-Type"))?
-        .validate_body(request.body())?;
+        .validate_parameters(request, &mut report)?;
 
-        Ok(ResponseValidator {
-            response_spec: &operation_spec.responses,
+        let mut body = match request.body() {
+            [] => BodyStream::new(request.body_stream()),
+            body => BodyStream::from_slice(body),
+        };
+        ContentTypeValidator {
+            method: operation.method,
+            operation_spec,
             components: self.components,
+            options: self.options,
+            schemas: self.schemas,
+            pointer: pointer.clone(),
+        }
+        .validate_content_type(request.get_header("Content-Type"), body.is_empty()?)?
+        .validate_body(&mut body, self.options.max_body_size, &mut report)?;
+
+        let accept = request.get_header("Accept");
+        if let Some(accept) = &accept {
+            validate_accept(accept, &operation_spec.responses, self.components)?;
+        }
+
+        Ok(ValidatedRequest {
+            response_validator: ResponseValidator {
+                response_spec: &operation_spec.responses,
+                components: self.components,
+                options: self.options,
+                schemas: self.schemas,
+                latency_budget: operation.latency_budget(),
+                pointer,
+                accept,
+            },
+            operation,
+            parameters,
+            security,
+            report,
         })
     }
 }
 
 #[cfg(test)]
 mod test_operations {
+    use crate::error::ValidationError;
+    use crate::extensions::{RateLimit, Sla};
+    use crate::method::Method;
+    use crate::options::{UnexpectedBody, ValidatorOptions};
+    use crate::report::ValidationWarning;
     use crate::request::test_helpers::*;
     use indoc::indoc;
+    use parameterized::parameterized;
     use std::collections::HashMap;
+    use std::time::Duration;
 
     #[test]
     fn accept_a_request_with_put_operation() {
@@ -93,6 +250,85 @@ mod test_operations {
             .is_ok());
     }
 
+    #[test]
+    fn accept_a_request_with_patch_operation() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /allowed/patch:
+                patch:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/allowed/patch".to_string(),
+            operation: "patch".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(Method::Patch, validated.operation.method);
+    }
+
+    #[test]
+    fn reject_a_request_with_unknown_operation() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /allowed/get:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+                post:
+                  responses:
+                    201:
+                      description: Created
+            "#
+        );
+        let request = |operation: &str| FakeRequest {
+            url: "http://test.com/allowed/get".to_string(),
+            operation: operation.to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        for operation in ["fetch", "delete"] {
+            assert_eq!(
+                Err(ValidationError::MethodNotAllowed {
+                    allowed: vec![Method::Get, Method::Post]
+                }),
+                validator.validate_request(&request(operation))
+            );
+        }
+    }
+
+    #[parameterized(operation = {"GET", "Get"})]
+    fn accept_a_request_whatever_the_case_of_its_method(operation: &str) {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /allowed/get:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/allowed/get".to_string(),
+            operation: operation.to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(Method::Get, validated.operation.method);
+    }
+
     #[test]
     fn accept_a_request_with_delete_operation() {
         let path_spec = indoc!(
@@ -115,4 +351,205 @@ mod test_operations {
             .validate_request(&request)
             .is_ok());
     }
+
+    #[test]
+    fn expose_operation_id_and_rate_limit_of_matched_operation() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /limited:
+                get:
+                  operationId: getLimited
+                  x-rate-limit: 100/m
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/limited".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        let operation = validator.match_operation(&request).unwrap();
+        assert_eq!(Some("getLimited"), operation.operation_id());
+        assert_eq!("/limited", operation.path);
+        assert_eq!(
+            Some(RateLimit {
+                requests: 100,
+                per: Duration::from_secs(60)
+            }),
+            operation.rate_limit()
+        );
+    }
+
+    #[test]
+    fn expose_timeout_and_sla_of_matched_operation() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /slow:
+                get:
+                  x-timeout: 5s
+                  x-sla:
+                    latency: 250ms
+                    availability: 99.95
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/slow".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        let operation = validator.match_operation(&request).unwrap();
+        assert_eq!(Some(Duration::from_secs(5)), operation.timeout());
+        assert_eq!(
+            Some(Sla {
+                latency: Some(Duration::from_millis(250)),
+                availability: Some(99.95)
+            }),
+            operation.sla()
+        );
+    }
+
+    const PER_METHOD_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things:
+            get:
+              parameters:
+                - in: query
+                  name: limit
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+              responses:
+                201:
+                  description: Created
+        "#
+    );
+
+    #[test]
+    fn apply_parameters_of_the_requested_operation_only() {
+        let request = FakeRequest {
+            url: "http://test.com/things".to_string(),
+            operation: "post".to_string(),
+            body: "{}".as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert!(make_validator_from_spec(PER_METHOD_SPEC)
+            .validate_request(&request)
+            .is_ok());
+    }
+
+    #[test]
+    fn apply_body_of_the_requested_operation_only() {
+        let request = FakeRequest {
+            url: "http://test.com/things?limit=10".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert!(make_validator_from_spec(PER_METHOD_SPEC)
+            .validate_request(&request)
+            .is_ok());
+    }
+
+    #[test]
+    fn warn_about_a_body_sent_to_an_operation_without_request_body() {
+        let request = FakeRequest {
+            url: "http://test.com/things?limit=10".to_string(),
+            operation: "get".to_string(),
+            body: "{}".as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert_eq!(
+            vec![ValidationWarning::UnexpectedBody],
+            make_validator_from_spec(PER_METHOD_SPEC)
+                .validate_request(&request)
+                .unwrap()
+                .report
+                .warnings
+        );
+    }
+
+    #[test]
+    fn reject_a_body_sent_to_an_operation_without_request_body_when_configured() {
+        let request = FakeRequest {
+            url: "http://test.com/things?limit=10".to_string(),
+            operation: "get".to_string(),
+            body: "{}".as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert_eq!(
+            Err(ValidationError::UnexpectedBody),
+            make_validator_from_spec(PER_METHOD_SPEC)
+                .with_options(ValidatorOptions {
+                    unexpected_body: UnexpectedBody::Reject,
+                    ..Default::default()
+                })
+                .validate_request(&request)
+        );
+    }
+
+    #[parameterized(operation = {"get", "head", "delete", "put"}, rejected = {true, true, true, false})]
+    fn reject_a_body_sent_with_a_bodyless_method_when_configured(operation: &str, rejected: bool) {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /things:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+                head:
+                  responses:
+                    200:
+                      description: API call successful
+                delete:
+                  responses:
+                    204:
+                      description: Deleted
+                put:
+                  responses:
+                    204:
+                      description: Replaced
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/things".to_string(),
+            operation: operation.to_string(),
+            body: "{}".as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        let validated = make_validator_from_spec(path_spec)
+            .with_options(ValidatorOptions {
+                unexpected_body: UnexpectedBody::RejectForBodylessMethods,
+                ..Default::default()
+            })
+            .validate_request(&request)
+            .map(|validated| validated.report.warnings);
+
+        match rejected {
+            true => assert_eq!(Err(ValidationError::UnexpectedBody), validated),
+            false => assert_eq!(Ok(vec![ValidationWarning::UnexpectedBody]), validated),
+        }
+    }
 }
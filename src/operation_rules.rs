@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::ValidationError;
+use crate::operation::OperationMatch;
+use crate::request::Request;
+
+/// A business rule checked after a request has passed spec validation, e.g. that two query
+/// parameters are mutually exclusive.
+pub type OperationRule =
+    dyn Fn(&OperationMatch, &dyn Request) -> Result<(), ValidationError> + Send + Sync;
+
+/// Rules registered against operations, keyed by `operationId`.
+#[derive(Clone, Default)]
+pub(crate) struct OperationRules {
+    by_operation_id: HashMap<String, Vec<Arc<OperationRule>>>,
+}
+
+impl OperationRules {
+    pub(crate) fn add(&mut self, operation_id: &str, rule: Arc<OperationRule>) {
+        self.by_operation_id
+            .entry(operation_id.to_string())
+            .or_default()
+            .push(rule);
+    }
+
+    /// Runs the rules for the matched operation in the order they were added, stopping at the
+    /// first one that fails.
+    pub(crate) fn check(
+        &self,
+        operation: &OperationMatch,
+        request: &dyn Request,
+    ) -> Result<(), ValidationError> {
+        operation
+            .operation_id()
+            .and_then(|operation_id| self.by_operation_id.get(operation_id))
+            .into_iter()
+            .flatten()
+            .try_for_each(|rule| rule(operation, request))
+    }
+}
+
+impl fmt::Debug for OperationRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(
+                self.by_operation_id
+                    .iter()
+                    .map(|(operation_id, rules)| (operation_id, rules.len())),
+            )
+            .finish()
+    }
+}
+
+/// Rules are equal when they share the same closures for the same operations.
+impl PartialEq for OperationRules {
+    fn eq(&self, other: &Self) -> bool {
+        self.by_operation_id.len() == other.by_operation_id.len()
+            && self.by_operation_id.iter().all(|(operation_id, rules)| {
+                other
+                    .by_operation_id
+                    .get(operation_id)
+                    .is_some_and(|other_rules| {
+                        rules.len() == other_rules.len()
+                            && rules
+                                .iter()
+                                .zip(other_rules)
+                                .all(|(rule, other_rule)| Arc::ptr_eq(rule, other_rule))
+                    })
+            })
+    }
+}
+
+#[cfg(test)]
+mod test_operation_rules {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use indoc::indoc;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    fn make_validator() -> Validator {
+        make_validator_from_spec(indoc!(
+            r#"
+            paths:
+              /pets:
+                get:
+                  operationId: listPets
+                  parameters:
+                  - in: query
+                    name: page
+                    schema:
+                      type: integer
+                  - in: query
+                    name: cursor
+                    schema:
+                      type: integer
+                  responses:
+                    200:
+                      description: API call successful
+              /owners:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        ))
+    }
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn page_or_cursor(
+        _: &crate::OperationMatch,
+        request: &dyn crate::Request,
+    ) -> Result<(), ValidationError> {
+        match request.url().contains("page=") && request.url().contains("cursor=") {
+            true => Err(ValidationError::InvalidParameter {
+                name: "cursor".to_string(),
+            }),
+            false => Ok(()),
+        }
+    }
+
+    #[test]
+    fn reject_a_request_failing_a_rule() {
+        let validator = make_validator().with_operation_rule("listPets", page_or_cursor);
+
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "cursor".to_string()
+            }),
+            validator
+                .validate_request(&get("https://test.com/pets?page=2&cursor=40"))
+                .map(|_| ())
+        );
+        assert!(validator
+            .validate_request(&get("https://test.com/pets?page=2"))
+            .is_ok());
+    }
+
+    #[test]
+    fn rules_run_only_after_built_in_checks_pass() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counted = calls.clone();
+        let validator = make_validator().with_operation_rule("listPets", move |_, _| {
+            counted.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        });
+
+        let _ = validator.validate_request(&get("https://test.com/pets?page=1&page=2"));
+        assert_eq!(0, calls.load(Ordering::SeqCst));
+
+        assert!(validator
+            .validate_request(&get("https://test.com/pets"))
+            .is_ok());
+        assert_eq!(1, calls.load(Ordering::SeqCst));
+    }
+
+    #[test]
+    fn rules_only_apply_to_their_operation() {
+        let validator = make_validator().with_operation_rule("listPets", |_, _| {
+            Err(ValidationError::InvalidParameter {
+                name: "cursor".to_string(),
+            })
+        });
+
+        assert!(validator
+            .validate_request(&get("https://test.com/owners"))
+            .is_ok());
+    }
+
+    #[test]
+    fn stop_at_the_first_failing_rule() {
+        let validator = make_validator()
+            .with_operation_rule("listPets", |_, _| {
+                Err(ValidationError::MissingParameter {
+                    name: "cursor".to_string(),
+                })
+            })
+            .with_operation_rule("listPets", |_, _| {
+                Err(ValidationError::InvalidParameter {
+                    name: "cursor".to_string(),
+                })
+            });
+
+        assert_eq!(
+            Err(ValidationError::MissingParameter {
+                name: "cursor".to_string()
+            }),
+            validator
+                .validate_request(&get("https://test.com/pets"))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn rules_receive_the_matched_operation() {
+        let validator = make_validator().with_operation_rule("listPets", |operation, _| {
+            assert_eq!("/pets", operation.path);
+            assert_eq!(Some("listPets"), operation.operation_id());
+            Ok(())
+        });
+
+        assert!(validator
+            .validate_request(&get("https://test.com/pets"))
+            .is_ok());
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use crate::checks::Check;
+
+/// Switches controlling how strictly requests are validated.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ValidatorOptions {
+    /// What to do with a body sent to an operation declaring no `requestBody`.
+    pub unexpected_body: UnexpectedBody,
+    /// Let bodies through unchecked, with a warning, when their content type is declared but
+    /// this crate has no validator for it, instead of failing the request.
+    pub pass_through_unsupported_media_types: bool,
+    /// Response checks to skip. Every check runs by default.
+    pub disabled_checks: Vec<Check>,
+    /// How to treat a body sent without a Content-Type header.
+    pub content_type_fallback: ContentTypeFallback,
+    /// Largest request body, in bytes, accepted for any operation.
+    pub max_body_size: Option<usize>,
+    /// Treat a UTF-8 byte order mark at the start of a JSON body as a syntax error instead of
+    /// skipping it.
+    pub reject_json_bom: bool,
+    /// What to do when a schema from the specification cannot be compiled for validation.
+    pub schema_compile_failure: SchemaCompileFailure,
+    /// What to do with `readOnly` properties sent in a request body.
+    pub read_only_properties: ReadOnlyProperties,
+    /// Accept strings whatever their `format`, instead of checking formats such as `date-time`,
+    /// `email` and `uuid`. Formats no checker is known for are always accepted.
+    pub skip_format_validation: bool,
+    /// Checkers for string formats the specification uses beyond the standard ones, by format
+    /// name. A checker registered for a standard format replaces the built-in one.
+    pub formats: BTreeMap<&'static str, fn(&str) -> bool>,
+    /// Fail numbers declared `format: float` that a 32-bit float cannot hold exactly, such as
+    /// `16777217` or `0.123456789`, instead of only checking they are within its range.
+    pub reject_inexact_floats: bool,
+    /// Fail responses whose content type the request's `Accept` header does not admit. Requests
+    /// whose `Accept` header no documented response media type satisfies always fail.
+    pub reject_unacceptable_responses: bool,
+    /// Route requests by their whole URL path, as if the specification declared no `servers`.
+    /// Otherwise the base path of a matching server is stripped before routing, and requests
+    /// matching no server are not found.
+    pub ignore_servers: bool,
+    /// Also require the scheme, host and port of the request URL to match a server. Relative
+    /// server URLs accept any origin.
+    pub enforce_server_origin: bool,
+    /// Route `/pets/` only to a template written with the trailing slash and `/pets` only to one
+    /// written without, and find no operation for paths with empty segments such as `//pets`.
+    /// Otherwise empty segments are ignored.
+    pub strict_path_slashes: bool,
+    /// Fail requests without credentials for any of the operation's security requirements, or
+    /// whose `Authorization` header does not match the `http` scheme it is sent for. A bearer
+    /// token must be a well-formed JWT when the scheme's `bearerFormat` is `JWT`.
+    pub enforce_security: bool,
+    /// Also fail `http` basic credentials that are not valid base64.
+    pub reject_malformed_basic_credentials: bool,
+    /// Read parameter and header values that are not valid JSON as strings, so `?name=rex`
+    /// passes a `type: string` schema. Items of array query parameters are read one by one, so
+    /// `?tag=a,b` and `?tag=a&tag=b` pass a `type: array` of strings. Otherwise such values are
+    /// invalid unless quoted.
+    pub coerce_parameter_strings: bool,
+    /// Fail requests with query parameters the operation does not declare.
+    pub reject_unknown_query_parameters: bool,
+}
+
+/// Handling of a non-empty body sent to an operation declaring no `requestBody`.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum UnexpectedBody {
+    /// Let the request through with a warning.
+    #[default]
+    Warn,
+    /// Fail GET, HEAD and DELETE requests, and warn for other methods.
+    RejectForBodylessMethods,
+    /// Fail the request whatever its method.
+    Reject,
+}
+
+/// Media type assumed for a non-empty body sent without a Content-Type header.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub enum ContentTypeFallback {
+    /// Fail the request with a missing content type error.
+    #[default]
+    Reject,
+    /// Validate against the operation's content entry when it declares exactly one.
+    SingleDeclared,
+    /// Validate as if the given content type had been sent.
+    Assume(String),
+}
+
+/// Handling of a schema the JSON Schema compiler rejects, such as one with an invalid
+/// `pattern`. The schema's location in the specification is recorded in the error, or as a
+/// warning in the request's report. Response validation has no report, so responses pass
+/// without one.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum SchemaCompileFailure {
+    /// Fail the request or response with an uncompilable schema error.
+    #[default]
+    Reject,
+    /// Let the value through unchecked.
+    Warn,
+    /// Only check the value against the schema's `type`.
+    TypeCheck,
+}
+
+/// Handling of request body properties whose schema is `readOnly`. They are never required in
+/// requests, whatever `required` says.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum ReadOnlyProperties {
+    /// Fail the request with an invalid body error.
+    #[default]
+    Reject,
+    /// Validate the body as if the properties had not been sent.
+    Strip,
+}
+
+impl ValidatorOptions {
+    /// Options turning every rule that would otherwise only warn into a hard failure. Security,
+    /// the origin of the request URL and trailing slashes in paths are also enforced.
+    pub fn strict() -> Self {
+        Self {
+            unexpected_body: UnexpectedBody::Reject,
+            pass_through_unsupported_media_types: false,
+            disabled_checks: vec![],
+            content_type_fallback: ContentTypeFallback::Reject,
+            max_body_size: None,
+            reject_json_bom: true,
+            schema_compile_failure: SchemaCompileFailure::Reject,
+            read_only_properties: ReadOnlyProperties::Reject,
+            skip_format_validation: false,
+            formats: BTreeMap::new(),
+            reject_inexact_floats: true,
+            reject_unacceptable_responses: true,
+            ignore_servers: false,
+            enforce_server_origin: true,
+            strict_path_slashes: true,
+            enforce_security: true,
+            reject_malformed_basic_credentials: true,
+            coerce_parameter_strings: false,
+            reject_unknown_query_parameters: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_strict_mode {
+    use super::ValidatorOptions;
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things/{id}:
+            parameters:
+              - in: path
+                name: id
+                required: true
+                schema:
+                  type: integer
+            get:
+              responses:
+                200:
+                  description: API call successful
+            delete:
+              responses:
+                204:
+                  description: Deleted
+        "#
+    );
+
+    #[parameterized(operation = {"get", "delete"})]
+    fn reject_a_body_without_request_body_in_strict_mode(operation: &str) {
+        let request = FakeRequest {
+            url: "http://test.com/things/1".to_string(),
+            operation: operation.to_string(),
+            body: "payload".as_bytes().to_vec(),
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::UnexpectedBody),
+            make_validator_from_spec(PATH_SPEC)
+                .with_options(ValidatorOptions::strict())
+                .validate_request(&request)
+        );
+    }
+
+    #[parameterized(operation = {"get", "delete"})]
+    fn accept_an_empty_body_without_request_body_in_strict_mode(operation: &str) {
+        let request = FakeRequest {
+            url: "http://test.com/things/1".to_string(),
+            operation: operation.to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .with_options(ValidatorOptions::strict())
+            .validate_request(&request)
+            .is_ok());
+    }
+}
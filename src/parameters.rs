@@ -1,93 +1,490 @@
-use std::collections::HashMap;
-use url::Url;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 
 use super::request::Request;
+use crate::content_type::is_json;
+use crate::error::ValidationError;
+use crate::extensions::ParameterDependencies;
 use crate::item_or_fetch::ItemOrFetch;
-use crate::to_jsonschema::ToJSONSchema;
-use crate::jsonschema::JSONSchemaValidator;
+use crate::jsonschema::{spec_pointer, validate_instance};
+use crate::options::ValidatorOptions;
+use crate::report::{ValidationReport, ValidationWarning};
+use crate::schema_cache::CompiledSchemas;
+use crate::security::cookie_value;
+
+/// Parameter values after validation, keyed by name for each location. Optional parameters
+/// that were absent from the request hold their schema `default` when one is declared.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ValidatedParameters {
+    pub path: HashMap<String, serde_json::Value>,
+    pub query: HashMap<String, serde_json::Value>,
+    pub header: HashMap<String, serde_json::Value>,
+    pub cookie: HashMap<String, serde_json::Value>,
+}
 
 pub struct ParametersValidator<'api, 'request> {
     pub operation_spec: &'api openapiv3::Operation,
+    /// Parameters declared on the path item, for every operation under it.
+    pub path_item_parameters: &'api [openapiv3::ReferenceOr<openapiv3::Parameter>],
     pub components: &'api Option<openapiv3::Components>,
-    pub path_parameters: HashMap<&'api str, &'request str>,
+    pub options: &'api ValidatorOptions,
+    pub(crate) schemas: &'api CompiledSchemas,
+    /// JSON pointer to the operation, for locating its parameters' schemas.
+    pub pointer: &'request str,
+    /// Relationships between parameters declared with `x-parameter-dependencies`.
+    pub dependencies: Option<ParameterDependencies>,
+    pub path_parameters: &'request HashMap<&'api str, &'request str>,
+    pub query_pairs: &'request [(Cow<'request, str>, Cow<'request, str>)],
 }
 
 impl<'api, 'request> ParametersValidator<'api, 'request> {
-    pub fn validate_parameters(self, request: &dyn Request) -> Result<(), ()> {
-        let all_parameters_valid = self.operation_spec.parameters.iter().all(|parameter| {
-            parameter
-                .item_or_fetch(self.components)
-                .validate(request, self.components, &self.path_parameters)
-                .is_ok()
-        });
+    pub fn validate_parameters(
+        &self,
+        request: &dyn Request,
+        report: &mut ValidationReport,
+    ) -> Result<ValidatedParameters, ValidationError> {
+        let mut parameters = ValidatedParameters::default();
+        let mut supplied = HashSet::new();
+        let mut declared_query = HashSet::new();
 
-        if !all_parameters_valid {
-            return Err(());
+        // operation parameters override those of the path item with the same name and location
+        let path_item_pointer =
+            (self.pointer.rsplit_once('/')).map_or("", |(path_item, _)| path_item);
+        let mut declared = vec![];
+        let path_item_parameters = (self.path_item_parameters.iter().enumerate())
+            .map(|(index, parameter)| (path_item_pointer, index, parameter));
+        let operation_parameters = (self.operation_spec.parameters.iter().enumerate())
+            .map(|(index, parameter)| (self.pointer, index, parameter));
+        for (pointer, index, parameter) in path_item_parameters.chain(operation_parameters) {
+            let spec = resolve_parameter(parameter, self.components)?;
+            declared.retain(|(other, ..): &(ParameterSpec, _, _, _)| {
+                (other.name, other.location) != (spec.name, spec.location)
+            });
+            declared.push((spec, pointer, index, parameter));
         }
 
-        Ok(())
+        for (parameter, pointer, index, reference) in declared {
+            let location = || match reference {
+                openapiv3::ReferenceOr::Reference { reference } => {
+                    spec_pointer(reference, ["schema"])
+                }
+                openapiv3::ReferenceOr::Item(..) => {
+                    spec_pointer(pointer, ["parameters", &index.to_string(), "schema"])
+                }
+            };
+            if parameter.location == ParameterLocation::Query {
+                declared_query.insert(parameter.name);
+            }
+            let value = match parameter.validate(
+                request,
+                self.components,
+                self.path_parameters,
+                self.query_pairs,
+                SchemaContext {
+                    options: self.options,
+                    schemas: self.schemas,
+                    location: &location,
+                    report,
+                },
+            )? {
+                Some(ParameterValue::Supplied(value)) => {
+                    supplied.insert(parameter.name.to_string());
+                    value
+                }
+                Some(ParameterValue::Default(value)) => value,
+                None => continue,
+            };
+            let name = parameter.name.to_string();
+
+            match parameter.location {
+                ParameterLocation::Path => parameters.path.insert(name, value),
+                ParameterLocation::Query => parameters.query.insert(name, value),
+                ParameterLocation::Header => parameters.header.insert(name, value),
+                ParameterLocation::Cookie => parameters.cookie.insert(name, value),
+            };
+        }
+
+        if let Some(dependencies) = &self.dependencies {
+            check_dependencies(dependencies, &supplied)?;
+        }
+
+        if self.options.reject_unknown_query_parameters {
+            // API keys sent in the query are declared by their security scheme instead
+            let api_keys = (self.components.iter())
+                .flat_map(|components| components.security_schemes.values())
+                .filter_map(|scheme| match scheme {
+                    openapiv3::ReferenceOr::Item(openapiv3::SecurityScheme::APIKey {
+                        location: openapiv3::APIKeyLocation::Query,
+                        name,
+                        ..
+                    }) => Some(name.as_str()),
+                    _ => None,
+                });
+            declared_query.extend(api_keys);
+            let unknown =
+                (self.query_pairs.iter()).find(|(name, _)| !declared_query.contains(name.as_ref()));
+            if let Some((name, _)) = unknown {
+                return Err(ValidationError::UnknownParameter {
+                    name: name.to_string(),
+                });
+            }
+        }
+
+        Ok(parameters)
     }
 }
 
-trait ParameterValidator {
-    fn validate(
-        &self,
-        request: &dyn Request,
-        components: &Option<openapiv3::Components>,
-        path_parameters: &HashMap<&str, &str>,
-    ) -> Result<(), ()>;
+/// Checks the `x-parameter-dependencies` of an operation against the parameters sent.
+fn check_dependencies(
+    dependencies: &ParameterDependencies,
+    supplied: &HashSet<String>,
+) -> Result<(), ValidationError> {
+    for group in &dependencies.exactly_one {
+        let present: Vec<String> = group
+            .iter()
+            .filter(|name| supplied.contains(*name))
+            .cloned()
+            .collect();
+        match present.len() {
+            0 => {
+                return Err(ValidationError::MissingOneOfParameters {
+                    names: group.clone(),
+                })
+            }
+            1 => (),
+            _ => return Err(ValidationError::ConflictingParameters { names: present }),
+        }
+    }
+
+    for (name, required) in &dependencies.requires {
+        if !supplied.contains(name) {
+            continue;
+        }
+        if let Some(missing) = required.iter().find(|other| !supplied.contains(*other)) {
+            return Err(ValidationError::MissingParameter {
+                name: missing.clone(),
+            });
+        }
+    }
+
+    for (name, conflicting) in &dependencies.conflicts {
+        if !supplied.contains(name) {
+            continue;
+        }
+        if let Some(other) = conflicting.iter().find(|other| supplied.contains(*other)) {
+            return Err(ValidationError::ConflictingParameters {
+                names: vec![name.clone(), other.clone()],
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// A validated parameter value, and whether it came from the request or a schema default.
+enum ParameterValue {
+    Supplied(serde_json::Value),
+    Default(serde_json::Value),
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+enum ParameterLocation {
+    Path,
+    Query,
+    Header,
+    Cookie,
+}
+
+/// The parts of a parameter validation reads, borrowed from the specification: either an
+/// operation parameter or a header component referenced as one.
+struct ParameterSpec<'api> {
+    name: &'api str,
+    location: ParameterLocation,
+    required: bool,
+    deprecated: Option<bool>,
+    format: &'api openapiv3::ParameterSchemaOrContent,
+}
+
+impl<'api> ParameterSpec<'api> {
+    fn from_parameter(parameter: &'api openapiv3::Parameter) -> Self {
+        let location = match parameter {
+            openapiv3::Parameter::Path { .. } => ParameterLocation::Path,
+            openapiv3::Parameter::Query { .. } => ParameterLocation::Query,
+            openapiv3::Parameter::Header { .. } => ParameterLocation::Header,
+            openapiv3::Parameter::Cookie { .. } => ParameterLocation::Cookie,
+        };
+        let parameter_data = parameter.parameter_data_ref();
+        Self {
+            name: &parameter_data.name,
+            location,
+            required: parameter_data.required,
+            deprecated: parameter_data.deprecated,
+            format: &parameter_data.format,
+        }
+    }
+
+    fn from_header(name: &'api str, header: &'api openapiv3::Header) -> Self {
+        Self {
+            name,
+            location: ParameterLocation::Header,
+            required: header.required,
+            deprecated: header.deprecated,
+            format: &header.format,
+        }
+    }
 }
 
-impl ParameterValidator for openapiv3::Parameter {
+/// How to handle a parameter schema that does not compile, and where to record it.
+struct SchemaContext<'a> {
+    options: &'a ValidatorOptions,
+    schemas: &'a CompiledSchemas,
+    location: &'a dyn Fn() -> String,
+    report: &'a mut ValidationReport,
+}
+
+impl ParameterSpec<'_> {
     fn validate(
         &self,
         request: &dyn Request,
         components: &Option<openapiv3::Components>,
         path_parameters: &HashMap<&str, &str>,
-    ) -> Result<(), ()> {
-        let parameter_data = self.clone().parameter_data();
-
-        //this has already been checked so unwrap is fine
-        let url = Url::parse(request.url()).unwrap();
-
-        let parameter_value = match self {
-            openapiv3::Parameter::Header { .. } => request.get_header(&parameter_data.name),
-            openapiv3::Parameter::Query { .. } => url.extract_query_parameter(&parameter_data.name),
-            openapiv3::Parameter::Path { .. } => path_parameters
-                .get(parameter_data.name.as_str())
-                .map(|value| value.to_string()),
-            _ => todo!(),
+        query_pairs: &[(Cow<str>, Cow<str>)],
+        context: SchemaContext,
+    ) -> Result<Option<ParameterValue>, ValidationError> {
+        let parameter_value = match self.location {
+            ParameterLocation::Header => request.get_header(self.name).map(SentValue::One),
+            ParameterLocation::Query => join_query_values(
+                query_pairs.extract_query_parameters(self.name),
+                self,
+                components,
+            )?,
+            ParameterLocation::Path => {
+                (path_parameters.get(self.name)).map(|value| SentValue::One(value.to_string()))
+            }
+            ParameterLocation::Cookie => (request.get_header("Cookie"))
+                .and_then(|cookies| Some(cookie_value(&cookies, self.name)?.to_string()))
+                .map(SentValue::One),
         };
 
+        if parameter_value.is_some() && self.deprecated == Some(true) {
+            context.report.warn(ValidationWarning::DeprecatedParameter {
+                name: self.name.to_string(),
+            });
+        }
+
         match parameter_value {
-            None if !parameter_data.required => Ok(()),
-            None => Err(()),
-            Some(parameter_value) => match parameter_data.format {
+            None if !self.required => Ok(match self.format {
                 openapiv3::ParameterSchemaOrContent::Schema(schema) => schema
-                    .item_or_fetch(components)
-                    .to_json_schema()
-                    .validates(&parameter_value),
-                _ => todo!(),
-            },
+                    .item_or_fetch(components)?
+                    .schema_data
+                    .default
+                    .clone()
+                    .map(ParameterValue::Default),
+                _ => None,
+            }),
+            None => Err(ValidationError::MissingParameter {
+                name: self.name.to_string(),
+            }),
+            Some(sent) => match sent {
+                SentValue::One(value) => validate_value(
+                    &value,
+                    self.format,
+                    components,
+                    context.options,
+                    context.schemas,
+                    context.location,
+                    Some(context.report),
+                ),
+                SentValue::Items(items) => query_array(&items, context.options)
+                    .ok_or(None)
+                    .and_then(|value| {
+                        check_value(
+                            value,
+                            self.format,
+                            components,
+                            context.options,
+                            context.schemas,
+                            context.location,
+                            Some(context.report),
+                        )
+                    }),
+            }
+            .map(|value| Some(ParameterValue::Supplied(value)))
+            .map_err(|error| {
+                error.unwrap_or(ValidationError::InvalidParameter {
+                    name: self.name.to_string(),
+                })
+            }),
         }
     }
 }
 
-trait ExtractQueryParameter {
-    fn extract_query_parameter(&self, name: &String) -> Option<String>;
+/// Parses a parameter or header value as JSON and checks it against its schema, failing with
+/// `None` when it is invalid. A value described by `content` is checked against the schema of
+/// its media type, and read as a string unless that media type is JSON; `location` still points
+/// at where a `schema` would be. See [`validate_instance`] for schemas that do not compile.
+pub(crate) fn validate_value(
+    value: &str,
+    format: &openapiv3::ParameterSchemaOrContent,
+    components: &Option<openapiv3::Components>,
+    options: &ValidatorOptions,
+    schemas: &CompiledSchemas,
+    location: impl FnOnce() -> String,
+    report: Option<&mut ValidationReport>,
+) -> Result<serde_json::Value, Option<ValidationError>> {
+    let json = match format {
+        openapiv3::ParameterSchemaOrContent::Schema(_) => true,
+        openapiv3::ParameterSchemaOrContent::Content(content) => (content.first())
+            .is_none_or(|(media_type, _)| media_type.parse().is_ok_and(|mime| is_json(&mime))),
+    };
+    let value = match json {
+        true => parse_value(value, options).ok_or(None)?,
+        false => value.into(),
+    };
+    check_value(
+        value, format, components, options, schemas, location, report,
+    )
 }
 
-impl ExtractQueryParameter for Url {
-    fn extract_query_parameter(&self, name: &String) -> Option<String> {
-        match self.query_pairs().find(|(key, ..)| key == name) {
-            Some((.., value)) => Some(value.to_string()),
-            None => None,
+/// Checks a value already read from a parameter or header against its schema, as
+/// [`validate_value`] does.
+fn check_value(
+    value: serde_json::Value,
+    format: &openapiv3::ParameterSchemaOrContent,
+    components: &Option<openapiv3::Components>,
+    options: &ValidatorOptions,
+    schemas: &CompiledSchemas,
+    location: impl FnOnce() -> String,
+    report: Option<&mut ValidationReport>,
+) -> Result<serde_json::Value, Option<ValidationError>> {
+    let (schema, pointer) = match format {
+        openapiv3::ParameterSchemaOrContent::Schema(schema) => (Some(schema), location()),
+        openapiv3::ParameterSchemaOrContent::Content(content) => {
+            let Some((media_type, media_type_spec)) = content.first() else {
+                return Ok(value);
+            };
+            let pointer = location();
+            let base = pointer.strip_suffix("/schema").unwrap_or(&pointer);
+            let pointer = spec_pointer(base, ["content", media_type, "schema"]);
+            (media_type_spec.schema.as_ref(), pointer)
         }
+    };
+    let Some(schema) = schema else {
+        return Ok(value);
+    };
+    let compiled = (schemas.schema(&pointer, schema, components, None, options)).map_err(Some)?;
+    validate_instance(&compiled, &value, options, || pointer, report).map(|_| value)
+}
+
+/// A parameter or header value as JSON, or as a string when it is not valid JSON and
+/// `coerce_parameter_strings` is set.
+fn parse_value(value: &str, options: &ValidatorOptions) -> Option<serde_json::Value> {
+    match serde_json::from_str(value) {
+        Ok(value) => Some(value),
+        Err(_) if options.coerce_parameter_strings => Some(value.into()),
+        Err(_) => None,
+    }
+}
+
+/// Resolves a parameter, treating a reference into `components.headers` as a header
+/// parameter named after the component key.
+fn resolve_parameter<'api>(
+    parameter: &'api openapiv3::ReferenceOr<openapiv3::Parameter>,
+    components: &'api Option<openapiv3::Components>,
+) -> Result<ParameterSpec<'api>, ValidationError> {
+    Ok(match parameter {
+        openapiv3::ReferenceOr::Reference { reference } => {
+            match reference.strip_prefix("#/components/headers/") {
+                Some(name) => ParameterSpec::from_header(
+                    name,
+                    (components.as_ref())
+                        .and_then(|components| components.headers.get(name))
+                        .ok_or_else(|| ValidationError::UnresolvedReference {
+                            reference: reference.clone(),
+                        })?
+                        .item_or_fetch(components)?,
+                ),
+                None => ParameterSpec::from_parameter(parameter.item_or_fetch(components)?),
+            }
+        }
+        openapiv3::ReferenceOr::Item(parameter) => ParameterSpec::from_parameter(parameter),
+    })
+}
+
+/// A parameter as it was sent: one value, or every occurrence of an array query parameter.
+enum SentValue {
+    One(String),
+    Items(Vec<String>),
+}
+
+/// A query key may only repeat when the parameter is an array, in which case its
+/// occurrences, however many, are collected by [`query_array`].
+fn join_query_values(
+    mut values: Vec<String>,
+    parameter: &ParameterSpec,
+    components: &Option<openapiv3::Components>,
+) -> Result<Option<SentValue>, ValidationError> {
+    if values.is_empty() {
+        return Ok(None);
+    }
+    let array = match parameter.format {
+        openapiv3::ParameterSchemaOrContent::Schema(schema) => matches!(
+            schema.item_or_fetch(components)?.schema_kind,
+            openapiv3::SchemaKind::Type(openapiv3::Type::Array(_))
+        ),
+        openapiv3::ParameterSchemaOrContent::Content(_) => false,
+    };
+    match (array, values.len()) {
+        (true, _) => Ok(Some(SentValue::Items(values))),
+        (false, 1) => Ok(values.pop().map(SentValue::One)),
+        (false, _) => Err(ValidationError::DuplicateParameter {
+            name: parameter.name.to_string(),
+        }),
+    }
+}
+
+/// The occurrences of an array query parameter as one JSON array of their values, so `?n=1`
+/// reads as `[1]` and `?n=1&n=2` as `[1,2]`. A single occurrence holding a JSON array, as in
+/// `?n=[1,2]`, reads as that array. With `coerce_parameter_strings`, an occurrence that is not
+/// valid JSON is split on commas and each item read as [`parse_value`] does, so `?tag=a,b`
+/// reads as `["a","b"]`. `None` when an occurrence is not valid JSON otherwise.
+fn query_array(values: &[String], options: &ValidatorOptions) -> Option<serde_json::Value> {
+    if let [value] = values {
+        if let Ok(array @ serde_json::Value::Array(_)) = serde_json::from_str(value) {
+            return Some(array);
+        }
+    }
+    let mut items = vec![];
+    for value in values {
+        match serde_json::from_str(value) {
+            Ok(item) => items.push(item),
+            Err(_) if options.coerce_parameter_strings => {
+                for item in value.split(',') {
+                    items.push(parse_value(item, options)?);
+                }
+            }
+            Err(_) => return None,
+        }
+    }
+    Some(serde_json::Value::Array(items))
+}
+
+trait ExtractQueryParameters {
+    fn extract_query_parameters(&self, name: &str) -> Vec<String>;
+}
+
+impl ExtractQueryParameters for [(Cow<'_, str>, Cow<'_, str>)] {
+    fn extract_query_parameters(&self, name: &str) -> Vec<String> {
+        self.iter()
+            .filter(|(key, ..)| key == name)
+            .map(|(.., value)| value.to_string())
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod test_header_parameters {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
     use std::collections::HashMap;
@@ -117,7 +514,9 @@ mod test_header_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -152,7 +551,9 @@ mod test_header_parameters {
             headers: HashMap::from([("thing".to_string(), "true".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingParameter {
+                name: "another_thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -182,7 +583,9 @@ mod test_header_parameters {
             headers: HashMap::from([("thing".to_string(), "1".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -220,7 +623,9 @@ mod test_header_parameters {
             ]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "another_thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -279,7 +684,9 @@ mod test_header_parameters {
             headers: HashMap::from([("thing".to_string(), "not_valid".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -346,7 +753,9 @@ mod test_header_parameters {
             headers: HashMap::from([("thing".to_string(), "not_valid".to_string())]),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -418,10 +827,140 @@ mod test_header_parameters {
             .validate_request(&request)
             .is_ok());
     }
+
+    const HEADER_COMPONENT_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /requires/header/parameter:
+            post:
+              parameters:
+                - $ref: '#/components/headers/X-Thing'
+              responses:
+                200:
+                  description: API call successful
+
+        components:
+          headers:
+            X-Thing:
+              required: true
+              schema:
+                type: integer
+        "#
+    );
+
+    #[test]
+    fn accept_a_request_given_a_header_component_reference() {
+        let request = FakeRequest {
+            url: "http://test.com/requires/header/parameter".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::from([("X-Thing".to_string(), "5".to_string())]),
+        };
+        assert_eq!(
+            Some(&serde_json::json!(5)),
+            make_validator_from_spec(HEADER_COMPONENT_SPEC)
+                .validate_request(&request)
+                .unwrap()
+                .parameters
+                .header
+                .get("X-Thing")
+        );
+    }
+
+    #[test]
+    fn reject_a_request_missing_a_required_header_component_reference() {
+        let request = FakeRequest {
+            url: "http://test.com/requires/header/parameter".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::MissingParameter {
+                name: "X-Thing".to_string()
+            }),
+            make_validator_from_spec(HEADER_COMPONENT_SPEC).validate_request(&request)
+        );
+    }
+
+    #[test]
+    fn reject_a_request_with_invalid_header_component_reference() {
+        let request = FakeRequest {
+            url: "http://test.com/requires/header/parameter".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::from([("X-Thing".to_string(), "true".to_string())]),
+        };
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "X-Thing".to_string()
+            }),
+            make_validator_from_spec(HEADER_COMPONENT_SPEC).validate_request(&request)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_cookie_parameters {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /preferences:
+            get:
+              parameters:
+                - in: cookie
+                  name: session
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn validate(cookie: Option<&str>) -> Result<Option<u64>, ValidationError> {
+        let request = FakeRequest {
+            url: "http://test.com/preferences".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: (cookie.iter())
+                .map(|cookie| ("Cookie".to_string(), cookie.to_string()))
+                .collect(),
+        };
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&request)?;
+        Ok(validated.cookie_parameter("session"))
+    }
+
+    #[test]
+    fn read_a_parameter_from_the_cookie_header() {
+        assert_eq!(Ok(Some(42)), validate(Some("theme=dark; session=42")));
+    }
+
+    #[test]
+    fn reject_an_invalid_or_missing_cookie_parameter() {
+        let missing = Err(ValidationError::MissingParameter {
+            name: "session".to_string(),
+        });
+        assert_eq!(missing, validate(None));
+        assert_eq!(missing, validate(Some("theme=dark")));
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "session".to_string()
+            }),
+            validate(Some("session=abc"))
+        );
+    }
 }
 
 #[cfg(test)]
 mod test_query_parameters {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
     use std::collections::HashMap;
@@ -451,7 +990,9 @@ mod test_query_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -486,7 +1027,9 @@ mod test_query_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingParameter {
+                name: "another".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -516,7 +1059,9 @@ mod test_query_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -604,7 +1149,9 @@ mod test_query_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -669,7 +1216,9 @@ mod test_query_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::MissingParameter {
+                name: "thing".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -743,8 +1292,144 @@ mod test_query_parameters {
     }
 }
 
+#[cfg(test)]
+mod test_duplicate_query_parameters {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /query/parameters:
+            get:
+              parameters:
+                - in: query
+                  name: single
+                  required: false
+                  schema:
+                    type: integer
+                - in: query
+                  name: many
+                  required: false
+                  schema:
+                    type: array
+                    items:
+                      type: integer
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    #[test]
+    fn reject_a_repeated_non_array_query_parameter() {
+        let request = FakeRequest {
+            url: "http://test.com/query/parameters?single=1&single=2".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::DuplicateParameter {
+                name: "single".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&request)
+        );
+    }
+
+    #[test]
+    fn collect_a_repeated_array_query_parameter() {
+        let request = FakeRequest {
+            url: "http://test.com/query/parameters?many=1&many=2&many=3".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Some(&json!([1, 2, 3])),
+            validator
+                .validate_request(&request)
+                .unwrap()
+                .parameters
+                .query
+                .get("many")
+        );
+    }
+
+    #[test]
+    fn reject_a_parameter_whose_schema_references_form_a_cycle() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /query/parameters:
+                get:
+                  parameters:
+                    - in: query
+                      name: cyclic
+                      schema:
+                        $ref: '#/components/schemas/A'
+                  responses:
+                    200:
+                      description: API call successful
+            components:
+              schemas:
+                A:
+                  $ref: '#/components/schemas/B'
+                B:
+                  $ref: '#/components/schemas/A'
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/query/parameters?cyclic=1".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert!(matches!(
+            make_validator_from_spec(path_spec).validate_request(&request),
+            Err(ValidationError::UnresolvedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn read_a_single_array_query_parameter_as_an_array() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        for (query, expected) in [("many=1", json!([1])), ("many=[1,2]", json!([1, 2]))] {
+            let request = FakeRequest {
+                url: format!("http://test.com/query/parameters?{query}"),
+                operation: "get".to_string(),
+                body: vec![],
+                headers: HashMap::new(),
+            };
+            let validated = validator.validate_request(&request).unwrap();
+            assert_eq!(Some(&expected), validated.parameters.query.get("many"));
+        }
+    }
+
+    #[test]
+    fn reject_a_repeated_array_query_parameter_with_invalid_item() {
+        let request = FakeRequest {
+            url: "http://test.com/query/parameters?many=1&many=two".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "many".to_string()
+            }),
+            make_validator_from_spec(PATH_SPEC).validate_request(&request)
+        );
+    }
+}
+
 #[cfg(test)]
 mod test_path_parameters {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
     use std::collections::HashMap;
@@ -774,7 +1459,7 @@ mod test_path_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::PathNotFound),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -804,7 +1489,9 @@ mod test_path_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "here".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -839,7 +1526,7 @@ mod test_path_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::PathNotFound),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -874,7 +1561,9 @@ mod test_path_parameters {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidParameter {
+                name: "another".to_string()
+            }),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -1009,4 +1698,352 @@ mod test_path_parameters {
             .validate_request(&request)
             .is_ok());
     }
+
+    #[test]
+    fn validate_parameters_declared_on_the_path_item() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /pets/{id}:
+                parameters:
+                  - in: path
+                    name: id
+                    required: true
+                    schema:
+                      type: integer
+                  - in: query
+                    name: limit
+                    schema:
+                      type: integer
+                get:
+                  parameters:
+                    - in: query
+                      name: limit
+                      schema:
+                        type: string
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        let validator = make_validator_from_spec(path_spec);
+        let request = |url: &str| FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "id".to_string()
+            }),
+            validator.validate_request(&request("http://test.com/pets/abc"))
+        );
+        let validated = validator
+            .validate_request(&request(r#"http://test.com/pets/7?limit="ten""#))
+            .unwrap();
+        assert_eq!(
+            Some(&serde_json::json!(7)),
+            validated.parameters.path.get("id")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_deprecated_parameters {
+    use crate::report::ValidationWarning;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /deprecated/query/parameter:
+            get:
+              parameters:
+                - in: query
+                  name: old
+                  required: false
+                  deprecated: true
+                  schema:
+                    type: boolean
+                - in: query
+                  name: new
+                  required: false
+                  schema:
+                    type: boolean
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    #[test]
+    fn warn_when_a_deprecated_parameter_is_used() {
+        let request = FakeRequest {
+            url: "http://test.com/deprecated/query/parameter?old=true".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            vec![ValidationWarning::DeprecatedParameter {
+                name: "old".to_string()
+            }],
+            make_validator_from_spec(PATH_SPEC)
+                .validate_request(&request)
+                .unwrap()
+                .report
+                .warnings
+        );
+    }
+
+    #[test]
+    fn do_not_warn_when_a_deprecated_parameter_is_absent() {
+        let request = FakeRequest {
+            url: "http://test.com/deprecated/query/parameter?new=true".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert!(!make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .unwrap()
+            .report
+            .has_warnings());
+    }
+
+    #[test]
+    fn reject_an_invalid_deprecated_parameter() {
+        let request = FakeRequest {
+            url: "http://test.com/deprecated/query/parameter?old=maybe".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .is_err());
+    }
+}
+
+#[cfg(test)]
+mod test_default_parameters {
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /defaulted/parameters:
+            get:
+              parameters:
+                - in: query
+                  name: limit
+                  required: false
+                  schema:
+                    type: integer
+                    default: 20
+                - in: header
+                  name: verbose
+                  required: false
+                  schema:
+                    type: boolean
+                    default: false
+                - in: query
+                  name: filter
+                  required: false
+                  schema:
+                    type: boolean
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    #[test]
+    fn apply_defaults_to_absent_optional_parameters() {
+        let request = FakeRequest {
+            url: "http://test.com/defaulted/parameters".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let parameters = validator.validate_request(&request).unwrap().parameters;
+        assert_eq!(
+            HashMap::from([("limit".to_string(), json!(20))]),
+            parameters.query
+        );
+        assert_eq!(
+            HashMap::from([("verbose".to_string(), json!(false))]),
+            parameters.header
+        );
+    }
+
+    #[test]
+    fn prefer_present_values_over_defaults() {
+        let request = FakeRequest {
+            url: "http://test.com/defaulted/parameters?limit=5&filter=true".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::from([("verbose".to_string(), "true".to_string())]),
+        };
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let parameters = validator.validate_request(&request).unwrap().parameters;
+        assert_eq!(
+            HashMap::from([
+                ("limit".to_string(), json!(5)),
+                ("filter".to_string(), json!(true))
+            ]),
+            parameters.query
+        );
+        assert_eq!(
+            HashMap::from([("verbose".to_string(), json!(true))]),
+            parameters.header
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_parameter_dependencies {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use parameterized::parameterized;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            get:
+              x-parameter-dependencies:
+                exactlyOne:
+                  - [page, cursor]
+                requires:
+                  sort_order: [sort]
+                conflicts:
+                  cursor: [offset]
+              parameters:
+                - in: query
+                  name: page
+                  schema:
+                    type: integer
+                    default: 1
+                - in: query
+                  name: cursor
+                  schema:
+                    type: integer
+                - in: query
+                  name: offset
+                  schema:
+                    type: integer
+                - in: query
+                  name: sort
+                  schema:
+                    type: boolean
+                - in: header
+                  name: sort_order
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn request(query: &str, headers: &[(&str, &str)]) -> FakeRequest {
+        FakeRequest {
+            url: format!("http://test.com/pets?{query}"),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    #[parameterized(query = {
+        "page=2", "cursor=40", "page=2&offset=10", "page=2&sort=true",
+    })]
+    fn accept_parameters_satisfying_the_dependencies(query: &str) {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert!(validator.validate_request(&request(query, &[])).is_ok());
+    }
+
+    #[test]
+    fn defaults_do_not_count_as_present() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::MissingOneOfParameters {
+                names: vec!["page".to_string(), "cursor".to_string()]
+            }),
+            validator.validate_request(&request("", &[])).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn reject_more_than_one_of_an_exclusive_group() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::ConflictingParameters {
+                names: vec!["page".to_string(), "cursor".to_string()]
+            }),
+            validator
+                .validate_request(&request("page=2&cursor=40", &[]))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn reject_a_parameter_without_the_one_it_requires() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::MissingParameter {
+                name: "sort".to_string()
+            }),
+            validator
+                .validate_request(&request("page=2", &[("sort_order", "1")]))
+                .map(|_| ())
+        );
+        assert!(validator
+            .validate_request(&request("page=2&sort=true", &[("sort_order", "1")]))
+            .is_ok());
+    }
+
+    #[test]
+    fn reject_conflicting_parameters() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::ConflictingParameters {
+                names: vec!["cursor".to_string(), "offset".to_string()]
+            }),
+            validator
+                .validate_request(&request("cursor=40&offset=10", &[]))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn invalid_values_are_reported_before_dependencies() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "page".to_string()
+            }),
+            validator
+                .validate_request(&request("page=two&cursor=40", &[]))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn ignore_operations_without_dependencies() {
+        let validator = make_validator_from_spec(&PATH_SPEC.replace("x-parameter-", "x-other-"));
+        assert!(validator.validate_request(&request("", &[])).is_ok());
+    }
 }
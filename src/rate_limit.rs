@@ -0,0 +1,364 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use tower::{BoxError, Layer, Service};
+
+use crate::request::{Request, Validator};
+use crate::resolver::ValidatorResolver;
+
+/// Tower layer enforcing the `x-rate-limit` extension of whichever operation a request routes
+/// to. Limits are counted per operationId, falling back to the method and path template for
+/// operations without one, and separately for each validator a resolver chooses between. The
+/// counts belong to the validator, so layers sharing one also share its counts. Requests that
+/// do not route to an operation are passed through.
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    resolver: Arc<dyn ValidatorResolver>,
+}
+
+impl RateLimitLayer {
+    pub fn new(validator: Arc<Validator>) -> Self {
+        Self::with_resolver(validator)
+    }
+
+    /// Picks the specification to enforce per request, e.g. per tenant.
+    pub fn with_resolver(resolver: impl ValidatorResolver + 'static) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimitService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimitService {
+            inner,
+            resolver: self.resolver.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct RateLimitService<S> {
+    inner: S,
+    resolver: Arc<dyn ValidatorResolver>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RateLimitExceeded {
+    pub operation: String,
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "rate limit exceeded for operation '{}'", self.operation)
+    }
+}
+
+impl std::error::Error for RateLimitExceeded {}
+
+struct Window {
+    start: Duration,
+    count: u32,
+}
+
+/// The current window of each rate limited operation, kept by the validator whose limits they
+/// count so that they go with it.
+#[derive(Default)]
+pub(crate) struct RateLimitWindows(Mutex<HashMap<String, Window>>);
+
+/// A clone starts counting afresh.
+impl Clone for RateLimitWindows {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+impl fmt::Debug for RateLimitWindows {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RateLimitWindows").finish_non_exhaustive()
+    }
+}
+
+/// Counts are state rather than configuration, so they never make validators differ.
+impl PartialEq for RateLimitWindows {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl<S> RateLimitService<S> {
+    fn check(&self, request: &dyn Request) -> Result<(), RateLimitExceeded> {
+        let validator = match self.resolver.resolve(request) {
+            Some(validator) => validator,
+            None => return Ok(()),
+        };
+        let operation = match validator.match_operation(request) {
+            Ok(operation) => operation,
+            Err(..) => return Ok(()),
+        };
+        let limit = match operation.rate_limit() {
+            Some(limit) => limit,
+            None => return Ok(()),
+        };
+        let key = operation
+            .operation_id()
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("{} {}", operation.method, operation.path));
+
+        let mut windows =
+            (validator.rate_limit_windows().0.lock()).unwrap_or_else(PoisonError::into_inner);
+        let now = validator.context().elapsed();
+        let window = windows.entry(key.clone()).or_insert(Window {
+            start: now,
+            count: 0,
+        });
+
+        let elapsed = now.saturating_sub(window.start);
+        if elapsed >= limit.per {
+            *window = Window {
+                start: now,
+                count: 0,
+            };
+        } else if window.count >= limit.requests {
+            return Err(RateLimitExceeded {
+                operation: key,
+                retry_after: limit.per - elapsed,
+            });
+        }
+
+        window.count += 1;
+        Ok(())
+    }
+}
+
+impl<S, R> Service<R> for RateLimitService<S>
+where
+    S: Service<R>,
+    S::Error: Into<BoxError>,
+    S::Response: Send + 'static,
+    S::Future: Send + 'static,
+    R: Request,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = Pin<Box<dyn Future<Output = Result<S::Response, BoxError>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: R) -> Self::Future {
+        if let Err(error) = self.check(&request) {
+            return Box::pin(std::future::ready(Err(error.into())));
+        }
+
+        let future = self.inner.call(request);
+        Box::pin(async move { future.await.map_err(Into::into) })
+    }
+}
+
+#[cfg(test)]
+mod test_rate_limit_layer {
+    use super::*;
+    use crate::context::{FixedClock, ValidationContext};
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::time::UNIX_EPOCH;
+    use tower::{service_fn, ServiceExt};
+
+    fn make_service() -> impl Service<FakeRequest, Response = (), Error = BoxError> + Clone {
+        make_service_with_context(ValidationContext::default())
+    }
+
+    fn make_service_with_context(
+        context: ValidationContext,
+    ) -> impl Service<FakeRequest, Response = (), Error = BoxError> + Clone {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /limited:
+                get:
+                  operationId: getLimited
+                  x-rate-limit: 2/h
+                  responses:
+                    200:
+                      description: API call successful
+              /unlimited:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        RateLimitLayer::new(Arc::new(
+            make_validator_from_spec(path_spec).with_context(context),
+        ))
+        .layer(service_fn(|_: FakeRequest| async { Ok::<_, BoxError>(()) }))
+    }
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn reject_requests_over_the_limit() {
+        let service = make_service();
+        for _ in 0..2 {
+            assert!(service
+                .clone()
+                .oneshot(get("http://test.com/limited"))
+                .await
+                .is_ok());
+        }
+        let error = service
+            .oneshot(get("http://test.com/limited"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            "getLimited",
+            error.downcast_ref::<RateLimitExceeded>().unwrap().operation
+        );
+    }
+
+    #[tokio::test]
+    async fn pass_through_operations_without_a_limit() {
+        let service = make_service();
+        for _ in 0..5 {
+            assert!(service
+                .clone()
+                .oneshot(get("http://test.com/unlimited"))
+                .await
+                .is_ok());
+        }
+    }
+
+    #[tokio::test]
+    async fn accept_requests_again_once_the_window_has_passed() {
+        let clock = Arc::new(FixedClock::new(UNIX_EPOCH));
+        let service = make_service_with_context(
+            ValidationContext::default().with_shared_clock(clock.clone()),
+        );
+        for _ in 0..2 {
+            assert!(service
+                .clone()
+                .oneshot(get("http://test.com/limited"))
+                .await
+                .is_ok());
+        }
+
+        clock.advance(Duration::from_secs(60 * 30));
+        let error = service
+            .clone()
+            .oneshot(get("http://test.com/limited"))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            Duration::from_secs(60 * 30),
+            error
+                .downcast_ref::<RateLimitExceeded>()
+                .unwrap()
+                .retry_after
+        );
+
+        clock.advance(Duration::from_secs(60 * 30));
+        assert!(service
+            .oneshot(get("http://test.com/limited"))
+            .await
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test_rate_limit_resolver {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use crate::resolver::KeyedValidators;
+    use indoc::indoc;
+    use std::collections::HashMap;
+    use tower::{service_fn, ServiceExt};
+
+    fn make_validator_with_limit(limit: &str) -> Validator {
+        make_validator_from_spec(&format!(
+            indoc!(
+                r#"
+                paths:
+                  /limited:
+                    get:
+                      operationId: getLimited
+                      x-rate-limit: {}
+                      responses:
+                        200:
+                          description: API call successful
+                "#
+            ),
+            limit
+        ))
+    }
+
+    fn get(tenant: &str) -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/limited".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::from([("X-Tenant-Id".to_string(), tenant.to_string())]),
+        }
+    }
+
+    #[tokio::test]
+    async fn count_each_tenant_against_its_own_contract() {
+        let service = RateLimitLayer::with_resolver(
+            KeyedValidators::by_header("X-Tenant-Id")
+                .with_validator("acme", make_validator_with_limit("1/h"))
+                .with_validator("globex", make_validator_with_limit("2/h")),
+        )
+        .layer(service_fn(|_: FakeRequest| async { Ok::<_, BoxError>(()) }));
+
+        assert!(service.clone().oneshot(get("acme")).await.is_ok());
+        assert!(service.clone().oneshot(get("acme")).await.is_err());
+
+        assert!(service.clone().oneshot(get("globex")).await.is_ok());
+        assert!(service.clone().oneshot(get("globex")).await.is_ok());
+        assert!(service.clone().oneshot(get("globex")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn share_counts_between_layers_over_the_same_validator() {
+        let validator = Arc::new(make_validator_with_limit("1/h"));
+        let service = |validator: &Arc<Validator>| {
+            RateLimitLayer::new(validator.clone())
+                .layer(service_fn(|_: FakeRequest| async { Ok::<_, BoxError>(()) }))
+        };
+
+        assert!(service(&validator).oneshot(get("acme")).await.is_ok());
+        assert!(service(&validator).oneshot(get("acme")).await.is_err());
+        let other = Arc::new(make_validator_with_limit("1/h"));
+        assert!(service(&other).oneshot(get("acme")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn pass_through_requests_without_a_validator() {
+        let service = RateLimitLayer::with_resolver(
+            KeyedValidators::by_header("X-Tenant-Id")
+                .with_validator("acme", make_validator_with_limit("1/h")),
+        )
+        .layer(service_fn(|_: FakeRequest| async { Ok::<_, BoxError>(()) }));
+
+        for _ in 0..3 {
+            assert!(service.clone().oneshot(get("initech")).await.is_ok());
+        }
+    }
+}
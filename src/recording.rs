@@ -0,0 +1,282 @@
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{mpsc, Arc, Mutex, PoisonError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::ValidationError;
+use crate::operation::OperationMatch;
+
+/// One validation recorded by a validator given a [`RecordSink`], as evidence of which
+/// operations were exercised and whether the traffic conformed.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExchangeRecord {
+    /// The method as sent, e.g. `GET`.
+    pub method: String,
+    /// The path template the request was routed to, or `None` when it matched no operation.
+    pub path: Option<String>,
+    pub operation_id: Option<String>,
+    /// When validation started and finished, by the validator's clock.
+    pub started: SystemTime,
+    pub finished: SystemTime,
+    pub request: Result<(), ValidationError>,
+    /// `None` when only the request was validated.
+    pub response: Option<Result<(), ValidationError>>,
+}
+
+impl ExchangeRecord {
+    pub(crate) fn new(
+        method: &str,
+        operation: Option<&OperationMatch>,
+        started: SystemTime,
+        finished: SystemTime,
+    ) -> Self {
+        Self {
+            method: method.to_string(),
+            path: operation.map(|operation| operation.path.to_string()),
+            operation_id: operation.and_then(|operation| operation.operation_id().map(Into::into)),
+            started,
+            finished,
+            request: Ok(()),
+            response: None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.request.is_ok() && !matches!(self.response, Some(Err(_)))
+    }
+
+    /// The record as a JSON object with times in milliseconds since the Unix epoch and errors
+    /// as their messages, e.g. one line of a [`JsonLinesSink`].
+    pub fn to_json(&self) -> serde_json::Value {
+        let millis = |time: SystemTime| {
+            let elapsed = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+            elapsed.as_millis() as u64
+        };
+        serde_json::json!({
+            "method": self.method,
+            "path": self.path,
+            "operation_id": self.operation_id,
+            "started_ms": millis(self.started),
+            "finished_ms": millis(self.finished),
+            "valid": self.is_valid(),
+            "request_error": self.request.as_ref().err().map(ToString::to_string),
+            "response_validated": self.response.is_some(),
+            "response_error": (self.response.as_ref())
+                .and_then(|response| response.as_ref().err())
+                .map(ToString::to_string),
+        })
+    }
+}
+
+/// Destination of [`ExchangeRecord`]s. Recording never fails validation, so sinks swallow
+/// their own errors.
+pub trait RecordSink: Send + Sync {
+    fn record(&self, record: &ExchangeRecord);
+}
+
+impl<F: Fn(&ExchangeRecord) + Send + Sync> RecordSink for F {
+    fn record(&self, record: &ExchangeRecord) {
+        self(record)
+    }
+}
+
+/// Sends records to a receiver, e.g. a thread aggregating a coverage report. Records are
+/// dropped once the receiver is.
+impl RecordSink for mpsc::Sender<ExchangeRecord> {
+    fn record(&self, record: &ExchangeRecord) {
+        let _ = self.send(record.clone());
+    }
+}
+
+/// Writes each record as a line of JSON.
+pub struct JsonLinesSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonLinesSink {
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Appends to the file at `path`, creating it if needed.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self::new(file))
+    }
+}
+
+impl RecordSink for JsonLinesSink {
+    fn record(&self, record: &ExchangeRecord) {
+        let mut writer = self.writer.lock().unwrap_or_else(PoisonError::into_inner);
+        let _ = writeln!(writer, "{}", record.to_json()).and_then(|()| writer.flush());
+    }
+}
+
+impl fmt::Debug for JsonLinesSink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonLinesSink").finish_non_exhaustive()
+    }
+}
+
+/// The sink a validator records to, if any.
+#[derive(Clone, Default)]
+pub(crate) struct Recorder(Option<Arc<dyn RecordSink>>);
+
+impl Recorder {
+    pub(crate) fn new(sink: Arc<dyn RecordSink>) -> Self {
+        Self(Some(sink))
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.0.is_some()
+    }
+
+    pub(crate) fn record(&self, record: &ExchangeRecord) {
+        if let Some(sink) = &self.0 {
+            sink.record(record);
+        }
+    }
+}
+
+impl fmt::Debug for Recorder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Recorder").field(&self.is_enabled()).finish()
+    }
+}
+
+/// Recorders are equal when they share the same sink.
+impl PartialEq for Recorder {
+    fn eq(&self, other: &Self) -> bool {
+        match (&self.0, &other.0) {
+            (Some(sink), Some(other)) => Arc::ptr_eq(sink, other),
+            (sink, other) => sink.is_none() && other.is_none(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_recording {
+    use super::*;
+    use crate::context::{FixedClock, ValidationContext};
+    use crate::request::test_helpers::*;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            get:
+              operationId: getPet
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "GET".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn recording_validator() -> (crate::Validator, mpsc::Receiver<ExchangeRecord>) {
+        let start = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let context = ValidationContext::default().with_clock(FixedClock::new(start));
+        let (sender, receiver) = mpsc::channel();
+        let validator = make_validator_from_spec(PATH_SPEC)
+            .with_context(context)
+            .with_recorder(sender);
+        (validator, receiver)
+    }
+
+    #[test]
+    fn record_validated_requests() {
+        let (validator, records) = recording_validator();
+        let _ = validator.validate_request(&get("http://test.com/pets/1"));
+        let _ = validator.validate_request(&get("http://test.com/pets/rex"));
+        let _ = validator.validate_request(&get("http://test.com/owners"));
+
+        let records: Vec<_> = records.try_iter().map(|record| record.to_json()).collect();
+        let record = |path: Option<&str>, id: Option<&str>, error: Option<&str>| {
+            json!({
+                "method": "GET",
+                "path": path,
+                "operation_id": id,
+                "started_ms": 1_700_000_000_000u64,
+                "finished_ms": 1_700_000_000_000u64,
+                "valid": error.is_none(),
+                "request_error": error,
+                "response_validated": false,
+                "response_error": null,
+            })
+        };
+        assert_eq!(
+            vec![
+                record(Some("/pets/{id}"), Some("getPet"), None),
+                record(
+                    Some("/pets/{id}"),
+                    Some("getPet"),
+                    Some("parameter 'id' is invalid")
+                ),
+                record(
+                    None,
+                    None,
+                    Some("no path in the specification matches the request")
+                ),
+            ],
+            records
+        );
+    }
+
+    #[test]
+    fn record_the_response_of_an_exchange() {
+        let (validator, records) = recording_validator();
+        let response = FakeResponse {
+            status_code: 404,
+            headers: HashMap::new(),
+            body: vec![],
+        };
+        let _ = validator.validate_exchange(&get("http://test.com/pets/1"), &response);
+
+        let record = records.try_recv().unwrap();
+        assert!(!record.is_valid());
+        assert_eq!(Ok(()), record.request);
+        assert!(matches!(record.response, Some(Err(_))));
+    }
+
+    #[test]
+    fn write_a_line_of_json_per_record() {
+        let path = std::env::temp_dir().join(format!("records-{}.jsonl", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let validator = make_validator_from_spec(PATH_SPEC)
+            .with_recorder(JsonLinesSink::create(&path).unwrap());
+        let _ = validator.validate_request(&get("http://test.com/pets/1"));
+        let _ = validator.validate_request(&get("http://test.com/pets/2"));
+
+        let lines = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let operations: Vec<serde_json::Value> = (lines.lines())
+            .map(|line| {
+                serde_json::from_str::<serde_json::Value>(line).unwrap()["operation_id"].clone()
+            })
+            .collect();
+        assert_eq!(vec![json!("getPet"), json!("getPet")], operations);
+    }
+}
@@ -0,0 +1,70 @@
+use std::fmt;
+
+use crate::error::ValidationError;
+use crate::operation::OperationMatch;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum ValidationWarning {
+    DeprecatedParameter {
+        name: String,
+    },
+    /// A body was sent to an operation that declares no `requestBody`.
+    UnexpectedBody,
+    /// The body's declared content type has no validator in this crate and was let through.
+    UnvalidatedBody {
+        content_type: String,
+    },
+    /// The schema at this location in the specification could not be compiled, so the value
+    /// it describes was not fully checked.
+    UncompilableSchema {
+        location: String,
+    },
+}
+
+impl fmt::Display for ValidationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DeprecatedParameter { name } => write!(f, "parameter '{name}' is deprecated"),
+            Self::UnexpectedBody => write!(f, "body sent to an operation that declares none"),
+            Self::UnvalidatedBody { content_type } => {
+                write!(f, "body of type {content_type} was not validated")
+            }
+            Self::UncompilableSchema { location } => {
+                write!(f, "schema at {location} could not be compiled")
+            }
+        }
+    }
+}
+
+/// Non-fatal findings collected while validating a request that passed.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct ValidationReport {
+    pub warnings: Vec<ValidationWarning>,
+}
+
+impl ValidationReport {
+    pub fn warn(&mut self, warning: ValidationWarning) {
+        self.warnings.push(warning);
+    }
+
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+/// Outcome of validating a request, and the response it received, against the operation the
+/// request was routed to.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExchangeReport<'api> {
+    pub operation: OperationMatch<'api>,
+    /// The request's warnings when it passed, or why it failed.
+    pub request: Result<ValidationReport, ValidationError>,
+    /// Why the response failed, if it did.
+    pub response: Result<(), ValidationError>,
+}
+
+impl ExchangeReport<'_> {
+    pub fn is_valid(&self) -> bool {
+        self.request.is_ok() && self.response.is_ok()
+    }
+}
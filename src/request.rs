@@ -1,125 +1,693 @@
-use regex::Regex;
+use indexmap::IndexMap;
+use std::cell::OnceCell;
 use std::collections::HashMap;
-use url::Url;
+use std::sync::Arc;
+use std::time::SystemTime;
 
-use super::operation::OperationValidator;
-use crate::response::ResponseValidator;
+use super::operation::{OperationMatch, OperationValidator};
+use crate::builder::ValidatorBuilder;
+use crate::checks::{Check, CheckHooks};
+use crate::context::ValidationContext;
+use crate::error::ValidationError;
+use crate::examples::lint_examples;
+use crate::lenient::{prune_unparseable, stringify_keys};
+use crate::method::Method;
+use crate::openapi31::{downgrade, is_openapi_31};
+use crate::operation_rules::{OperationRule, OperationRules};
+use crate::options::ValidatorOptions;
+#[cfg(feature = "tower")]
+use crate::rate_limit::RateLimitWindows;
+use crate::recording::{ExchangeRecord, RecordSink, Recorder};
+use crate::report::ExchangeReport;
+use crate::response::{Response, ResponseValidator};
+use crate::router::Router;
+use crate::schema_cache::{CacheStrategy, CompiledSchemas, SchemaCache};
+use crate::security::{SchemeCheck, ScopeChecker, SecurityHandlers};
+use crate::servers::ServerPattern;
+use crate::spec_check::{
+    check_ambiguous_paths, check_bodyless_responses, check_document, check_duplicate_operation_ids,
+    check_path_templates, SpecError,
+};
+use crate::swagger2::{is_swagger_2, upgrade};
+use crate::target::RequestTarget;
+use crate::to_jsonschema::schema_document;
+use crate::validated_request::ValidatedRequest;
+use url::Url;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct Validator {
     api: openapiv3::OpenAPI,
+    spec_errors: Vec<SpecError>,
+    options: ValidatorOptions,
+    context: ValidationContext,
+    rules: OperationRules,
+    recorder: Recorder,
+    security_handlers: SecurityHandlers,
+    webhooks: IndexMap<String, openapiv3::ReferenceOr<openapiv3::PathItem>>,
+    servers: Vec<ServerPattern>,
+    router: Router,
+    schemas: SchemaCache,
+    #[cfg(feature = "tower")]
+    rate_limit_windows: RateLimitWindows,
 }
 
-#[allow(dead_code)]
+// Validators, and what they hand out, must stay usable from multi-threaded servers.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Validator>();
+    assert_send_sync::<ValidatedRequest>();
+    assert_send_sync::<ResponseValidator>();
+    assert_send_sync::<ExchangeReport>();
+};
+
 impl Validator {
-    fn new(api: openapiv3::OpenAPI) -> Self {
-        Self { api }
+    pub fn new(api: openapiv3::OpenAPI) -> Self {
+        let mut spec_errors = check_path_templates(&api);
+        spec_errors.extend(check_ambiguous_paths(&api));
+        spec_errors.extend(check_bodyless_responses(&api));
+        let servers = api.servers.iter().map(ServerPattern::from_server).collect();
+        let router = Router::new(&api.paths);
+        Self {
+            api,
+            spec_errors,
+            options: ValidatorOptions::default(),
+            context: ValidationContext::default(),
+            rules: OperationRules::default(),
+            recorder: Recorder::default(),
+            security_handlers: SecurityHandlers::default(),
+            webhooks: IndexMap::new(),
+            servers,
+            router,
+            schemas: SchemaCache::default(),
+            #[cfg(feature = "tower")]
+            rate_limit_windows: RateLimitWindows::default(),
+        }
     }
 
-    pub fn validate_request(&self, request: &dyn Request) -> Result<ResponseValidator, ()> {
-        let url = self.parse_url(request.url())?;
+    /// Like [`Validator::new`], but schemas compiled ahead are converted and compiled across the
+    /// rayon thread pool, which shortens startup for documents with thousands of schemas.
+    /// Compilation still happens on the first validation, or on
+    /// [`Validator::precompile_schemas`], so that it uses the final options.
+    #[cfg(feature = "parallel")]
+    pub fn new_parallel(api: openapiv3::OpenAPI) -> Self {
+        let mut validator = Self::new(api);
+        validator.schemas.parallel = true;
+        validator
+    }
 
-        self.validate_path(url.path())?.validate_operation(request)
+    /// Starts configuring a validator for `api` setting by setting.
+    pub fn builder(api: openapiv3::OpenAPI) -> ValidatorBuilder {
+        ValidatorBuilder::new(api)
     }
 
-    fn parse_url(&self, url: &str) -> Result<Url, ()> {
-        match Url::parse(url) {
-            Ok(url) => Ok(url),
-            Err(..) => Err(()),
+    /// Like [`Validator::new`], but only accepts a document free of the problems
+    /// [`Validator::spec_errors`] reports, and that also has resolvable local references,
+    /// unique `operationId`s, `enum` values of their schema's type and discriminator mappings
+    /// to existing schemas. Fails with every problem found.
+    pub fn new_checked(api: openapiv3::OpenAPI) -> Result<Self, Vec<SpecError>> {
+        let mut errors = check_document(&api);
+        errors.extend(check_duplicate_operation_ids(&api));
+        // building the validator resolves references, which must not be dangling
+        if errors
+            .iter()
+            .any(|error| matches!(error, SpecError::UnresolvedReference { .. }))
+        {
+            return Err(errors);
+        }
+        let validator = Self::new(api);
+        errors.extend(validator.spec_errors.iter().cloned());
+        match errors.is_empty() {
+            true => Ok(validator),
+            false => Err(errors),
         }
     }
 
-    fn validate_path<'api, 'request>(
-        &'api self,
-        request_path: &'request str,
-    ) -> Result<OperationValidator<'api, 'request>, ()> {
-        let request_segments = split_path(request_path);
+    /// Loads a document of any supported OpenAPI version. Swagger 2.0 and OpenAPI 3.1 documents
+    /// are brought into the 3.0 model first, the latter keeping their webhooks for
+    /// [`Validator::validate_webhook`].
+    pub fn from_document(document: serde_yaml::Value) -> Result<Self, serde_yaml::Error> {
+        Self::load(document, false)
+    }
+
+    /// Loads a document that may contain operations or components that do not parse, e.g.
+    /// because of a malformed vendor extension. Those parts are skipped and reported through
+    /// [`Validator::spec_errors`] so the rest of the API is still validated. Fails only when
+    /// what remains is not a valid document.
+    pub fn new_lenient(document: serde_yaml::Value) -> Result<Self, serde_yaml::Error> {
+        Self::load(document, true)
+    }
+
+    fn load(mut document: serde_yaml::Value, lenient: bool) -> Result<Self, serde_yaml::Error> {
+        stringify_keys(&mut document);
+        if is_swagger_2(&document) {
+            upgrade(&mut document);
+        }
+        let webhooks = match is_openapi_31(&document) {
+            true => downgrade(&mut document),
+            false => None,
+        };
+        let unparseable = match lenient {
+            true => prune_unparseable(&mut document),
+            false => vec![],
+        };
+        let mut validator = Self::new(serde_yaml::from_value(document)?);
+        if let Some(webhooks) = webhooks {
+            validator.webhooks = serde_yaml::from_value(webhooks)?;
+        }
+        validator.spec_errors.splice(0..0, unparseable);
+        Ok(validator)
+    }
+
+    pub fn with_options(mut self, options: ValidatorOptions) -> Self {
+        self.options = options;
+        self.schemas.clear();
+        self
+    }
+
+    /// Checks strings with `format: <name>` using `check`, e.g. an organisation's own
+    /// identifier format. Options set afterwards with `with_options` replace the checker.
+    pub fn with_format(mut self, name: &'static str, check: fn(&str) -> bool) -> Self {
+        self.options.formats.insert(name, check);
+        self.schemas.clear();
+        self
+    }
+
+    /// Compiles every schema in the document now rather than on the first validation, which
+    /// otherwise pays for it. Schemas are compiled once per validator and reused by every
+    /// request and response it validates, until its options change. Does nothing with a
+    /// [`CacheStrategy::Lru`] cache, which compiles schemas as they are used.
+    pub fn precompile_schemas(&self) {
+        self.compiled_schemas();
+    }
+
+    /// Chooses whether schemas are all compiled ahead, the default, or on first use into a
+    /// bounded cache. Compiled schemas are dropped.
+    pub fn with_schema_cache(mut self, strategy: CacheStrategy) -> Self {
+        self.schemas.set_strategy(strategy);
+        self
+    }
+
+    /// Compiles the schemas and wraps the validator for sharing between threads, e.g. the
+    /// workers of a server. Every validation borrows the validator, so one instance serves
+    /// any number of concurrent requests.
+    pub fn shared(self) -> Arc<Self> {
+        self.precompile_schemas();
+        Arc::new(self)
+    }
+
+    pub(crate) fn compiled_schemas(&self) -> &CompiledSchemas {
+        self.schemas.get_or_compile(|| {
+            #[cfg(feature = "parallel")]
+            if self.schemas.parallel {
+                return CompiledSchemas::new_parallel(&self.api, &self.webhooks, &self.options);
+            }
+            CompiledSchemas::new(&self.api, &self.webhooks, &self.options)
+        })
+    }
+
+    pub fn options(&self) -> &ValidatorOptions {
+        &self.options
+    }
+
+    /// Replaces the clock used for time dependent checks, such as rate limit windows, the
+    /// timing of recorded exchanges and token expiry judged by security hooks.
+    pub fn with_context(mut self, context: ValidationContext) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Adds a rule checked after the built-in validation passes for requests routed to the
+    /// operation with this `operationId`. Rules for the same operation run in the order they
+    /// were added; operations without an `operationId` cannot have rules.
+    pub fn with_operation_rule(
+        mut self,
+        operation_id: &str,
+        rule: impl Fn(&OperationMatch, &dyn Request) -> Result<(), ValidationError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.rules
+            .add(operation_id, Arc::new(rule) as Arc<OperationRule>);
+        self
+    }
+
+    /// Records every request validated with [`Validator::validate_request`], and every exchange
+    /// validated with [`Validator::validate_exchange`], to `sink`, timed by the validator's
+    /// clock.
+    pub fn with_recorder(mut self, sink: impl RecordSink + 'static) -> Self {
+        self.recorder = Recorder::new(Arc::new(sink));
+        self
+    }
+
+    /// Verifies the tokens sent for `oauth2` and `openIdConnect` schemes carry the scopes the
+    /// operation requires. Without a checker only the presence of a bearer token is checked.
+    pub fn with_scope_checker(mut self, checker: impl ScopeChecker + 'static) -> Self {
+        self.security_handlers.scope_checker = Some(Arc::new(checker));
+        self
+    }
+
+    /// Checks credentials for the named security scheme with `check` instead of the built-in
+    /// checks, so schemes such as mutual TLS or request signatures take part in validation.
+    /// The scheme need not be declared in `components`.
+    pub fn with_security_scheme(
+        mut self,
+        name: &str,
+        check: impl Fn(&dyn Request, &ValidationContext) -> Result<(), ValidationError>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        self.security_handlers
+            .schemes
+            .insert(name.to_string(), Arc::new(check) as Arc<SchemeCheck>);
+        self
+    }
+
+    pub fn context(&self) -> &ValidationContext {
+        &self.context
+    }
+
+    #[cfg(feature = "tower")]
+    pub(crate) fn rate_limit_windows(&self) -> &RateLimitWindows {
+        &self.rate_limit_windows
+    }
+
+    /// Inconsistencies found in the specification while building the validator. Requests to
+    /// affected operations are still validated, but the outcome may not be what the author
+    /// of the specification intended.
+    pub fn spec_errors(&self) -> &[SpecError] {
+        &self.spec_errors
+    }
+
+    /// Examples in the specification that do not match their schemas, checked with the formats
+    /// and options in use for validation.
+    pub fn lint_examples(&self) -> Vec<SpecError> {
+        lint_examples(&self.api, &self.options)
+    }
+
+    /// The specification requests are validated against.
+    pub fn spec(&self) -> &openapiv3::OpenAPI {
+        &self.api
+    }
+
+    /// A component schema converted to a self-contained JSON Schema document, with the
+    /// components it refers to under `$defs`. `None` when there is no such component.
+    pub fn component_schema(&self, name: &str) -> Option<serde_json::Value> {
+        let components = &self.api.components;
+        let schema = components.as_ref()?.schemas.get(name)?;
+        schema_document(schema, components, None).ok()
+    }
+
+    pub fn spec_yaml(&self) -> Result<String, serde_yaml::Error> {
+        serde_yaml::to_string(&self.api)
+    }
+
+    pub fn spec_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string_pretty(&self.api)
+    }
+
+    pub fn validate_request(
+        &self,
+        request: &dyn Request,
+    ) -> Result<ValidatedRequest<'_>, ValidationError> {
+        self.validate_request_with_url(request, &OnceCell::new())
+    }
+
+    /// Like [`Validator::validate_request`], reusing the request URL when it was already parsed
+    /// into `url`, e.g. while choosing between validators.
+    pub(crate) fn validate_request_with_url(
+        &self,
+        request: &dyn Request,
+        url: &OnceCell<Url>,
+    ) -> Result<ValidatedRequest<'_>, ValidationError> {
+        let started = self.recorder.is_enabled().then(|| self.context.now());
+        let outcome = self.validate_routed_request(request, url);
+
+        if let Some(started) = started {
+            let operation = match &outcome {
+                Ok(validated) => Some(validated.operation.clone()),
+                Err(_) => self.match_operation_with_url(request, url).ok(),
+            };
+            let mut record = self.exchange_record(request, operation.as_ref(), started);
+            record.request = outcome.as_ref().map(|_| ()).map_err(Clone::clone);
+            self.recorder.record(&record);
+        }
+        outcome
+    }
+
+    fn validate_routed_request(
+        &self,
+        request: &dyn Request,
+        url: &OnceCell<Url>,
+    ) -> Result<ValidatedRequest<'_>, ValidationError> {
+        let target = RequestTarget::from_request(request, url, &self.options)?;
+
+        let validated = self.validate_path(&target)?.validate_operation(request)?;
+        self.rules.check(&validated.operation, request)?;
+
+        Ok(validated)
+    }
+
+    fn exchange_record(
+        &self,
+        request: &dyn Request,
+        operation: Option<&OperationMatch>,
+        started: SystemTime,
+    ) -> ExchangeRecord {
+        ExchangeRecord::new(request.operation(), operation, started, self.context.now())
+    }
+
+    /// Validates a request delivering the named OpenAPI 3.1 webhook. Its URL is the receiver's,
+    /// so only the method, query, headers and body are checked against the webhook.
+    pub fn validate_webhook(
+        &self,
+        name: &str,
+        request: &dyn Request,
+    ) -> Result<ValidatedRequest<'_>, ValidationError> {
+        let (name, path_spec) = self
+            .webhooks
+            .get_key_value(name)
+            .and_then(|(name, path_spec)| Some((name, path_spec.as_item()?)))
+            .ok_or(ValidationError::PathNotFound)?;
+        let url = OnceCell::new();
+        let target = RequestTarget::from_request(request, &url, &self.options)?;
+
+        let validated = OperationValidator {
+            path: name,
+            path_spec,
+            webhook: true,
+            components: &self.api.components,
+            security: &self.api.security,
+            security_handlers: &self.security_handlers,
+            context: &self.context,
+            options: &self.options,
+            schemas: self.compiled_schemas(),
+            path_parameters: HashMap::new(),
+            query_pairs: &target.query_pairs,
+        }
+        .validate_operation(request)?;
+        self.rules.check(&validated.operation, request)?;
+
+        Ok(validated)
+    }
 
-        self.api
+    /// Validates the request, then runs every enabled response check against the response,
+    /// calling `hooks` along the way. Returns the failing checks; an invalid request is an error.
+    pub fn run_checks(
+        &self,
+        request: &dyn Request,
+        response: &dyn Response,
+        hooks: &mut dyn CheckHooks,
+    ) -> Result<Vec<(Check, ValidationError)>, ValidationError> {
+        hooks.pre_request(request);
+        let validated = self.validate_request(request)?;
+
+        Ok(validated.response_validator.run_checks(response, hooks))
+    }
+
+    /// Validates a request and the response it received against the operation the request
+    /// routes to. Both halves are checked even when the request is invalid; only a request that
+    /// routes to no operation is an error.
+    pub fn validate_exchange(
+        &self,
+        request: &dyn Request,
+        response: &dyn Response,
+    ) -> Result<ExchangeReport<'_>, ValidationError> {
+        let started = self.recorder.is_enabled().then(|| self.context.now());
+        let report = self.validate_routed_exchange(request, response);
+
+        if let Some(started) = started {
+            let operation = report.as_ref().ok().map(|report| &report.operation);
+            let mut record = self.exchange_record(request, operation, started);
+            match &report {
+                Ok(report) => {
+                    record.request = report.request.as_ref().map(|_| ()).map_err(Clone::clone);
+                    record.response = Some(report.response.clone());
+                }
+                Err(error) => record.request = Err(error.clone()),
+            }
+            self.recorder.record(&record);
+        }
+        report
+    }
+
+    fn validate_routed_exchange(
+        &self,
+        request: &dyn Request,
+        response: &dyn Response,
+    ) -> Result<ExchangeReport<'_>, ValidationError> {
+        let url = OnceCell::new();
+        let target = RequestTarget::from_request(request, &url, &self.options)?;
+        let operation_validator = self.validate_path(&target)?;
+        let operation = operation_validator.match_operation(request)?;
+
+        let request_outcome =
+            operation_validator
+                .validate_operation(request)
+                .and_then(|validated| {
+                    self.rules.check(&validated.operation, request)?;
+                    Ok(validated.report)
+                });
+        let response_outcome = self
+            .operation_response_validator(&operation, request.get_header("Accept"))
+            .validate_response(response);
+
+        Ok(ExchangeReport {
+            operation,
+            request: request_outcome,
+            response: response_outcome,
+        })
+    }
+
+    /// Checks only that a status code is documented for the operation a request routes to,
+    /// regardless of whether the request itself is valid. Useful for monitoring live traffic.
+    pub fn check_status_code(
+        &self,
+        request: &dyn Request,
+        status_code: u16,
+    ) -> Result<(), ValidationError> {
+        let operation = self.match_operation(request)?;
+
+        self.operation_response_validator(&operation, None)
+            .check_status_code(status_code)
+    }
+
+    /// Validator for the responses of an operation given by its path template and method, e.g.
+    /// `("/pets/{id}", "get")`, so handler outputs can be checked without a request.
+    pub fn response_validator(
+        &self,
+        path: &str,
+        method: &str,
+    ) -> Result<ResponseValidator<'_>, ValidationError> {
+        let (path, path_spec) = self
+            .api
             .paths
             .paths
+            .get_key_value(path)
+            .and_then(|(path, path_spec)| Some((path, path_spec.as_item()?)))
+            .ok_or(ValidationError::PathNotFound)?;
+        let not_allowed = || ValidationError::MethodNotAllowed {
+            allowed: Method::allowed(path_spec),
+        };
+        let method = method.parse::<Method>().map_err(|_| not_allowed())?;
+        let operation_spec = method.operation(path_spec).ok_or_else(not_allowed)?;
+
+        let operation = OperationMatch {
+            path,
+            method,
+            operation_spec,
+            webhook: false,
+        };
+        Ok(self.operation_response_validator(&operation, None))
+    }
+
+    pub(crate) fn operation_response_validator<'api>(
+        &'api self,
+        operation: &OperationMatch<'api>,
+        accept: Option<String>,
+    ) -> ResponseValidator<'api> {
+        ResponseValidator {
+            response_spec: &operation.operation_spec.responses,
+            components: &self.api.components,
+            options: &self.options,
+            schemas: self.compiled_schemas(),
+            latency_budget: operation.latency_budget(),
+            pointer: operation.pointer(),
+            accept,
+        }
+    }
+
+    /// Routes a request to its operation without validating parameters or body.
+    pub fn match_operation(
+        &self,
+        request: &dyn Request,
+    ) -> Result<OperationMatch<'_>, ValidationError> {
+        self.match_operation_with_url(request, &OnceCell::new())
+    }
+
+    /// Like [`Validator::match_operation`], reusing the request URL when it was already parsed
+    /// into `url`.
+    pub(crate) fn match_operation_with_url(
+        &self,
+        request: &dyn Request,
+        url: &OnceCell<Url>,
+    ) -> Result<OperationMatch<'_>, ValidationError> {
+        let target = RequestTarget::from_request(request, url, &self.options)?;
+
+        self.validate_path(&target)?.match_operation(request)
+    }
+
+    /// Strips the base path of each server the request may have been sent to, then routes what
+    /// remains. Without servers, or when they are ignored, the whole URL path is routed.
+    fn validate_path<'api, 'request>(
+        &'api self,
+        target: &'request RequestTarget<'request>,
+    ) -> Result<OperationValidator<'api, 'request>, ValidationError> {
+        let path_segments = target.path_segments.as_slice();
+        if self.options.ignore_servers || self.servers.is_empty() {
+            return self.route(path_segments, target);
+        }
+
+        let origin = target
+            .origin
+            .as_deref()
+            .filter(|_| self.options.enforce_server_origin);
+        self.servers
             .iter()
-            .map(|(spec_path, path_spec)| (Segment::list_from_str(spec_path), path_spec))
-            .find(|(spec_segments, _)| Segment::list_matches(spec_segments, &request_segments))
-            .map(|(spec_segments, path_spec)| OperationValidator {
-                //unwrap as we currently don't have references
-                path_spec: path_spec.as_item().unwrap(),
-                components: &self.api.components,
-                path_parameters: extract_path_parameters(spec_segments, request_segments),
-            })
-            .ok_or(())
+            .filter_map(|server| server.strip(origin, path_segments))
+            .find_map(|request_segments| self.route(request_segments, target).ok())
+            .ok_or(ValidationError::PathNotFound)
+    }
+
+    /// The number of path segments taken by the longest base path of a server the request may
+    /// have been sent to, `0` when servers are ignored or none are declared, or `None` when it
+    /// was sent to none of them. The origin is compared when given.
+    pub(crate) fn base_path_len(
+        &self,
+        origin: Option<&str>,
+        path_segments: &[&str],
+    ) -> Option<usize> {
+        if self.options.ignore_servers || self.servers.is_empty() {
+            return Some(0);
+        }
+        self.servers
+            .iter()
+            .filter_map(|server| server.strip(origin, path_segments))
+            .map(|rest| path_segments.len() - rest.len())
+            .max()
+    }
+
+    fn route<'api, 'request>(
+        &'api self,
+        request_segments: &[&'request str],
+        target: &'request RequestTarget<'request>,
+    ) -> Result<OperationValidator<'api, 'request>, ValidationError> {
+        let (index, path_parameters) = self
+            .router
+            .route(request_segments, self.options.strict_path_slashes)
+            .ok_or(ValidationError::PathNotFound)?;
+        let (spec_path, path_spec) = (self.api.paths.paths)
+            .get_index(index)
+            .ok_or(ValidationError::PathNotFound)?;
+        let path_spec = match path_spec {
+            openapiv3::ReferenceOr::Item(path_spec) => path_spec,
+            openapiv3::ReferenceOr::Reference { reference } => {
+                return Err(ValidationError::UnresolvedReference {
+                    reference: reference.clone(),
+                })
+            }
+        };
+        Ok(OperationValidator {
+            path: spec_path,
+            path_spec,
+            webhook: false,
+            components: &self.api.components,
+            security: &self.api.security,
+            security_handlers: &self.security_handlers,
+            context: &self.context,
+            options: &self.options,
+            schemas: self.compiled_schemas(),
+            path_parameters,
+            query_pairs: &target.query_pairs,
+        })
     }
 }
 
 #[allow(dead_code)]
 #[derive(Debug)]
-enum Segment<'path> {
+pub(crate) enum Segment<'path> {
     Fixed { literal: &'path str },
     Parameter { name: &'path str },
 }
 
 impl<'path> Segment<'path> {
-    fn matches(&self, request_segment: &str) -> bool {
-        match self {
-            Segment::Fixed { literal } => literal == &request_segment,
-            Segment::Parameter { .. } => true,
-        }
-    }
-
-    fn list_from_str(path: &'path str) -> Vec<Self> {
+    pub(crate) fn list_from_str(path: &'path str) -> Vec<Self> {
         split_path(path)
             .iter()
             .map(|segment| {
-                let re = Regex::new(r"^\{[^}]*}$").unwrap();
-                match re.is_match(segment) {
-                    true => Self::Parameter {
-                        name: &segment[1..segment.len() - 1],
-                    },
-                    false => Self::Fixed { literal: segment },
+                let name = (segment.strip_prefix('{'))
+                    .and_then(|segment| segment.strip_suffix('}'))
+                    .filter(|name| !name.contains('}'));
+                match name {
+                    Some(name) => Self::Parameter { name },
+                    None => Self::Fixed { literal: segment },
                 }
             })
             .collect::<Vec<Self>>()
     }
-
-    fn list_matches(spec_segments: &[Segment], request_segments: &[&str]) -> bool {
-        if spec_segments.len() != request_segments.len() {
-            return false;
-        }
-        spec_segments
-            .iter()
-            .zip(request_segments.iter())
-            .all(|(spec_segment, request_segment)| spec_segment.matches(request_segment))
-    }
 }
 
-fn split_path(path: &str) -> Vec<&str> {
+pub(crate) fn split_path(path: &str) -> Vec<&str> {
     path.split('/')
         .filter(|component| !component.is_empty())
         .collect::<Vec<&str>>()
 }
 
-fn extract_path_parameters<'api, 'request>(
-    spec_segments: Vec<Segment<'api>>,
-    request_segments: Vec<&'request str>,
-) -> HashMap<&'api str, &'request str> {
-    spec_segments
-        .iter()
-        .zip(request_segments.iter())
-        .filter_map(|(spec_segment, request_segment)| match spec_segment {
-            Segment::Parameter { name } => Some((*name, *request_segment)),
-            Segment::Fixed { .. } => None,
-        })
-        .collect()
+/// Splits a path keeping its empty segments, so `/pets/` ends with one and `//pets` starts
+/// with one. The root path has no segments.
+pub(crate) fn split_path_strictly(path: &str) -> Vec<&str> {
+    match path.strip_prefix('/').unwrap_or(path) {
+        "" => vec![],
+        path => path.split('/').collect(),
+    }
 }
 
 pub trait Request {
+    /// The absolute request URL, or an origin-form one such as `/pets?limit=10` as servers
+    /// receive it, in which case the origin is not compared with the `servers`.
     fn url(&self) -> &str;
 
     fn operation(&self) -> &str;
 
+    /// The request method, parsed from [`Request::operation`] unless overridden by
+    /// implementations that already hold a typed method.
+    fn method(&self) -> Option<Method> {
+        self.operation().parse().ok()
+    }
+
     fn body(&self) -> &[u8];
 
+    /// The body as a stream, for implementations that would rather not hold it in memory. It
+    /// is read at most once per validation, and only as far as validation needs, and only when
+    /// [`Request::body`] is empty: a body held in memory is parsed in place. Defaults to
+    /// reading [`Request::body`].
+    fn body_stream(&self) -> Box<dyn std::io::Read + '_> {
+        Box::new(self.body())
+    }
+
     fn get_header(&self, key: &str) -> Option<String>;
+
+    /// Non-empty segments of the URL path, for implementations that have already split it.
+    /// When both this and [`Request::query_pairs`] are provided the URL is never parsed.
+    fn path_segments(&self) -> Option<Vec<&str>> {
+        None
+    }
+
+    /// Percent-decoded query pairs, for implementations that have already parsed them.
+    fn query_pairs(&self) -> Option<Vec<(&str, &str)>> {
+        None
+    }
 }
 
 #[cfg(test)]
@@ -163,25 +731,37 @@ pub mod test_helpers {
         )
         .to_string()
             + path_spec;
-        Validator::new(serde_yaml::from_str(&openapi).unwrap())
+        Validator::from_yaml_str(&openapi).unwrap()
     }
 
-    pub fn make_validator() -> Result<Validator, ()> {
-        let spec = match std::fs::read_to_string("./specs/openapi.yaml") {
-            Ok(spec) => spec,
-            Err(..) => return Err(()),
-        };
-        let api = match serde_yaml::from_str(&spec) {
-            Ok(api) => api,
-            Err(..) => return Err(()),
+    pub fn make_validator() -> Validator {
+        Validator::from_yaml_file("./specs/openapi.yaml").unwrap()
+    }
+}
+
+#[cfg(test)]
+mod test_segments {
+    use super::Segment;
+    use parameterized::parameterized;
+
+    #[parameterized(segment = {
+        "{id}", "{}", "id", "{id", "id}", "{a}{b}", "v{id}",
+    }, parameter = {
+        Some("id"), Some(""), None, None, None, None, None,
+    })]
+    fn parse_a_templated_segment(segment: &str, parameter: Option<&str>) {
+        let parsed = match Segment::list_from_str(segment).as_slice() {
+            [Segment::Parameter { name }] => Some(*name),
+            _ => None,
         };
-        Ok(Validator::new(api))
+        assert_eq!(parameter, parsed);
     }
 }
 
 #[cfg(test)]
 mod test_url {
     use super::test_helpers::*;
+    use crate::error::ValidationError;
     use indoc::indoc;
     use std::collections::HashMap;
 
@@ -204,7 +784,7 @@ mod test_url {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::PathNotFound),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -228,7 +808,7 @@ mod test_url {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::InvalidUrl),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
@@ -236,13 +816,15 @@ mod test_url {
 
 #[cfg(test)]
 mod test_paths {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use indoc::indoc;
+    use parameterized::parameterized;
     use std::collections::HashMap;
 
     #[test]
     fn accept_a_request_with_valid_path() {
-        let validator = make_validator().unwrap();
+        let validator = make_validator();
         let request = FakeRequest {
             url: "http://test.com/ping".to_string(),
             operation: "get".to_string(),
@@ -272,8 +854,516 @@ mod test_paths {
             headers: HashMap::new(),
         };
         assert_eq!(
-            Err(()),
+            Err(ValidationError::PathNotFound),
             make_validator_from_spec(path_spec).validate_request(&request)
         );
     }
+
+    const OVERLAPPING_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            get:
+              operationId: getPet
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+          /{kind}/mine:
+            get:
+              operationId: getMine
+              parameters:
+                - in: path
+                  name: kind
+                  required: true
+                  schema:
+                    type: string
+              responses:
+                200:
+                  description: API call successful
+          /pets/mine:
+            get:
+              operationId: getMyPets
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    #[parameterized(url = {
+        "http://test.com/pets/mine",
+        "http://test.com/pets/7",
+        "http://test.com/owners/mine",
+    }, operation_id = {
+        "getMyPets", "getPet", "getMine",
+    })]
+    fn prefer_concrete_segments_over_templated_ones(url: &str, operation_id: &str) {
+        let request = FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Some(operation_id),
+            make_validator_from_spec(OVERLAPPING_SPEC)
+                .match_operation(&request)
+                .unwrap()
+                .operation_id()
+        );
+    }
+
+    #[parameterized(url = {
+        "http://test.com/pets/7", "http://test.com/pets/7/", "http://test.com//pets/7",
+    }, strict = {
+        Ok(()), Err(ValidationError::PathNotFound), Err(ValidationError::PathNotFound),
+    })]
+    fn distinguish_slashes_in_strict_mode(url: &str, strict: Result<(), ValidationError>) {
+        let request = FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(OVERLAPPING_SPEC);
+        assert!(validator.match_operation(&request).is_ok());
+        assert_eq!(
+            strict,
+            validator
+                .with_options(crate::ValidatorOptions {
+                    strict_path_slashes: true,
+                    ..Default::default()
+                })
+                .match_operation(&request)
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn reject_a_request_routed_to_a_path_item_reference() {
+        let validator = make_validator_from_spec(indoc!(
+            r#"
+            paths:
+              /pets:
+                $ref: 'pets.yaml#/paths/~1pets'
+            "#
+        ));
+        let request = FakeRequest {
+            url: "http://test.com/pets".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::UnresolvedReference {
+                reference: "pets.yaml#/paths/~1pets".to_string()
+            }),
+            validator.match_operation(&request).map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_spec_serialization {
+    use super::test_helpers::*;
+
+    #[test]
+    fn spec_yaml_round_trips_to_the_same_spec() {
+        let validator = make_validator();
+        let yaml = validator.spec_yaml().unwrap();
+        assert_eq!(
+            validator.spec(),
+            &serde_yaml::from_str::<openapiv3::OpenAPI>(&yaml).unwrap()
+        );
+    }
+
+    #[test]
+    fn spec_json_round_trips_to_the_same_spec() {
+        let validator = make_validator();
+        let json = validator.spec_json().unwrap();
+        assert_eq!(
+            validator.spec(),
+            &serde_json::from_str::<openapiv3::OpenAPI>(&json).unwrap()
+        );
+    }
+
+    #[test]
+    fn reserialized_spec_builds_an_equal_validator() {
+        let validator = make_validator();
+        let yaml = validator.spec_yaml().unwrap();
+        assert_eq!(
+            validator,
+            super::Validator::new(serde_yaml::from_str(&yaml).unwrap())
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_component_schemas {
+    use super::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths: {}
+        components:
+          schemas:
+            Page:
+              type: object
+              properties:
+                size:
+                  type: integer
+                  default: 20
+                owner:
+                  $ref: '#/components/schemas/Owner'
+            Owner:
+              type: string
+        "#
+    );
+
+    #[test]
+    fn convert_a_component_by_name() {
+        assert_eq!(
+            Some(json!({
+                "type": "object",
+                "properties": {
+                    "size": {"type": "integer", "default": 20},
+                    "owner": {"$ref": "#/$defs/Owner"}
+                },
+                "$defs": {"Owner": {"type": "string"}}
+            })),
+            make_validator_from_spec(PATH_SPEC).component_schema("Page")
+        );
+    }
+
+    #[test]
+    fn unknown_component() {
+        assert_eq!(
+            None,
+            make_validator_from_spec(PATH_SPEC).component_schema("Missing")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_exchanges {
+    use super::test_helpers::*;
+    use crate::error::ValidationError;
+    use crate::report::ValidationReport;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            get:
+              operationId: listPets
+              parameters:
+                - in: query
+                  name: limit
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: array
+        "#
+    );
+
+    fn make_request(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn make_response(status_code: u16, body: &str) -> FakeResponse {
+        FakeResponse {
+            status_code,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn validate_both_halves_of_an_exchange() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let report = validator
+            .validate_exchange(
+                &make_request("http://test.com/pets?limit=2"),
+                &make_response(200, "[]"),
+            )
+            .unwrap();
+
+        assert_eq!(Some("listPets"), report.operation.operation_id());
+        assert_eq!(Ok(ValidationReport::default()), report.request);
+        assert_eq!(Ok(()), report.response);
+        assert!(report.is_valid());
+    }
+
+    #[test]
+    fn validate_from_many_threads_with_a_shared_validator() {
+        let validator = make_validator_from_spec(PATH_SPEC).shared();
+        let threads = (0..4).map(|limit| {
+            let validator = validator.clone();
+            std::thread::spawn(move || {
+                let validated = validator
+                    .validate_request(&make_request(&format!(
+                        "http://test.com/pets?limit={limit}"
+                    )))
+                    .unwrap();
+                validated.validate_response(&make_response(200, "[]"))?;
+                validated.validate_response(&make_response(200, "{}"))
+            })
+        });
+
+        for thread in threads.collect::<Vec<_>>() {
+            assert_eq!(
+                Err(ValidationError::InvalidResponseBody),
+                thread.join().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn validate_the_response_of_an_invalid_request() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let report = validator
+            .validate_exchange(
+                &make_request("http://test.com/pets?limit=many"),
+                &make_response(404, ""),
+            )
+            .unwrap();
+
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "limit".to_string()
+            }),
+            report.request
+        );
+        assert!(matches!(
+            report.response,
+            Err(ValidationError::UndocumentedStatusCode {
+                status_code: 404,
+                ..
+            })
+        ));
+        assert!(!report.is_valid());
+    }
+
+    #[test]
+    fn reject_an_exchange_that_routes_to_no_operation() {
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            make_validator_from_spec(PATH_SPEC)
+                .validate_exchange(
+                    &make_request("http://test.com/owners"),
+                    &make_response(200, "[]")
+                )
+                .map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_response_validators {
+    use super::test_helpers::*;
+    use crate::error::ValidationError;
+    use crate::method::Method;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [name]
+        "#
+    );
+
+    fn make_response(body: &str) -> FakeResponse {
+        FakeResponse {
+            status_code: 200,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    #[test]
+    fn validate_a_response_without_a_request() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let response_validator = || validator.response_validator("/pets/{id}", "get").unwrap();
+
+        assert!(response_validator()
+            .validate_response(&make_response(r#"{"name": "Rex"}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            response_validator().validate_response(&make_response("{}"))
+        );
+    }
+
+    #[test]
+    fn reject_an_unknown_operation() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            validator.response_validator("/pets/1", "get").map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::MethodNotAllowed {
+                allowed: vec![Method::Get]
+            }),
+            validator
+                .response_validator("/pets/{id}", "post")
+                .map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::MethodNotAllowed {
+                allowed: vec![Method::Get]
+            }),
+            validator
+                .response_validator("/pets/{id}", "fetch")
+                .map(|_| ())
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_servers {
+    use super::test_helpers::*;
+    use super::Validator;
+    use crate::error::ValidationError;
+    use crate::options::ValidatorOptions;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const SPEC: &str = indoc!(
+        r#"
+        openapi: 3.0.0
+        info:
+          title: Pets
+          version: 1.0.0
+        servers:
+          - url: https://{region}.api.test.com/{version}
+            variables:
+              region:
+                default: eu
+                enum: [eu, us]
+              version:
+                default: v1
+          - url: /internal
+        paths:
+          /pets:
+            get:
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn validator(options: ValidatorOptions) -> Validator {
+        Validator::from_yaml_str(SPEC)
+            .unwrap()
+            .with_options(options)
+    }
+
+    #[parameterized(url = {
+        "https://eu.api.test.com/v1/pets",
+        "https://us.api.test.com/v2/pets",
+        "http://localhost:8080/v1/pets",
+        "http://localhost:8080/internal/pets",
+    })]
+    fn strip_the_base_path_of_a_server(url: &str) {
+        assert!(validator(ValidatorOptions::default())
+            .validate_request(&make_request(url))
+            .is_ok());
+    }
+
+    #[parameterized(url = {"https://eu.api.test.com/pets", "https://eu.api.test.com/v1/v1/pets"})]
+    fn reject_a_path_outside_every_server(url: &str) {
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            validator(ValidatorOptions::default())
+                .validate_request(&make_request(url))
+                .map(|_| ())
+        );
+    }
+
+    #[parameterized(url = {
+        "https://eu.api.test.com/v1/pets",
+        "http://localhost:8080/internal/pets",
+    })]
+    fn accept_the_origin_of_a_server_when_configured(url: &str) {
+        let validator = validator(ValidatorOptions {
+            enforce_server_origin: true,
+            ..Default::default()
+        });
+        assert!(validator.validate_request(&make_request(url)).is_ok());
+    }
+
+    #[parameterized(url = {
+        "http://eu.api.test.com/v1/pets",
+        "https://ap.api.test.com/v1/pets",
+        "http://localhost:8080/v1/pets",
+    })]
+    fn reject_another_origin_when_configured(url: &str) {
+        let validator = validator(ValidatorOptions {
+            enforce_server_origin: true,
+            ..Default::default()
+        });
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            validator.validate_request(&make_request(url)).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn route_the_whole_path_when_ignoring_servers() {
+        let validator = validator(ValidatorOptions {
+            ignore_servers: true,
+            ..Default::default()
+        });
+        assert!(validator
+            .validate_request(&make_request("https://eu.api.test.com/pets"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            validator
+                .validate_request(&make_request("https://eu.api.test.com/v1/pets"))
+                .map(|_| ())
+        );
+    }
 }
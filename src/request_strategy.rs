@@ -0,0 +1,796 @@
+use std::sync::Arc;
+
+use openapiv3::{ReferenceOr, Schema, SchemaKind, Type};
+use proptest::collection::vec;
+use proptest::prelude::*;
+use proptest::sample::select;
+use proptest::strategy::Union;
+use serde_json::{json, Value};
+
+use crate::content_type::is_json;
+use crate::error::ValidationError;
+use crate::item_or_fetch::ItemOrFetch;
+use crate::method::Method;
+use crate::mock::{fit_length, format_example, to_text};
+use crate::request::{Request, Validator};
+
+/// Nesting of schemas past which `null` is generated, so recursive schemas stay finite.
+const MAX_DEPTH: usize = 6;
+
+/// How far generated numbers stray from the bound they are given, when a schema only has one
+/// or none.
+const SPREAD: i64 = 1000;
+
+const ALPHANUMERIC: &str = "abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+
+/// Values standing in for a parameter or body of the wrong type.
+const WRONG_VALUES: [&str; 3] = [r#"{"unexpected":true}"#, "[true]", "true"];
+
+/// The requests a [`request_strategy`] generates.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Inputs {
+    /// Requests the validator accepts.
+    #[default]
+    Valid,
+    /// Requests the validator accepts, with numbers, lengths and item counts at their bounds
+    /// wherever the schema declares them.
+    Boundary,
+    /// Requests the validator rejects because of one parameter or the body: a required
+    /// parameter left out, a value of the wrong type, or a malformed body.
+    Invalid,
+}
+
+/// A request generated for an operation, with an origin-form URL such as `/pets/7?limit=10`
+/// that includes the base path of the first server.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GeneratedRequest {
+    /// The method as sent, e.g. `GET`.
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Request for GeneratedRequest {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn operation(&self) -> &str {
+        &self.method
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        (self.headers.iter())
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, value)| value.clone())
+    }
+}
+
+#[cfg(feature = "http")]
+impl TryFrom<GeneratedRequest> for http::Request<Vec<u8>> {
+    type Error = http::Error;
+
+    fn try_from(request: GeneratedRequest) -> Result<Self, Self::Error> {
+        let mut builder = http::Request::builder()
+            .method(request.method.as_str())
+            .uri(request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name, value);
+        }
+        builder.body(request.body)
+    }
+}
+
+/// Generates requests for the operation at the `path` template with `method`, for fuzzing a
+/// handler with inputs this crate holds to the specification. Every request is validated as
+/// it is generated, so [`Inputs::Valid`] and [`Inputs::Boundary`] requests always pass and
+/// [`Inputs::Invalid`] ones always fail.
+///
+/// Values are generated from the parameter and body schemas, honouring enums, lengths,
+/// bounds, `multipleOf`, patterns and common formats, and leaving out `readOnly` properties.
+/// Parameters are sent as JSON text, or bare strings when `coerce_parameter_strings` is set;
+/// as path segments are read as JSON, string path parameters need that option. Cookie
+/// parameters, parameters described by `content` and bodies other than JSON are not generated,
+/// and requests carry no credentials.
+pub fn request_strategy(
+    validator: &Arc<Validator>,
+    path: &str,
+    method: &str,
+    inputs: Inputs,
+) -> Result<BoxedStrategy<GeneratedRequest>, ValidationError> {
+    let spec = validator.spec();
+    let (path, path_spec) = (spec.paths.paths)
+        .get_key_value(path)
+        .and_then(|(path, path_spec)| Some((path, path_spec.as_item()?)))
+        .ok_or(ValidationError::PathNotFound)?;
+    let not_allowed = || ValidationError::MethodNotAllowed {
+        allowed: Method::allowed(path_spec),
+    };
+    let method = method.parse::<Method>().map_err(|_| not_allowed())?;
+    let operation_spec = method.operation(path_spec).ok_or_else(not_allowed)?;
+
+    let values = ValueStrategies {
+        components: &spec.components,
+        boundary: inputs == Inputs::Boundary,
+    };
+    let mut parameters: Vec<&openapiv3::Parameter> = vec![];
+    // operation parameters override those of the path item with the same name and location
+    for parameter in (path_spec.parameters.iter()).chain(operation_spec.parameters.iter()) {
+        let Ok(parameter) = parameter.item_or_fetch(&spec.components) else {
+            continue;
+        };
+        parameters.retain(|other| !same_parameter(other, parameter));
+        parameters.push(parameter);
+    }
+    let mut template = Template {
+        method: method.as_str().to_uppercase(),
+        path: format!("{}{path}", base_path(validator)),
+        parameters: vec![],
+        content_type: None,
+        coerce: validator.options().coerce_parameter_strings,
+    };
+    let mut strategies = vec![];
+    let mut mutations = vec![];
+    for parameter in parameters {
+        let data = parameter.parameter_data_ref();
+        let location = match parameter {
+            openapiv3::Parameter::Path { .. } => Location::Path,
+            openapiv3::Parameter::Query { .. } => Location::Query,
+            openapiv3::Parameter::Header { .. } => Location::Header,
+            openapiv3::Parameter::Cookie { .. } => continue,
+        };
+        let openapiv3::ParameterSchemaOrContent::Schema(schema) = &data.format else {
+            continue;
+        };
+        let value = values.schema(schema, 0);
+        let required = data.required || location == Location::Path;
+        if required && location != Location::Path {
+            mutations.push(Mutation::Omit(strategies.len()));
+        }
+        mutations.push(Mutation::Replace(strategies.len()));
+        strategies.push(match required {
+            true => value.prop_map(Some).boxed(),
+            false => proptest::option::of(value).boxed(),
+        });
+        template.parameters.push((data.name.clone(), location));
+    }
+
+    let request_body = (operation_spec.request_body.as_ref())
+        .map(|request_body| request_body.item_or_fetch(&spec.components))
+        .transpose()?;
+    let body = match request_body {
+        Some(request_body) => {
+            let json = (request_body.content.iter())
+                .find(|(content_type, _)| content_type.parse().is_ok_and(|mime| is_json(&mime)));
+            match (json, request_body.content.first()) {
+                (Some((content_type, media_type)), _) => {
+                    template.content_type = Some(content_type.clone());
+                    let value = match &media_type.schema {
+                        Some(schema) => values.schema(schema, 0),
+                        None => Just(json!({})).boxed(),
+                    };
+                    mutations.extend([Mutation::ReplaceBody, Mutation::MalformedBody]);
+                    if request_body.required {
+                        mutations.push(Mutation::OmitBody);
+                        value.prop_map(Some).boxed()
+                    } else {
+                        proptest::option::of(value).boxed()
+                    }
+                }
+                (None, Some((content_type, _))) if request_body.required => {
+                    return Err(ValidationError::UnsupportedMediaTypeHandler {
+                        content_type: content_type.clone(),
+                    });
+                }
+                (None, _) => Just(None).boxed(),
+            }
+        }
+        None => Just(None).boxed(),
+    };
+    if mutations.is_empty() {
+        mutations.push(Mutation::UnknownPath);
+    }
+
+    let validator = validator.clone();
+    let strategy = match inputs {
+        Inputs::Valid | Inputs::Boundary => (strategies, body)
+            .prop_map(move |(values, body)| template.render(values, body, None))
+            .prop_filter("the validator rejects the request", move |request| {
+                is_sendable(request) && validator.validate_request(request).is_ok()
+            })
+            .boxed(),
+        Inputs::Invalid => (
+            strategies,
+            body,
+            select(mutations),
+            select(&WRONG_VALUES[..]),
+        )
+            .prop_map(move |(values, body, mutation, wrong)| {
+                template.render(values, body, Some((mutation, wrong)))
+            })
+            .prop_filter("the validator accepts the request", move |request| {
+                is_sendable(request) && validator.validate_request(request).is_err()
+            })
+            .boxed(),
+    };
+    Ok(strategy)
+}
+
+fn same_parameter(parameter: &openapiv3::Parameter, other: &openapiv3::Parameter) -> bool {
+    std::mem::discriminant(parameter) == std::mem::discriminant(other)
+        && parameter.parameter_data_ref().name == other.parameter_data_ref().name
+}
+
+/// The path of the first server's URL with its variables at their defaults, or nothing when
+/// servers are ignored or none are declared.
+fn base_path(validator: &Validator) -> String {
+    let server = validator.spec().servers.first();
+    let Some(server) = server.filter(|_| !validator.options().ignore_servers) else {
+        return String::new();
+    };
+    let mut url = server.url.clone();
+    for (name, variable) in server.variables.iter().flatten() {
+        url = url.replace(&format!("{{{name}}}"), &variable.default);
+    }
+    let origin = url::Url::parse("http://localhost").expect("a valid URL");
+    (origin.join(&url).ok())
+        .map(|url| url.path().trim_end_matches('/').to_string())
+        .unwrap_or_default()
+}
+
+/// Whether every header value can be sent, as visible ASCII.
+fn is_sendable(request: &GeneratedRequest) -> bool {
+    (request.headers.iter())
+        .all(|(_, value)| value.bytes().all(|byte| (b' '..=b'~').contains(&byte)))
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Location {
+    Path,
+    Query,
+    Header,
+}
+
+/// One way of breaking a valid request, by the index of the parameter it breaks.
+#[derive(Debug, Clone, Copy)]
+enum Mutation {
+    Omit(usize),
+    Replace(usize),
+    OmitBody,
+    ReplaceBody,
+    MalformedBody,
+    /// For operations without parameters or a body to break.
+    UnknownPath,
+}
+
+/// What a request is rendered from, owned so strategies can outlive the specification.
+struct Template {
+    method: String,
+    /// The path template, after the base path.
+    path: String,
+    parameters: Vec<(String, Location)>,
+    /// The JSON media type bodies are sent as.
+    content_type: Option<String>,
+    coerce: bool,
+}
+
+impl Template {
+    fn render(
+        &self,
+        mut values: Vec<Option<Value>>,
+        body: Option<Value>,
+        mutation: Option<(Mutation, &str)>,
+    ) -> GeneratedRequest {
+        let mut path = self.path.clone();
+        let mut body = body.map(|body| body.to_string().into_bytes());
+        match mutation {
+            Some((Mutation::Omit(index), _)) => values[index] = None,
+            Some((Mutation::Replace(index), wrong)) => {
+                values[index] = serde_json::from_str(wrong).ok();
+            }
+            Some((Mutation::OmitBody, _)) => body = None,
+            Some((Mutation::ReplaceBody, wrong)) => body = Some(wrong.as_bytes().to_vec()),
+            Some((Mutation::MalformedBody, _)) => body = Some(b"{".to_vec()),
+            Some((Mutation::UnknownPath, _)) => path.push_str("/unexpected"),
+            None => {}
+        }
+
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        let mut headers = vec![];
+        for ((name, location), value) in self.parameters.iter().zip(values) {
+            let Some(value) = value else {
+                continue;
+            };
+            let text = match self.coerce {
+                true => to_text(value),
+                false => value.to_string(),
+            };
+            match location {
+                Location::Path => {
+                    let segment: String = url::form_urlencoded::byte_serialize(text.as_bytes())
+                        .collect::<String>()
+                        .replace('+', "%20");
+                    path = path.replace(&format!("{{{name}}}"), &segment);
+                }
+                Location::Query => {
+                    query.append_pair(name, &text);
+                }
+                Location::Header => headers.push((name.clone(), text)),
+            }
+        }
+        let query = query.finish();
+        if !query.is_empty() {
+            path = format!("{path}?{query}");
+        }
+        if let (Some(content_type), Some(_)) = (&self.content_type, &body) {
+            headers.push(("Content-Type".to_string(), content_type.clone()));
+        }
+        GeneratedRequest {
+            method: self.method.clone(),
+            url: path,
+            headers,
+            body: body.unwrap_or_default(),
+        }
+    }
+}
+
+/// Builds value strategies from schemas, borrowing the specification only while building.
+struct ValueStrategies<'api> {
+    components: &'api Option<openapiv3::Components>,
+    boundary: bool,
+}
+
+impl ValueStrategies<'_> {
+    /// Unresolvable references generate `null`.
+    fn schema(&self, schema: &impl ItemOrFetch<Schema>, depth: usize) -> BoxedStrategy<Value> {
+        match schema.item_or_fetch(self.components) {
+            Ok(schema) if depth < MAX_DEPTH => self.value(schema, depth + 1),
+            _ => Just(Value::Null).boxed(),
+        }
+    }
+
+    fn value(&self, schema: &Schema, depth: usize) -> BoxedStrategy<Value> {
+        let value = match &schema.schema_kind {
+            SchemaKind::Type(Type::String(string)) => self.string(string),
+            SchemaKind::Type(Type::Integer(integer)) => {
+                let values: Vec<i64> = integer.enumeration.iter().flatten().copied().collect();
+                if !values.is_empty() {
+                    return select(values).prop_map(Value::from).boxed();
+                }
+                let minimum = (integer.minimum)
+                    .map(|minimum| minimum.saturating_add(i64::from(integer.exclusive_minimum)));
+                let maximum = (integer.maximum)
+                    .map(|maximum| maximum.saturating_sub(i64::from(integer.exclusive_maximum)));
+                let step = integer.multiple_of.filter(|step| *step > 0);
+                self.integer(minimum, maximum, step)
+                    .prop_map(Value::from)
+                    .boxed()
+            }
+            SchemaKind::Type(Type::Number(number)) => {
+                let values: Vec<f64> = number.enumeration.iter().flatten().copied().collect();
+                if !values.is_empty() {
+                    return select(values).prop_map(|value| json!(value)).boxed();
+                }
+                let minimum = (number.minimum).filter(|_| !number.exclusive_minimum);
+                let maximum = (number.maximum).filter(|_| !number.exclusive_maximum);
+                self.number(
+                    (number.minimum, minimum),
+                    (number.maximum, maximum),
+                    number.multiple_of.filter(|step| *step > 0.0),
+                )
+            }
+            SchemaKind::Type(Type::Boolean {}) => proptest::bool::ANY.prop_map(Value::Bool).boxed(),
+            SchemaKind::Type(Type::Object(object)) => {
+                self.object(&object.properties, &object.required, depth)
+            }
+            SchemaKind::Type(Type::Array(array)) => self.array(
+                array.items.as_ref(),
+                array.min_items,
+                array.max_items,
+                depth,
+            ),
+            SchemaKind::OneOf { one_of: schemas } | SchemaKind::AnyOf { any_of: schemas } => {
+                self.one_of(schemas, depth)
+            }
+            SchemaKind::AllOf { all_of } => self.all_of(all_of, depth),
+            SchemaKind::Not { .. } => Just(Value::Null).boxed(),
+            SchemaKind::Any(any) => self.any(any, depth),
+        };
+        match schema.schema_data.nullable {
+            true => prop_oneof![1 => Just(Value::Null), 4 => value].boxed(),
+            false => value,
+        }
+    }
+
+    /// Strings from the enum, else matching the pattern, else the format's example, else
+    /// alphanumeric strings of a fitting length.
+    fn string(&self, string: &openapiv3::StringType) -> BoxedStrategy<Value> {
+        let values: Vec<String> = string.enumeration.iter().flatten().cloned().collect();
+        if !values.is_empty() {
+            return select(values).prop_map(Value::String).boxed();
+        }
+        let pattern = (string.pattern.as_deref())
+            .map(|pattern| pattern.trim_start_matches('^').trim_end_matches('$'))
+            .and_then(|pattern| proptest::string::string_regex(pattern).ok());
+        if let Some(pattern) = pattern {
+            return pattern.prop_map(Value::String).boxed();
+        }
+        let example = format_example(&string.format);
+        if example != "string" {
+            let example = fit_length(example, string.min_length, string.max_length);
+            return Just(Value::String(example)).boxed();
+        }
+        self.alphanumeric(string.min_length, string.max_length)
+    }
+
+    fn alphanumeric(&self, min: Option<usize>, max: Option<usize>) -> BoxedStrategy<Value> {
+        let characters = select(ALPHANUMERIC.chars().collect::<Vec<_>>());
+        self.length(min, max, 16)
+            .prop_flat_map(move |length| vec(characters.clone(), length))
+            .prop_map(|characters| Value::String(characters.into_iter().collect()))
+            .boxed()
+    }
+
+    /// Lengths within the bounds, up to `spread` more than the minimum without a maximum, or
+    /// only the bounds declared when generating boundaries.
+    fn length(
+        &self,
+        min: Option<usize>,
+        max: Option<usize>,
+        spread: usize,
+    ) -> BoxedStrategy<usize> {
+        let low = min.unwrap_or(0);
+        let high = max.unwrap_or(low + spread).max(low);
+        match self.boundary {
+            true if min.is_some() || max.is_some() => {
+                select(min.into_iter().chain(max).collect::<Vec<_>>()).boxed()
+            }
+            _ => (low..=high).boxed(),
+        }
+    }
+
+    /// Multiples of `step` within the inclusive bounds, defaulting to [`SPREAD`] either side.
+    fn integer(
+        &self,
+        minimum: Option<i64>,
+        maximum: Option<i64>,
+        step: Option<i64>,
+    ) -> BoxedStrategy<i64> {
+        let (low, high) = match (minimum, maximum) {
+            (Some(low), Some(high)) => (low, high),
+            (Some(low), None) => (low, low.saturating_add(SPREAD)),
+            (None, Some(high)) => (high.saturating_sub(SPREAD), high),
+            (None, None) => (-SPREAD, SPREAD),
+        };
+        if low > high {
+            return Just(low).boxed();
+        }
+        // rounds up to a multiple of the step, or down when that leaves the bounds
+        let snap = move |value: i64| match step {
+            Some(step) if value.rem_euclid(step) != 0 => {
+                let up = value.saturating_add(step - value.rem_euclid(step));
+                match up > high {
+                    true => value - value.rem_euclid(step),
+                    false => up,
+                }
+            }
+            _ => value,
+        };
+        match self.boundary {
+            true if minimum.is_some() || maximum.is_some() => {
+                let bounds = minimum
+                    .into_iter()
+                    .chain(maximum)
+                    .map(snap)
+                    .collect::<Vec<_>>();
+                select(bounds).boxed()
+            }
+            _ => (low..=high).prop_map(snap).boxed(),
+        }
+    }
+
+    /// Numbers within the bounds, each given as it is declared and as it is inclusive. With a
+    /// `multipleOf` the number is a multiple of it; boundaries are the inclusive bounds.
+    fn number(
+        &self,
+        (minimum, inclusive_minimum): (Option<f64>, Option<f64>),
+        (maximum, inclusive_maximum): (Option<f64>, Option<f64>),
+        step: Option<f64>,
+    ) -> BoxedStrategy<Value> {
+        let spread = SPREAD as f64;
+        let (low, high) = match (minimum, maximum) {
+            (Some(low), Some(high)) => (low, high),
+            (Some(low), None) => (low, low + spread),
+            (None, Some(high)) => (high - spread, high),
+            (None, None) => (-spread, spread),
+        };
+        if self.boundary && (inclusive_minimum.is_some() || inclusive_maximum.is_some()) {
+            let bounds: Vec<f64> = inclusive_minimum
+                .into_iter()
+                .chain(inclusive_maximum)
+                .collect();
+            return select(bounds).prop_map(|value| json!(value)).boxed();
+        }
+        if low > high {
+            return Just(json!(low)).boxed();
+        }
+        match step {
+            Some(step) => {
+                let (low, high) = ((low / step).ceil() as i64, (high / step).floor() as i64);
+                let multiple = move |multiple: i64| json!(multiple as f64 * step);
+                match low > high {
+                    true => Just(multiple(low)).boxed(),
+                    false => (low..=high).prop_map(multiple).boxed(),
+                }
+            }
+            None => (low..=high).prop_map(|value| json!(value)).boxed(),
+        }
+    }
+
+    /// Required properties always, optional ones sometimes, and `readOnly` ones never.
+    fn object(
+        &self,
+        properties: &indexmap::IndexMap<String, ReferenceOr<Box<Schema>>>,
+        required: &[String],
+        depth: usize,
+    ) -> BoxedStrategy<Value> {
+        let mut names = vec![];
+        let mut values = vec![];
+        for (name, property) in properties {
+            let read_only = (property.item_or_fetch(self.components))
+                .is_ok_and(|property| property.schema_data.read_only);
+            if read_only {
+                continue;
+            }
+            let value = self.schema(property, depth);
+            names.push(name.clone());
+            values.push(match required.contains(name) {
+                true => value.prop_map(Some).boxed(),
+                false => proptest::option::of(value).boxed(),
+            });
+        }
+        values
+            .prop_map(move |values| {
+                let properties = (names.iter().cloned())
+                    .zip(values)
+                    .filter_map(|(name, value)| Some((name, value?)));
+                Value::Object(properties.collect())
+            })
+            .boxed()
+    }
+
+    fn array(
+        &self,
+        items: Option<&ReferenceOr<Box<Schema>>>,
+        min_items: Option<usize>,
+        max_items: Option<usize>,
+        depth: usize,
+    ) -> BoxedStrategy<Value> {
+        let item = match items {
+            Some(items) => self.schema(items, depth),
+            None => Just(Value::Null).boxed(),
+        };
+        self.length(min_items, max_items, 3)
+            .prop_flat_map(move |length| vec(item.clone(), length))
+            .prop_map(Value::Array)
+            .boxed()
+    }
+
+    fn one_of(&self, schemas: &[ReferenceOr<Schema>], depth: usize) -> BoxedStrategy<Value> {
+        match schemas.is_empty() {
+            true => Just(Value::Null).boxed(),
+            false => {
+                let schemas = schemas.iter().map(|schema| self.schema(schema, depth));
+                Union::new(schemas).boxed()
+            }
+        }
+    }
+
+    /// Merges the objects generated for each schema; a schema that is not an object wins.
+    fn all_of(&self, schemas: &[ReferenceOr<Schema>], depth: usize) -> BoxedStrategy<Value> {
+        (schemas.iter().map(|schema| self.schema(schema, depth)))
+            .collect::<Vec<_>>()
+            .prop_map(|values| {
+                let mut merged = serde_json::Map::new();
+                for value in values {
+                    match value {
+                        Value::Object(object) => merged.extend(object),
+                        Value::Null => {}
+                        value => return value,
+                    }
+                }
+                Value::Object(merged)
+            })
+            .boxed()
+    }
+
+    /// Schemas without a `type`, read from whichever keywords they use.
+    fn any(&self, any: &openapiv3::AnySchema, depth: usize) -> BoxedStrategy<Value> {
+        if !any.enumeration.is_empty() {
+            return select(any.enumeration.clone()).boxed();
+        }
+        if !any.all_of.is_empty() {
+            return self.all_of(&any.all_of, depth);
+        }
+        if !any.one_of.is_empty() || !any.any_of.is_empty() {
+            return self.one_of(&[&any.one_of[..], &any.any_of[..]].concat(), depth);
+        }
+        match any.typ.as_deref() {
+            Some("object") => self.object(&any.properties, &any.required, depth),
+            None if !any.properties.is_empty() => {
+                self.object(&any.properties, &any.required, depth)
+            }
+            Some("array") => self.array(any.items.as_ref(), any.min_items, any.max_items, depth),
+            None if any.items.is_some() => {
+                self.array(any.items.as_ref(), any.min_items, any.max_items, depth)
+            }
+            Some("string") => self.alphanumeric(any.min_length, any.max_length),
+            Some("integer") => {
+                let minimum = any.minimum.map(|minimum| minimum.ceil() as i64);
+                let maximum = any.maximum.map(|maximum| maximum.floor() as i64);
+                (self.integer(minimum, maximum, None))
+                    .prop_map(Value::from)
+                    .boxed()
+            }
+            Some("number") => {
+                self.number((any.minimum, any.minimum), (any.maximum, any.maximum), None)
+            }
+            Some("boolean") => proptest::bool::ANY.prop_map(Value::Bool).boxed(),
+            _ => Just(Value::Null).boxed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_request_strategy {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use proptest::strategy::ValueTree;
+    use proptest::test_runner::TestRunner;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            parameters:
+              - in: path
+                name: id
+                required: true
+                schema:
+                  type: integer
+                  minimum: 1
+                  maximum: 100
+            post:
+              parameters:
+                - in: query
+                  name: limit
+                  required: true
+                  schema:
+                    type: integer
+                    minimum: 5
+                    maximum: 50
+                    multipleOf: 5
+                - in: header
+                  name: X-Trace
+                  schema:
+                    type: string
+                    maxLength: 8
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+                      properties:
+                        id:
+                          type: integer
+                          readOnly: true
+                        name:
+                          type: string
+                          minLength: 1
+                          maxLength: 10
+                        owners:
+                          type: array
+                          maxItems: 2
+                          items:
+                            type: string
+                            pattern: "^[a-z]{3}$"
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn validator() -> Arc<Validator> {
+        Arc::new(make_validator_from_spec(PATH_SPEC))
+    }
+
+    fn sample(validator: &Arc<Validator>, inputs: Inputs) -> Vec<GeneratedRequest> {
+        let strategy = request_strategy(validator, "/pets/{id}", "post", inputs).unwrap();
+        let mut runner = TestRunner::deterministic();
+        (0..50)
+            .map(|_| strategy.new_tree(&mut runner).unwrap().current())
+            .collect()
+    }
+
+    fn query(request: &GeneratedRequest, name: &str) -> Option<String> {
+        let url = url::Url::parse("http://localhost")
+            .unwrap()
+            .join(&request.url);
+        (url.unwrap().query_pairs()).find_map(|(key, value)| (key == name).then(|| value.into()))
+    }
+
+    #[test]
+    fn generate_requests_the_validator_accepts() {
+        let validator = validator();
+        let requests = sample(&validator, Inputs::Valid);
+        for request in &requests {
+            assert!(validator.validate_request(request).is_ok(), "{request:?}");
+            let body: Value = serde_json::from_slice(&request.body).unwrap();
+            assert_eq!(None, body.get("id"));
+        }
+        let traced = |request: &GeneratedRequest| request.get_header("x-trace").is_some();
+        assert!(requests.iter().any(traced));
+        assert!(!requests.iter().all(traced));
+    }
+
+    #[test]
+    fn generate_values_at_the_bounds() {
+        let validator = validator();
+        for request in sample(&validator, Inputs::Boundary) {
+            let limit = query(&request, "limit");
+            assert!(
+                ["5", "50"].contains(&limit.as_deref().unwrap()),
+                "{request:?}"
+            );
+            let id = request.url.trim_start_matches("/pets/").split('?').next();
+            assert!(["1", "100"].contains(&id.unwrap()), "{request:?}");
+        }
+    }
+
+    #[test]
+    fn generate_requests_the_validator_rejects() {
+        let validator = validator();
+        let errors: Vec<ValidationError> = sample(&validator, Inputs::Invalid)
+            .iter()
+            .map(|request| validator.validate_request(request).unwrap_err())
+            .collect();
+        let rejected = |expected: fn(&ValidationError) -> bool| errors.iter().any(expected);
+        assert!(rejected(|error| matches!(
+            error,
+            ValidationError::MissingParameter { .. }
+        )));
+        assert!(rejected(|error| matches!(
+            error,
+            ValidationError::InvalidParameter { .. }
+        )));
+        assert!(rejected(|error| matches!(
+            error,
+            ValidationError::InvalidBody
+        )));
+        assert!(rejected(|error| matches!(
+            error,
+            ValidationError::MissingBody
+        )));
+    }
+
+    #[test]
+    fn refuse_an_undocumented_operation() {
+        let validator = validator();
+        assert_eq!(
+            Some(ValidationError::PathNotFound),
+            request_strategy(&validator, "/owners", "get", Inputs::Valid).err()
+        );
+        assert!(matches!(
+            request_strategy(&validator, "/pets/{id}", "get", Inputs::Valid),
+            Err(ValidationError::MethodNotAllowed { .. })
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+use std::fmt;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::error::ValidationError;
+use crate::request::{Request, Validator};
+use crate::response::Response;
+
+/// Validates outgoing `reqwest` requests. Only bodies held in memory are read; a streamed body
+/// is validated as if it were empty.
+impl Request for reqwest::Request {
+    fn url(&self) -> &str {
+        reqwest::Request::url(self).as_str()
+    }
+
+    fn operation(&self) -> &str {
+        reqwest::Request::method(self).as_str()
+    }
+
+    fn body(&self) -> &[u8] {
+        (reqwest::Request::body(self))
+            .and_then(reqwest::Body::as_bytes)
+            .unwrap_or(&[])
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        let value = self.headers().get(key)?;
+        value.to_str().ok().map(str::to_string)
+    }
+}
+
+/// A `reqwest` response with its body read into memory, so that it can be validated.
+#[derive(Debug, Clone)]
+pub struct BufferedResponse {
+    pub status: reqwest::StatusCode,
+    pub headers: reqwest::header::HeaderMap,
+    pub body: Vec<u8>,
+    /// Time from sending the request until the body was read, when measured.
+    pub elapsed: Option<Duration>,
+}
+
+impl BufferedResponse {
+    /// Reads the whole body of `response`.
+    pub async fn read(response: reqwest::Response) -> reqwest::Result<Self> {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?.into();
+        Ok(Self {
+            status,
+            headers,
+            body,
+            elapsed: None,
+        })
+    }
+
+    /// The body deserialized from JSON.
+    pub fn json<T: serde::de::DeserializeOwned>(&self) -> serde_json::Result<T> {
+        serde_json::from_slice(&self.body)
+    }
+}
+
+impl Response for BufferedResponse {
+    fn status_code(&self) -> u16 {
+        self.status.as_u16()
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        let value = self.headers.get(key)?;
+        value.to_str().ok().map(str::to_string)
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    fn elapsed(&self) -> Option<Duration> {
+        self.elapsed
+    }
+}
+
+/// Why a [`ValidatedClient`] did not return a response.
+#[derive(Debug)]
+pub enum ClientError {
+    /// The request does not conform to the specification and was not sent.
+    InvalidRequest(ValidationError),
+    /// The server answered with a response that does not conform to the specification.
+    InvalidResponse {
+        response: BufferedResponse,
+        error: ValidationError,
+    },
+    /// Sending the request or reading the response failed.
+    Http(reqwest::Error),
+}
+
+impl fmt::Display for ClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRequest(error) => write!(f, "invalid request: {error}"),
+            Self::InvalidResponse { error, .. } => write!(f, "invalid response: {error}"),
+            Self::Http(error) => write!(f, "request failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidRequest(error) | Self::InvalidResponse { error, .. } => Some(error),
+            Self::Http(error) => Some(error),
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::Http(error)
+    }
+}
+
+/// A `reqwest` client that validates every request against the specification before sending
+/// it, and the response it gets back, e.g. to check a server in contract tests.
+#[derive(Debug, Clone)]
+pub struct ValidatedClient {
+    client: reqwest::Client,
+    validator: Arc<Validator>,
+}
+
+impl ValidatedClient {
+    pub fn new(client: reqwest::Client, validator: Arc<Validator>) -> Self {
+        Self { client, validator }
+    }
+
+    /// The underlying client, for building requests to pass to [`ValidatedClient::execute`].
+    pub fn client(&self) -> &reqwest::Client {
+        &self.client
+    }
+
+    pub async fn execute(
+        &self,
+        request: reqwest::Request,
+    ) -> Result<BufferedResponse, ClientError> {
+        let validated = (self.validator)
+            .validate_request(&request)
+            .map_err(ClientError::InvalidRequest)?;
+        let sent = Instant::now();
+        let response = self.client.execute(request).await?;
+        let mut response = BufferedResponse::read(response).await?;
+        response.elapsed = Some(sent.elapsed());
+        match validated.validate_response(&response) {
+            Ok(()) => Ok(response),
+            Err(error) => Err(ClientError::InvalidResponse { response, error }),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use std::cell::OnceCell;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::Arc;
+use url::Url;
+
+use crate::error::ValidationError;
+use crate::request::{split_path, Request, Validator};
+use crate::validated_request::ValidatedRequest;
+
+/// Chooses the validator a request is checked against, so a single layer can enforce a
+/// different contract per host, path prefix or tenant. Requests for which no validator is
+/// resolved are passed through unchecked.
+pub trait ValidatorResolver: Send + Sync {
+    fn resolve(&self, request: &dyn Request) -> Option<&Validator>;
+}
+
+/// A single validator serves every request.
+impl ValidatorResolver for Validator {
+    fn resolve(&self, _: &dyn Request) -> Option<&Validator> {
+        Some(self)
+    }
+}
+
+impl<R: ValidatorResolver + ?Sized> ValidatorResolver for Arc<R> {
+    fn resolve(&self, request: &dyn Request) -> Option<&Validator> {
+        self.as_ref().resolve(request)
+    }
+}
+
+type KeyFn = dyn Fn(&dyn Request) -> Option<String> + Send + Sync;
+
+/// Validators selected by a key taken from the request, e.g. a tenant id header, the host or
+/// the first path segment. Requests without a key, or with an unknown one, use the fallback
+/// validator when there is one.
+pub struct KeyedValidators {
+    key: Box<KeyFn>,
+    validators: HashMap<String, Validator>,
+    fallback: Option<Validator>,
+}
+
+impl KeyedValidators {
+    pub fn new(key: impl Fn(&dyn Request) -> Option<String> + Send + Sync + 'static) -> Self {
+        Self {
+            key: Box::new(key),
+            validators: HashMap::new(),
+            fallback: None,
+        }
+    }
+
+    /// Keys requests by the value of a header, such as `Host` or `X-Tenant-Id`.
+    pub fn by_header(name: &str) -> Self {
+        let name = name.to_string();
+        Self::new(move |request| request.get_header(&name))
+    }
+
+    pub fn with_validator(mut self, key: &str, validator: Validator) -> Self {
+        self.validators.insert(key.to_string(), validator);
+        self
+    }
+
+    pub fn with_fallback(mut self, validator: Validator) -> Self {
+        self.fallback = Some(validator);
+        self
+    }
+}
+
+impl ValidatorResolver for KeyedValidators {
+    fn resolve(&self, request: &dyn Request) -> Option<&Validator> {
+        (self.key)(request)
+            .and_then(|key| self.validators.get(&key))
+            .or(self.fallback.as_ref())
+    }
+}
+
+impl fmt::Debug for KeyedValidators {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedValidators")
+            .field("keys", &self.validators.keys().collect::<Vec<_>>())
+            .field("fallback", &self.fallback.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Several APIs served by one process, each dispatched to by the `servers` of its document:
+/// a request goes to the validator with a server matching its origin and the longest base
+/// path, preferring one whose paths it routes to. Validators whose documents declare no
+/// servers match every request with an empty base path. Each is also kept under a key for
+/// callers that know which API a request is for.
+#[derive(Debug, Default)]
+pub struct ValidatorSet {
+    validators: Vec<(String, Validator)>,
+}
+
+impl ValidatorSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a validator, replacing the one already kept under `key`.
+    pub fn with_validator(mut self, key: &str, validator: Validator) -> Self {
+        match self
+            .validators
+            .iter_mut()
+            .find(|(existing, _)| existing == key)
+        {
+            Some((_, existing)) => *existing = validator,
+            None => self.validators.push((key.to_string(), validator)),
+        }
+        self
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Validator> {
+        self.validators
+            .iter()
+            .find_map(|(existing, validator)| (existing == key).then_some(validator))
+    }
+
+    /// The key of the validator a request is dispatched to.
+    pub fn key_for(&self, request: &dyn Request) -> Option<&str> {
+        self.dispatch(request, &OnceCell::new()).map(|(key, _)| key)
+    }
+
+    /// Validates a request against the validator it is dispatched to, failing with
+    /// [`ValidationError::PathNotFound`] when there is none.
+    pub fn validate_request<'set>(
+        &'set self,
+        request: &dyn Request,
+    ) -> Result<ValidatedRequest<'set>, ValidationError> {
+        let url = OnceCell::new();
+        let (_, validator) = self
+            .dispatch(request, &url)
+            .ok_or(ValidationError::PathNotFound)?;
+        validator.validate_request_with_url(request, &url)
+    }
+
+    /// The URL is parsed into `url` once, and reused by every validator tried.
+    fn dispatch(&self, request: &dyn Request, url: &OnceCell<Url>) -> Option<(&str, &Validator)> {
+        if let Ok(parsed) = Url::parse(request.url()) {
+            let _ = url.set(parsed);
+        }
+        let origin = url.get().map(|url| url.origin().ascii_serialization());
+        let path_segments = request
+            .path_segments()
+            .or_else(|| url.get().map(|url| split_path(url.path())))
+            .unwrap_or_default();
+
+        let mut candidates = self
+            .validators
+            .iter()
+            .filter_map(|(key, validator)| {
+                let base_path_len = validator.base_path_len(origin.as_deref(), &path_segments)?;
+                Some((base_path_len, key.as_str(), validator))
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by_key(|(base_path_len, ..)| Reverse(*base_path_len));
+        candidates
+            .iter()
+            .find(|(_, _, validator)| {
+                !matches!(
+                    validator.match_operation_with_url(request, url),
+                    Err(ValidationError::PathNotFound)
+                )
+            })
+            .or(candidates.first())
+            .map(|(_, key, validator)| (*key, *validator))
+    }
+}
+
+impl ValidatorResolver for ValidatorSet {
+    fn resolve(&self, request: &dyn Request) -> Option<&Validator> {
+        self.dispatch(request, &OnceCell::new())
+            .map(|(_, validator)| validator)
+    }
+}
+
+#[cfg(test)]
+mod test_resolver {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    fn make_validator_with_path(path: &str) -> Validator {
+        make_validator_from_spec(&format!(
+            indoc!(
+                r#"
+                paths:
+                  {}:
+                    get:
+                      responses:
+                        200:
+                          description: API call successful
+                "#
+            ),
+            path
+        ))
+    }
+
+    fn get(url: &str, headers: &[(&str, &str)]) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect::<HashMap<_, _>>(),
+        }
+    }
+
+    fn make_resolver() -> KeyedValidators {
+        KeyedValidators::by_header("X-Tenant-Id")
+            .with_validator("acme", make_validator_with_path("/rockets"))
+            .with_validator("globex", make_validator_with_path("/domes"))
+    }
+
+    #[test]
+    fn a_single_validator_resolves_every_request() {
+        let validator = make_validator_with_path("/rockets");
+        assert_eq!(
+            Some(&validator),
+            validator.resolve(&get("http://test.com/anything", &[]))
+        );
+    }
+
+    #[test]
+    fn select_the_validator_for_the_key() {
+        let resolver = make_resolver();
+        let request = get("http://test.com/domes", &[("X-Tenant-Id", "globex")]);
+
+        let validator = resolver.resolve(&request).unwrap();
+        assert!(validator.validate_request(&request).is_ok());
+        assert!(validator
+            .validate_request(&get("http://test.com/rockets", &[]))
+            .is_err());
+    }
+
+    #[test]
+    fn unknown_keys_resolve_nothing_without_a_fallback() {
+        let resolver = make_resolver();
+        assert_eq!(
+            None,
+            resolver.resolve(&get("http://test.com/domes", &[("X-Tenant-Id", "initech")]))
+        );
+        assert_eq!(None, resolver.resolve(&get("http://test.com/domes", &[])));
+    }
+
+    #[test]
+    fn unknown_keys_use_the_fallback() {
+        let fallback = make_validator_with_path("/staplers");
+        let resolver = make_resolver().with_fallback(fallback.clone());
+        assert_eq!(
+            Some(&fallback),
+            resolver.resolve(&get("http://test.com/staplers", &[]))
+        );
+    }
+
+    #[test]
+    fn key_by_path_prefix() {
+        let resolver = KeyedValidators::new(|request| {
+            url::Url::parse(request.url())
+                .ok()?
+                .path_segments()?
+                .next()
+                .map(str::to_string)
+        })
+        .with_validator("v1", make_validator_with_path("/v1/rockets"))
+        .with_validator("v2", make_validator_with_path("/v2/rockets"));
+
+        let request = get("http://test.com/v2/rockets", &[]);
+        assert!(resolver
+            .resolve(&request)
+            .unwrap()
+            .validate_request(&request)
+            .is_ok());
+    }
+}
+
+#[cfg(test)]
+mod test_validator_set {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::formatdoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    fn make_validator(server: &str, path: &str) -> Validator {
+        Validator::from_yaml_str(&formatdoc!(
+            r#"
+            openapi: 3.0.0
+            info:
+              title: API
+              version: 1.0.0
+            servers: [{server}]
+            paths:
+              {path}:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        ))
+        .unwrap()
+    }
+
+    fn get(url: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    fn make_set() -> ValidatorSet {
+        ValidatorSet::new()
+            .with_validator("pets", make_validator("url: /api", "/pets"))
+            .with_validator("pets-v2", make_validator("url: /api/v2", "/pets"))
+            .with_validator("toys", make_validator("url: /api", "/toys"))
+            .with_validator(
+                "billing",
+                make_validator("url: https://billing.test.com", "/invoices"),
+            )
+    }
+
+    #[parameterized(url = {
+        "http://test.com/api/pets",
+        "http://test.com/api/v2/pets",
+        "http://test.com/api/toys",
+        "https://billing.test.com/invoices",
+        "http://test.com/api/food",
+        "http://test.com/invoices",
+    }, expected = {
+        Some("pets"), Some("pets-v2"), Some("toys"), Some("billing"), Some("pets"), None,
+    })]
+    fn dispatch_by_server_and_base_path(url: &str, expected: Option<&str>) {
+        assert_eq!(expected, make_set().key_for(&get(url)));
+    }
+
+    #[test]
+    fn validate_against_the_dispatched_validator() {
+        let set = make_set();
+        assert!(set
+            .validate_request(&get("https://billing.test.com/invoices"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::PathNotFound),
+            set.validate_request(&get("http://test.com/invoices"))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn select_a_validator_by_key() {
+        let set = make_set().with_validator("toys", make_validator("url: /shop", "/toys"));
+        assert!(set
+            .get("toys")
+            .unwrap()
+            .validate_request(&get("http://test.com/shop/toys"))
+            .is_ok());
+        assert_eq!(None, set.get("food"));
+    }
+
+    /// Counts the times its URL is read, which is once per parse.
+    struct CountingRequest {
+        request: FakeRequest,
+        url_reads: std::cell::Cell<usize>,
+    }
+
+    impl Request for CountingRequest {
+        fn url(&self) -> &str {
+            self.url_reads.set(self.url_reads.get() + 1);
+            self.request.url()
+        }
+
+        fn operation(&self) -> &str {
+            self.request.operation()
+        }
+
+        fn body(&self) -> &[u8] {
+            self.request.body()
+        }
+
+        fn get_header(&self, key: &str) -> Option<String> {
+            self.request.get_header(key)
+        }
+    }
+
+    #[test]
+    fn parse_the_url_once_across_validators() {
+        let request = CountingRequest {
+            request: get("http://test.com/api/toys"),
+            url_reads: Default::default(),
+        };
+        assert!(make_set().validate_request(&request).is_ok());
+        assert_eq!(1, request.url_reads.get());
+    }
+}
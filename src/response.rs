@@ -1,44 +1,466 @@
+use std::time::Duration;
+
+use crate::body::{BodyStream, BodyValidator};
+use crate::checks::{Check, CheckHooks};
+use crate::content_type::{find_media_type, is_acceptable, is_json};
+use crate::error::ValidationError;
+use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::{spec_pointer, SchemaLocation};
+use crate::options::ValidatorOptions;
+use crate::parameters::validate_value;
+use crate::schema_cache::CompiledSchemas;
+use crate::streaming::{Framing, StreamingResponseValidator};
+use crate::to_jsonschema::Direction;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct ResponseValidator<'api> {
     pub response_spec: &'api openapiv3::Responses,
     pub components: &'api Option<openapiv3::Components>,
+    pub options: &'api ValidatorOptions,
+    pub(crate) schemas: &'api CompiledSchemas,
+    pub latency_budget: Option<Duration>,
+    /// JSON pointer to the operation, for locating its response schemas.
+    pub pointer: String,
+    /// The request's `Accept` header, which the response content type should honour.
+    pub accept: Option<String>,
 }
 
 impl<'api> ResponseValidator<'api> {
-    pub fn validate_response(self, response: &dyn Response) -> Result<(), ()> {
-        self.validate_status_code(response.status_code())
+    /// Runs the enabled checks in order, stopping at the first failure.
+    pub fn validate_response(&self, response: &dyn Response) -> Result<(), ValidationError> {
+        let response_spec = match self.validate_status_code(response.status_code()) {
+            Ok(response_spec) => response_spec,
+            Err(error) if self.is_enabled(Check::StatusCodeConformance) => return Err(error),
+            Err(..) => return Ok(()),
+        };
+
+        for check in self.enabled_checks() {
+            self.run_check(check, response_spec, response)?;
+        }
+
+        Ok(())
+    }
+
+    /// Runs every enabled check, reporting each outcome to `hooks`, and collects the failures.
+    /// Checks needing the documented response are skipped when the status code is undocumented.
+    pub fn run_checks(
+        &self,
+        response: &dyn Response,
+        hooks: &mut dyn CheckHooks,
+    ) -> Vec<(Check, ValidationError)> {
+        hooks.post_response(response);
+        let mut failures = vec![];
+
+        let response_spec = self.validate_status_code(response.status_code());
+        if self.is_enabled(Check::StatusCodeConformance) {
+            let outcome = response_spec.clone().map(|_| ());
+            hooks.on_check(Check::StatusCodeConformance, &outcome);
+            if let Err(error) = outcome {
+                failures.push((Check::StatusCodeConformance, error));
+            }
+        }
+        let Ok(response_spec) = response_spec else {
+            return failures;
+        };
+
+        for check in self.enabled_checks() {
+            let outcome = self.run_check(check, response_spec, response);
+            hooks.on_check(check, &outcome);
+            if let Err(error) = outcome {
+                failures.push((check, error));
+            }
+        }
+
+        failures
+    }
+
+    fn is_enabled(&self, check: Check) -> bool {
+        !self.options.disabled_checks.contains(&check)
+    }
+
+    fn enabled_checks(&self) -> impl Iterator<Item = Check> + '_ {
+        Check::ALL
+            .into_iter()
+            .filter(|check| *check != Check::StatusCodeConformance && self.is_enabled(*check))
     }
 
-    fn validate_status_code(self, status_code: u16) -> Result<(), ()> {
-        dbg!(self.response_spec);
+    fn run_check(
+        &self,
+        check: Check,
+        response_spec: &'api openapiv3::Response,
+        response: &dyn Response,
+    ) -> Result<(), ValidationError> {
+        match check {
+            Check::StatusCodeConformance => Ok(()),
+            Check::ResponseHeadersConformance => self.validate_headers(response_spec, response),
+            Check::ContentTypeConformance => self
+                .validate_content_type(response_spec, response)
+                .map(|_| ()),
+            Check::ResponseSchemaConformance => self.validate_body(response_spec, response),
+            Check::ResponseTimeConformance => self.validate_elapsed(response),
+            Check::ResponseLinksConformance => self.validate_links(response_spec, response),
+        }
+    }
 
+    /// Responses without timing metadata, or operations without a budget, pass.
+    fn validate_elapsed(&self, response: &dyn Response) -> Result<(), ValidationError> {
+        match (response.elapsed(), self.latency_budget) {
+            (Some(elapsed), Some(budget)) if elapsed > budget => {
+                Err(ValidationError::SlowResponse { elapsed, budget })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn validate_status_code(
+        &self,
+        status_code: u16,
+    ) -> Result<&'api openapiv3::Response, ValidationError> {
+        self.documented_response(status_code)
+            .ok_or_else(|| ValidationError::UndocumentedStatusCode {
+                status_code,
+                nearest: self.nearest_documented(status_code),
+            })
+            .and_then(|(_, response_spec)| response_spec.item_or_fetch(self.components))
+    }
+
+    /// The response documented for a status code and the key it is documented under: the exact
+    /// code, then its `1XX`-`5XX` range, then the `default` response.
+    fn documented_response(
+        &self,
+        status_code: u16,
+    ) -> Option<(String, &'api openapiv3::ReferenceOr<openapiv3::Response>)> {
         let responses = &self.response_spec.responses;
+        let range = openapiv3::StatusCode::Range(status_code / 100);
 
         responses
-            .get(&openapiv3::StatusCode::Code(status_code))
-            .or_else(|| responses.get(&Self::extract_range_from_status_code(status_code)))
-            .ok_or(())
-            .map(|_| ())
-    }
-
-    fn extract_range_from_status_code(status_code: u16) -> openapiv3::StatusCode {
-        openapiv3::StatusCode::Range(match status_code {
-            100..=199 => 1,
-            200..=299 => 2,
-            300..=399 => 3,
-            400..=499 => 4,
-            500..=599 => 5,
-            _ => todo!(),
+            .get_key_value(&openapiv3::StatusCode::Code(status_code))
+            .or_else(|| responses.get_key_value(&range))
+            .map(|(key, response_spec)| (key.to_string(), response_spec))
+            .or_else(|| {
+                self.response_spec
+                    .default
+                    .as_ref()
+                    .map(|response_spec| ("default".to_string(), response_spec))
+            })
+    }
+
+    /// Starts validating a `text/event-stream` or `application/x-ndjson` response whose body
+    /// will arrive in chunks, after checking its status code and content type are documented.
+    /// The schema of the media type describes one event or line. Headers are not checked.
+    pub fn streaming(
+        &self,
+        status_code: u16,
+        content_type: &str,
+    ) -> Result<StreamingResponseValidator<'api>, ValidationError> {
+        let response_spec = self.validate_status_code(status_code)?;
+        let undeclared = || ValidationError::UndeclaredResponseContentType {
+            content_type: content_type.to_string(),
+        };
+        let mime = content_type
+            .parse::<mime::Mime>()
+            .map_err(|_| undeclared())?;
+        let (range, media_type) =
+            find_media_type(&response_spec.content, content_type, &mime).ok_or_else(undeclared)?;
+        let framing = Framing::from_mime(&mime).ok_or_else(|| {
+            ValidationError::UnsupportedMediaTypeHandler {
+                content_type: content_type.to_string(),
+            }
+        })?;
+
+        let location = SchemaLocation {
+            options: self.options,
+            schemas: self.schemas,
+            pointer: spec_pointer(
+                &self.response_pointer(status_code),
+                ["content", range, "schema"],
+            ),
+            direction: Direction::Response,
+        };
+        Ok(StreamingResponseValidator {
+            framing,
+            schema: (media_type.schema.as_ref())
+                .map(|schema| location.compile(schema, self.components))
+                .transpose()?,
+            location,
+            buffer: vec![],
+            data: vec![],
+            items: 0,
+        })
+    }
+
+    /// Only checks that the status code is documented, for use without the other checks.
+    pub fn check_status_code(&self, status_code: u16) -> Result<(), ValidationError> {
+        self.validate_status_code(status_code).map(|_| ())
+    }
+
+    /// Up to three documented codes or ranges closest to an undocumented status code.
+    fn nearest_documented(&self, status_code: u16) -> Vec<String> {
+        let mut documented: Vec<(u16, String)> = self
+            .response_spec
+            .responses
+            .keys()
+            .map(|documented| match documented {
+                openapiv3::StatusCode::Code(code) => (code.abs_diff(status_code), code.to_string()),
+                openapiv3::StatusCode::Range(range) => {
+                    let low = range * 100;
+                    let distance = if status_code < low {
+                        low - status_code
+                    } else {
+                        status_code.saturating_sub(low + 99)
+                    };
+                    (distance, format!("{range}XX"))
+                }
+            })
+            .collect();
+        documented.sort_by_key(|(distance, _)| *distance);
+
+        documented
+            .into_iter()
+            .take(3)
+            .map(|(_, documented)| documented)
+            .collect()
+    }
+
+    fn validate_headers(
+        &self,
+        response_spec: &'api openapiv3::Response,
+        response: &dyn Response,
+    ) -> Result<(), ValidationError> {
+        for (name, header) in &response_spec.headers {
+            // Content-Type is described by the content map, not by a header definition
+            if name.eq_ignore_ascii_case("content-type") {
+                continue;
+            }
+
+            let location = || match header {
+                openapiv3::ReferenceOr::Reference { reference } => {
+                    spec_pointer(reference, ["schema"])
+                }
+                openapiv3::ReferenceOr::Item(..) => spec_pointer(
+                    &self.response_pointer(response.status_code()),
+                    ["headers", name, "schema"],
+                ),
+            };
+            let header = header.item_or_fetch(self.components)?;
+
+            match response.get_header(name) {
+                Some(value) => {
+                    validate_value(
+                        &value,
+                        &header.format,
+                        self.components,
+                        self.options,
+                        self.schemas,
+                        location,
+                        None,
+                    )
+                    .map_err(|error| {
+                        error.unwrap_or(ValidationError::InvalidResponseHeader {
+                            name: name.clone(),
+                        })
+                    })?;
+                }
+                None if header.required => {
+                    return Err(ValidationError::MissingResponseHeader { name: name.clone() })
+                }
+                None => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_content_type(
+        &self,
+        response_spec: &'api openapiv3::Response,
+        response: &dyn Response,
+    ) -> Result<Option<(mime::Mime, &'api str, &'api openapiv3::MediaType)>, ValidationError> {
+        // HTTP forbids these a body, whatever the specification documents
+        if forbids_body(response.status_code()) {
+            return match response.body().is_empty() {
+                true => Ok(None),
+                false => Err(ValidationError::UnexpectedResponseBody),
+            };
+        }
+        if response_spec.content.is_empty() {
+            return Ok(None);
+        }
+        let content_type = response
+            .get_header("Content-Type")
+            .ok_or(ValidationError::MissingResponseContentType)?;
+
+        let (mime, range, media_type) = content_type
+            .parse::<mime::Mime>()
+            .ok()
+            .and_then(|mime| {
+                find_media_type(&response_spec.content, &content_type, &mime)
+                    .map(|(range, media_type)| (mime, range, media_type))
+            })
+            .ok_or_else(|| ValidationError::UndeclaredResponseContentType {
+                content_type: content_type.clone(),
+            })?;
+
+        match &self.accept {
+            Some(accept)
+                if self.options.reject_unacceptable_responses
+                    && !is_acceptable(accept, &content_type) =>
+            {
+                Err(ValidationError::NotAcceptable {
+                    accept: accept.clone(),
+                })
+            }
+            _ => Ok(Some((mime, range, media_type))),
+        }
+    }
+
+    /// Only JSON bodies are checked against their schema, other media types pass.
+    fn validate_body(
+        &self,
+        response_spec: &'api openapiv3::Response,
+        response: &dyn Response,
+    ) -> Result<(), ValidationError> {
+        // a missing or undeclared content type is reported by the content type check
+        let Ok(Some((mime, range, media_type))) =
+            self.validate_content_type(response_spec, response)
+        else {
+            return Ok(());
+        };
+        if !is_json(&mime) {
+            return Ok(());
+        }
+
+        let location = SchemaLocation {
+            options: self.options,
+            schemas: self.schemas,
+            pointer: spec_pointer(
+                &self.response_pointer(response.status_code()),
+                ["content", range, "schema"],
+            ),
+            direction: Direction::Response,
+        };
+        BodyValidator::validate_json(
+            media_type,
+            &mut BodyStream::from_slice(response.body()),
+            !self.options.reject_json_bom,
+            &location,
+            self.components,
+            None,
+        )
+        .map_err(|error| match error {
+            Some(error @ ValidationError::UncompilableSchema { .. }) => error,
+            _ => ValidationError::InvalidResponseBody,
         })
     }
+
+    /// Resolves the `$response.body#/...` and `$response.header.*` runtime expressions used by
+    /// the response's links, whether given whole or embedded in braces, e.g.
+    /// `/pets/{$response.body#/id}`. Other expressions and constants are not checked.
+    fn validate_links(
+        &self,
+        response_spec: &'api openapiv3::Response,
+        response: &dyn Response,
+    ) -> Result<(), ValidationError> {
+        if response_spec.links.is_empty() {
+            return Ok(());
+        }
+        let body = serde_json::from_slice::<serde_json::Value>(response.body()).ok();
+
+        for (name, link) in &response_spec.links {
+            let link = link.item_or_fetch(self.components)?;
+            let mut expressions = vec![];
+            for value in link.parameters.values().chain(link.request_body.iter()) {
+                runtime_expressions(value, &mut expressions);
+            }
+
+            for expression in expressions {
+                let resolved = if let Some(pointer) = expression.strip_prefix("$response.body#") {
+                    body.as_ref()
+                        .is_some_and(|body| body.pointer(pointer).is_some())
+                } else if expression == "$response.body" {
+                    body.is_some()
+                } else if let Some(header) = expression.strip_prefix("$response.header.") {
+                    response.get_header(header).is_some()
+                } else {
+                    true
+                };
+                if !resolved {
+                    return Err(ValidationError::BrokenLink {
+                        link: name.clone(),
+                        expression: expression.to_string(),
+                    });
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// JSON pointer to the response documented for a status code: the response component it
+    /// references, or its entry in the operation's responses.
+    fn response_pointer(&self, status_code: u16) -> String {
+        match self.documented_response(status_code) {
+            Some((_, openapiv3::ReferenceOr::Reference { reference })) => reference.clone(),
+            Some((key, openapiv3::ReferenceOr::Item(..))) => {
+                spec_pointer(&self.pointer, ["responses", &key])
+            }
+            None => spec_pointer(&self.pointer, ["responses"]),
+        }
+    }
+}
+
+/// Whether HTTP forbids a response with this status code from carrying a body: informational
+/// responses, 204 No Content and 304 Not Modified.
+pub(crate) fn forbids_body(status_code: u16) -> bool {
+    matches!(status_code, 100..=199 | 204 | 304)
+}
+
+/// Runtime expressions in a link parameter or request body: a string that is one, and every
+/// `{expression}` embedded in other strings, including those nested in objects and arrays.
+fn runtime_expressions<'api>(value: &'api serde_json::Value, expressions: &mut Vec<&'api str>) {
+    match value {
+        serde_json::Value::String(string) if string.starts_with('$') => expressions.push(string),
+        serde_json::Value::String(string) => {
+            let mut rest = string.as_str();
+            while let Some((_, after)) = rest.split_once('{') {
+                let Some((expression, after)) = after.split_once('}') else {
+                    break;
+                };
+                if expression.starts_with('$') {
+                    expressions.push(expression);
+                }
+                rest = after;
+            }
+        }
+        serde_json::Value::Array(items) => items
+            .iter()
+            .for_each(|item| runtime_expressions(item, expressions)),
+        serde_json::Value::Object(fields) => fields
+            .values()
+            .for_each(|field| runtime_expressions(field, expressions)),
+        _ => (),
+    }
 }
 
 pub trait Response {
     fn status_code(&self) -> u16;
+    fn get_header(&self, key: &str) -> Option<String>;
+
+    /// The body held in memory. Empty unless implemented, so responses known only by their
+    /// status code and headers are checked as bodiless.
+    fn body(&self) -> &[u8] {
+        &[]
+    }
+
+    /// Time taken to produce the response, when measured.
+    fn elapsed(&self) -> Option<Duration> {
+        None
+    }
 }
 
 #[cfg(test)]
-mod test_responses {
+pub(crate) mod test_responses {
+    use crate::error::ValidationError;
     use crate::request::test_helpers::*;
     use crate::response::Response;
     use indoc::indoc;
@@ -47,12 +469,22 @@ mod test_responses {
 
     pub struct FakeResponse {
         pub status_code: u16,
+        pub headers: HashMap<String, String>,
+        pub body: Vec<u8>,
     }
 
     impl Response for FakeResponse {
         fn status_code(&self) -> u16 {
             self.status_code
         }
+
+        fn get_header(&self, key: &str) -> Option<String> {
+            self.headers.get(key).cloned()
+        }
+
+        fn body(&self) -> &[u8] {
+            &self.body
+        }
     }
 
     #[test]
@@ -73,7 +505,11 @@ mod test_responses {
             body: vec![],
             headers: HashMap::new(),
         };
-        let response = FakeResponse { status_code: 200 };
+        let response = FakeResponse {
+            status_code: 200,
+            headers: HashMap::new(),
+            body: vec![],
+        };
 
         assert!(make_validator_from_spec(path_spec)
             .validate_request(&request)
@@ -82,6 +518,44 @@ mod test_responses {
             .is_ok());
     }
 
+    /// A response whose body is not available, relying on the default [`Response::body`].
+    struct StatusOnly(u16);
+
+    impl Response for StatusOnly {
+        fn status_code(&self) -> u16 {
+            self.0
+        }
+
+        fn get_header(&self, _: &str) -> Option<String> {
+            None
+        }
+    }
+
+    #[test]
+    fn check_a_response_known_only_by_its_status_code() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /my/path:
+                post:
+                  responses:
+                    204:
+                      description: No content
+            "#
+        );
+        let request = FakeRequest {
+            url: "http:/test.com/my/path".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let validator = make_validator_from_spec(path_spec);
+        let validated = validator.validate_request(&request).unwrap();
+
+        assert!(validated.validate_response(&StatusOnly(204)).is_ok());
+        assert!(validated.validate_response(&StatusOnly(200)).is_err());
+    }
+
     #[test]
     fn reject_a_response_with_invalid_status_code() {
         let path_spec = indoc!(
@@ -100,7 +574,11 @@ mod test_responses {
             body: vec![],
             headers: HashMap::new(),
         };
-        let response = FakeResponse { status_code: 404 };
+        let response = FakeResponse {
+            status_code: 404,
+            headers: HashMap::new(),
+            body: vec![],
+        };
 
         assert!(make_validator_from_spec(path_spec)
             .validate_request(&request)
@@ -132,6 +610,8 @@ mod test_responses {
         };
         let response = FakeResponse {
             status_code: { response_code },
+            headers: HashMap::new(),
+            body: vec![],
         };
 
         assert!(make_validator_from_spec(&path_spec)
@@ -140,4 +620,429 @@ mod test_responses {
             .validate_response(&response)
             .is_ok());
     }
+
+    const DEFAULT_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /my/path:
+            post:
+              responses:
+                200:
+                  description: API call successful
+                4XX:
+                  description: Client error
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [message]
+                default:
+                  description: Unexpected error
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [code]
+        "#
+    );
+
+    fn respond(status_code: u16, body: &str) -> Result<(), ValidationError> {
+        let request = FakeRequest {
+            url: "http://test.com/my/path".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let response = FakeResponse {
+            status_code,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: body.as_bytes().to_vec(),
+        };
+        make_validator_from_spec(DEFAULT_SPEC)
+            .validate_request(&request)
+            .unwrap()
+            .validate_response(&response)
+    }
+
+    #[parameterized(status_code={500, 302, 999})]
+    fn fall_back_to_the_default_response(status_code: u16) {
+        assert!(respond(status_code, r#"{"code": 1}"#).is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            respond(status_code, r#"{"message": "oops"}"#)
+        );
+    }
+
+    #[test]
+    fn prefer_a_documented_code_or_range_over_the_default_response() {
+        assert!(respond(200, "{}").is_ok());
+        assert!(respond(404, r#"{"message": "not found"}"#).is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            respond(404, r#"{"code": 1}"#)
+        );
+    }
+
+    #[test]
+    fn reject_status_codes_outside_every_range_without_a_default_response() {
+        let path_spec = DEFAULT_SPEC.split("      default:").next().unwrap();
+        let request = FakeRequest {
+            url: "http://test.com/my/path".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        assert_eq!(
+            Err(ValidationError::UndocumentedStatusCode {
+                status_code: 999,
+                nearest: vec!["4XX".to_string(), "200".to_string()]
+            }),
+            make_validator_from_spec(path_spec).check_status_code(&request, 999)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_response_headers {
+    use super::test_responses::FakeResponse;
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /my/path:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                  headers:
+                    X-Rate-Limit:
+                      $ref: '#/components/headers/X-Rate-Limit'
+                    X-Request-Id:
+                      required: true
+                      schema:
+                        type: string
+                    Content-Type:
+                      required: true
+                      schema:
+                        type: string
+
+        components:
+          headers:
+            X-Rate-Limit:
+              required: true
+              schema:
+                type: integer
+        "#
+    );
+
+    fn validate(headers: &[(&str, &str)]) -> Result<(), ValidationError> {
+        let request = FakeRequest {
+            url: "http://test.com/my/path".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let response = FakeResponse {
+            status_code: 200,
+            headers: headers
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            body: vec![],
+        };
+        make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .unwrap()
+            .validate_response(&response)
+    }
+
+    #[test]
+    fn accept_a_response_with_valid_headers() {
+        assert!(validate(&[("X-Rate-Limit", "100"), ("X-Request-Id", r#""abc""#)]).is_ok());
+    }
+
+    #[test]
+    fn reject_a_response_missing_a_required_header() {
+        assert_eq!(
+            Err(ValidationError::MissingResponseHeader {
+                name: "X-Request-Id".to_string()
+            }),
+            validate(&[("X-Rate-Limit", "100")])
+        );
+    }
+
+    #[test]
+    fn reject_a_response_with_invalid_referenced_header() {
+        assert_eq!(
+            Err(ValidationError::InvalidResponseHeader {
+                name: "X-Rate-Limit".to_string()
+            }),
+            validate(&[("X-Rate-Limit", "lots"), ("X-Request-Id", r#""abc""#)])
+        );
+    }
+
+    #[test]
+    fn validate_a_header_described_by_content() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /my/path:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+                      headers:
+                        X-Rate-Limit:
+                          content:
+                            application/json:
+                              schema:
+                                type: object
+                                required: [remaining]
+                                properties:
+                                  remaining:
+                                    type: integer
+            "#
+        );
+        let validate = |value: &str| {
+            let request = FakeRequest {
+                url: "http://test.com/my/path".to_string(),
+                operation: "get".to_string(),
+                body: vec![],
+                headers: HashMap::new(),
+            };
+            let response = FakeResponse {
+                status_code: 200,
+                headers: HashMap::from([("X-Rate-Limit".to_string(), value.to_string())]),
+                body: vec![],
+            };
+            make_validator_from_spec(path_spec)
+                .validate_request(&request)
+                .unwrap()
+                .validate_response(&response)
+        };
+        assert_eq!(Ok(()), validate(r#"{"remaining": 10}"#));
+        assert_eq!(
+            Err(ValidationError::InvalidResponseHeader {
+                name: "X-Rate-Limit".to_string()
+            }),
+            validate(r#"{"remaining": "lots"}"#)
+        );
+    }
+
+    #[test]
+    fn reject_a_response_given_an_unresolved_header_reference() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /my/path:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+                      headers:
+                        X-Rate-Limit:
+                          $ref: '#/components/headers/NotThere'
+            "#
+        );
+        let request = FakeRequest {
+            url: "http://test.com/my/path".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let response = FakeResponse {
+            status_code: 200,
+            headers: HashMap::new(),
+            body: vec![],
+        };
+        assert_eq!(
+            Err(ValidationError::UnresolvedReference {
+                reference: "#/components/headers/NotThere".to_string()
+            }),
+            make_validator_from_spec(path_spec)
+                .validate_request(&request)
+                .unwrap()
+                .validate_response(&response)
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_undocumented_status_codes {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things:
+            get:
+              parameters:
+                - in: query
+                  name: page
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+                201:
+                  description: Created
+                4XX:
+                  description: Client error
+                500:
+                  description: Server error
+        "#
+    );
+
+    fn get_things() -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/things".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn report_the_nearest_documented_status_codes() {
+        assert_eq!(
+            Err(ValidationError::UndocumentedStatusCode {
+                status_code: 204,
+                nearest: vec!["201".to_string(), "200".to_string(), "4XX".to_string()]
+            }),
+            make_validator_from_spec(PATH_SPEC).check_status_code(&get_things(), 204)
+        );
+    }
+
+    #[test]
+    fn check_status_codes_for_requests_that_fail_validation() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        assert!(validator.validate_request(&get_things()).is_err());
+
+        assert!(validator.check_status_code(&get_things(), 404).is_ok());
+    }
+
+    #[test]
+    fn describe_the_nearest_documented_status_codes() {
+        assert_eq!(
+            "status code 204 is not documented, nearest documented: 201, 200, 4XX",
+            make_validator_from_spec(PATH_SPEC)
+                .check_status_code(&get_things(), 204)
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_response_links {
+    use super::test_responses::FakeResponse;
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              responses:
+                201:
+                  description: Pet created
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                  links:
+                    GetPet:
+                      operationId: getPet
+                      parameters:
+                        id: $response.body#/id
+                        version: $response.header.ETag
+                    GetOwner:
+                      $ref: '#/components/links/GetOwner'
+        components:
+          links:
+            GetOwner:
+              operationId: getOwner
+              parameters:
+                url: /owners/{$response.body#/owner/id}
+                source: $request.path.id
+              requestBody:
+                note: adopted by {$response.body#/owner/name}
+        "#
+    );
+
+    fn validate(headers: &[(&str, &str)], body: &str) -> Result<(), ValidationError> {
+        let request = FakeRequest {
+            url: "http://test.com/pets".to_string(),
+            operation: "post".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let mut headers: HashMap<String, String> = headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        let response = FakeResponse {
+            status_code: 201,
+            headers,
+            body: body.as_bytes().to_vec(),
+        };
+        make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .unwrap()
+            .validate_response(&response)
+    }
+
+    #[test]
+    fn accept_a_response_holding_every_linked_value() {
+        assert!(validate(
+            &[("ETag", "3")],
+            r#"{"id": 1, "owner": {"id": 2, "name": "Sam"}}"#
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn reject_a_response_missing_a_linked_body_field() {
+        assert_eq!(
+            Err(ValidationError::BrokenLink {
+                link: "GetPet".to_string(),
+                expression: "$response.body#/id".to_string(),
+            }),
+            validate(&[("ETag", "3")], r#"{"owner": {"id": 2, "name": "Sam"}}"#)
+        );
+    }
+
+    #[test]
+    fn reject_a_response_missing_a_linked_header() {
+        assert_eq!(
+            Err(ValidationError::BrokenLink {
+                link: "GetPet".to_string(),
+                expression: "$response.header.ETag".to_string(),
+            }),
+            validate(&[], r#"{"id": 1, "owner": {"id": 2, "name": "Sam"}}"#)
+        );
+    }
+
+    #[test]
+    fn resolve_expressions_embedded_in_referenced_links() {
+        assert_eq!(
+            Err(ValidationError::BrokenLink {
+                link: "GetOwner".to_string(),
+                expression: "$response.body#/owner/name".to_string(),
+            }),
+            validate(&[("ETag", "3")], r#"{"id": 1, "owner": {"id": 2}}"#)
+        );
+    }
 }
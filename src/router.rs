@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use crate::request::Segment;
+
+/// The specification's path templates arranged by segment, built once when the validator is
+/// created so a request is routed by walking its own segments rather than matching against
+/// every template.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub(crate) struct Router {
+    root: Node,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+struct Node {
+    fixed: HashMap<String, Node>,
+    parameter: Option<Box<Node>>,
+    /// The template ending at this node.
+    route: Option<Route>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+struct Route {
+    /// Position of the template in the specification's `paths`.
+    index: usize,
+    /// Names of the template's variables, in the order they appear.
+    names: Vec<String>,
+}
+
+impl Router {
+    pub(crate) fn new(paths: &openapiv3::Paths) -> Self {
+        let mut root = Node::default();
+        for (index, path) in paths.paths.keys().enumerate() {
+            let mut node = &mut root;
+            let mut names = vec![];
+            let mut segments = Segment::list_from_str(path);
+            if path.len() > 1 && path.ends_with('/') {
+                segments.push(Segment::Fixed { literal: "" });
+            }
+            for segment in segments {
+                node = match segment {
+                    Segment::Fixed { literal } => {
+                        node.fixed.entry(literal.to_string()).or_default()
+                    }
+                    Segment::Parameter { name } => {
+                        names.push(name.to_string());
+                        node.parameter.get_or_insert_with(Default::default)
+                    }
+                };
+            }
+            // templates differing only in variable names are ambiguous, the first one wins
+            node.route.get_or_insert(Route { index, names });
+        }
+        Self { root }
+    }
+
+    /// The position in `paths` of the template matching the request path, with the value of
+    /// each of its variables. Concrete segments take precedence over templated ones, the
+    /// leftmost deciding.
+    ///
+    /// With `strict_slashes` a trailing slash is an empty last segment, only matched by a
+    /// template ending in `/`, and no variable matches an empty segment. Otherwise a template
+    /// matches with or without its trailing slash.
+    pub(crate) fn route<'router, 'request>(
+        &'router self,
+        request_segments: &[&'request str],
+        strict_slashes: bool,
+    ) -> Option<(usize, HashMap<&'router str, &'request str>)> {
+        let mut values = vec![];
+        let route = self
+            .root
+            .find(request_segments, strict_slashes, &mut values)?;
+        let parameters = route.names.iter().map(String::as_str).zip(values).collect();
+        Some((route.index, parameters))
+    }
+}
+
+impl Node {
+    fn find<'request>(
+        &self,
+        request_segments: &[&'request str],
+        strict_slashes: bool,
+        values: &mut Vec<&'request str>,
+    ) -> Option<&Route> {
+        let Some((segment, rest)) = request_segments.split_first() else {
+            if strict_slashes {
+                return self.route.as_ref();
+            }
+            // the earlier of the templates with and without a trailing slash
+            let with_slash = self.fixed.get("").and_then(|node| node.route.as_ref());
+            return [self.route.as_ref(), with_slash]
+                .into_iter()
+                .flatten()
+                .min_by_key(|route| route.index);
+        };
+        if let Some(route) = self
+            .fixed
+            .get(*segment)
+            .and_then(|node| node.find(rest, strict_slashes, values))
+        {
+            return Some(route);
+        }
+        let node = self.parameter.as_ref().filter(|_| !segment.is_empty())?;
+        values.push(segment);
+        let route = node.find(rest, strict_slashes, values);
+        if route.is_none() {
+            values.pop();
+        }
+        route
+    }
+}
+
+#[cfg(test)]
+mod test_router {
+    use super::*;
+    use parameterized::parameterized;
+
+    fn router(paths: &[&str]) -> Router {
+        let paths = paths
+            .iter()
+            .map(|path| format!("{path}: {{}}"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        Router::new(&serde_yaml::from_str(&paths).unwrap())
+    }
+
+    #[parameterized(request = {
+        "/pets/mine/toys", "/pets/7/toys", "/pets/mine/food", "/pets/7", "/",
+    }, expected = {
+        Some(2), Some(1), Some(0), Some(3), Some(4),
+    })]
+    fn route_to_the_most_concrete_template(request: &str, expected: Option<usize>) {
+        let router = router(&[
+            "/pets/{id}/food",
+            "/pets/{id}/toys",
+            "/pets/mine/toys",
+            "/pets/{name}",
+            "/",
+        ]);
+        let segments = crate::request::split_path(request);
+        assert_eq!(
+            expected,
+            router.route(&segments, false).map(|(index, _)| index)
+        );
+    }
+
+    #[test]
+    fn backtrack_out_of_a_concrete_segment() {
+        let router = router(&["/pets/mine", "/pets/{id}/toys/{toy}"]);
+        assert_eq!(
+            Some((1, HashMap::from([("id", "mine"), ("toy", "ball")]))),
+            router.route(&["pets", "mine", "toys", "ball"], false)
+        );
+        assert_eq!(None, router.route(&["pets", "mine", "toys"], false));
+    }
+
+    #[parameterized(request = {
+        &["pets"], &["pets", ""], &["", "pets"], &["pets", "", "toys"],
+    }, expected = {
+        Some(0), Some(1), None, None,
+    })]
+    fn distinguish_trailing_slashes_when_strict(request: &[&str], expected: Option<usize>) {
+        let router = router(&["/pets", "/pets/", "/{id}/pets", "/pets/{id}/toys"]);
+        assert_eq!(
+            expected,
+            router.route(request, true).map(|(index, _)| index)
+        );
+    }
+
+    #[test]
+    fn ignore_trailing_slashes_otherwise() {
+        let router = router(&["/pets/", "/toys/", "/toys"]);
+        assert_eq!(
+            Some(0),
+            router.route(&["pets"], false).map(|(index, _)| index)
+        );
+        assert_eq!(
+            Some(1),
+            router.route(&["toys"], false).map(|(index, _)| index)
+        );
+    }
+}
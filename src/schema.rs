@@ -0,0 +1,66 @@
+use crate::to_jsonschema::convert_document;
+
+/// Converts an OpenAPI schema into the JSON Schema document the validator checks values
+/// against. Component schemas it refers to, directly or through other components, are
+/// converted into `$defs` and referenced from there. `nullable`, `default`, `readOnly` and
+/// `writeOnly` carry over, as do `title`, `description`, `deprecated` and `example`, the last
+/// as a one-item `examples`.
+pub fn to_json_schema(
+    schema: &openapiv3::Schema,
+    components: &openapiv3::Components,
+) -> serde_json::Value {
+    convert_document(schema, Some(components), None)
+}
+
+#[cfg(test)]
+mod test_schema {
+    use super::*;
+    use indoc::indoc;
+    use serde_json::json;
+
+    #[test]
+    fn convert_a_schema_with_its_references() {
+        let components: openapiv3::Components = serde_yaml::from_str(indoc!(
+            r#"
+            schemas:
+              Name:
+                type: string
+                nullable: true
+            "#
+        ))
+        .unwrap();
+        let schema: openapiv3::Schema = serde_yaml::from_str(indoc!(
+            r#"
+            type: object
+            properties:
+              name:
+                $ref: '#/components/schemas/Name'
+              id:
+                type: integer
+                readOnly: true
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(
+            json!({
+                "type": "object",
+                "properties": {
+                    "name": {"$ref": "#/$defs/Name"},
+                    "id": {"type": "integer", "readOnly": true}
+                },
+                "$defs": {"Name": {"type": ["string", "null"]}}
+            }),
+            to_json_schema(&schema, &components)
+        );
+    }
+
+    #[test]
+    fn convert_a_schema_without_components() {
+        let schema: openapiv3::Schema = serde_yaml::from_str("type: boolean").unwrap();
+        assert_eq!(
+            json!({"type": "boolean"}),
+            to_json_schema(&schema, &Default::default())
+        );
+    }
+}
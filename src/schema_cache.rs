@@ -0,0 +1,546 @@
+use indexmap::IndexMap;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+
+use crate::error::ValidationError;
+use crate::jsonschema::{spec_pointer, CompiledSchema};
+use crate::method::Method;
+use crate::options::{ReadOnlyProperties, ValidatorOptions};
+use crate::to_jsonschema::{schema_document, Direction};
+
+/// How a validator keeps the schemas it compiles.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub enum CacheStrategy {
+    /// Compile every schema in the document before the first validation and keep them all.
+    #[default]
+    Precompile,
+    /// Compile schemas the first time they are used, keeping at most `capacity` of them and
+    /// dropping the least recently used first. Suits very large documents of which only a part
+    /// is exercised.
+    Lru { capacity: usize },
+}
+
+/// Every schema of parameters, headers and request and response content in a document,
+/// converted and compiled once, by the pointer used to locate errors in it. A pointer always
+/// names the same schema, read the same way: request bodies are only ever read as requests and
+/// responses as responses.
+#[derive(Default)]
+pub(crate) struct CompiledSchemas {
+    schemas: HashMap<String, Arc<CompiledSchema>>,
+    /// Schemas compiled on first use, when they are not compiled ahead.
+    recent: Option<Mutex<RecentSchemas>>,
+}
+
+impl CompiledSchemas {
+    /// Compiles nothing ahead, keeping the `capacity` schemas most recently used instead.
+    pub(crate) fn bounded(capacity: usize) -> Self {
+        Self {
+            schemas: HashMap::new(),
+            recent: Some(Mutex::new(RecentSchemas {
+                capacity,
+                ..Default::default()
+            })),
+        }
+    }
+
+    pub(crate) fn new(
+        api: &openapiv3::OpenAPI,
+        webhooks: &IndexMap<String, openapiv3::ReferenceOr<openapiv3::PathItem>>,
+        options: &ValidatorOptions,
+    ) -> Self {
+        let mut compiler = Compiler::collect(api, webhooks, options);
+        let pending = std::mem::take(&mut compiler.pending);
+        Self::precompiled(
+            pending
+                .into_iter()
+                .filter_map(|schema| compiler.compile(schema))
+                .collect(),
+        )
+    }
+
+    /// Like [`CompiledSchemas::new`], but converts and compiles the schemas across the rayon
+    /// thread pool.
+    #[cfg(feature = "parallel")]
+    pub(crate) fn new_parallel(
+        api: &openapiv3::OpenAPI,
+        webhooks: &IndexMap<String, openapiv3::ReferenceOr<openapiv3::PathItem>>,
+        options: &ValidatorOptions,
+    ) -> Self {
+        use rayon::prelude::*;
+
+        let mut compiler = Compiler::collect(api, webhooks, options);
+        let pending = std::mem::take(&mut compiler.pending);
+        Self::precompiled(
+            pending
+                .into_par_iter()
+                .filter_map(|schema| compiler.compile(schema))
+                .collect(),
+        )
+    }
+
+    fn precompiled(schemas: HashMap<String, Arc<CompiledSchema>>) -> Self {
+        Self {
+            schemas,
+            recent: None,
+        }
+    }
+
+    /// The compiled schema at `pointer`, or `schema` compiled now when it was not compiled
+    /// ahead, e.g. because its reference did not resolve when the document was loaded.
+    pub(crate) fn schema(
+        &self,
+        pointer: &str,
+        schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+        components: &Option<openapiv3::Components>,
+        direction: Option<Direction>,
+        options: &ValidatorOptions,
+    ) -> Result<Arc<CompiledSchema>, ValidationError> {
+        if let Some(compiled) = self.schemas.get(pointer) {
+            return Ok(compiled.clone());
+        }
+        // the lock is not held while compiling, so other schemas can be fetched meanwhile
+        if let Some(compiled) = self.recent().and_then(|mut recent| recent.get(pointer)) {
+            return Ok(compiled);
+        }
+        let compiled = Arc::new(CompiledSchema::new(
+            schema_document(schema, components, direction)?,
+            options,
+        ));
+        if let Some(mut recent) = self.recent() {
+            recent.insert(pointer, compiled.clone());
+        }
+        Ok(compiled)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.schemas.len() + self.recent().map_or(0, |recent| recent.schemas.len())
+    }
+
+    fn recent(&self) -> Option<MutexGuard<'_, RecentSchemas>> {
+        (self.recent.as_ref()).map(|recent| recent.lock().unwrap_or_else(PoisonError::into_inner))
+    }
+}
+
+/// Schemas compiled on first use, at most `capacity` of them, each with the tick it was last
+/// used at. The least recently used one is found by a scan when making room, which only
+/// happens when a schema is compiled anyway.
+#[derive(Default, Clone)]
+struct RecentSchemas {
+    capacity: usize,
+    tick: u64,
+    schemas: HashMap<String, (Arc<CompiledSchema>, u64)>,
+}
+
+impl RecentSchemas {
+    fn get(&mut self, pointer: &str) -> Option<Arc<CompiledSchema>> {
+        self.tick += 1;
+        let (compiled, used) = self.schemas.get_mut(pointer)?;
+        *used = self.tick;
+        Some(compiled.clone())
+    }
+
+    fn insert(&mut self, pointer: &str, compiled: Arc<CompiledSchema>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.schemas.len() >= self.capacity && !self.schemas.contains_key(pointer) {
+            let oldest = (self.schemas.iter())
+                .min_by_key(|(_, (_, used))| *used)
+                .map(|(pointer, _)| pointer.clone());
+            if let Some(oldest) = oldest {
+                self.schemas.remove(&oldest);
+            }
+        }
+        self.tick += 1;
+        self.schemas
+            .insert(pointer.to_string(), (compiled, self.tick));
+    }
+}
+
+impl Clone for CompiledSchemas {
+    fn clone(&self) -> Self {
+        Self {
+            schemas: self.schemas.clone(),
+            recent: self.recent().map(|recent| Mutex::new(recent.clone())),
+        }
+    }
+}
+
+impl fmt::Debug for CompiledSchemas {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledSchemas")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl PartialEq for CompiledSchemas {
+    fn eq(&self, other: &Self) -> bool {
+        self.schemas.len() == other.schemas.len()
+            && (self.schemas.keys()).all(|pointer| other.schemas.contains_key(pointer))
+            && self.recent.is_some() == other.recent.is_some()
+    }
+}
+
+/// A schema found in the document, to be converted and compiled.
+struct PendingSchema<'api> {
+    pointer: String,
+    schema: &'api openapiv3::ReferenceOr<openapiv3::Schema>,
+    direction: Option<Direction>,
+}
+
+/// Collects every schema of a document first, so that they can be compiled in any order.
+struct Compiler<'api> {
+    components: &'api Option<openapiv3::Components>,
+    options: &'api ValidatorOptions,
+    pending: Vec<PendingSchema<'api>>,
+}
+
+impl<'api> Compiler<'api> {
+    fn collect(
+        api: &'api openapiv3::OpenAPI,
+        webhooks: &'api IndexMap<String, openapiv3::ReferenceOr<openapiv3::PathItem>>,
+        options: &'api ValidatorOptions,
+    ) -> Self {
+        let mut compiler = Compiler {
+            components: &api.components,
+            options,
+            pending: Vec::new(),
+        };
+        for (section, path_items) in [("#/paths", &api.paths.paths), ("#/webhooks", webhooks)] {
+            for (path, path_item) in path_items {
+                let Some(path_item) = path_item.as_item() else {
+                    continue;
+                };
+                let pointer = spec_pointer(section, [path.as_str()]);
+                compiler.parameters(&path_item.parameters, &pointer);
+                for method in Method::ALL {
+                    if let Some(operation) = method.operation(path_item) {
+                        let pointer = spec_pointer(section, [path.as_str(), method.as_str()]);
+                        compiler.operation(operation, &pointer);
+                    }
+                }
+            }
+        }
+
+        if let Some(components) = &api.components {
+            let pointer = |section: &str, name: &str| spec_pointer(section, [name]);
+            for (name, parameter) in &components.parameters {
+                if let openapiv3::ReferenceOr::Item(parameter) = parameter {
+                    let format = &parameter.parameter_data_ref().format;
+                    compiler.format(format, &pointer("#/components/parameters", name));
+                }
+            }
+            for (name, header) in &components.headers {
+                if let openapiv3::ReferenceOr::Item(header) = header {
+                    compiler.format(&header.format, &pointer("#/components/headers", name));
+                }
+            }
+            for (name, request_body) in &components.request_bodies {
+                if let openapiv3::ReferenceOr::Item(request_body) = request_body {
+                    let pointer = pointer("#/components/requestBodies", name);
+                    compiler.request_body(request_body, &pointer);
+                }
+            }
+            for (name, response) in &components.responses {
+                if let openapiv3::ReferenceOr::Item(response) = response {
+                    compiler.response(response, &pointer("#/components/responses", name));
+                }
+            }
+        }
+
+        compiler
+    }
+
+    /// Follows the pointers the validators give a schema: inline objects by their position
+    /// under the operation, referenced ones where they are defined in `components`.
+    fn operation(&mut self, operation: &'api openapiv3::Operation, pointer: &str) {
+        self.parameters(&operation.parameters, pointer);
+        if let Some(openapiv3::ReferenceOr::Item(request_body)) = &operation.request_body {
+            self.request_body(request_body, &spec_pointer(pointer, ["requestBody"]));
+        }
+        let responses = &operation.responses;
+        let responses = (responses
+            .default
+            .iter()
+            .map(|response| ("default".to_string(), response)))
+        .chain(
+            (responses.responses.iter()).map(|(status, response)| (status.to_string(), response)),
+        );
+        for (status, response) in responses {
+            if let openapiv3::ReferenceOr::Item(response) = response {
+                self.response(response, &spec_pointer(pointer, ["responses", &status]));
+            }
+        }
+    }
+
+    /// Parameters of an operation or a path item, at `pointer`.
+    fn parameters(
+        &mut self,
+        parameters: &'api [openapiv3::ReferenceOr<openapiv3::Parameter>],
+        pointer: &str,
+    ) {
+        for (index, parameter) in parameters.iter().enumerate() {
+            if let openapiv3::ReferenceOr::Item(parameter) = parameter {
+                let pointer = spec_pointer(pointer, ["parameters", &index.to_string()]);
+                self.format(&parameter.parameter_data_ref().format, &pointer);
+            }
+        }
+    }
+
+    fn request_body(&mut self, request_body: &'api openapiv3::RequestBody, pointer: &str) {
+        let direction = Direction::Request {
+            strip_read_only: self.options.read_only_properties == ReadOnlyProperties::Strip,
+        };
+        self.content(&request_body.content, direction, pointer);
+    }
+
+    fn response(&mut self, response: &'api openapiv3::Response, pointer: &str) {
+        for (name, header) in &response.headers {
+            if let openapiv3::ReferenceOr::Item(header) = header {
+                self.format(&header.format, &spec_pointer(pointer, ["headers", name]));
+            }
+        }
+        self.content(&response.content, Direction::Response, pointer);
+    }
+
+    fn content(&mut self, content: &'api openapiv3::Content, direction: Direction, pointer: &str) {
+        for (range, media_type) in content {
+            let pointer = spec_pointer(pointer, ["content", range]);
+            if let Some(schema) = &media_type.schema {
+                self.add(schema, Some(direction), spec_pointer(&pointer, ["schema"]));
+            }
+            for (name, encoding) in &media_type.encoding {
+                for (header_name, header) in &encoding.headers {
+                    if let openapiv3::ReferenceOr::Item(header) = header {
+                        let pointer =
+                            spec_pointer(&pointer, ["encoding", name, "headers", header_name]);
+                        self.format(&header.format, &pointer);
+                    }
+                }
+            }
+        }
+    }
+
+    fn format(&mut self, format: &'api openapiv3::ParameterSchemaOrContent, pointer: &str) {
+        if let openapiv3::ParameterSchemaOrContent::Schema(schema) = format {
+            self.add(schema, None, spec_pointer(pointer, ["schema"]));
+        }
+    }
+
+    fn add(
+        &mut self,
+        schema: &'api openapiv3::ReferenceOr<openapiv3::Schema>,
+        direction: Option<Direction>,
+        pointer: String,
+    ) {
+        self.pending.push(PendingSchema {
+            pointer,
+            schema,
+            direction,
+        });
+    }
+
+    /// Schemas whose references do not resolve are skipped, to be reported when used.
+    fn compile(&self, pending: PendingSchema) -> Option<(String, Arc<CompiledSchema>)> {
+        let document = schema_document(pending.schema, self.components, pending.direction).ok()?;
+        let compiled = CompiledSchema::new(document, self.options);
+        Some((pending.pointer, Arc::new(compiled)))
+    }
+}
+
+/// The compiled schemas of a validator, built the first time it validates anything so that
+/// they are compiled with its final options, and dropped whenever the options change.
+#[derive(Default, Clone)]
+pub(crate) struct SchemaCache {
+    strategy: CacheStrategy,
+    /// Whether schemas compiled ahead are compiled across the rayon thread pool.
+    #[cfg(feature = "parallel")]
+    pub(crate) parallel: bool,
+    compiled: OnceLock<CompiledSchemas>,
+}
+
+impl SchemaCache {
+    /// Replaces the strategy, dropping the compiled schemas.
+    pub(crate) fn set_strategy(&mut self, strategy: CacheStrategy) {
+        self.strategy = strategy;
+        self.clear();
+    }
+
+    /// Drops the compiled schemas, keeping the strategy.
+    pub(crate) fn clear(&mut self) {
+        self.compiled = OnceLock::new();
+    }
+
+    /// The compiled schemas, with `compile` compiling them all ahead when that is the strategy.
+    pub(crate) fn get_or_compile(
+        &self,
+        compile: impl FnOnce() -> CompiledSchemas,
+    ) -> &CompiledSchemas {
+        self.compiled.get_or_init(|| match self.strategy {
+            CacheStrategy::Precompile => compile(),
+            CacheStrategy::Lru { capacity } => CompiledSchemas::bounded(capacity),
+        })
+    }
+}
+
+impl fmt::Debug for SchemaCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SchemaCache")
+            .field("strategy", &self.strategy)
+            .field("compiled", &self.compiled.get().map(CompiledSchemas::len))
+            .finish()
+    }
+}
+
+/// Beyond the strategy, the cache only holds what the document and options determine, which
+/// validators are compared by already.
+impl PartialEq for SchemaCache {
+    fn eq(&self, other: &Self) -> bool {
+        self.strategy == other.strategy
+    }
+}
+
+#[cfg(test)]
+mod test_schema_cache {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use indoc::indoc;
+
+    const SPEC: &str = indoc!(
+        "
+        openapi: 3.0.0
+        info:
+          title: test
+          version: 1.0.0
+        paths:
+          /test/{id}:
+            post:
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+                - $ref: '#/components/parameters/Limit'
+              requestBody:
+                content:
+                  application/json:
+                    schema:
+                      $ref: '#/components/schemas/Test'
+              responses:
+                200:
+                  description: ok
+                  headers:
+                    X-Rate:
+                      schema:
+                        type: integer
+                  content:
+                    application/json:
+                      schema:
+                        $ref: '#/components/schemas/Test'
+        components:
+          parameters:
+            Limit:
+              in: query
+              name: limit
+              schema:
+                type: integer
+          schemas:
+            Test:
+              type: string
+              format: even
+        "
+    );
+
+    #[test]
+    fn compile_every_schema_by_the_pointer_it_is_validated_at() {
+        let api: openapiv3::OpenAPI = serde_yaml::from_str(SPEC).unwrap();
+        let compiled = CompiledSchemas::new(&api, &IndexMap::new(), &ValidatorOptions::default());
+        let mut pointers = compiled.schemas.keys().cloned().collect::<Vec<_>>();
+        pointers.sort();
+
+        assert_eq!(
+            vec![
+                "#/components/parameters/Limit/schema",
+                "#/paths/~1test~1{id}/post/parameters/0/schema",
+                "#/paths/~1test~1{id}/post/requestBody/content/application~1json/schema",
+                "#/paths/~1test~1{id}/post/responses/200/content/application~1json/schema",
+                "#/paths/~1test~1{id}/post/responses/200/headers/X-Rate/schema",
+            ],
+            pointers
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn compile_the_same_schemas_in_parallel() {
+        let api: openapiv3::OpenAPI = serde_yaml::from_str(SPEC).unwrap();
+        let options = ValidatorOptions::default();
+        let serial = CompiledSchemas::new(&api, &IndexMap::new(), &options);
+        let parallel = CompiledSchemas::new_parallel(&api, &IndexMap::new(), &options);
+
+        assert_eq!(serial, parallel);
+        for (pointer, compiled) in &serial.schemas {
+            assert_eq!(compiled, &parallel.schemas[pointer]);
+        }
+    }
+
+    #[test]
+    fn recompile_schemas_when_formats_change() {
+        let validator = Validator::from_yaml_str(SPEC).unwrap();
+        validator.precompile_schemas();
+        let request = FakeRequest {
+            url: "http://test.com/test/1".to_string(),
+            operation: "post".to_string(),
+            body: br#""odd""#.to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+        assert!(validator.validate_request(&request).is_ok());
+
+        let validator = validator.with_format("even", |value| value.len() % 2 == 0);
+        assert!(validator.validate_request(&request).is_err());
+    }
+
+    #[test]
+    fn keep_the_most_recently_used_schemas_within_capacity() {
+        let compiled = CompiledSchemas::bounded(2);
+        let options = ValidatorOptions::default();
+        let fetch = |pointer: &str| {
+            let schema = serde_yaml::from_str("type: integer").unwrap();
+            compiled
+                .schema(pointer, &schema, &None, None, &options)
+                .unwrap()
+        };
+
+        let first = fetch("#/first");
+        fetch("#/second");
+        assert!(Arc::ptr_eq(&first, &fetch("#/first")));
+        fetch("#/third");
+
+        assert_eq!(2, compiled.len());
+        let recent = compiled.recent().unwrap();
+        assert!(recent.schemas.contains_key("#/first"));
+        assert!(!recent.schemas.contains_key("#/second"));
+    }
+
+    #[test]
+    fn validate_with_schemas_compiled_on_first_use() {
+        let validator = Validator::from_yaml_str(SPEC)
+            .unwrap()
+            .with_format("even", |value| value.len() % 2 == 0)
+            .with_schema_cache(CacheStrategy::Lru { capacity: 1 });
+        let request = |body: &str| FakeRequest {
+            url: "http://test.com/test/1?limit=2".to_string(),
+            operation: "post".to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+        };
+
+        assert!(validator.validate_request(&request(r#""even""#)).is_ok());
+        assert!(validator.validate_request(&request(r#""odd""#)).is_err());
+        assert_eq!(1, validator.compiled_schemas().len());
+    }
+}
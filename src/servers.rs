@@ -0,0 +1,162 @@
+use crate::request::split_path;
+
+/// A piece of a server URL template.
+#[derive(Debug, PartialEq, Clone)]
+enum Part {
+    Literal(String),
+    /// A `{variable}`, matching one of its `enum` values, or anything when it has none.
+    Variable(Option<Vec<String>>),
+}
+
+/// A server URL from the specification's `servers`, split into the origin requests must be
+/// sent to and the base path their URL path starts with, with variables left as wildcards.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) struct ServerPattern {
+    /// `scheme://host[:port]`, for absolute server URLs.
+    origin: Option<Vec<Part>>,
+    /// One pattern per segment of the base path.
+    base_path: Vec<Vec<Part>>,
+}
+
+impl ServerPattern {
+    pub(crate) fn from_server(server: &openapiv3::Server) -> Self {
+        let (origin, path) = match server.url.split_once("://") {
+            Some((scheme, rest)) => {
+                let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                (Some(format!("{scheme}://{authority}")), path)
+            }
+            None => (None, server.url.as_str()),
+        };
+        Self {
+            origin: origin.map(|origin| parse_template(&origin.to_ascii_lowercase(), server)),
+            base_path: split_path(path)
+                .into_iter()
+                .map(|segment| parse_template(segment, server))
+                .collect(),
+        }
+    }
+
+    /// The path segments left once this server's base path is stripped, or `None` if the
+    /// request was not sent to this server. The origin is only compared when given.
+    pub(crate) fn strip<'request, 'segments>(
+        &self,
+        origin: Option<&str>,
+        path_segments: &'segments [&'request str],
+    ) -> Option<&'segments [&'request str]> {
+        if let (Some(pattern), Some(origin)) = (&self.origin, origin) {
+            if !matches_template(pattern, &origin.to_ascii_lowercase()) {
+                return None;
+            }
+        }
+        if path_segments.len() < self.base_path.len() {
+            return None;
+        }
+        let (base_path, rest) = path_segments.split_at(self.base_path.len());
+        self.base_path
+            .iter()
+            .zip(base_path)
+            .all(|(pattern, segment)| matches_template(pattern, segment))
+            .then_some(rest)
+    }
+}
+
+fn parse_template(template: &str, server: &openapiv3::Server) -> Vec<Part> {
+    let mut parts = vec![];
+    let mut rest = template;
+    while let Some((literal, after)) = rest.split_once('{') {
+        let Some((name, after)) = after.split_once('}') else {
+            break;
+        };
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal.to_string()));
+        }
+        let values = server
+            .variables
+            .as_ref()
+            .and_then(|variables| variables.get(name))
+            .and_then(|variable| match variable.enumeration.is_empty() {
+                true => None,
+                false => Some(variable.enumeration.clone()),
+            });
+        parts.push(Part::Variable(values));
+        rest = after;
+    }
+    if !rest.is_empty() {
+        parts.push(Part::Literal(rest.to_string()));
+    }
+    parts
+}
+
+fn matches_template(parts: &[Part], value: &str) -> bool {
+    match parts.split_first() {
+        None => value.is_empty(),
+        Some((Part::Literal(literal), rest)) => value
+            .strip_prefix(literal.as_str())
+            .is_some_and(|value| matches_template(rest, value)),
+        Some((Part::Variable(Some(values)), rest)) => values.iter().any(|candidate| {
+            value
+                .strip_prefix(candidate.as_str())
+                .is_some_and(|value| matches_template(rest, value))
+        }),
+        Some((Part::Variable(None), rest)) => (1..=value.len())
+            .filter(|end| value.is_char_boundary(*end))
+            .any(|end| matches_template(rest, &value[end..])),
+    }
+}
+
+#[cfg(test)]
+mod test_server_patterns {
+    use super::*;
+    use parameterized::parameterized;
+
+    fn pattern(server: &str) -> ServerPattern {
+        ServerPattern::from_server(&serde_yaml::from_str(server).unwrap())
+    }
+
+    #[parameterized(server = {
+        "url: https://api.test.com/v1",
+        "url: /v1",
+        concat!(
+            "{url: 'https://{env}.test.com/{version}', ",
+            "variables: {env: {default: api}, version: {default: v1, enum: [v1, v2]}}}"
+        ),
+    })]
+    fn strip_the_base_path(server: &str) {
+        assert_eq!(
+            Some(&["pets"][..]),
+            pattern(server).strip(Some("https://api.test.com"), &["v1", "pets"])
+        );
+    }
+
+    #[parameterized(server = {
+        "url: https://api.test.com/v1",
+        concat!(
+            "{url: 'https://api.test.com/{version}', ",
+            "variables: {version: {default: v1, enum: [v1, v2]}}}"
+        ),
+    })]
+    fn reject_another_base_path(server: &str) {
+        assert_eq!(None, pattern(server).strip(None, &["v3", "pets"]));
+        assert_eq!(None, pattern(server).strip(None, &[]));
+    }
+
+    #[test]
+    fn compare_origins_when_given() {
+        let pattern = pattern(concat!(
+            "{url: 'https://{region}.test.com:{port}', ",
+            "variables: {region: {default: eu}, port: {default: '443'}}}"
+        ));
+        assert_eq!(
+            Some(&["pets"][..]),
+            pattern.strip(Some("https://EU.test.com:8443"), &["pets"])
+        );
+        assert_eq!(
+            None,
+            pattern.strip(Some("http://eu.test.com:8443"), &["pets"])
+        );
+        assert_eq!(
+            None,
+            pattern.strip(Some("https://eu.other.com:8443"), &["pets"])
+        );
+    }
+}
@@ -0,0 +1,746 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::item_or_fetch::ItemOrFetch;
+use crate::jsonschema::spec_pointer;
+use crate::method::Method;
+use crate::request::Segment;
+use crate::response::forbids_body;
+
+/// A problem with the specification itself, found when the validator is built.
+#[derive(Debug, PartialEq, Clone)]
+pub enum SpecError {
+    /// The path template contains `{name}` but the operation declares no `in: path`
+    /// parameter called `name`.
+    UndeclaredPathParameter {
+        path: String,
+        method: Method,
+        name: String,
+    },
+    /// The operation declares an `in: path` parameter that does not appear in the template.
+    UnknownPathParameter {
+        path: String,
+        method: Method,
+        name: String,
+    },
+    /// Part of the document did not parse and was skipped by the lenient loader.
+    Unparseable { pointer: String, message: String },
+    /// An example does not match the schema it illustrates.
+    InvalidExample { pointer: String },
+    /// Two path templates differ only in the names of their variables, e.g. `/pets/{id}` and
+    /// `/pets/{name}`, so no request can tell them apart.
+    AmbiguousPaths { path: String, other: String },
+    /// A response documents content for a status code that cannot carry a body: 1XX, 204 or
+    /// 304.
+    BodylessResponseContent { pointer: String },
+    /// A local `$ref` points at nothing in the document.
+    UnresolvedReference { pointer: String, reference: String },
+    /// Following `$ref`s from `pointer` leads back to it without ever reaching a schema.
+    ReferenceCycle { pointer: String, reference: String },
+    /// Two operations share an `operationId`; `pointer` is the later one.
+    DuplicateOperationId {
+        operation_id: String,
+        pointer: String,
+    },
+    /// An `enum` value is not of the schema's `type`.
+    InvalidEnumValue { pointer: String },
+    /// A discriminator `mapping` names a schema that does not exist.
+    UnknownDiscriminatorMapping { pointer: String, target: String },
+}
+
+impl fmt::Display for SpecError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UndeclaredPathParameter { path, method, name } => write!(
+                f,
+                "{method} {path}: template variable '{name}' has no path parameter"
+            ),
+            Self::UnknownPathParameter { path, method, name } => write!(
+                f,
+                "{method} {path}: path parameter '{name}' is not in the template"
+            ),
+            Self::Unparseable { pointer, message } => {
+                write!(f, "{pointer}: skipped as it could not be parsed: {message}")
+            }
+            Self::InvalidExample { pointer } => {
+                write!(f, "{pointer}: example does not match its schema")
+            }
+            Self::AmbiguousPaths { path, other } => {
+                write!(f, "{path}: matches the same requests as {other}")
+            }
+            Self::BodylessResponseContent { pointer } => {
+                write!(
+                    f,
+                    "{pointer}: responses with this status code cannot have a body"
+                )
+            }
+            Self::UnresolvedReference { pointer, reference } => {
+                write!(f, "{pointer}: reference '{reference}' does not resolve")
+            }
+            Self::ReferenceCycle { pointer, reference } => {
+                write!(f, "{pointer}: reference '{reference}' leads back to itself")
+            }
+            Self::DuplicateOperationId {
+                operation_id,
+                pointer,
+            } => write!(f, "{pointer}: operationId '{operation_id}' is already used"),
+            Self::InvalidEnumValue { pointer } => {
+                write!(f, "{pointer}: enum value does not match the schema's type")
+            }
+            Self::UnknownDiscriminatorMapping { pointer, target } => {
+                write!(
+                    f,
+                    "{pointer}: discriminator maps to unknown schema '{target}'"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SpecError {}
+
+pub fn check_path_templates(api: &openapiv3::OpenAPI) -> Vec<SpecError> {
+    let mut errors = vec![];
+
+    for (path, path_spec) in api.paths.paths.iter() {
+        let path_spec = match path_spec.as_item() {
+            Some(path_spec) => path_spec,
+            None => continue,
+        };
+        let template: HashSet<&str> = Segment::list_from_str(path)
+            .into_iter()
+            .filter_map(|segment| match segment {
+                Segment::Parameter { name } => Some(name),
+                Segment::Fixed { .. } => None,
+            })
+            .collect();
+
+        for method in Method::ALL {
+            let operation_spec = match method.operation(path_spec) {
+                Some(operation_spec) => operation_spec,
+                None => continue,
+            };
+            let declared: HashSet<&str> = path_spec
+                .parameters
+                .iter()
+                .chain(operation_spec.parameters.iter())
+                // header components can only ever describe header parameters
+                .filter(|parameter| {
+                    !matches!(parameter, openapiv3::ReferenceOr::Reference { reference }
+                        if reference.starts_with("#/components/headers/"))
+                })
+                .filter_map(|parameter| parameter.item_or_fetch(&api.components).ok())
+                .filter(|parameter| matches!(parameter, openapiv3::Parameter::Path { .. }))
+                .map(|parameter| parameter.parameter_data_ref().name.as_str())
+                .collect();
+
+            let mut undeclared: Vec<&str> = template.difference(&declared).copied().collect();
+            undeclared.sort_unstable();
+            errors.extend(
+                undeclared
+                    .into_iter()
+                    .map(|name| SpecError::UndeclaredPathParameter {
+                        path: path.to_string(),
+                        method,
+                        name: name.to_string(),
+                    }),
+            );
+
+            let mut unknown: Vec<&str> = declared.difference(&template).copied().collect();
+            unknown.sort_unstable();
+            errors.extend(
+                unknown
+                    .into_iter()
+                    .map(|name| SpecError::UnknownPathParameter {
+                        path: path.to_string(),
+                        method,
+                        name: name.to_string(),
+                    }),
+            );
+        }
+    }
+
+    errors
+}
+
+pub fn check_ambiguous_paths(api: &openapiv3::OpenAPI) -> Vec<SpecError> {
+    let mut templates: HashMap<Vec<Option<&str>>, &str> = HashMap::new();
+    let mut errors = vec![];
+
+    for path in api.paths.paths.keys() {
+        let template = Segment::list_from_str(path)
+            .into_iter()
+            .map(|segment| match segment {
+                Segment::Fixed { literal } => Some(literal),
+                Segment::Parameter { .. } => None,
+            })
+            .collect();
+        match templates.get(&template) {
+            Some(other) => errors.push(SpecError::AmbiguousPaths {
+                path: path.to_string(),
+                other: other.to_string(),
+            }),
+            None => {
+                templates.insert(template, path);
+            }
+        }
+    }
+
+    errors
+}
+
+pub fn check_bodyless_responses(api: &openapiv3::OpenAPI) -> Vec<SpecError> {
+    let mut errors = vec![];
+
+    for (path, path_spec) in api.paths.paths.iter() {
+        let Some(path_spec) = path_spec.as_item() else {
+            continue;
+        };
+        for method in Method::ALL {
+            let Some(operation_spec) = method.operation(path_spec) else {
+                continue;
+            };
+            for (status_code, response) in operation_spec.responses.responses.iter() {
+                let bodyless = match status_code {
+                    openapiv3::StatusCode::Code(code) => forbids_body(*code),
+                    openapiv3::StatusCode::Range(range) => *range == 1,
+                };
+                if bodyless
+                    && response
+                        .item_or_fetch(&api.components)
+                        .is_ok_and(|response| !response.content.is_empty())
+                {
+                    errors.push(SpecError::BodylessResponseContent {
+                        pointer: spec_pointer(
+                            "#/paths",
+                            [
+                                path.as_str(),
+                                method.as_str(),
+                                "responses",
+                                &status_code.to_string(),
+                                "content",
+                            ],
+                        ),
+                    });
+                }
+            }
+        }
+    }
+
+    errors
+}
+
+pub fn check_duplicate_operation_ids(api: &openapiv3::OpenAPI) -> Vec<SpecError> {
+    let mut seen = HashSet::new();
+    let mut errors = vec![];
+
+    for (path, path_spec) in api.paths.paths.iter() {
+        let Some(path_spec) = path_spec.as_item() else {
+            continue;
+        };
+        for method in Method::ALL {
+            let Some(operation_id) = method
+                .operation(path_spec)
+                .and_then(|operation_spec| operation_spec.operation_id.as_deref())
+            else {
+                continue;
+            };
+            if !seen.insert(operation_id) {
+                errors.push(SpecError::DuplicateOperationId {
+                    operation_id: operation_id.to_string(),
+                    pointer: spec_pointer("#/paths", [path.as_str(), method.as_str()]),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+/// Maps whose keys are names chosen by the author rather than keywords.
+const NAMED_MAPS: [&str; 15] = [
+    "paths",
+    "schemas",
+    "properties",
+    "responses",
+    "parameters",
+    "examples",
+    "requestBodies",
+    "headers",
+    "securitySchemes",
+    "links",
+    "callbacks",
+    "content",
+    "encoding",
+    "variables",
+    "mapping",
+];
+
+/// Keywords holding instance values rather than parts of the document.
+const VALUE_KEYWORDS: [&str; 3] = ["example", "default", "value"];
+
+/// Checks that need the whole document: local references resolve, `enum` values have their
+/// schema's type and discriminator mappings name existing schemas.
+pub fn check_document(api: &openapiv3::OpenAPI) -> Vec<SpecError> {
+    let Ok(document) = serde_json::to_value(api) else {
+        return vec![];
+    };
+    let mut errors = vec![];
+    check_value(&document, &document, "#", None, &mut errors);
+    errors
+}
+
+fn check_value(
+    document: &serde_json::Value,
+    value: &serde_json::Value,
+    pointer: &str,
+    parent_key: Option<&str>,
+    errors: &mut Vec<SpecError>,
+) {
+    match value {
+        serde_json::Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                let pointer = spec_pointer(pointer, [index.to_string().as_str()]);
+                check_value(document, item, &pointer, None, errors);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            let keywords = !parent_key.is_some_and(|key| NAMED_MAPS.contains(&key));
+            if keywords {
+                check_keywords(document, object, pointer, errors);
+            }
+            for (key, child) in object {
+                let is_value = VALUE_KEYWORDS.contains(&key.as_str())
+                    || key == "enum"
+                    || key.starts_with("x-");
+                if keywords && is_value {
+                    continue;
+                }
+                let pointer = spec_pointer(pointer, [key.as_str()]);
+                let key = keywords.then_some(key.as_str());
+                check_value(document, child, &pointer, key, errors);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Whether the chain of objects that are only a `$ref`, starting at `reference`, comes back
+/// to the object at `pointer`.
+fn returns_to(document: &serde_json::Value, reference: &str, pointer: &str) -> bool {
+    let mut seen = HashSet::new();
+    let mut reference = reference;
+    while seen.insert(reference) {
+        if reference == pointer {
+            return true;
+        }
+        let next = reference
+            .strip_prefix('#')
+            .and_then(|local| document.pointer(local))
+            .and_then(|target| target.get("$ref"))
+            .and_then(serde_json::Value::as_str);
+        match next {
+            Some(next) => reference = next,
+            None => return false,
+        }
+    }
+    false
+}
+
+fn check_keywords(
+    document: &serde_json::Value,
+    object: &serde_json::Map<String, serde_json::Value>,
+    pointer: &str,
+    errors: &mut Vec<SpecError>,
+) {
+    if let Some(serde_json::Value::String(reference)) = object.get("$ref") {
+        if let Some(local) = reference.strip_prefix('#') {
+            if document.pointer(local).is_none() {
+                errors.push(SpecError::UnresolvedReference {
+                    pointer: pointer.to_string(),
+                    reference: reference.to_string(),
+                });
+            } else if returns_to(document, reference, pointer) {
+                errors.push(SpecError::ReferenceCycle {
+                    pointer: pointer.to_string(),
+                    reference: reference.to_string(),
+                });
+            }
+        }
+    }
+
+    if let (Some(serde_json::Value::String(kind)), Some(serde_json::Value::Array(values))) =
+        (object.get("type"), object.get("enum"))
+    {
+        let nullable = object.get("nullable") == Some(&serde_json::Value::Bool(true));
+        for (index, value) in values.iter().enumerate() {
+            if !(value.is_null() && nullable || has_type(value, kind)) {
+                errors.push(SpecError::InvalidEnumValue {
+                    pointer: spec_pointer(pointer, ["enum", index.to_string().as_str()]),
+                });
+            }
+        }
+    }
+
+    let mapping = object
+        .get("discriminator")
+        .filter(|discriminator| discriminator.get("propertyName").is_some())
+        .and_then(|discriminator| discriminator.get("mapping"))
+        .and_then(serde_json::Value::as_object);
+    for (value, target) in mapping.into_iter().flatten() {
+        let Some(target) = target.as_str() else {
+            continue;
+        };
+        let exists = match target.strip_prefix('#') {
+            Some(local) => document.pointer(local).is_some(),
+            None => document
+                .pointer(&spec_pointer("/components/schemas", [target]))
+                .is_some(),
+        };
+        if !exists {
+            errors.push(SpecError::UnknownDiscriminatorMapping {
+                pointer: spec_pointer(pointer, ["discriminator", "mapping", value.as_str()]),
+                target: target.to_string(),
+            });
+        }
+    }
+}
+
+fn has_type(value: &serde_json::Value, kind: &str) -> bool {
+    match kind {
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        "boolean" => value.is_boolean(),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod test_path_templates {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+
+    #[test]
+    fn accept_matching_template_and_parameters() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /things/{id}:
+                parameters:
+                  - in: path
+                    name: id
+                    required: true
+                    schema:
+                      type: integer
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        assert!(make_validator_from_spec(path_spec).spec_errors().is_empty());
+    }
+
+    #[test]
+    fn report_template_variable_without_parameter() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /things/{id}:
+                get:
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        assert_eq!(
+            vec![SpecError::UndeclaredPathParameter {
+                path: "/things/{id}".to_string(),
+                method: Method::Get,
+                name: "id".to_string()
+            }],
+            make_validator_from_spec(path_spec).spec_errors()
+        );
+    }
+
+    #[test]
+    fn report_path_parameter_missing_from_template() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /things:
+                post:
+                  parameters:
+                    - in: path
+                      name: id
+                      required: true
+                      schema:
+                        type: integer
+                  responses:
+                    200:
+                      description: API call successful
+            "#
+        );
+        assert_eq!(
+            vec![SpecError::UnknownPathParameter {
+                path: "/things".to_string(),
+                method: Method::Post,
+                name: "id".to_string()
+            }],
+            make_validator_from_spec(path_spec).spec_errors()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_bodyless_responses {
+    use super::*;
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use parameterized::parameterized;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                1XX:
+                  description: Informational
+                204:
+                  description: No content
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                304:
+                  $ref: '#/components/responses/NotModified'
+        components:
+          responses:
+            NotModified:
+              description: Not modified
+              content:
+                text/plain: {}
+        "#
+    );
+
+    #[test]
+    fn report_content_documented_for_bodyless_status_codes() {
+        assert_eq!(
+            vec![
+                SpecError::BodylessResponseContent {
+                    pointer: "#/paths/~1things/get/responses/204/content".to_string()
+                },
+                SpecError::BodylessResponseContent {
+                    pointer: "#/paths/~1things/get/responses/304/content".to_string()
+                },
+            ],
+            make_validator_from_spec(PATH_SPEC).spec_errors()
+        );
+    }
+
+    fn respond(status_code: u16, body: &str) -> Result<(), ValidationError> {
+        let request = FakeRequest {
+            url: "http://test.com/things".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::new(),
+        };
+        let response = FakeResponse {
+            status_code,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: body.as_bytes().to_vec(),
+        };
+        make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .unwrap()
+            .validate_response(&response)
+    }
+
+    #[parameterized(status_code = {103, 204, 304})]
+    fn reject_a_body_sent_with_a_bodyless_status_code(status_code: u16) {
+        assert_eq!(
+            Err(ValidationError::UnexpectedResponseBody),
+            respond(status_code, "{}")
+        );
+        assert_eq!(Ok(()), respond(status_code, ""));
+    }
+
+    #[test]
+    fn accept_a_body_sent_with_other_status_codes() {
+        assert_eq!(Ok(()), respond(200, "{}"));
+    }
+}
+
+#[cfg(test)]
+mod test_ambiguous_paths {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+
+    #[test]
+    fn report_templates_differing_only_in_variable_names() {
+        let path_spec = indoc!(
+            r#"
+            paths:
+              /pets/{id}:
+                parameters:
+                  - in: path
+                    name: id
+                    required: true
+                    schema:
+                      type: integer
+              /pets/mine: {}
+              /pets/{name}:
+                parameters:
+                  - in: path
+                    name: name
+                    required: true
+                    schema:
+                      type: string
+            "#
+        );
+        assert_eq!(
+            vec![SpecError::AmbiguousPaths {
+                path: "/pets/{name}".to_string(),
+                other: "/pets/{id}".to_string()
+            }],
+            make_validator_from_spec(path_spec).spec_errors()
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_checked_documents {
+    use super::*;
+    use crate::request::Validator;
+    use indoc::indoc;
+
+    #[test]
+    fn accept_a_consistent_document() {
+        let document = std::fs::read_to_string("./specs/openapi.yaml").unwrap();
+        assert!(Validator::new_checked(serde_yaml::from_str(&document).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn report_every_problem_with_the_document() {
+        let document = indoc!(
+            r#"
+            openapi: 3.0.0
+            info:
+              title: Pets
+              version: 1.0.0
+            paths:
+              /pets:
+                get:
+                  operationId: listPets
+                  responses:
+                    200:
+                      $ref: '#/components/responses/Pets'
+                post:
+                  operationId: listPets
+                  requestBody:
+                    content:
+                      application/json:
+                        schema:
+                          $ref: '#/components/schemas/Pet'
+                  responses:
+                    201:
+                      description: Created
+            components:
+              schemas:
+                Pet:
+                  oneOf:
+                    - $ref: '#/components/schemas/Cat'
+                  discriminator:
+                    propertyName: kind
+                    mapping:
+                      cat: Cat
+                      dog: '#/components/schemas/Dog'
+                Cat:
+                  type: object
+                  properties:
+                    kind:
+                      type: string
+                      enum: [cat, 1]
+                    default:
+                      $ref: '#/components/schemas/Missing'
+                    lives:
+                      type: integer
+                      nullable: true
+                      enum: [9, 7.0, null]
+            "#
+        );
+        let errors = Validator::new_checked(serde_yaml::from_str(document).unwrap()).unwrap_err();
+        assert_eq!(
+            vec![
+                SpecError::UnresolvedReference {
+                    pointer: "#/components/schemas/Cat/properties/default".to_string(),
+                    reference: "#/components/schemas/Missing".to_string()
+                },
+                SpecError::InvalidEnumValue {
+                    pointer: "#/components/schemas/Cat/properties/kind/enum/1".to_string()
+                },
+                SpecError::UnknownDiscriminatorMapping {
+                    pointer: "#/components/schemas/Pet/discriminator/mapping/dog".to_string(),
+                    target: "#/components/schemas/Dog".to_string()
+                },
+                SpecError::UnresolvedReference {
+                    pointer: "#/paths/~1pets/get/responses/200".to_string(),
+                    reference: "#/components/responses/Pets".to_string()
+                },
+                SpecError::DuplicateOperationId {
+                    operation_id: "listPets".to_string(),
+                    pointer: "#/paths/~1pets/post".to_string()
+                },
+            ],
+            errors
+        );
+    }
+
+    #[test]
+    fn report_references_that_lead_back_to_themselves() {
+        let document = indoc!(
+            r#"
+            openapi: 3.0.0
+            info:
+              title: Pets
+              version: 1.0.0
+            paths: {}
+            components:
+              schemas:
+                Pet:
+                  $ref: '#/components/schemas/Animal'
+                Animal:
+                  $ref: '#/components/schemas/Pet'
+                Owner:
+                  $ref: '#/components/schemas/Pet'
+            "#
+        );
+        let errors = Validator::new_checked(serde_yaml::from_str(document).unwrap()).unwrap_err();
+        assert_eq!(
+            vec![
+                SpecError::ReferenceCycle {
+                    pointer: "#/components/schemas/Animal".to_string(),
+                    reference: "#/components/schemas/Pet".to_string()
+                },
+                SpecError::ReferenceCycle {
+                    pointer: "#/components/schemas/Pet".to_string(),
+                    reference: "#/components/schemas/Animal".to_string()
+                },
+            ],
+            errors
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::error::ValidationError;
+use crate::jsonschema::{CompiledSchema, SchemaLocation};
+use std::sync::Arc;
+
+/// How a streamed body is split into the items its schema describes.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum Framing {
+    /// `application/x-ndjson`: one JSON value per line.
+    NewlineDelimited,
+    /// `text/event-stream`: the `data` of each server-sent event, as JSON when it parses and as
+    /// a string otherwise.
+    EventStream,
+}
+
+impl Framing {
+    pub(crate) fn from_mime(mime: &mime::Mime) -> Option<Self> {
+        match (mime.type_(), mime.subtype().as_str()) {
+            (mime::APPLICATION, "x-ndjson") => Some(Self::NewlineDelimited),
+            (mime::TEXT, "event-stream") => Some(Self::EventStream),
+            _ => None,
+        }
+    }
+}
+
+/// Validates a streamed response as it arrives: callers [`feed`](Self::feed) chunks as they
+/// are received and [`finish`](Self::finish) once the stream ends. Each item is checked against
+/// the schema of the documented media type as soon as it is complete, so a long-lived stream
+/// fails on its first bad item rather than once it closes.
+///
+/// Created by [`crate::ResponseValidator::streaming`], which has already checked the status
+/// code and content type.
+#[derive(Debug, PartialEq, Clone)]
+pub struct StreamingResponseValidator<'api> {
+    pub(crate) framing: Framing,
+    /// The item schema compiled, if the media type declares one.
+    pub(crate) schema: Option<Arc<CompiledSchema>>,
+    pub(crate) location: SchemaLocation<'api>,
+    pub(crate) buffer: Vec<u8>,
+    /// `data` lines of the event being received.
+    pub(crate) data: Vec<String>,
+    pub(crate) items: usize,
+}
+
+impl StreamingResponseValidator<'_> {
+    /// Validates every item completed by this chunk. Fails with the index of the first item
+    /// that does not match its schema; the stream should not be fed further after a failure.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<(), ValidationError> {
+        self.buffer.extend_from_slice(chunk);
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            self.line(&line[..end])?;
+        }
+        Ok(())
+    }
+
+    /// Validates what remains once the stream has ended: a final line without a newline. An
+    /// event not terminated by a blank line is incomplete and, as in browsers, discarded.
+    pub fn finish(mut self) -> Result<(), ValidationError> {
+        if self.framing == Framing::NewlineDelimited && !self.buffer.is_empty() {
+            let line = std::mem::take(&mut self.buffer);
+            self.line(&line)?;
+        }
+        Ok(())
+    }
+
+    /// Number of items validated so far.
+    pub fn items(&self) -> usize {
+        self.items
+    }
+
+    fn line(&mut self, line: &[u8]) -> Result<(), ValidationError> {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        let Ok(line) = std::str::from_utf8(line) else {
+            return Err(self.invalid_item());
+        };
+
+        match self.framing {
+            Framing::NewlineDelimited if line.trim().is_empty() => Ok(()),
+            Framing::NewlineDelimited => match serde_json::from_str(line) {
+                Ok(item) => self.item(item),
+                Err(..) => Err(self.invalid_item()),
+            },
+            Framing::EventStream if line.is_empty() => {
+                if self.data.is_empty() {
+                    return Ok(());
+                }
+                let data = std::mem::take(&mut self.data).join("\n");
+                let item = serde_json::from_str(&data).unwrap_or(serde_json::Value::String(data));
+                self.item(item)
+            }
+            Framing::EventStream => {
+                let (field, value) = line.split_once(':').unwrap_or((line, ""));
+                if field == "data" {
+                    self.data
+                        .push(value.strip_prefix(' ').unwrap_or(value).to_string());
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn item(&mut self, item: serde_json::Value) -> Result<(), ValidationError> {
+        if let Some(schema) = &self.schema {
+            self.location
+                .validate(schema, &item, None)
+                .map_err(|error| {
+                    error.unwrap_or(ValidationError::InvalidStreamItem { index: self.items })
+                })?;
+        }
+        self.items += 1;
+        Ok(())
+    }
+
+    fn invalid_item(&self) -> ValidationError {
+        ValidationError::InvalidStreamItem { index: self.items }
+    }
+}
+
+#[cfg(test)]
+mod test_streaming_responses {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use indoc::indoc;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /events:
+            get:
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/x-ndjson:
+                      schema:
+                        $ref: '#/components/schemas/Event'
+                    text/event-stream:
+                      schema:
+                        $ref: '#/components/schemas/Event'
+                    application/json:
+                      schema:
+                        type: array
+        components:
+          schemas:
+            Event:
+              type: object
+              required: [id]
+              properties:
+                id:
+                  type: integer
+        "#
+    );
+
+    fn streaming<'api>(
+        validator: &'api Validator,
+        content_type: &str,
+    ) -> Result<super::StreamingResponseValidator<'api>, ValidationError> {
+        validator
+            .response_validator("/events", "get")
+            .unwrap()
+            .streaming(200, content_type)
+    }
+
+    #[test]
+    fn validate_lines_split_across_chunks() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let mut stream = streaming(&validator, "application/x-ndjson").unwrap();
+
+        assert_eq!(Ok(()), stream.feed(b"{\"id\": 1}\n{\"id\""));
+        assert_eq!(1, stream.items());
+        assert_eq!(Ok(()), stream.feed(b": 2}\r\n\n{\"id\": 3}"));
+        assert_eq!(2, stream.items());
+        assert_eq!(Ok(()), stream.finish());
+    }
+
+    #[test]
+    fn reject_the_first_invalid_line() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let mut stream = streaming(&validator, "application/x-ndjson").unwrap();
+
+        assert_eq!(
+            Err(ValidationError::InvalidStreamItem { index: 1 }),
+            stream.feed(b"{\"id\": 1}\n{\"name\": \"tick\"}\n")
+        );
+        let mut stream = streaming(&validator, "application/x-ndjson").unwrap();
+        assert_eq!(Ok(()), stream.feed(b"{\"id\": 1}\n{\"id\": "));
+        assert_eq!(
+            Err(ValidationError::InvalidStreamItem { index: 1 }),
+            stream.finish()
+        );
+    }
+
+    #[test]
+    fn validate_the_data_of_each_event() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let mut stream = streaming(&validator, "text/event-stream").unwrap();
+
+        assert_eq!(
+            Ok(()),
+            stream.feed(b": keep-alive\n\nevent: tick\ndata: {\"id\":\ndata: 1}\n\n")
+        );
+        assert_eq!(1, stream.items());
+        assert_eq!(
+            Err(ValidationError::InvalidStreamItem { index: 1 }),
+            stream.feed(b"id: 7\ndata: done\n\n")
+        );
+    }
+
+    #[test]
+    fn discard_an_unterminated_event() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let mut stream = streaming(&validator, "text/event-stream").unwrap();
+
+        assert_eq!(Ok(()), stream.feed(b"data: done\n"));
+        assert_eq!(Ok(()), stream.finish());
+    }
+
+    #[test]
+    fn check_the_status_code_and_content_type_up_front() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let response_validator = || validator.response_validator("/events", "get").unwrap();
+
+        assert!(matches!(
+            response_validator().streaming(500, "text/event-stream"),
+            Err(ValidationError::UndocumentedStatusCode { .. })
+        ));
+        assert_eq!(
+            Err(ValidationError::UndeclaredResponseContentType {
+                content_type: "text/csv".to_string()
+            }),
+            streaming(&validator, "text/csv").map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::UnsupportedMediaTypeHandler {
+                content_type: "application/json".to_string()
+            }),
+            streaming(&validator, "application/json").map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,968 @@
+use serde_yaml::{Mapping, Value};
+
+use crate::method::Method;
+
+/// Keywords of a Swagger 2.0 non-body parameter or header that describe its value, and so move
+/// into its `schema` in OpenAPI 3.0.
+const SCHEMA_KEYWORDS: [&str; 17] = [
+    "type",
+    "format",
+    "items",
+    "default",
+    "maximum",
+    "exclusiveMaximum",
+    "minimum",
+    "exclusiveMinimum",
+    "maxLength",
+    "minLength",
+    "pattern",
+    "maxItems",
+    "minItems",
+    "uniqueItems",
+    "enum",
+    "multipleOf",
+    "x-nullable",
+];
+
+const FORM_MEDIA_TYPES: [&str; 2] = ["application/x-www-form-urlencoded", "multipart/form-data"];
+
+/// Whether the document is a Swagger 2.0 specification.
+pub(crate) fn is_swagger_2(document: &Value) -> bool {
+    document
+        .get("swagger")
+        .and_then(Value::as_str)
+        .is_some_and(|version| version.starts_with("2."))
+}
+
+/// Rewrites a Swagger 2.0 document as OpenAPI 3.0:
+/// - `host`, `basePath` and `schemes` become `servers`
+/// - `definitions`, `parameters`, `responses` and `securityDefinitions` move to `components`
+/// - `body` and `formData` parameters become request bodies, one per media type the operation
+///   `consumes`
+/// - response schemas become content, one per media type the operation `produces`
+/// - the value keywords of other parameters and of headers move into a `schema`
+pub(crate) fn upgrade(document: &mut Value) {
+    let Some(root) = document.as_mapping_mut() else {
+        return;
+    };
+    let global_parameters = root
+        .remove("parameters")
+        .and_then(|parameters| parameters.as_mapping().cloned())
+        .unwrap_or_default();
+    let upgrade = Upgrade {
+        consumes: media_types(root.remove("consumes"), "application/json"),
+        produces: media_types(root.remove("produces"), "application/json"),
+        global_parameters: &global_parameters,
+    };
+
+    root.remove("swagger");
+    root.insert("openapi".into(), "3.0.3".into());
+    let servers = servers(
+        root.remove("host"),
+        root.remove("basePath"),
+        root.remove("schemes"),
+    );
+    if !servers.is_empty() {
+        root.insert("servers".into(), servers.into());
+    }
+
+    if let Some(paths) = root.get_mut("paths").and_then(Value::as_mapping_mut) {
+        for path_item in paths.values_mut() {
+            if let Some(path_item) = path_item.as_mapping_mut() {
+                upgrade.path_item(path_item);
+            }
+        }
+    }
+
+    let mut components = Mapping::new();
+    if let Some(definitions) = root.remove("definitions") {
+        components.insert("schemas".into(), definitions);
+    }
+    let mut parameters = Mapping::new();
+    let mut request_bodies = Mapping::new();
+    for (name, parameter) in global_parameters.iter() {
+        match parameter.get("in").and_then(Value::as_str) {
+            Some("body") | Some("formData") => {
+                if let Some(request_body) =
+                    upgrade.request_body(std::slice::from_ref(parameter), &upgrade.consumes)
+                {
+                    request_bodies.insert(name.clone(), request_body);
+                }
+            }
+            _ => {
+                parameters.insert(name.clone(), upgrade_parameter(parameter.clone()));
+            }
+        }
+    }
+    if !parameters.is_empty() {
+        components.insert("parameters".into(), parameters.into());
+    }
+    if !request_bodies.is_empty() {
+        components.insert("requestBodies".into(), request_bodies.into());
+    }
+    if let Some(Value::Mapping(responses)) = root.remove("responses") {
+        let responses: Mapping = responses
+            .into_iter()
+            .map(|(name, response)| (name, upgrade_response(response, &upgrade.produces)))
+            .collect();
+        components.insert("responses".into(), responses.into());
+    }
+    if let Some(Value::Mapping(schemes)) = root.remove("securityDefinitions") {
+        let schemes: Mapping = schemes
+            .into_iter()
+            .map(|(name, scheme)| (name, upgrade_security_scheme(scheme)))
+            .collect();
+        components.insert("securitySchemes".into(), schemes.into());
+    }
+    if !components.is_empty() {
+        root.insert("components".into(), components.into());
+    }
+
+    upgrade_schemas(document);
+}
+
+struct Upgrade<'document> {
+    consumes: Vec<Value>,
+    produces: Vec<Value>,
+    global_parameters: &'document Mapping,
+}
+
+impl Upgrade<'_> {
+    fn path_item(&self, path_item: &mut Mapping) {
+        let shared = path_item
+            .remove("parameters")
+            .and_then(|parameters| parameters.as_sequence().cloned())
+            .unwrap_or_default();
+
+        for method in Method::ALL {
+            let Some(operation) = path_item
+                .get_mut(method.as_str())
+                .and_then(Value::as_mapping_mut)
+            else {
+                continue;
+            };
+            let own = operation
+                .remove("parameters")
+                .and_then(|parameters| parameters.as_sequence().cloned())
+                .unwrap_or_default();
+            // an operation's parameter overrides a shared one with the same name and location
+            let parameters: Vec<Value> = shared
+                .iter()
+                .filter(|parameter| {
+                    !own.iter()
+                        .any(|own| self.identity(own) == self.identity(parameter))
+                })
+                .chain(own.iter())
+                .cloned()
+                .collect();
+
+            let consumes = match operation.remove("consumes") {
+                Some(consumes) => media_types(Some(consumes), "application/json"),
+                None => self.consumes.clone(),
+            };
+            let produces = match operation.remove("produces") {
+                Some(produces) => media_types(Some(produces), "application/json"),
+                None => self.produces.clone(),
+            };
+
+            let (body, others): (Vec<Value>, Vec<Value>) =
+                parameters.into_iter().partition(|parameter| {
+                    matches!(
+                        self.location(parameter).as_deref(),
+                        Some("body" | "formData")
+                    )
+                });
+            if let Some(request_body) = self.body_reference(&body) {
+                operation.insert("requestBody".into(), request_body);
+            } else if let Some(request_body) = self.request_body(&body, &consumes) {
+                operation.insert("requestBody".into(), request_body);
+            }
+            if !others.is_empty() {
+                let others: Vec<Value> = others.into_iter().map(upgrade_parameter).collect();
+                operation.insert("parameters".into(), others.into());
+            }
+
+            if let Some(Value::Mapping(responses)) = operation.get_mut("responses") {
+                for response in responses.values_mut() {
+                    *response = upgrade_response(std::mem::take(response), &produces);
+                }
+            }
+        }
+    }
+
+    /// A single body parameter given by reference keeps pointing at the request body component
+    /// it becomes.
+    fn body_reference(&self, body: &[Value]) -> Option<Value> {
+        match body {
+            [parameter] => {
+                let reference = parameter.get("$ref")?.as_str()?;
+                let name = reference.strip_prefix("#/parameters/")?;
+                Some(Mapping::from_iter([("$ref".into(), request_body_reference(name))]).into())
+            }
+            _ => None,
+        }
+    }
+
+    fn resolve<'a>(&'a self, parameter: &'a Value) -> &'a Value {
+        parameter
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|reference| reference.strip_prefix("#/parameters/"))
+            .and_then(|name| self.global_parameters.get(name))
+            .unwrap_or(parameter)
+    }
+
+    fn location(&self, parameter: &Value) -> Option<String> {
+        self.resolve(parameter)
+            .get("in")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+
+    fn identity(&self, parameter: &Value) -> (Option<String>, Option<String>) {
+        let parameter = self.resolve(parameter);
+        (
+            parameter
+                .get("name")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            self.location(parameter),
+        )
+    }
+
+    /// The request body described by `body` and `formData` parameters, if there are any.
+    fn request_body(&self, parameters: &[Value], consumes: &[Value]) -> Option<Value> {
+        let parameters: Vec<&Value> = parameters
+            .iter()
+            .map(|parameter| self.resolve(parameter))
+            .collect();
+        let mut request_body = Mapping::new();
+
+        if let Some(body) = parameters
+            .iter()
+            .find(|parameter| parameter.get("in").and_then(Value::as_str) == Some("body"))
+        {
+            copy(body, &mut request_body, ["description", "required"]);
+            let schema = body.get("schema").cloned().unwrap_or_default();
+            let content: Mapping = consumes
+                .iter()
+                .map(|media_type| {
+                    let media_type_spec = Mapping::from_iter([("schema".into(), schema.clone())]);
+                    (media_type.clone(), media_type_spec.into())
+                })
+                .collect();
+            request_body.insert("content".into(), content.into());
+            return Some(request_body.into());
+        }
+
+        let fields: Vec<&Value> = parameters
+            .into_iter()
+            .filter(|parameter| parameter.get("in").and_then(Value::as_str) == Some("formData"))
+            .collect();
+        if fields.is_empty() {
+            return None;
+        }
+        let mut properties = Mapping::new();
+        let mut required = vec![];
+        for field in &fields {
+            let Some(name) = field.get("name").cloned() else {
+                continue;
+            };
+            if field.get("required").and_then(Value::as_bool) == Some(true) {
+                required.push(name.clone());
+            }
+            let mut schema = value_schema(field);
+            copy(field, &mut schema, ["description"]);
+            properties.insert(name, schema.into());
+        }
+        let mut schema = Mapping::from_iter([
+            ("type".into(), "object".into()),
+            ("properties".into(), properties.into()),
+        ]);
+        if !required.is_empty() {
+            schema.insert("required".into(), required.into());
+            request_body.insert("required".into(), true.into());
+        }
+
+        let has_files = fields
+            .iter()
+            .any(|field| field.get("type").and_then(Value::as_str) == Some("file"));
+        let mut media_types: Vec<Value> = consumes
+            .iter()
+            .filter(|media_type| {
+                media_type
+                    .as_str()
+                    .is_some_and(|media_type| FORM_MEDIA_TYPES.contains(&media_type))
+            })
+            .cloned()
+            .collect();
+        if media_types.is_empty() {
+            media_types.push(FORM_MEDIA_TYPES[usize::from(has_files)].into());
+        }
+        let content: Mapping = media_types
+            .into_iter()
+            .map(|media_type| {
+                let media_type_spec =
+                    Mapping::from_iter([("schema".into(), schema.clone().into())]);
+                (media_type, media_type_spec.into())
+            })
+            .collect();
+        request_body.insert("content".into(), content.into());
+        Some(request_body.into())
+    }
+}
+
+fn media_types(media_types: Option<Value>, default: &str) -> Vec<Value> {
+    match media_types {
+        Some(Value::Sequence(media_types)) if !media_types.is_empty() => media_types,
+        _ => vec![default.into()],
+    }
+}
+
+fn servers(host: Option<Value>, base_path: Option<Value>, schemes: Option<Value>) -> Vec<Value> {
+    let base_path = base_path
+        .as_ref()
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .trim_end_matches('/');
+    let urls: Vec<String> = match host.as_ref().and_then(Value::as_str) {
+        Some(host) => {
+            let schemes = match schemes {
+                Some(Value::Sequence(schemes)) if !schemes.is_empty() => schemes,
+                _ => vec!["https".into()],
+            };
+            schemes
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|scheme| format!("{scheme}://{host}{base_path}"))
+                .collect()
+        }
+        None if !base_path.is_empty() => vec![base_path.to_string()],
+        None => vec![],
+    };
+    urls.into_iter()
+        .map(|url| Mapping::from_iter([("url".into(), url.into())]).into())
+        .collect()
+}
+
+fn request_body_reference(name: &str) -> Value {
+    format!("#/components/requestBodies/{name}").into()
+}
+
+fn copy<const N: usize>(from: &Value, to: &mut Mapping, keys: [&str; N]) {
+    for key in keys {
+        if let Some(value) = from.get(key) {
+            to.insert(key.into(), value.clone());
+        }
+    }
+}
+
+/// The schema of a non-body parameter, header or form field, built from its value keywords.
+fn value_schema(value: &Value) -> Mapping {
+    let mut schema = Mapping::new();
+    copy(value, &mut schema, SCHEMA_KEYWORDS);
+    if let Some(items) = schema.get_mut("items") {
+        if let Some(items) = items.as_mapping_mut() {
+            items.remove("collectionFormat");
+        }
+    }
+    if schema.get("type").and_then(Value::as_str) == Some("file") {
+        schema.insert("type".into(), "string".into());
+        schema.insert("format".into(), "binary".into());
+    }
+    schema
+}
+
+fn upgrade_parameter(parameter: Value) -> Value {
+    let Value::Mapping(mut parameter) = parameter else {
+        return parameter;
+    };
+    if parameter.contains_key("$ref") {
+        return parameter.into();
+    }
+    let schema = value_schema(&parameter.clone().into());
+    for key in SCHEMA_KEYWORDS {
+        parameter.remove(key);
+    }
+    let location = parameter
+        .get("in")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let (style, explode) = match (
+        parameter
+            .remove("collectionFormat")
+            .as_ref()
+            .and_then(Value::as_str),
+        location.as_str(),
+    ) {
+        (Some("multi"), _) => ("form", true),
+        (Some("ssv"), _) => ("spaceDelimited", false),
+        (Some("pipes"), _) => ("pipeDelimited", false),
+        (_, "query") => ("form", false),
+        _ => ("simple", false),
+    };
+    if schema.get("type").and_then(Value::as_str) == Some("array") {
+        parameter.insert("style".into(), style.into());
+        parameter.insert("explode".into(), explode.into());
+    }
+    parameter.insert("schema".into(), schema.into());
+    parameter.into()
+}
+
+fn upgrade_response(response: Value, produces: &[Value]) -> Value {
+    let Value::Mapping(mut response) = response else {
+        return response;
+    };
+    if response.contains_key("$ref") {
+        return response.into();
+    }
+    let examples = response.remove("examples");
+    if let Some(schema) = response.remove("schema") {
+        let content: Mapping = produces
+            .iter()
+            .map(|media_type| {
+                let mut media_type_spec = Mapping::from_iter([("schema".into(), schema.clone())]);
+                if let Some(example) = examples
+                    .as_ref()
+                    .and_then(|examples| examples.get(media_type))
+                {
+                    media_type_spec.insert("example".into(), example.clone());
+                }
+                (media_type.clone(), media_type_spec.into())
+            })
+            .collect();
+        response.insert("content".into(), content.into());
+    }
+    if let Some(Value::Mapping(headers)) = response.get_mut("headers") {
+        for header in headers.values_mut() {
+            let mut upgraded = Mapping::new();
+            copy(header, &mut upgraded, ["description"]);
+            upgraded.insert("schema".into(), value_schema(header).into());
+            *header = upgraded.into();
+        }
+    }
+    response.into()
+}
+
+fn upgrade_security_scheme(scheme: Value) -> Value {
+    let Value::Mapping(mut scheme) = scheme else {
+        return scheme;
+    };
+    match scheme.get("type").and_then(Value::as_str) {
+        Some("basic") => {
+            scheme.insert("type".into(), "http".into());
+            scheme.insert("scheme".into(), "basic".into());
+        }
+        Some("oauth2") => {
+            let flow = scheme.remove("flow");
+            let mut flow_spec = Mapping::new();
+            for key in ["authorizationUrl", "tokenUrl"] {
+                if let Some(url) = scheme.remove(key) {
+                    flow_spec.insert(key.into(), url);
+                }
+            }
+            flow_spec.insert(
+                "scopes".into(),
+                scheme
+                    .remove("scopes")
+                    .unwrap_or_else(|| Mapping::new().into()),
+            );
+            let flow = match flow.as_ref().and_then(Value::as_str) {
+                Some("implicit") => "implicit",
+                Some("password") => "password",
+                Some("application") => "clientCredentials",
+                _ => "authorizationCode",
+            };
+            scheme.insert(
+                "flows".into(),
+                Mapping::from_iter([(flow.into(), flow_spec.into())]).into(),
+            );
+        }
+        _ => (),
+    }
+    scheme.into()
+}
+
+/// Points references at their new home under `components`, and rewrites what 2.0 schemas say
+/// differently: `x-nullable`, `type: file` and a discriminator given as a property name.
+fn upgrade_schemas(value: &mut Value) {
+    match value {
+        Value::Mapping(mapping) => {
+            if let Some(Value::String(reference)) = mapping.get_mut("$ref") {
+                for (from, to) in [
+                    ("#/definitions/", "#/components/schemas/"),
+                    ("#/parameters/", "#/components/parameters/"),
+                    ("#/responses/", "#/components/responses/"),
+                ] {
+                    if let Some(name) = reference.strip_prefix(from) {
+                        *reference = format!("{to}{name}");
+                    }
+                }
+            }
+            if let Some(nullable) = mapping.remove("x-nullable") {
+                mapping.insert("nullable".into(), nullable);
+            }
+            if mapping.get("type").and_then(Value::as_str) == Some("file") {
+                mapping.insert("type".into(), "string".into());
+                mapping.insert("format".into(), "binary".into());
+            }
+            if let Some(Value::String(property)) = mapping.get("discriminator") {
+                let discriminator =
+                    Mapping::from_iter([("propertyName".into(), property.clone().into())]);
+                mapping.insert("discriminator".into(), discriminator.into());
+            }
+            mapping.values_mut().for_each(upgrade_schemas);
+        }
+        Value::Sequence(items) => items.iter_mut().for_each(upgrade_schemas),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod test_swagger_2 {
+    use super::*;
+    use indoc::indoc;
+
+    fn upgraded(document: &str) -> Value {
+        let mut document = serde_yaml::from_str(document).unwrap();
+        upgrade(&mut document);
+        document
+    }
+
+    fn yaml(document: &str) -> Value {
+        serde_yaml::from_str(document).unwrap()
+    }
+
+    #[test]
+    fn detect_swagger_2_documents() {
+        assert!(is_swagger_2(&yaml("swagger: '2.0'")));
+        assert!(!is_swagger_2(&yaml("openapi: 3.0.3")));
+    }
+
+    #[test]
+    fn servers_come_from_host_base_path_and_schemes() {
+        assert_eq!(
+            yaml(indoc!(
+                r#"
+                openapi: 3.0.3
+                servers:
+                  - url: http://api.test.com/v1
+                  - url: https://api.test.com/v1
+                "#
+            )),
+            upgraded(indoc!(
+                r#"
+                swagger: '2.0'
+                host: api.test.com
+                basePath: /v1/
+                schemes: [http, https]
+                "#
+            ))
+        );
+        assert_eq!(
+            yaml("{openapi: 3.0.3, servers: [{url: /v1}]}"),
+            upgraded("{swagger: '2.0', basePath: /v1}")
+        );
+    }
+
+    #[test]
+    fn definitions_parameters_and_responses_move_to_components() {
+        assert_eq!(
+            yaml(indoc!(
+                r#"
+                openapi: 3.0.3
+                components:
+                  schemas:
+                    Pet:
+                      type: object
+                      discriminator:
+                        propertyName: kind
+                      properties:
+                        owner:
+                          $ref: '#/components/schemas/Owner'
+                        tag:
+                          type: string
+                          nullable: true
+                  parameters:
+                    Limit:
+                      in: query
+                      name: limit
+                      schema:
+                        type: integer
+                  requestBodies:
+                    Pet:
+                      required: true
+                      content:
+                        application/json:
+                          schema:
+                            $ref: '#/components/schemas/Pet'
+                  responses:
+                    Pet:
+                      description: A pet
+                      content:
+                        application/json:
+                          schema:
+                            $ref: '#/components/schemas/Pet'
+                  securitySchemes:
+                    basic:
+                      type: http
+                      scheme: basic
+                    oauth:
+                      type: oauth2
+                      flows:
+                        clientCredentials:
+                          tokenUrl: https://test.com/token
+                          scopes:
+                            read: Read pets
+                "#
+            )),
+            upgraded(indoc!(
+                r#"
+                swagger: '2.0'
+                definitions:
+                  Pet:
+                    type: object
+                    discriminator: kind
+                    properties:
+                      owner:
+                        $ref: '#/definitions/Owner'
+                      tag:
+                        type: string
+                        x-nullable: true
+                parameters:
+                  Limit:
+                    in: query
+                    name: limit
+                    type: integer
+                  Pet:
+                    in: body
+                    name: pet
+                    required: true
+                    schema:
+                      $ref: '#/definitions/Pet'
+                responses:
+                  Pet:
+                    description: A pet
+                    schema:
+                      $ref: '#/definitions/Pet'
+                securityDefinitions:
+                  basic:
+                    type: basic
+                  oauth:
+                    type: oauth2
+                    flow: application
+                    tokenUrl: https://test.com/token
+                    scopes:
+                      read: Read pets
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn operations_take_request_bodies_and_content_from_consumes_and_produces() {
+        assert_eq!(
+            yaml(indoc!(
+                r#"
+                openapi: 3.0.3
+                paths:
+                  /pets:
+                    post:
+                      requestBody:
+                        required: true
+                        content:
+                          application/json:
+                            schema:
+                              type: object
+                          application/xml:
+                            schema:
+                              type: object
+                      parameters:
+                        - in: query
+                          name: tags
+                          style: form
+                          explode: true
+                          schema:
+                            type: array
+                            items:
+                              type: string
+                      responses:
+                        200:
+                          description: API call successful
+                          headers:
+                            X-Rate-Limit:
+                              description: Calls left
+                              schema:
+                                type: integer
+                          content:
+                            text/plain:
+                              schema:
+                                type: string
+                              example: Rex
+                "#
+            )),
+            upgraded(indoc!(
+                r#"
+                swagger: '2.0'
+                consumes: [application/json, application/xml]
+                produces: [application/json]
+                paths:
+                  /pets:
+                    post:
+                      produces: [text/plain]
+                      parameters:
+                        - in: body
+                          name: pet
+                          required: true
+                          schema:
+                            type: object
+                        - in: query
+                          name: tags
+                          type: array
+                          collectionFormat: multi
+                          items:
+                            type: string
+                      responses:
+                        200:
+                          description: API call successful
+                          headers:
+                            X-Rate-Limit:
+                              description: Calls left
+                              type: integer
+                          schema:
+                            type: string
+                          examples:
+                            text/plain: Rex
+                "#
+            ))
+        );
+    }
+
+    #[test]
+    fn form_data_parameters_become_an_object_body() {
+        assert_eq!(
+            yaml(indoc!(
+                r#"
+                openapi: 3.0.3
+                paths:
+                  /pets/{id}/photo:
+                    put:
+                      requestBody:
+                        required: true
+                        content:
+                          multipart/form-data:
+                            schema:
+                              type: object
+                              properties:
+                                photo:
+                                  type: string
+                                  format: binary
+                                caption:
+                                  type: string
+                                  maxLength: 20
+                                  description: Shown below the photo
+                              required: [photo]
+                      parameters:
+                        - in: path
+                          name: id
+                          required: true
+                          schema:
+                            type: integer
+                      responses:
+                        204:
+                          description: Photo uploaded
+                "#
+            )),
+            upgraded(indoc!(
+                r#"
+                swagger: '2.0'
+                paths:
+                  /pets/{id}/photo:
+                    parameters:
+                      - in: path
+                        name: id
+                        required: true
+                        type: integer
+                    put:
+                      parameters:
+                        - in: formData
+                          name: photo
+                          required: true
+                          type: file
+                        - in: formData
+                          name: caption
+                          type: string
+                          maxLength: 20
+                          description: Shown below the photo
+                      responses:
+                        204:
+                          description: Photo uploaded
+                "#
+            ))
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_swagger_2_documents {
+    use crate::error::ValidationError;
+    use crate::request::test_helpers::*;
+    use crate::request::Validator;
+    use crate::response::test_responses::FakeResponse;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const DOCUMENT: &str = indoc!(
+        r#"
+        swagger: '2.0'
+        info:
+          title: Pets
+          version: "1.0.0"
+        consumes: [application/json]
+        produces: [application/json]
+        paths:
+          /pets:
+            get:
+              parameters:
+                - $ref: '#/parameters/Limit'
+              responses:
+                200:
+                  description: API call successful
+                  schema:
+                    type: array
+                    items:
+                      $ref: '#/definitions/Pet'
+            post:
+              parameters:
+                - in: body
+                  name: pet
+                  required: true
+                  schema:
+                    $ref: '#/definitions/Pet'
+              responses:
+                201:
+                  description: Pet created
+          /pets/{id}/name:
+            put:
+              consumes: [application/x-www-form-urlencoded]
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  type: integer
+                - in: formData
+                  name: name
+                  required: true
+                  type: string
+              responses:
+                204:
+                  description: Pet renamed
+        parameters:
+          Limit:
+            in: query
+            name: limit
+            type: integer
+            maximum: 10
+        definitions:
+          Pet:
+            type: object
+            required: [name]
+            properties:
+              name:
+                type: string
+        "#
+    );
+
+    fn load() -> Validator {
+        Validator::from_yaml_str(DOCUMENT).unwrap()
+    }
+
+    fn request(operation: &str, url: &str, content_type: &str, body: &str) -> FakeRequest {
+        FakeRequest {
+            url: url.to_string(),
+            operation: operation.to_string(),
+            body: body.as_bytes().to_vec(),
+            headers: HashMap::from([("Content-Type".to_string(), content_type.to_string())]),
+        }
+    }
+
+    #[test]
+    fn validate_body_parameters() {
+        let validator = load();
+        let post = |body| request("post", "http://test.com/pets", "application/json", body);
+        assert!(validator
+            .validate_request(&post(r#"{"name": "Rex"}"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&post("{}")).map(|_| ())
+        );
+        assert_eq!(
+            Err(ValidationError::EmptyJsonBody),
+            validator.validate_request(&post("")).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn validate_form_data_parameters() {
+        let validator = load();
+        let put = |body| {
+            request(
+                "put",
+                "http://test.com/pets/1/name",
+                "application/x-www-form-urlencoded",
+                body,
+            )
+        };
+        assert!(validator.validate_request(&put("name=Rex")).is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidBody),
+            validator.validate_request(&put("nickname=Rex")).map(|_| ())
+        );
+    }
+
+    #[test]
+    fn validate_referenced_parameters() {
+        let validator = load();
+        let get = |url| request("get", url, "application/json", "");
+        assert!(validator
+            .validate_request(&get("http://test.com/pets?limit=5"))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidParameter {
+                name: "limit".to_string()
+            }),
+            validator
+                .validate_request(&get("http://test.com/pets?limit=50"))
+                .map(|_| ())
+        );
+    }
+
+    #[test]
+    fn validate_responses_against_produces() {
+        let validator = load();
+        let response = |body: &str| FakeResponse {
+            status_code: 200,
+            headers: HashMap::from([("Content-Type".to_string(), "application/json".to_string())]),
+            body: body.as_bytes().to_vec(),
+        };
+        let validated = || {
+            validator
+                .validate_request(&request(
+                    "get",
+                    "http://test.com/pets",
+                    "application/json",
+                    "",
+                ))
+                .unwrap()
+        };
+        assert!(validated()
+            .validate_response(&response(r#"[{"name": "Rex"}]"#))
+            .is_ok());
+        assert_eq!(
+            Err(ValidationError::InvalidResponseBody),
+            validated().validate_response(&response("[{}]")).map(|_| ())
+        );
+    }
+}
@@ -0,0 +1,179 @@
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use url::Url;
+
+use crate::error::ValidationError;
+use crate::options::ValidatorOptions;
+use crate::request::{split_path, split_path_strictly, Request};
+
+/// Host origin-form URLs such as `/pets?limit=10`, as servers receive them, are resolved
+/// against. Their origin is left unknown.
+const ORIGIN_FORM_HOST: &str = "origin-form.invalid";
+
+/// The parts of the request URL used during validation, taken from the request when it
+/// provides them and otherwise parsed from [`Request::url`].
+pub struct RequestTarget<'request> {
+    /// `scheme://host[:port]`, known only when the URL was parsed.
+    pub origin: Option<String>,
+    pub path_segments: Vec<&'request str>,
+    pub query_pairs: Vec<(Cow<'request, str>, Cow<'request, str>)>,
+}
+
+impl<'request> RequestTarget<'request> {
+    /// `url` holds the parsed URL for as long as the target borrows from it. It is parsed only
+    /// when the request does not provide every component itself or the origin is needed, and
+    /// only once when the same `url` is shared between validators.
+    pub fn from_request(
+        request: &'request dyn Request,
+        url: &'request OnceCell<Url>,
+        options: &ValidatorOptions,
+    ) -> Result<Self, ValidationError> {
+        let need_origin = options.enforce_server_origin && !options.ignore_servers;
+        let split = match options.strict_path_slashes {
+            true => split_path_strictly,
+            false => split_path,
+        };
+        let path_segments = request.path_segments();
+        let query_pairs = request.query_pairs();
+
+        if url.get().is_none() && (need_origin || path_segments.is_none() || query_pairs.is_none())
+        {
+            let _ = url.set(parse_url(request.url())?);
+        }
+        let url = url.get();
+        let origin = url.filter(|url| url.host_str() != Some(ORIGIN_FORM_HOST));
+
+        Ok(Self {
+            origin: origin.map(|url| url.origin().ascii_serialization()),
+            path_segments: path_segments
+                .or_else(|| url.map(|url| split(url.path())))
+                .unwrap_or_default(),
+            query_pairs: query_pairs
+                .map(|pairs| {
+                    pairs
+                        .into_iter()
+                        .map(|(key, value)| (Cow::Borrowed(key), Cow::Borrowed(value)))
+                        .collect()
+                })
+                .or_else(|| url.map(|url| url.query_pairs().collect()))
+                .unwrap_or_default(),
+        })
+    }
+}
+
+fn parse_url(url: &str) -> Result<Url, ValidationError> {
+    let parsed = match url.starts_with('/') {
+        true => Url::parse(&format!("http://{ORIGIN_FORM_HOST}")).and_then(|base| base.join(url)),
+        false => Url::parse(url),
+    };
+    parsed.map_err(|_| ValidationError::InvalidUrl)
+}
+
+#[cfg(test)]
+mod test_pre_split_request {
+    use crate::options::ValidatorOptions;
+    use crate::request::test_helpers::*;
+    use crate::request::Request;
+    use indoc::indoc;
+    use serde_json::json;
+
+    struct PreSplitRequest {
+        path_segments: Vec<String>,
+        query_pairs: Vec<(String, String)>,
+    }
+
+    impl Request for PreSplitRequest {
+        fn url(&self) -> &str {
+            "not a url"
+        }
+
+        fn operation(&self) -> &str {
+            "get"
+        }
+
+        fn body(&self) -> &[u8] {
+            &[]
+        }
+
+        fn get_header(&self, _: &str) -> Option<String> {
+            None
+        }
+
+        fn path_segments(&self) -> Option<Vec<&str>> {
+            Some(self.path_segments.iter().map(String::as_str).collect())
+        }
+
+        fn query_pairs(&self) -> Option<Vec<(&str, &str)>> {
+            Some(
+                self.query_pairs
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.as_str()))
+                    .collect(),
+            )
+        }
+    }
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /things/{id}:
+            get:
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+                - in: query
+                  name: verbose
+                  required: true
+                  schema:
+                    type: boolean
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    #[test]
+    fn validate_using_provided_components_without_parsing_url() {
+        let request = PreSplitRequest {
+            path_segments: vec!["things".to_string(), "7".to_string()],
+            query_pairs: vec![("verbose".to_string(), "true".to_string())],
+        };
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(Some(&json!(7)), validated.parameters.path.get("id"));
+        assert_eq!(
+            Some(&json!(true)),
+            validated.parameters.query.get("verbose")
+        );
+    }
+
+    #[test]
+    fn reject_invalid_provided_query_pair() {
+        let request = PreSplitRequest {
+            path_segments: vec!["things".to_string(), "7".to_string()],
+            query_pairs: vec![("verbose".to_string(), "sometimes".to_string())],
+        };
+        assert!(make_validator_from_spec(PATH_SPEC)
+            .validate_request(&request)
+            .is_err());
+    }
+
+    #[test]
+    fn validate_an_origin_form_url_whatever_the_server_origin() {
+        let request = FakeRequest {
+            url: "/things/7?verbose=true".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: std::collections::HashMap::new(),
+        };
+        let validator = make_validator_from_spec(PATH_SPEC).with_options(ValidatorOptions {
+            enforce_server_origin: true,
+            ..Default::default()
+        });
+        let validated = validator.validate_request(&request).unwrap();
+        assert_eq!(Some(&json!(7)), validated.parameters.path.get("id"));
+    }
+}
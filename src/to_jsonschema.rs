@@ -1,13 +1,56 @@
 use openapiv3::Type;
 use serde_json::json;
 
+use crate::error::ValidationError;
+use crate::item_or_fetch::ItemOrFetch;
+
+/// Converts an OpenAPI schema to JSON Schema. References to `components.schemas` point into
+/// the `$defs` that [`convert_document`] fills in.
 pub trait ToJSONSchema {
     fn to_json_schema(&self) -> serde_json::Value;
 }
 
 impl ToJSONSchema for openapiv3::Schema {
     fn to_json_schema(&self) -> serde_json::Value {
-        match &self.schema_kind {
+        let mut json = self.schema_kind.to_json_schema();
+        if let (Some(prefix_items), Some(json)) = (
+            self.schema_data.extensions.get(PREFIX_ITEMS),
+            json.as_object_mut(),
+        ) {
+            add_prefix_items(json, prefix_items);
+        }
+        if self.schema_data.nullable {
+            json = allow_null(json);
+        }
+        if let Some(json) = json.as_object_mut() {
+            json.insert_if_some("title", &self.schema_data.title);
+            json.insert_if_some("description", &self.schema_data.description);
+            json.insert_if_some(
+                "examples",
+                &self
+                    .schema_data
+                    .extensions
+                    .get(EXAMPLES)
+                    .cloned()
+                    .or_else(|| {
+                        self.schema_data
+                            .example
+                            .as_ref()
+                            .map(|example| json!([example]))
+                    }),
+            );
+            json.insert_if_true("deprecated", self.schema_data.deprecated);
+            json.insert_if_some("default", &self.schema_data.default);
+            json.insert_if_true("readOnly", self.schema_data.read_only);
+            json.insert_if_true("writeOnly", self.schema_data.write_only);
+        }
+        json
+    }
+}
+
+impl ToJSONSchema for openapiv3::SchemaKind {
+    fn to_json_schema(&self) -> serde_json::Value {
+        match self {
             openapiv3::SchemaKind::Type(Type::Boolean {}) => json!({"type": "boolean"}),
             openapiv3::SchemaKind::Type(Type::String(string_schema)) => {
                 string_schema.to_json_schema()
@@ -42,21 +85,61 @@ impl ToJSONSchema for openapiv3::Schema {
                 json.insert("not".to_string(), not.to_json_schema());
                 json.into()
             }
-            _ => todo!(),
+            openapiv3::SchemaKind::Any(any_schema) => any_schema.to_json_schema(),
         }
     }
 }
 
+/// Extensions carrying OpenAPI 3.1 keywords the 3.0 model has no field for.
+pub(crate) const PREFIX_ITEMS: &str = "x-prefixItems";
+pub(crate) const EXAMPLES: &str = "x-examples";
+
+/// Turns an array schema into the draft 7 form of a tuple: `items` lists the schemas of the
+/// leading items and `additionalItems` takes over what `items` said about the rest.
+fn add_prefix_items(
+    json: &mut serde_json::Map<String, serde_json::Value>,
+    prefix_items: &serde_json::Value,
+) {
+    let Ok(prefix_items) = serde_json::from_value::<Vec<openapiv3::ReferenceOr<openapiv3::Schema>>>(
+        prefix_items.clone(),
+    ) else {
+        return;
+    };
+    if let Some(items) = json.remove("items") {
+        json.insert("additionalItems".to_string(), items);
+    }
+    json.insert("items".to_string(), prefix_items.to_json_schema());
+}
+
+/// Widens a converted schema to also accept `null`, the JSON Schema form of `nullable: true`.
+fn allow_null(mut json: serde_json::Value) -> serde_json::Value {
+    match json.get("type").and_then(serde_json::Value::as_str) {
+        Some(kind) => {
+            let kind = kind.to_string();
+            json["type"] = json!([kind, "null"]);
+            if let Some(enumeration) = json
+                .get_mut("enum")
+                .and_then(serde_json::Value::as_array_mut)
+                .filter(|enumeration| !enumeration.contains(&serde_json::Value::Null))
+            {
+                enumeration.push(serde_json::Value::Null);
+            }
+            json
+        }
+        None => json!({"anyOf": [json, {"type": "null"}]}),
+    }
+}
+
 impl ToJSONSchema for openapiv3::StringType {
     fn to_json_schema(&self) -> serde_json::Value {
         let mut json = serde_json::Map::new();
         json.insert("type".to_string(), serde_json::Value::from("string"));
         json.insert_if_some("minLength", &self.min_length);
         json.insert_if_some("maxLength", &self.max_length);
-        json.insert_if_not_empty("enum", &self.enumeration);
+        json.insert_enum(&self.enumeration);
         json.insert_if_some("pattern", &self.pattern);
-        if let openapiv3::VariantOrUnknownOrEmpty::Item(format) = &self.format {
-            match format {
+        match &self.format {
+            openapiv3::VariantOrUnknownOrEmpty::Item(format) => match format {
                 openapiv3::StringFormat::DateTime => {
                     json.insert("format".to_string(), "date-time".into());
                 }
@@ -72,7 +155,11 @@ impl ToJSONSchema for openapiv3::StringType {
                 openapiv3::StringFormat::Binary => {
                     json.insert("format".to_string(), "binary".into());
                 }
+            },
+            openapiv3::VariantOrUnknownOrEmpty::Unknown(format) => {
+                json.insert("format".to_string(), format.as_str().into());
             }
+            openapiv3::VariantOrUnknownOrEmpty::Empty => (),
         }
         json.into()
     }
@@ -82,12 +169,32 @@ impl ToJSONSchema for openapiv3::NumberType {
     fn to_json_schema(&self) -> serde_json::Value {
         let mut json = serde_json::Map::new();
         json.insert("type".to_string(), serde_json::Value::from("number"));
-        json.insert_if_some("minimum", &self.minimum);
-        json.insert_if_some("maximum", &self.maximum);
-        json.insert_if_true("exclusiveMinimum", self.exclusive_minimum);
-        json.insert_if_true("exclusiveMaximum", self.exclusive_maximum);
+        json.insert_bound("minimum", &self.minimum, self.exclusive_minimum);
+        json.insert_bound("maximum", &self.maximum, self.exclusive_maximum);
         json.insert_if_some("multipleOf", &self.multiple_of);
-        json.insert_if_not_empty("enum", &self.enumeration);
+        json.insert_enum(&self.enumeration);
+        match &self.format {
+            openapiv3::VariantOrUnknownOrEmpty::Item(openapiv3::NumberFormat::Float) => {
+                json.insert("format".to_string(), "float".into());
+                // Every JSON number parses to a double, so only floats need bounds.
+                let highest = f64::from(f32::MAX);
+                if self.minimum.is_none_or(|minimum| minimum < -highest) {
+                    json.insert("minimum".to_string(), (-highest).into());
+                    json.remove("exclusiveMinimum");
+                }
+                if self.maximum.is_none_or(|maximum| maximum > highest) {
+                    json.insert("maximum".to_string(), highest.into());
+                    json.remove("exclusiveMaximum");
+                }
+            }
+            openapiv3::VariantOrUnknownOrEmpty::Item(openapiv3::NumberFormat::Double) => {
+                json.insert("format".to_string(), "double".into());
+            }
+            openapiv3::VariantOrUnknownOrEmpty::Unknown(format) => {
+                json.insert("format".to_string(), format.as_str().into());
+            }
+            openapiv3::VariantOrUnknownOrEmpty::Empty => (),
+        }
         json.into()
     }
 }
@@ -96,12 +203,32 @@ impl ToJSONSchema for openapiv3::IntegerType {
     fn to_json_schema(&self) -> serde_json::Value {
         let mut json = serde_json::Map::new();
         json.insert("type".to_string(), serde_json::Value::from("integer"));
-        json.insert_if_some("minimum", &self.minimum);
-        json.insert_if_some("maximum", &self.maximum);
-        json.insert_if_true("exclusiveMinimum", self.exclusive_minimum);
-        json.insert_if_true("exclusiveMaximum", self.exclusive_maximum);
+        json.insert_bound("minimum", &self.minimum, self.exclusive_minimum);
+        json.insert_bound("maximum", &self.maximum, self.exclusive_maximum);
         json.insert_if_some("multipleOf", &self.multiple_of);
-        json.insert_if_not_empty("enum", &self.enumeration);
+        json.insert_enum(&self.enumeration);
+        match &self.format {
+            openapiv3::VariantOrUnknownOrEmpty::Item(format) => {
+                let (name, lowest, highest) = match format {
+                    openapiv3::IntegerFormat::Int32 => ("int32", i32::MIN.into(), i32::MAX.into()),
+                    openapiv3::IntegerFormat::Int64 => ("int64", i64::MIN, i64::MAX),
+                };
+                json.insert("format".to_string(), name.into());
+                // The format's range applies unless the schema is already narrower.
+                if self.minimum.is_none_or(|minimum| minimum < lowest) {
+                    json.insert("minimum".to_string(), lowest.into());
+                    json.remove("exclusiveMinimum");
+                }
+                if self.maximum.is_none_or(|maximum| maximum > highest) {
+                    json.insert("maximum".to_string(), highest.into());
+                    json.remove("exclusiveMaximum");
+                }
+            }
+            openapiv3::VariantOrUnknownOrEmpty::Unknown(format) => {
+                json.insert("format".to_string(), format.as_str().into());
+            }
+            openapiv3::VariantOrUnknownOrEmpty::Empty => (),
+        }
         json.into()
     }
 }
@@ -115,11 +242,7 @@ impl ToJSONSchema for openapiv3::ArrayType {
         json.insert_if_true("uniqueItems", self.unique_items);
         json.insert_if_some(
             "items",
-            &self
-                .items
-                .as_ref()
-                .and_then(openapiv3::ReferenceOr::as_item)
-                .map(|schema| schema.to_json_schema()),
+            &self.items.as_ref().map(|schema| schema.to_json_schema()),
         );
         json.into()
     }
@@ -131,24 +254,95 @@ impl ToJSONSchema for openapiv3::ObjectType {
         json.insert("type".to_string(), serde_json::Value::from("object"));
         json.insert_if_some("minProperties", &self.min_properties);
         json.insert_if_some("maxProperties", &self.max_properties);
-        if let Some(additional_properties) = &self.additional_properties {
-            json.insert(
-                "additionalProperties".to_string(),
-                match additional_properties {
-                    openapiv3::AdditionalProperties::Any(value) => value.clone().into(),
-                    openapiv3::AdditionalProperties::Schema(schema) => schema.to_json_schema(),
-                },
-            );
+        json.insert_if_some(
+            "additionalProperties",
+            &self
+                .additional_properties
+                .as_ref()
+                .map(|additional_properties| additional_properties.to_json_schema()),
+        );
+        json.insert_if_map_not_empty("properties", &self.properties);
+        json.insert_if_not_empty("required", &self.required);
+        json.into()
+    }
+}
+
+impl ToJSONSchema for openapiv3::AdditionalProperties {
+    fn to_json_schema(&self) -> serde_json::Value {
+        match self {
+            openapiv3::AdditionalProperties::Any(value) => (*value).into(),
+            openapiv3::AdditionalProperties::Schema(schema) => schema.to_json_schema(),
         }
+    }
+}
+
+/// A schema that does not fit any single type, such as `{}` or one mixing keywords of several
+/// types. It accepts any value the keywords present allow.
+impl ToJSONSchema for openapiv3::AnySchema {
+    fn to_json_schema(&self) -> serde_json::Value {
+        let mut json = serde_json::Map::new();
+        json.insert_if_some("type", &self.typ);
+        json.insert_if_some("format", &self.format);
+        json.insert_if_some("pattern", &self.pattern);
+        json.insert_if_some("minLength", &self.min_length);
+        json.insert_if_some("maxLength", &self.max_length);
+        json.insert_bound(
+            "minimum",
+            &self.minimum,
+            self.exclusive_minimum.unwrap_or(false),
+        );
+        json.insert_bound(
+            "maximum",
+            &self.maximum,
+            self.exclusive_maximum.unwrap_or(false),
+        );
+        json.insert_if_some("multipleOf", &self.multiple_of);
+        json.insert_if_some("minProperties", &self.min_properties);
+        json.insert_if_some("maxProperties", &self.max_properties);
+        json.insert_if_some(
+            "additionalProperties",
+            &self
+                .additional_properties
+                .as_ref()
+                .map(|additional_properties| additional_properties.to_json_schema()),
+        );
         json.insert_if_map_not_empty("properties", &self.properties);
         json.insert_if_not_empty("required", &self.required);
+        json.insert_if_some("minItems", &self.min_items);
+        json.insert_if_some("maxItems", &self.max_items);
+        json.insert_if_true("uniqueItems", self.unique_items.unwrap_or(false));
+        json.insert_if_some(
+            "items",
+            &self.items.as_ref().map(|schema| schema.to_json_schema()),
+        );
+        json.insert_if_not_empty("enum", &self.enumeration);
+        if !self.one_of.is_empty() {
+            json.insert("oneOf".to_string(), self.one_of.to_json_schema());
+        }
+        if !self.all_of.is_empty() {
+            json.insert("allOf".to_string(), self.all_of.to_json_schema());
+        }
+        if !self.any_of.is_empty() {
+            json.insert("anyOf".to_string(), self.any_of.to_json_schema());
+        }
+        json.insert_if_some("not", &self.not.as_ref().map(|not| not.to_json_schema()));
         json.into()
     }
 }
 
-impl<T: ToJSONSchema + Clone> ToJSONSchema for openapiv3::ReferenceOr<T> {
+/// References to component schemas become pointers into the `$defs` of the document built by
+/// [`schema_document`]; other references are kept as they are.
+impl<T: ToJSONSchema> ToJSONSchema for openapiv3::ReferenceOr<T> {
     fn to_json_schema(&self) -> serde_json::Value {
-        self.clone().as_item().unwrap().to_json_schema()
+        match self {
+            openapiv3::ReferenceOr::Item(item) => item.to_json_schema(),
+            openapiv3::ReferenceOr::Reference { reference } => {
+                match reference.strip_prefix(COMPONENT_SCHEMAS) {
+                    Some(name) => json!({"$ref": format!("{DEFINITIONS}{name}")}),
+                    None => json!({"$ref": reference}),
+                }
+            }
+        }
     }
 }
 
@@ -164,6 +358,179 @@ impl<T: ToJSONSchema> ToJSONSchema for Box<T> {
     }
 }
 
+const COMPONENT_SCHEMAS: &str = "#/components/schemas/";
+pub(crate) const DEFINITIONS: &str = "#/$defs/";
+
+/// Which side of an exchange a body schema validates. Properties marked `readOnly` are only
+/// sent by servers and `writeOnly` ones only by clients, so each is neither required nor
+/// accepted in the other direction.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Direction {
+    /// Validating a request body. With `strip_read_only`, `readOnly` properties are ignored
+    /// instead of rejected.
+    Request {
+        strip_read_only: bool,
+    },
+    Response,
+}
+
+/// Converts a schema, or the component schema it refers to, into a self-contained JSON Schema
+/// document.
+pub(crate) fn schema_document(
+    schema: &openapiv3::ReferenceOr<openapiv3::Schema>,
+    components: &Option<openapiv3::Components>,
+    direction: Option<Direction>,
+) -> Result<serde_json::Value, ValidationError> {
+    Ok(convert_document(
+        schema.item_or_fetch(components)?,
+        components.as_ref(),
+        direction,
+    ))
+}
+
+/// Every component schema the document refers to, directly or through other components, is
+/// converted once into `$defs`, so shared and recursive schemas compile without being inlined
+/// at each use.
+pub(crate) fn convert_document(
+    schema: &openapiv3::Schema,
+    components: Option<&openapiv3::Components>,
+    direction: Option<Direction>,
+) -> serde_json::Value {
+    let mut document = schema.to_json_schema();
+
+    let mut definitions = serde_json::Map::new();
+    let mut pending = vec![];
+    collect_definitions(&document, &mut pending);
+    while let Some(name) = pending.pop() {
+        if definitions.contains_key(&name) {
+            continue;
+        }
+        let Some(schema) = components.and_then(|components| components.schemas.get(&name)) else {
+            continue;
+        };
+        let definition = schema.to_json_schema();
+        collect_definitions(&definition, &mut pending);
+        definitions.insert(name, definition);
+    }
+
+    if let (false, Some(document)) = (definitions.is_empty(), document.as_object_mut()) {
+        document.insert("$defs".to_string(), definitions.into());
+    }
+    if let Some(direction) = direction {
+        restrict_to(&mut document, direction);
+    }
+    document
+}
+
+/// Drops the properties a direction does not carry from `required`, and replaces their schema
+/// with one rejecting any value, or accepting any when read-only properties are stripped.
+fn restrict_to(document: &mut serde_json::Value, direction: Direction) {
+    let (flag, accept) = match direction {
+        Direction::Request { strip_read_only } => ("readOnly", strip_read_only),
+        Direction::Response => ("writeOnly", false),
+    };
+    let definitions = document.get("$defs").cloned().unwrap_or_else(|| json!({}));
+    restrict_properties(
+        document,
+        &|schema| is_flagged(schema, flag, &definitions),
+        accept,
+    );
+}
+
+/// Whether a converted schema, or the definition it points to, carries `flag`.
+fn is_flagged(schema: &serde_json::Value, flag: &str, definitions: &serde_json::Value) -> bool {
+    let mut schema = schema;
+    // a chain of references can be no longer than the number of definitions
+    for _ in 0..=definitions.as_object().map_or(0, serde_json::Map::len) {
+        if schema.get(flag) == Some(&serde_json::Value::Bool(true)) {
+            return true;
+        }
+        let Some(name) = schema
+            .get("$ref")
+            .and_then(serde_json::Value::as_str)
+            .and_then(|reference| reference.strip_prefix(DEFINITIONS))
+        else {
+            return false;
+        };
+        match definitions.get(name.replace("~1", "/").replace("~0", "~")) {
+            Some(definition) => schema = definition,
+            None => return false,
+        }
+    }
+    false
+}
+
+fn restrict_properties(
+    json: &mut serde_json::Value,
+    is_excluded: &dyn Fn(&serde_json::Value) -> bool,
+    accept: bool,
+) {
+    match json {
+        serde_json::Value::Object(object) => {
+            let excluded: Vec<String> = object
+                .get("properties")
+                .and_then(serde_json::Value::as_object)
+                .map(|properties| {
+                    properties
+                        .iter()
+                        .filter(|(_, schema)| is_excluded(schema))
+                        .map(|(name, _)| name.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            for name in &excluded {
+                object["properties"][name] = accept.into();
+            }
+            if let Some(required) = object
+                .get_mut("required")
+                .and_then(serde_json::Value::as_array_mut)
+            {
+                required.retain(|name| {
+                    !name
+                        .as_str()
+                        .is_some_and(|name| excluded.contains(&name.to_string()))
+                });
+                if required.is_empty() {
+                    object.remove("required");
+                }
+            }
+            for value in object.values_mut() {
+                restrict_properties(value, is_excluded, accept);
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                restrict_properties(item, is_excluded, accept);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// Names of the definitions a converted schema points to, unescaped from their pointers.
+fn collect_definitions(json: &serde_json::Value, names: &mut Vec<String>) {
+    match json {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                match (key.as_str(), value.as_str()) {
+                    ("$ref", Some(reference)) => {
+                        if let Some(name) = reference.strip_prefix(DEFINITIONS) {
+                            names.push(name.replace("~1", "/").replace("~0", "~"));
+                        }
+                    }
+                    _ => collect_definitions(value, names),
+                }
+            }
+        }
+        serde_json::Value::Array(items) => {
+            for item in items {
+                collect_definitions(item, names);
+            }
+        }
+        _ => (),
+    }
+}
+
 trait InsertIf {
     fn insert_if_some<T: Into<serde_json::Value> + Clone>(
         &mut self,
@@ -171,12 +538,15 @@ trait InsertIf {
         optional_value: &Option<T>,
     );
     fn insert_if_true(&mut self, key: &str, value: bool);
-    fn insert_if_not_empty<T: Into<serde_json::Value> + Clone>(
+    fn insert_bound<T: Into<serde_json::Value> + Clone>(
         &mut self,
         key: &str,
-        value: &Vec<T>,
+        bound: &Option<T>,
+        exclusive: bool,
     );
-    fn insert_if_map_not_empty<T: ToJSONSchema + Clone>(
+    fn insert_if_not_empty<T: Into<serde_json::Value> + Clone>(&mut self, key: &str, value: &[T]);
+    fn insert_enum<T: Into<serde_json::Value> + Clone>(&mut self, enumeration: &[Option<T>]);
+    fn insert_if_map_not_empty<T: ToJSONSchema>(
         &mut self,
         key: &str,
         value: &indexmap::map::IndexMap<String, T>,
@@ -200,17 +570,46 @@ impl InsertIf for serde_json::Map<String, serde_json::Value> {
         }
     }
 
-    fn insert_if_not_empty<T: Into<serde_json::Value> + Clone>(
+    /// OpenAPI 3.0 marks a `minimum` or `maximum` as exclusive with a flag; JSON Schema gives
+    /// an exclusive bound its own keyword instead, e.g. `exclusiveMinimum: 2`.
+    fn insert_bound<T: Into<serde_json::Value> + Clone>(
         &mut self,
         key: &str,
-        value: &Vec<T>,
+        bound: &Option<T>,
+        exclusive: bool,
     ) {
+        if let Some(bound) = bound {
+            let key = match (exclusive, key) {
+                (true, "minimum") => "exclusiveMinimum".to_string(),
+                (true, "maximum") => "exclusiveMaximum".to_string(),
+                _ => key.to_string(),
+            };
+            self.insert(key, bound.clone().into());
+        }
+    }
+
+    fn insert_if_not_empty<T: Into<serde_json::Value> + Clone>(&mut self, key: &str, value: &[T]) {
         if !value.is_empty() {
-            self.insert(key.to_string(), value.clone().into());
+            self.insert(key.to_string(), value.to_vec().into());
         }
     }
 
-    fn insert_if_map_not_empty<T: ToJSONSchema + Clone>(
+    /// `null` entries are left out: they only take effect for a `nullable` schema, whose
+    /// conversion adds `null` back. An enumeration of nothing but `null` still restricts the
+    /// schema, to no values at all.
+    fn insert_enum<T: Into<serde_json::Value> + Clone>(&mut self, enumeration: &[Option<T>]) {
+        if !enumeration.is_empty() {
+            let values: Vec<serde_json::Value> = enumeration
+                .iter()
+                .flatten()
+                .cloned()
+                .map(Into::into)
+                .collect();
+            self.insert("enum".to_string(), values.into());
+        }
+    }
+
+    fn insert_if_map_not_empty<T: ToJSONSchema>(
         &mut self,
         key: &str,
         value: &indexmap::map::IndexMap<String, T>,
@@ -440,6 +839,7 @@ mod test_string {
 #[cfg(test)]
 mod test_number {
     use super::*;
+    use crate::jsonschema::{JSONSchemaValidator, SchemaFailure};
     use openapiv3::NumberType;
 
     #[test]
@@ -518,7 +918,7 @@ mod test_number {
                 }))
             }
             .to_json_schema(),
-            json!({"type": "number", "minimum": 2.1, "maximum": 5.6, "exclusiveMinimum": true, "exclusiveMaximum": true})
+            json!({"type": "number", "exclusiveMinimum": 2.1, "exclusiveMaximum": 5.6})
         )
     }
 
@@ -561,11 +961,64 @@ mod test_number {
             json!({"type": "number", "enum": [1.1, 2.2]})
         )
     }
+
+    fn with_format(format: openapiv3::NumberFormat, maximum: Option<f64>) -> openapiv3::Schema {
+        openapiv3::Schema {
+            schema_data: Default::default(),
+            schema_kind: openapiv3::SchemaKind::Type(Type::Number(NumberType {
+                format: openapiv3::VariantOrUnknownOrEmpty::Item(format),
+                multiple_of: None,
+                exclusive_minimum: false,
+                exclusive_maximum: false,
+                minimum: None,
+                maximum,
+                enumeration: vec![],
+            })),
+        }
+    }
+
+    #[test]
+    fn float_bounds() {
+        assert_eq!(
+            with_format(openapiv3::NumberFormat::Float, Some(1.5)).to_json_schema(),
+            json!({
+                "type": "number",
+                "format": "float",
+                "minimum": -f64::from(f32::MAX),
+                "maximum": 1.5
+            })
+        )
+    }
+
+    #[test]
+    fn double() {
+        assert_eq!(
+            with_format(openapiv3::NumberFormat::Double, None).to_json_schema(),
+            json!({"type": "number", "format": "double"})
+        )
+    }
+
+    #[test]
+    fn reject_values_overflowing_a_float() {
+        let schema = with_format(openapiv3::NumberFormat::Float, None).to_json_schema();
+        let options = Default::default();
+
+        assert_eq!(Ok(()), schema.validates_value(&json!(3.0e38), &options));
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(3.5e38), &options)
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(-3.5e38), &options)
+        );
+    }
 }
 
 #[cfg(test)]
 mod test_integer {
     use super::*;
+    use crate::jsonschema::{JSONSchemaValidator, SchemaFailure};
     use openapiv3::IntegerType;
 
     #[test]
@@ -644,7 +1097,7 @@ mod test_integer {
                 }))
             }
             .to_json_schema(),
-            json!({"type": "integer", "minimum": 2, "maximum": 5, "exclusiveMinimum": true, "exclusiveMaximum": true})
+            json!({"type": "integer", "exclusiveMinimum": 2, "exclusiveMaximum": 5})
         )
     }
 
@@ -687,6 +1140,83 @@ mod test_integer {
             json!({"type": "integer", "enum": [1, 2]})
         )
     }
+
+    fn with_format(format: openapiv3::IntegerFormat, minimum: Option<i64>) -> openapiv3::Schema {
+        openapiv3::Schema {
+            schema_data: Default::default(),
+            schema_kind: openapiv3::SchemaKind::Type(Type::Integer(IntegerType {
+                format: openapiv3::VariantOrUnknownOrEmpty::Item(format),
+                multiple_of: None,
+                exclusive_minimum: minimum.is_some(),
+                exclusive_maximum: false,
+                minimum,
+                maximum: None,
+                enumeration: vec![],
+            })),
+        }
+    }
+
+    #[test]
+    fn int32_bounds() {
+        assert_eq!(
+            with_format(openapiv3::IntegerFormat::Int32, None).to_json_schema(),
+            json!({
+                "type": "integer",
+                "format": "int32",
+                "minimum": i32::MIN,
+                "maximum": i32::MAX
+            })
+        )
+    }
+
+    #[test]
+    fn int64_bounds() {
+        assert_eq!(
+            with_format(openapiv3::IntegerFormat::Int64, None).to_json_schema(),
+            json!({
+                "type": "integer",
+                "format": "int64",
+                "minimum": i64::MIN,
+                "maximum": i64::MAX
+            })
+        )
+    }
+
+    #[test]
+    fn narrower_bounds_are_kept() {
+        assert_eq!(
+            with_format(openapiv3::IntegerFormat::Int32, Some(0)).to_json_schema(),
+            json!({
+                "type": "integer",
+                "format": "int32",
+                "exclusiveMinimum": 0,
+                "maximum": i32::MAX
+            })
+        )
+    }
+
+    #[test]
+    fn reject_values_overflowing_the_format() {
+        let schema = with_format(openapiv3::IntegerFormat::Int32, None).to_json_schema();
+        let options = Default::default();
+
+        assert_eq!(Ok(()), schema.validates_value(&json!(i32::MAX), &options));
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(i64::from(i32::MAX) + 1), &options)
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(i64::from(i32::MIN) - 1), &options)
+        );
+
+        let schema = with_format(openapiv3::IntegerFormat::Int64, None).to_json_schema();
+        assert_eq!(Ok(()), schema.validates_value(&json!(i64::MAX), &options));
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(u64::MAX), &options)
+        );
+    }
 }
 
 #[cfg(test)]
@@ -895,13 +1425,13 @@ mod test_array {
     }
 
     #[test]
-    fn invalid_items() {
+    fn referenced_items() {
         assert_eq!(
             openapiv3::Schema {
                 schema_data: Default::default(),
                 schema_kind: openapiv3::SchemaKind::Type(Type::Array(ArrayType {
                     items: Some(ReferenceOr::Reference {
-                        reference: "not valid".to_string()
+                        reference: "#/components/schemas/NotThere".to_string()
                     }),
                     min_items: None,
                     max_items: None,
@@ -909,7 +1439,7 @@ mod test_array {
                 }))
             }
             .to_json_schema(),
-            json!({"type": "array"})
+            json!({"type": "array", "items": {"$ref": "#/$defs/NotThere"}})
         )
     }
 }
@@ -1162,7 +1692,7 @@ mod test_object {
             openapiv3::Schema {
                 schema_data: Default::default(),
                 schema_kind: openapiv3::SchemaKind::Type(Type::Object(ObjectType {
-                    properties: properties,
+                    properties,
                     required: vec!["is_working".to_string()],
                     additional_properties: Some(openapiv3::AdditionalProperties::Any(false)),
                     min_properties: None,
@@ -1424,7 +1954,7 @@ mod test_validation {
 
         let instance = json!(true);
         let schema = JSONSchema::compile(&schema_json).expect("a valid schema");
-        assert_eq!(true, schema.is_valid(&instance));
+        assert!(schema.is_valid(&instance));
     }
 
     #[test]
@@ -1448,8 +1978,8 @@ mod test_validation {
         let good_json = json!("length");
         let bad_json = json!("length_too_long");
         let schema = JSONSchema::compile(&schema_json).expect("a valid schema");
-        assert_eq!(true, schema.is_valid(&good_json));
-        assert_eq!(false, schema.is_valid(&bad_json));
+        assert!(schema.is_valid(&good_json));
+        assert!(!schema.is_valid(&bad_json));
     }
 
     #[test]
@@ -1492,8 +2022,308 @@ mod test_validation {
         let wrong_key = json!({"not_count": 10.1});
         let wrong_type = json!({"count": "string"});
         let schema = JSONSchema::compile(&schema_json).expect("a valid schema");
-        assert_eq!(true, schema.is_valid(&good_json));
-        assert_eq!(false, schema.is_valid(&wrong_key));
-        assert_eq!(false, schema.is_valid(&wrong_type));
+        assert!(schema.is_valid(&good_json));
+        assert!(!schema.is_valid(&wrong_key));
+        assert!(!schema.is_valid(&wrong_type));
+    }
+}
+
+#[cfg(test)]
+mod test_nullable {
+    use super::*;
+
+    fn convert(schema: &str) -> serde_json::Value {
+        serde_yaml::from_str::<openapiv3::Schema>(schema)
+            .unwrap()
+            .to_json_schema()
+    }
+
+    #[test]
+    fn typed_schema() {
+        assert_eq!(
+            json!({"type": ["string", "null"]}),
+            convert("type: string\nnullable: true\n")
+        );
+    }
+
+    #[test]
+    fn enumeration_accepts_null() {
+        assert_eq!(
+            json!({"type": ["string", "null"], "enum": ["a", "b", null]}),
+            convert("type: string\nnullable: true\nenum: [a, b]\n")
+        );
+    }
+
+    #[test]
+    fn listed_null_is_kept_once() {
+        assert_eq!(
+            json!({"type": ["string", "null"], "enum": ["a", null]}),
+            convert("type: string\nnullable: true\nenum: [a, null]\n")
+        );
+    }
+
+    #[test]
+    fn listed_null_is_dropped_without_nullable() {
+        assert_eq!(
+            json!({"type": "integer", "enum": [1, 2]}),
+            convert("type: integer\nenum: [1, null, 2]\n")
+        );
+    }
+
+    #[test]
+    fn enumeration_of_only_null_without_nullable_accepts_nothing() {
+        let schema = convert("type: string\nenum: [null]\n");
+        assert_eq!(json!({"type": "string", "enum": []}), schema);
+        assert_eq!(
+            Err(crate::jsonschema::SchemaFailure::Invalid),
+            crate::jsonschema::JSONSchemaValidator::validates_value(
+                &schema,
+                &json!(null),
+                &Default::default()
+            )
+        );
+    }
+
+    #[test]
+    fn composite_schema() {
+        assert_eq!(
+            json!({"anyOf": [
+                {"oneOf": [{"type": "string"}, {"type": "integer"}]},
+                {"type": "null"}
+            ]}),
+            convert("nullable: true\noneOf:\n  - type: string\n  - type: integer\n")
+        );
+    }
+}
+
+#[cfg(test)]
+mod test_references {
+    use super::*;
+
+    fn components() -> Option<openapiv3::Components> {
+        serde_yaml::from_str(indoc::indoc!(
+            r#"
+            schemas:
+              Name:
+                type: string
+                minLength: 1
+              Tag:
+                type: object
+                properties:
+                  name:
+                    $ref: '#/components/schemas/Name'
+              Alias:
+                $ref: '#/components/schemas/Name'
+              Node:
+                type: object
+                properties:
+                  children:
+                    type: array
+                    items:
+                      $ref: '#/components/schemas/Node'
+              a/b:
+                type: boolean
+            "#
+        ))
+        .unwrap()
+    }
+
+    fn convert(schema: &str) -> serde_json::Value {
+        schema_document(&serde_yaml::from_str(schema).unwrap(), &components(), None).unwrap()
+    }
+
+    fn name() -> serde_json::Value {
+        json!({"type": "string", "minLength": 1})
+    }
+
+    #[test]
+    fn top_level_reference_is_resolved() {
+        assert_eq!(name(), convert("$ref: '#/components/schemas/Name'"));
+    }
+
+    #[test]
+    fn reference_to_a_reference() {
+        assert_eq!(
+            json!({
+                "type": "array",
+                "items": {"$ref": "#/$defs/Alias"},
+                "$defs": {"Alias": {"$ref": "#/$defs/Name"}, "Name": name()}
+            }),
+            convert("type: array\nitems:\n  $ref: '#/components/schemas/Alias'\n")
+        );
+    }
+
+    #[test]
+    fn nested_properties() {
+        assert_eq!(
+            json!({
+                "type": "object",
+                "properties": {"tag": {"$ref": "#/$defs/Tag"}},
+                "$defs": {
+                    "Tag": {
+                        "type": "object",
+                        "properties": {"name": {"$ref": "#/$defs/Name"}}
+                    },
+                    "Name": name()
+                }
+            }),
+            convert("type: object\nproperties:\n  tag:\n    $ref: '#/components/schemas/Tag'\n")
+        );
+    }
+
+    #[test]
+    fn additional_properties() {
+        assert_eq!(
+            json!({
+                "type": "object",
+                "additionalProperties": {"$ref": "#/$defs/Name"},
+                "$defs": {"Name": name()}
+            }),
+            convert("type: object\nadditionalProperties:\n  $ref: '#/components/schemas/Name'\n")
+        );
+    }
+
+    #[test]
+    fn composite_members() {
+        for keyword in ["oneOf", "allOf", "anyOf"] {
+            assert_eq!(
+                json!({
+                    keyword: [{"$ref": "#/$defs/Name"}, {"type": "integer"}],
+                    "$defs": {"Name": name()}
+                }),
+                convert(&format!(
+                    "{keyword}:\n  - $ref: '#/components/schemas/Name'\n  - type: integer\n"
+                ))
+            );
+        }
+    }
+
+    #[test]
+    fn negated_reference() {
+        assert_eq!(
+            json!({"not": {"$ref": "#/$defs/Name"}, "$defs": {"Name": name()}}),
+            convert("not:\n  $ref: '#/components/schemas/Name'\n")
+        );
+    }
+
+    #[test]
+    fn recursive_schema_is_defined_once() {
+        assert_eq!(
+            json!({
+                "type": "object",
+                "properties": {
+                    "children": {"type": "array", "items": {"$ref": "#/$defs/Node"}}
+                },
+                "$defs": {
+                    "Node": {
+                        "type": "object",
+                        "properties": {
+                            "children": {"type": "array", "items": {"$ref": "#/$defs/Node"}}
+                        }
+                    }
+                }
+            }),
+            convert("$ref: '#/components/schemas/Node'")
+        );
+    }
+
+    #[test]
+    fn escaped_names() {
+        assert_eq!(
+            json!({"not": {"$ref": "#/$defs/a~1b"}, "$defs": {"a/b": {"type": "boolean"}}}),
+            convert("not:\n  $ref: '#/components/schemas/a~1b'\n")
+        );
+    }
+
+    #[test]
+    fn recursive_schema_compiles_and_validates() {
+        use crate::jsonschema::{JSONSchemaValidator, SchemaFailure};
+
+        let schema = convert("$ref: '#/components/schemas/Node'");
+        assert_eq!(
+            Ok(()),
+            schema.validates_value(
+                &json!({"children": [{"children": []}, {}]}),
+                &Default::default()
+            )
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(
+                &json!({"children": [{"children": [1]}]}),
+                &Default::default()
+            )
+        );
+    }
+
+    #[test]
+    fn escaped_names_compile_and_validate() {
+        use crate::jsonschema::{JSONSchemaValidator, SchemaFailure};
+
+        let schema = convert("not:\n  $ref: '#/components/schemas/a~1b'\n");
+        assert_eq!(
+            Ok(()),
+            schema.validates_value(&json!(1), &Default::default())
+        );
+        assert_eq!(
+            Err(SchemaFailure::Invalid),
+            schema.validates_value(&json!(true), &Default::default())
+        );
+    }
+}
+
+/// Converts every schema in `tests/golden/to_jsonschema` and compares it with the JSON Schema
+/// stored next to it. Run with `UPDATE_GOLDEN=1` to rewrite the golden files after an
+/// intentional change to the conversion.
+#[cfg(test)]
+mod test_golden_files {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn conversions_match_golden_files() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/golden/to_jsonschema");
+        let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+
+        let mut schemas = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| {
+                path.extension()
+                    .is_some_and(|extension| extension == "yaml")
+            })
+            .collect::<Vec<_>>();
+        schemas.sort();
+        assert!(!schemas.is_empty());
+
+        let mut mismatches = vec![];
+        for path in schemas {
+            let schema: openapiv3::Schema =
+                serde_yaml::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            let actual = schema.to_json_schema();
+            let golden = path.with_extension("json");
+
+            if update {
+                std::fs::write(
+                    &golden,
+                    serde_json::to_string_pretty(&actual).unwrap() + "\n",
+                )
+                .unwrap();
+                continue;
+            }
+
+            let expected: serde_json::Value = std::fs::read_to_string(&golden)
+                .map(|golden| serde_json::from_str(&golden).unwrap())
+                .unwrap_or_default();
+            if actual != expected {
+                mismatches.push(format!(
+                    "{}:\n{}",
+                    golden.display(),
+                    serde_json::to_string_pretty(&actual).unwrap()
+                ));
+            }
+        }
+
+        assert!(mismatches.is_empty(), "{}", mismatches.join("\n"));
     }
 }
@@ -0,0 +1,193 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::error::ValidationError;
+use crate::request::Validator;
+
+/// Reads the body of a `ureq` response into memory, up to ureq's default limit, so that the
+/// response can be validated.
+pub fn buffer_response(
+    response: http::Response<ureq::Body>,
+) -> Result<http::Response<Vec<u8>>, ureq::Error> {
+    let (parts, mut body) = response.into_parts();
+    Ok(http::Response::from_parts(parts, body.read_to_vec()?))
+}
+
+/// Why a [`ValidatedAgent`] did not return a response.
+#[derive(Debug)]
+pub enum AgentError {
+    /// The request does not conform to the specification and was not sent.
+    InvalidRequest(ValidationError),
+    /// The server answered with a response that does not conform to the specification.
+    InvalidResponse {
+        response: Box<http::Response<Vec<u8>>>,
+        error: ValidationError,
+    },
+    /// Sending the request or reading the response failed.
+    Http(ureq::Error),
+}
+
+impl fmt::Display for AgentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidRequest(error) => write!(f, "invalid request: {error}"),
+            Self::InvalidResponse { error, .. } => write!(f, "invalid response: {error}"),
+            Self::Http(error) => write!(f, "request failed: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for AgentError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::InvalidRequest(error) | Self::InvalidResponse { error, .. } => Some(error),
+            Self::Http(error) => Some(error),
+        }
+    }
+}
+
+impl From<ureq::Error> for AgentError {
+    fn from(error: ureq::Error) -> Self {
+        Self::Http(error)
+    }
+}
+
+/// A blocking `ureq` agent that validates every request against the specification before
+/// sending it, and the response it gets back, e.g. in CLI tools or blocking integration tests.
+/// Error statuses are returned as responses to validate rather than as errors.
+#[derive(Debug, Clone)]
+pub struct ValidatedAgent {
+    agent: ureq::Agent,
+    validator: Arc<Validator>,
+}
+
+impl ValidatedAgent {
+    pub fn new(agent: ureq::Agent, validator: Arc<Validator>) -> Self {
+        Self { agent, validator }
+    }
+
+    pub fn agent(&self) -> &ureq::Agent {
+        &self.agent
+    }
+
+    pub fn run<B>(&self, request: http::Request<B>) -> Result<http::Response<Vec<u8>>, AgentError>
+    where
+        B: AsRef<[u8]> + ureq::AsSendBody,
+    {
+        let validated = (self.validator)
+            .validate_request(&request)
+            .map_err(AgentError::InvalidRequest)?;
+        let request = (self.agent.configure_request(request))
+            .http_status_as_error(false)
+            .build();
+        let response = self.agent.run(request)?;
+        let response = buffer_response(response)?;
+        match validated.validate_response(&response) {
+            Ok(()) => Ok(response),
+            Err(error) => Err(AgentError::InvalidResponse {
+                response: Box::new(response),
+                error,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_ureq_agent {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+              responses:
+                201:
+                  description: created
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [id]
+                400:
+                  description: bad request
+        "#
+    );
+
+    /// Answers one request on a local port with `status` and the JSON `body`, returning the
+    /// base URL.
+    fn serve_once(status: &'static str, body: &'static str) -> String {
+        let response = format!(
+            "HTTP/1.1 {status}\r\ncontent-type: application/json\r\n\
+             content-length: {}\r\n\r\n{body}",
+            body.len()
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buffer = [0; 4096];
+            let _ = stream.read(&mut buffer).unwrap();
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        format!("http://{address}")
+    }
+
+    fn agent() -> ValidatedAgent {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        ValidatedAgent::new(ureq::Agent::new_with_defaults(), Arc::new(validator))
+    }
+
+    fn post(base: &str, body: &'static str) -> http::Request<&'static str> {
+        http::Request::post(format!("{base}/pets"))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .unwrap()
+    }
+
+    #[test]
+    fn return_a_valid_response() {
+        let base = serve_once("201 Created", r#"{"id": 1}"#);
+        let response = agent().run(post(&base, r#"{"name": "rex"}"#)).unwrap();
+        assert_eq!(201, response.status());
+        assert_eq!(br#"{"id": 1}"#, response.body().as_slice());
+    }
+
+    #[test]
+    fn return_a_documented_error_status() {
+        let base = serve_once("400 Bad Request", "{}");
+        let response = agent().run(post(&base, r#"{"name": "rex"}"#)).unwrap();
+        assert_eq!(400, response.status());
+    }
+
+    #[test]
+    fn reject_an_invalid_request_without_sending_it() {
+        assert!(matches!(
+            agent().run(post("http://127.0.0.1:9", "{}")),
+            Err(AgentError::InvalidRequest(ValidationError::InvalidBody))
+        ));
+    }
+
+    #[test]
+    fn reject_an_invalid_response() {
+        let base = serve_once("201 Created", "{}");
+        assert!(matches!(
+            agent().run(post(&base, r#"{"name": "rex"}"#)),
+            Err(AgentError::InvalidResponse {
+                error: ValidationError::InvalidResponseBody,
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,114 @@
+/// Validates UTF-8 one chunk at a time, carrying a character split across chunk boundaries
+/// over to the next chunk. Stops at the first invalid byte and reports its offset from the
+/// start of the input.
+#[derive(Debug, Default)]
+pub(crate) struct Utf8Decoder {
+    offset: usize,
+    pending: [u8; 4],
+    pending_len: usize,
+}
+
+impl Utf8Decoder {
+    /// Appends the text decoded from `chunk` to `out`, or returns the offset of the first
+    /// invalid byte.
+    pub fn push(&mut self, mut chunk: &[u8], out: &mut String) -> Result<(), usize> {
+        if self.pending_len > 0 {
+            let needed = sequence_length(self.pending[0]);
+            let take = (needed - self.pending_len).min(chunk.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&chunk[..take]);
+            self.pending_len += take;
+            chunk = &chunk[take..];
+
+            match std::str::from_utf8(&self.pending[..self.pending_len]) {
+                Ok(character) => {
+                    out.push_str(character);
+                    self.offset += self.pending_len;
+                    self.pending_len = 0;
+                }
+                Err(error) if error.error_len().is_none() => return Ok(()),
+                Err(..) => return Err(self.offset),
+            }
+        }
+
+        match std::str::from_utf8(chunk) {
+            Ok(text) => {
+                out.push_str(text);
+                self.offset += chunk.len();
+                Ok(())
+            }
+            Err(error) => {
+                let (valid, rest) = chunk.split_at(error.valid_up_to());
+                out.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                self.offset += valid.len();
+
+                match error.error_len() {
+                    Some(..) => Err(self.offset),
+                    None => {
+                        self.pending[..rest.len()].copy_from_slice(rest);
+                        self.pending_len = rest.len();
+                        Ok(())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Fails if the input ended part way through a character.
+    pub fn finish(&self) -> Result<(), usize> {
+        match self.pending_len {
+            0 => Ok(()),
+            _ => Err(self.offset),
+        }
+    }
+}
+
+/// Length of the sequence introduced by a lead byte already known to start a valid but
+/// incomplete character.
+fn sequence_length(lead: u8) -> usize {
+    match lead {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod test_utf8_decoder {
+    use super::Utf8Decoder;
+    use parameterized::parameterized;
+
+    fn decode(chunks: &[&[u8]]) -> Result<String, usize> {
+        let mut decoder = Utf8Decoder::default();
+        let mut out = String::new();
+        for chunk in chunks {
+            decoder.push(chunk, &mut out)?;
+        }
+        decoder.finish().map(|_| out)
+    }
+
+    #[parameterized(split = { 1, 2, 3, 4, 5 })]
+    fn decode_characters_split_across_chunks(split: usize) {
+        let text = "a€😀";
+        let (first, second) = text.as_bytes().split_at(split);
+        assert_eq!(Ok(text.to_string()), decode(&[first, second]));
+    }
+
+    #[test]
+    fn decode_a_character_split_across_three_chunks() {
+        let bytes = "😀".as_bytes();
+        assert_eq!(
+            Ok("😀".to_string()),
+            decode(&[&bytes[..1], &bytes[1..2], &bytes[2..]])
+        );
+    }
+
+    #[test]
+    fn report_the_offset_of_the_first_invalid_byte() {
+        assert_eq!(Err(3), decode(&[b"ab", b"c\xe2\x82", b"\x28"]));
+    }
+
+    #[test]
+    fn report_a_truncated_final_character() {
+        assert_eq!(Err(1), decode(&[b"a\xf0\x9f"]));
+    }
+}
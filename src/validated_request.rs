@@ -0,0 +1,169 @@
+use serde::de::DeserializeOwned;
+
+use crate::error::ValidationError;
+use crate::operation::OperationMatch;
+use crate::parameters::ValidatedParameters;
+use crate::report::ValidationReport;
+use crate::response::{Response, ResponseValidator};
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ValidatedRequest<'api> {
+    pub operation: OperationMatch<'api>,
+    pub parameters: ValidatedParameters,
+    /// The security requirement the request met, by scheme name with the scopes each requires.
+    /// `None` when the operation requires no security, or when the request met none of its
+    /// requirements but security is not enforced.
+    pub security: Option<&'api openapiv3::SecurityRequirement>,
+    pub report: ValidationReport,
+    pub response_validator: ResponseValidator<'api>,
+}
+
+impl<'api> ValidatedRequest<'api> {
+    /// `operationId` of the operation the request was routed to.
+    pub fn operation_id(&self) -> Option<&'api str> {
+        self.operation.operation_id()
+    }
+
+    pub fn tags(&self) -> &'api [String] {
+        self.operation.tags()
+    }
+
+    /// Scopes required by the security requirement the request met, across its schemes.
+    pub fn required_scopes(&self) -> Vec<&'api str> {
+        let mut scopes: Vec<&str> = vec![];
+        for scope in self
+            .security
+            .into_iter()
+            .flat_map(|requirement| requirement.values())
+        {
+            for scope in scope.iter().map(String::as_str) {
+                if !scopes.contains(&scope) {
+                    scopes.push(scope);
+                }
+            }
+        }
+        scopes
+    }
+
+    /// The path template the request matched, e.g. `/users/{id}`, or the webhook name.
+    pub fn path_template(&self) -> &'api str {
+        self.operation.path
+    }
+
+    /// Path parameter converted to `T`, from the value validated against its schema. Template
+    /// variables no parameter declares have no value.
+    pub fn path_parameter<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        deserialize(self.parameters.path.get(name))
+    }
+
+    pub fn query_parameter<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        deserialize(self.parameters.query.get(name))
+    }
+
+    pub fn header_parameter<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        deserialize(self.parameters.header.get(name))
+    }
+
+    pub fn cookie_parameter<T: DeserializeOwned>(&self, name: &str) -> Option<T> {
+        deserialize(self.parameters.cookie.get(name))
+    }
+
+    pub fn validate_response(&self, response: &dyn Response) -> Result<(), ValidationError> {
+        self.response_validator.validate_response(response)
+    }
+}
+
+fn deserialize<T: DeserializeOwned>(value: Option<&serde_json::Value>) -> Option<T> {
+    value.and_then(|value| T::deserialize(value).ok())
+}
+
+#[cfg(test)]
+mod test_validated_request {
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use std::collections::HashMap;
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /users/{id}/posts/{slug}:
+            get:
+              operationId: getPost
+              tags: [users, posts]
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+                - in: query
+                  name: page
+                  required: false
+                  schema:
+                    type: integer
+                    default: 1
+                - in: header
+                  name: trace
+                  required: true
+                  schema:
+                    type: boolean
+              responses:
+                200:
+                  description: API call successful
+        "#
+    );
+
+    fn make_request() -> FakeRequest {
+        FakeRequest {
+            url: "http://test.com/users/42/posts/hello-world".to_string(),
+            operation: "get".to_string(),
+            body: vec![],
+            headers: HashMap::from([("trace".to_string(), "true".to_string())]),
+        }
+    }
+
+    #[test]
+    fn extract_declared_path_parameter_as_its_schema_type() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!(Some(42), validated.path_parameter::<u64>("id"));
+    }
+
+    #[test]
+    fn extract_nothing_for_an_undeclared_template_variable() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!(None, validated.path_parameter::<String>("slug"));
+    }
+
+    #[test]
+    fn extract_query_and_header_parameters() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!(Some(1), validated.query_parameter::<u32>("page"));
+        assert_eq!(Some(true), validated.header_parameter::<bool>("trace"));
+    }
+
+    #[test]
+    fn extract_parameter_as_wrong_type() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!(None, validated.path_parameter::<bool>("id"));
+    }
+
+    #[test]
+    fn expose_matched_path_template() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!("/users/{id}/posts/{slug}", validated.operation.path);
+    }
+
+    #[test]
+    fn expose_operation_metadata() {
+        let validator = make_validator_from_spec(PATH_SPEC);
+        let validated = validator.validate_request(&make_request()).unwrap();
+        assert_eq!(Some("getPost"), validated.operation_id());
+        assert_eq!(["users", "posts"], validated.tags());
+        assert_eq!("/users/{id}/posts/{slug}", validated.path_template());
+    }
+}
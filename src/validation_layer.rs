@@ -0,0 +1,255 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use tower::{Layer, Service};
+
+use crate::error::ValidationError;
+use crate::http_request::problem_json;
+use crate::request::Validator;
+use crate::resolver::ValidatorResolver;
+use crate::response::ResponseValidator;
+
+type Rejection = dyn Fn(&ValidationError) -> http::Response<String> + Send + Sync;
+
+/// Tower layer rejecting requests that do not conform to the specification before they reach
+/// the inner service, answering with [`problem_json`] unless another rejection is given.
+/// Requests no validator is resolved for are passed through. Request bodies must already be
+/// collected, e.g. as `Bytes` or `String`.
+#[derive(Clone)]
+pub struct ValidationLayer {
+    resolver: Arc<dyn ValidatorResolver>,
+    rejection: Arc<Rejection>,
+    validate_responses: bool,
+}
+
+impl ValidationLayer {
+    pub fn new(validator: Arc<Validator>) -> Self {
+        Self::with_resolver(validator)
+    }
+
+    /// Picks the specification to validate against per request, e.g. per tenant.
+    pub fn with_resolver(resolver: impl ValidatorResolver + 'static) -> Self {
+        Self {
+            resolver: Arc::new(resolver),
+            rejection: Arc::new(problem_json),
+            validate_responses: false,
+        }
+    }
+
+    /// Answers invalid requests, and invalid responses when those are validated, with the
+    /// response `rejection` builds from the error.
+    pub fn with_rejection(
+        mut self,
+        rejection: impl Fn(&ValidationError) -> http::Response<String> + Send + Sync + 'static,
+    ) -> Self {
+        self.rejection = Arc::new(rejection);
+        self
+    }
+
+    /// Also validates the responses of the inner service, replacing invalid ones with a
+    /// rejection, which by default has the status 500. Response bodies must be held in memory.
+    pub fn validate_responses(mut self, validate: bool) -> Self {
+        self.validate_responses = validate;
+        self
+    }
+}
+
+impl fmt::Debug for ValidationLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ValidationLayer")
+            .field("validate_responses", &self.validate_responses)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<S> Layer<S> for ValidationLayer {
+    type Service = ValidationService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        ValidationService {
+            inner,
+            layer: self.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ValidationService<S> {
+    inner: S,
+    layer: ValidationLayer,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for ValidationService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Error: Send + 'static,
+    S::Future: Send + 'static,
+    ReqBody: AsRef<[u8]>,
+    ResBody: AsRef<[u8]> + From<String> + Send + 'static,
+{
+    type Response = http::Response<ResBody>;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, S::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: http::Request<ReqBody>) -> Self::Future {
+        let layer = &self.layer;
+        let Some(validator) = layer.resolver.resolve(&request) else {
+            return Box::pin(self.inner.call(request));
+        };
+        let operation = match validator.validate_request(&request) {
+            Ok(validated) => validated.operation,
+            Err(error) => {
+                let response = (layer.rejection)(&error).map(ResBody::from);
+                return Box::pin(std::future::ready(Ok(response)));
+            }
+        };
+        if !layer.validate_responses {
+            return Box::pin(self.inner.call(request));
+        }
+
+        // the request goes to the inner service, so its head is kept to resolve the validator
+        // again; the response is checked against the operation the request was routed to
+        let (path, method) = (operation.path.to_string(), operation.method);
+        let accept = (request.headers().get(http::header::ACCEPT))
+            .and_then(|accept| accept.to_str().ok())
+            .map(str::to_string);
+        let (parts, body) = request.into_parts();
+        let head = http::Request::from_parts(parts.clone(), Vec::new());
+        let future = self.inner.call(http::Request::from_parts(parts, body));
+        let layer = layer.clone();
+        Box::pin(async move {
+            let response = future.await?;
+            let outcome = match layer.resolver.resolve(&head) {
+                Some(validator) => validator
+                    .response_validator(&path, method.as_str())
+                    .and_then(|response_validator| {
+                        ResponseValidator {
+                            accept,
+                            ..response_validator
+                        }
+                        .validate_response(&response)
+                    }),
+                None => Ok(()),
+            };
+            Ok(match outcome {
+                Ok(()) => response,
+                Err(error) => (layer.rejection)(&error).map(ResBody::from),
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_validation_layer {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+    use tower::{service_fn, BoxError, ServiceExt};
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets/{id}:
+            get:
+              parameters:
+                - in: path
+                  name: id
+                  required: true
+                  schema:
+                    type: integer
+              responses:
+                200:
+                  description: API call successful
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [name]
+        "#
+    );
+
+    async fn call(
+        layer: ValidationLayer,
+        uri: &str,
+        answer: &'static str,
+    ) -> http::Response<String> {
+        let inner = service_fn(move |_: http::Request<String>| async move {
+            let mut response = http::Response::new(answer.to_string());
+            response.headers_mut().insert(
+                http::header::CONTENT_TYPE,
+                http::HeaderValue::from_static("application/json"),
+            );
+            Ok::<_, BoxError>(response)
+        });
+        let request = http::Request::get(uri).body(String::new()).unwrap();
+        layer.layer(inner).oneshot(request).await.unwrap()
+    }
+
+    fn layer() -> ValidationLayer {
+        ValidationLayer::new(Arc::new(make_validator_from_spec(PATH_SPEC)))
+    }
+
+    #[tokio::test]
+    async fn pass_a_valid_request_through() {
+        let response = call(layer(), "/pets/1", r#"{"name": "rex"}"#).await;
+        assert_eq!(200, response.status());
+        assert_eq!(r#"{"name": "rex"}"#, response.body());
+    }
+
+    #[tokio::test]
+    async fn reject_an_invalid_request_with_a_problem() {
+        let response = call(layer(), "/pets/rex", "{}").await;
+        assert_eq!(400, response.status());
+        assert_eq!(
+            Some("application/problem+json"),
+            response.headers()[http::header::CONTENT_TYPE].to_str().ok()
+        );
+        let problem: serde_json::Value = serde_json::from_str(response.body()).unwrap();
+        assert_eq!(
+            json!({
+                "type": "about:blank",
+                "title": "Bad Request",
+                "status": 400,
+                "detail": "parameter 'id' is invalid",
+            }),
+            problem
+        );
+    }
+
+    #[tokio::test]
+    async fn reject_with_a_custom_response() {
+        let layer = layer().with_rejection(|error| {
+            let mut response = http::Response::new(String::new());
+            *response.status_mut() = http::StatusCode::from_u16(error.suggested_status()).unwrap();
+            response
+        });
+        let response = call(layer, "/unknown", "{}").await;
+        assert_eq!(404, response.status());
+        assert_eq!("", response.body());
+    }
+
+    #[tokio::test]
+    async fn validate_responses_when_asked() {
+        assert_eq!(200, call(layer(), "/pets/1", "{}").await.status());
+        let response = call(layer().validate_responses(true), "/pets/1", "{}").await;
+        assert_eq!(500, response.status());
+    }
+
+    #[tokio::test]
+    async fn validate_the_request_once_when_validating_responses() {
+        let (sender, records) = std::sync::mpsc::channel();
+        let validator = make_validator_from_spec(PATH_SPEC).with_recorder(sender);
+        let layer = ValidationLayer::new(Arc::new(validator)).validate_responses(true);
+        let response = call(layer, "/pets/1", r#"{"name": "rex"}"#).await;
+        assert_eq!(200, response.status());
+        assert_eq!(1, records.try_iter().count());
+    }
+}
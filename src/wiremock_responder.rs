@@ -0,0 +1,159 @@
+use std::sync::Arc;
+
+use serde::Serialize;
+use wiremock::{Respond, ResponseTemplate};
+
+use crate::request::{Request, Validator};
+
+/// Validates the requests a wiremock `MockServer` receives. Only the path and query of the URL
+/// are read, as the mock server's origin is not one of the specification's `servers`.
+impl Request for wiremock::Request {
+    fn url(&self) -> &str {
+        &self.url[url::Position::BeforePath..]
+    }
+
+    fn operation(&self) -> &str {
+        self.method.as_str()
+    }
+
+    fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        let value = self.headers.get(key)?;
+        value.to_str().ok().map(str::to_string)
+    }
+}
+
+/// A wiremock responder answering with a canned response once it has checked that the stubbed
+/// request conforms to the specification, and that the canned response conforms to what the
+/// specification documents for it. Like wiremock's own checks, it panics otherwise, which
+/// drops the connection so that the client under test sees an error.
+#[derive(Debug, Clone)]
+pub struct SpecConformingResponder {
+    validator: Arc<Validator>,
+    response: http::Response<Vec<u8>>,
+}
+
+impl SpecConformingResponder {
+    pub fn new(validator: Arc<Validator>, status: u16) -> Self {
+        let mut response = http::Response::new(Vec::new());
+        *response.status_mut() = http::StatusCode::from_u16(status).expect("invalid status code");
+        Self {
+            validator,
+            response,
+        }
+    }
+
+    pub fn insert_header(mut self, key: &'static str, value: &str) -> Self {
+        let value = http::HeaderValue::from_str(value).expect("invalid header value");
+        self.response.headers_mut().insert(key, value);
+        self
+    }
+
+    /// Answers with `body` serialized as JSON, with the content type `application/json`.
+    pub fn set_body_json(self, body: impl Serialize) -> Self {
+        let body = serde_json::to_vec(&body).expect("body cannot be serialized as JSON");
+        self.set_body_raw(body, "application/json")
+    }
+
+    pub fn set_body_raw(mut self, body: impl Into<Vec<u8>>, content_type: &str) -> Self {
+        *self.response.body_mut() = body.into();
+        self.insert_header("content-type", content_type)
+    }
+}
+
+impl Respond for SpecConformingResponder {
+    fn respond(&self, request: &wiremock::Request) -> ResponseTemplate {
+        let target = format!("{} {}", request.method, request.url());
+        let validated = match self.validator.validate_request(request) {
+            Ok(validated) => validated,
+            Err(error) => panic!("{target} does not conform to the specification: {error}"),
+        };
+        if let Err(error) = validated.validate_response(&self.response) {
+            panic!(
+                "the {} response stubbed for {target} does not conform to the specification: \
+                 {error}",
+                self.response.status()
+            );
+        }
+
+        let template = ResponseTemplate::new(self.response.status().as_u16())
+            .set_body_bytes(self.response.body().clone());
+        (self.response.headers().iter()).fold(template, |template, (key, value)| {
+            template.append_header(key, value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod test_wiremock_responder {
+    use super::*;
+    use crate::request::test_helpers::*;
+    use indoc::indoc;
+    use serde_json::json;
+    use wiremock::matchers::method;
+    use wiremock::{Mock, MockServer};
+
+    const PATH_SPEC: &str = indoc!(
+        r#"
+        paths:
+          /pets:
+            post:
+              requestBody:
+                required: true
+                content:
+                  application/json:
+                    schema:
+                      type: object
+                      required: [name]
+              responses:
+                201:
+                  description: created
+                  content:
+                    application/json:
+                      schema:
+                        type: object
+                        required: [id]
+        "#
+    );
+
+    async fn post(responder: SpecConformingResponder, body: &'static str) -> reqwest::Result<u16> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(responder)
+            .mount(&server)
+            .await;
+        let response = reqwest::Client::new()
+            .post(format!("{}/pets", server.uri()))
+            .header("Content-Type", "application/json")
+            .body(body)
+            .send()
+            .await?;
+        Ok(response.status().as_u16())
+    }
+
+    fn responder(body: serde_json::Value) -> SpecConformingResponder {
+        let validator = Arc::new(make_validator_from_spec(PATH_SPEC));
+        SpecConformingResponder::new(validator, 201).set_body_json(body)
+    }
+
+    #[tokio::test]
+    async fn answer_a_conforming_exchange() {
+        let status = post(responder(json!({"id": 1})), r#"{"name": "rex"}"#).await;
+        assert_eq!(201, status.unwrap());
+    }
+
+    #[tokio::test]
+    async fn fail_a_request_that_does_not_conform() {
+        assert!(post(responder(json!({"id": 1})), "{}").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn fail_a_canned_response_that_does_not_conform() {
+        assert!(post(responder(json!({})), r#"{"name": "rex"}"#)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,75 @@
+use std::process::{Command, Output};
+
+fn validate(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_openapi-validator"))
+        .args(["validate", "tests/cli/pets.yaml"])
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn report_a_valid_request_described_by_flags() {
+    let output = validate(&[
+        "--method",
+        "POST",
+        "--url",
+        "http://localhost/pets",
+        "-H",
+        "Content-Type: application/json",
+        "--json",
+        r#"{"name": "rex"}"#,
+    ]);
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!(
+        "POST http://localhost/pets: valid, routed to createPet\n",
+        stdout(&output)
+    );
+}
+
+#[test]
+fn report_an_invalid_request_file_as_json() {
+    let output = validate(&[
+        "--request",
+        "tests/cli/invalid_request.json",
+        "--format",
+        "json",
+    ]);
+    assert_eq!(Some(1), output.status.code());
+    let report: serde_json::Value = serde_json::from_str(&stdout(&output)).unwrap();
+    assert_eq!(
+        serde_json::json!({
+            "request": "POST /pets",
+            "valid": false,
+            "status": 400,
+            "error": "body does not match the specification",
+        }),
+        report
+    );
+}
+
+#[test]
+fn validate_a_cookie_parameter() {
+    let request = |cookie: &str| validate(&["--method", "GET", "--url", "/pets", "-H", cookie]);
+    let output = request("Cookie: theme=dark; session=42");
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("GET /pets: valid, routed to listPets\n", stdout(&output));
+    assert_eq!(Some(1), request("Cookie: session=abc").status.code());
+}
+
+#[test]
+fn fail_when_the_specification_cannot_be_loaded() {
+    let output = Command::new(env!("CARGO_BIN_EXE_openapi-validator"))
+        .args(["validate", "tests/cli/missing.yaml", "--method", "GET"])
+        .args(["--url", "/pets"])
+        .output()
+        .unwrap();
+    assert_eq!(Some(2), output.status.code());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .starts_with("error: could not read tests/cli/missing.yaml"));
+}
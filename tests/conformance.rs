@@ -0,0 +1,142 @@
+//! Validates curated request/response fixtures against vendored third-party specs. Each spec in
+//! `tests/conformance/specs` is paired with a fixture file of the same name in
+//! `tests/conformance/fixtures`. Run with `cargo test --features conformance-tests`.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+use validator::Validator;
+
+#[derive(Deserialize)]
+struct Fixture {
+    name: String,
+    request: FixtureRequest,
+    response: Option<FixtureResponse>,
+    #[serde(default = "ok")]
+    expect_request: String,
+    #[serde(default = "ok")]
+    expect_response: String,
+}
+
+fn ok() -> String {
+    "ok".to_string()
+}
+
+#[derive(Deserialize)]
+struct FixtureRequest {
+    method: String,
+    url: String,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: String,
+}
+
+impl validator::Request for FixtureRequest {
+    fn url(&self) -> &str {
+        &self.url
+    }
+
+    fn operation(&self) -> &str {
+        &self.method
+    }
+
+    fn body(&self) -> &[u8] {
+        self.body.as_bytes()
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        self.headers.get(key).cloned()
+    }
+}
+
+#[derive(Deserialize)]
+struct FixtureResponse {
+    status: u16,
+    #[serde(default)]
+    headers: HashMap<String, String>,
+    #[serde(default)]
+    body: String,
+}
+
+impl validator::Response for FixtureResponse {
+    fn status_code(&self) -> u16 {
+        self.status
+    }
+
+    fn get_header(&self, key: &str) -> Option<String> {
+        self.headers.get(key).cloned()
+    }
+
+    fn body(&self) -> &[u8] {
+        self.body.as_bytes()
+    }
+}
+
+/// `ok`, or the name of the error variant, so fixtures don't depend on error details.
+fn outcome<T>(result: &Result<T, validator::ValidationError>) -> String {
+    match result {
+        Ok(..) => ok(),
+        Err(error) => format!("{error:?}")
+            .split([' ', '{', '('])
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+/// Runs every fixture for a spec and returns a description of each one that did not behave
+/// as expected.
+fn run_fixtures(spec: &str) -> (usize, Vec<String>) {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/conformance");
+    let document =
+        std::fs::read_to_string(directory.join("specs").join(format!("{spec}.yaml"))).unwrap();
+    let validator = Validator::from_yaml_str(&document).unwrap();
+    assert_eq!(Vec::<validator::SpecError>::new(), validator.spec_errors());
+
+    let fixtures: Vec<Fixture> = serde_yaml::from_str(
+        &std::fs::read_to_string(directory.join("fixtures").join(format!("{spec}.yaml"))).unwrap(),
+    )
+    .unwrap();
+
+    let mut failures = vec![];
+    for fixture in &fixtures {
+        let validated = validator.validate_request(&fixture.request);
+        let request_outcome = outcome(&validated);
+        if request_outcome != fixture.expect_request {
+            failures.push(format!(
+                "{spec}: {}: expected request {}, got {request_outcome}",
+                fixture.name, fixture.expect_request
+            ));
+            continue;
+        }
+
+        if let (Ok(validated), Some(response)) = (validated, &fixture.response) {
+            let response_outcome = outcome(&validated.validate_response(response));
+            if response_outcome != fixture.expect_response {
+                failures.push(format!(
+                    "{spec}: {}: expected response {}, got {response_outcome}",
+                    fixture.name, fixture.expect_response
+                ));
+            }
+        }
+    }
+
+    (fixtures.len(), failures)
+}
+
+#[test]
+fn conformance_report() {
+    let mut failures = vec![];
+    for spec in ["petstore", "github", "stripe"] {
+        let (total, spec_failures) = run_fixtures(spec);
+        eprintln!(
+            "{spec}: {}/{total} fixtures conform",
+            total - spec_failures.len()
+        );
+        failures.extend(spec_failures);
+    }
+
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
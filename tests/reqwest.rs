@@ -1,34 +1,107 @@
-struct ReqwestWrapper(reqwest::Request);
-
-impl validator::Request for ReqwestWrapper {
-    fn url(&self) -> &str {
-        self.0.url().as_str()
-    }
-
-    fn operation(&self) -> &str {
-        self.0.method().as_str()
-    }
-
-    //make this option
-    fn body(&self) -> &[u8] {
-        self.0
-            .body()
-            .and_then(reqwest::Body::as_bytes)
-            .unwrap_or(&[])
-    }
-
-    fn get_header(&self, key: &str) -> Option<String> {
-        self.0.headers().get(key).and_then(|header_value| {
-            header_value
-                .to_str()
-                .ok()
-                .map(|header_value| header_value.to_string())
-        })
-    }
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::sync::Arc;
+
+use validator::{ClientError, ValidatedClient, ValidationError, Validator};
+
+const SPEC: &str = r#"
+openapi: 3.0.0
+info:
+  title: pets
+  version: 1.0.0
+paths:
+  /pets:
+    post:
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema:
+              type: object
+              required: [name]
+      responses:
+        201:
+          description: created
+          content:
+            application/json:
+              schema:
+                type: object
+                required: [id]
+"#;
+
+/// Answers one request with a `201` carrying the JSON `body` on a local port, returning the
+/// base URL.
+fn serve_once(body: &'static str) -> String {
+    let response = format!(
+        "HTTP/1.1 201 Created\r\ncontent-type: application/json\r\n\
+         content-length: {}\r\n\r\n{body}",
+        body.len()
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut buffer = [0; 4096];
+        let _ = stream.read(&mut buffer).unwrap();
+        stream.write_all(response.as_bytes()).unwrap();
+    });
+    format!("http://{address}")
+}
+
+fn client() -> ValidatedClient {
+    let validator = Validator::from_yaml_str(SPEC).unwrap();
+    ValidatedClient::new(reqwest::Client::new(), Arc::new(validator))
+}
+
+fn post(client: &ValidatedClient, base: &str, body: &'static str) -> reqwest::Request {
+    client
+        .client()
+        .post(format!("{base}/pets"))
+        .header("Content-Type", "application/json")
+        .body(body)
+        .build()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn return_a_valid_response() {
+    let base = serve_once(r#"{"id": 1}"#);
+    let client = client();
+    let response = client
+        .execute(post(&client, &base, r#"{"name": "rex"}"#))
+        .await
+        .unwrap();
+
+    assert_eq!(201, response.status.as_u16());
+    assert_eq!(
+        serde_json::json!({"id": 1}),
+        response.json::<serde_json::Value>().unwrap()
+    );
+    assert!(response.elapsed.is_some());
 }
 
-#[cfg(test)]
-mod test_reqwest {
-    #[test]
-    fn test_reqwest_get() {}
+#[tokio::test]
+async fn reject_an_invalid_request_without_sending_it() {
+    let client = client();
+    let request = post(&client, "http://127.0.0.1:9", "{}");
+    assert!(matches!(
+        client.execute(request).await,
+        Err(ClientError::InvalidRequest(ValidationError::InvalidBody))
+    ));
+}
+
+#[tokio::test]
+async fn reject_an_invalid_response() {
+    let base = serve_once("{}");
+    let client = client();
+    let result = client
+        .execute(post(&client, &base, r#"{"name": "rex"}"#))
+        .await;
+    assert!(matches!(
+        result,
+        Err(ClientError::InvalidResponse {
+            error: ValidationError::InvalidResponseBody,
+            ..
+        })
+    ));
 }